@@ -0,0 +1,46 @@
+//! GNSS autonomous scan on a simulated chip (feature `sim`)
+//!
+//! Run with `cargo run --example sim_gnss_scan`; `cargo test` also runs it as an integration test.
+//! Not built with the `std` feature, which brings the time driver of `embassy-time` (requiring a timer queue),
+//! nor with `gnss_v1` (different scan API).
+
+#[cfg(not(any(feature = "std", feature = "gnss_v1")))]
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+#[cfg(not(any(feature = "std", feature = "gnss_v1")))]
+fn main() {
+    use embassy_futures::block_on;
+    use lr1120::gnss::GnssScanCfg;
+    use lr1120::sim::SimChip;
+    use lr1120::status::{Intr, IRQ_MASK_GNSS_DONE};
+
+    // NAV message, starting with the destination byte (host)
+    let nav = [0x01, 0x8A, 0x12, 0x34, 0x56, 0x78, 0x9A];
+    let chip = SimChip::new();
+    chip.set_gnss_result(5, &nav);
+    let mut lr = chip.driver();
+    block_on(async {
+        lr.get_status().await.unwrap();
+        lr.gnss_scan(GnssScanCfg::new(false)).await.unwrap();
+        let (_, intr) = lr.get_status().await.unwrap();
+        assert!(intr.gnss_done());
+        lr.clear_irqs(Some(Intr::new(IRQ_MASK_GNSS_DONE))).await.unwrap();
+
+        let nb_sv = lr.gnss_get_nb_sv().await.unwrap();
+        assert_eq!(nb_sv, 5);
+        assert_eq!(lr.gnss_get_result_size().await.unwrap() as usize, nav.len());
+        let result = lr.gnss_read_results().await.unwrap();
+        assert_eq!(result, &nav);
+        println!("{nb_sv} satellites detected, NAV message: {result:02X?}");
+    });
+}
+
+#[cfg(any(feature = "std", feature = "gnss_v1"))]
+fn main() {}
+
+/// Run the example with `cargo test`
+#[test]
+fn sim_gnss_scan() {
+    main();
+}
@@ -0,0 +1,72 @@
+//! LoRa ping-pong between two simulated chips (feature `sim`)
+//!
+//! The payload written in the TX buffer of one chip is delivered to the other one with [`SimChip::transmit_to`].
+//! Run with `cargo run --example sim_lora_ping_pong`; `cargo test` also runs it as an integration test.
+//! Not built with the `std` feature, which brings the time driver of `embassy-time` (requiring a timer queue).
+
+#[cfg(not(feature = "std"))]
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+#[cfg(not(feature = "std"))]
+type SimDriver<'a> = lr1120::Lr1120<lr1120::sim::SimPin<'a>, lr1120::sim::SimSpi<'a>, lr1120::BusyBlocking<lr1120::sim::SimPin<'a>>>;
+
+/// Send `frame` from one chip to the other and check it is received
+#[cfg(not(feature = "std"))]
+async fn exchange(tx: &lr1120::sim::SimChip, lr_tx: &mut SimDriver<'_>, rx: &lr1120::sim::SimChip, lr_rx: &mut SimDriver<'_>, frame: &[u8]) {
+    use lr1120::status::{Intr, IRQ_MASK_RX_DONE, IRQ_MASK_TX_DONE};
+
+    lr_tx.wr_tx_buffer_from(frame).await.unwrap();
+    lr_tx.set_tx(0).await.unwrap();
+    let (_, intr) = lr_tx.get_status().await.unwrap();
+    assert!(intr.tx_done());
+    lr_tx.clear_irqs(Some(Intr::new(IRQ_MASK_TX_DONE))).await.unwrap();
+
+    tx.transmit_to(rx);
+    lr_rx.set_rx(0, true).await.unwrap();
+    let (_, intr) = lr_rx.get_status().await.unwrap();
+    assert!(intr.rx_done());
+    let payload = lr_rx.get_lora_payload(intr, false).await.unwrap();
+    assert_eq!(payload.data, frame);
+    println!("Received {} #{}", core::str::from_utf8(&payload.data[..4]).unwrap(), payload.data[4]);
+    lr_rx.clear_irqs(Some(Intr::new(IRQ_MASK_RX_DONE))).await.unwrap();
+}
+
+#[cfg(not(feature = "std"))]
+fn main() {
+    use embassy_futures::block_on;
+    use lr1120::lora::{LoraBw, LoraModulationParams, LoraPacketParams, Sf};
+    use lr1120::radio::PacketType;
+    use lr1120::sim::SimChip;
+
+    const NB_ROUND: u8 = 4;
+
+    let ping = SimChip::new();
+    let pong = SimChip::new();
+    let mut lr_ping = ping.driver();
+    let mut lr_pong = pong.driver();
+    block_on(async {
+        let modulation = LoraModulationParams::basic(Sf::Sf7, LoraBw::Bw125);
+        let packet = LoraPacketParams::basic(8, &modulation);
+        for lr in [&mut lr_ping, &mut lr_pong] {
+            lr.get_status().await.unwrap();
+            lr.set_packet_type(PacketType::Lora).await.unwrap();
+            lr.set_lora_modulation(&modulation).await.unwrap();
+            lr.set_lora_packet(&packet).await.unwrap();
+        }
+
+        for round in 0..NB_ROUND {
+            exchange(&ping, &mut lr_ping, &pong, &mut lr_pong, &[b'P', b'I', b'N', b'G', round]).await;
+            exchange(&pong, &mut lr_pong, &ping, &mut lr_ping, &[b'P', b'O', b'N', b'G', round]).await;
+        }
+    });
+}
+
+#[cfg(feature = "std")]
+fn main() {}
+
+/// Run the example with `cargo test`
+#[test]
+fn sim_lora_ping_pong() {
+    main();
+}
@@ -0,0 +1,55 @@
+//! WiFi passive scan on a simulated chip (feature `sim`)
+//!
+//! Run with `cargo run --example sim_wifi_scan`; `cargo test` also runs it as an integration test.
+//! Not built with the `std` feature, which brings the time driver of `embassy-time` (requiring a timer queue).
+
+#[cfg(not(feature = "std"))]
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+#[cfg(not(feature = "std"))]
+fn main() {
+    use embassy_futures::block_on;
+    use lr1120::sim::SimChip;
+    use lr1120::status::{Intr, IRQ_MASK_WIFI_DONE};
+    use lr1120::wifi_scan::{AcqMode, WifiScanParams, WifiStandard};
+
+    let aps = [
+        ([0x02, 0x11, 0x22, 0x33, 0x44, 0x55], 1, -58),
+        ([0x02, 0x66, 0x77, 0x88, 0x99, 0xAA], 6, -71),
+        ([0x02, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF], 11, -84),
+    ];
+    let chip = SimChip::new();
+    chip.set_wifi_results(&aps);
+    let mut lr = chip.driver();
+    block_on(async {
+        lr.get_status().await.unwrap();
+        lr.wifi_scan(&WifiScanParams::new(WifiStandard::All, AcqMode::BeaconSearch)).await.unwrap();
+        let (_, intr) = lr.get_status().await.unwrap();
+        assert!(intr.wifi_done());
+        lr.clear_irqs(Some(Intr::new(IRQ_MASK_WIFI_DONE))).await.unwrap();
+
+        let nb = lr.wifi_get_nb_res().await.unwrap();
+        assert_eq!(nb as usize, aps.len());
+        let results = lr.wifi_get_result_short(0, nb).await.unwrap();
+        let mut count = 0;
+        for (res, (mac, channel, rssi)) in results.zip(aps) {
+            let res = res.unwrap();
+            assert_eq!(res.mac().bytes(), mac);
+            assert_eq!(res.channel_id(), channel);
+            assert_eq!(res.rssi_dbm(), rssi.into());
+            println!("AP {:02X?}: channel {} / {} dBm", res.mac().bytes(), res.channel_id(), res.rssi_dbm());
+            count += 1;
+        }
+        assert_eq!(count, aps.len());
+    });
+}
+
+#[cfg(feature = "std")]
+fn main() {}
+
+/// Run the example with `cargo test`
+#[test]
+fn sim_wifi_scan() {
+    main();
+}
@@ -0,0 +1,18 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use lr1120::gnss::*;
+use lr1120::gnss_tracker::GnssNav;
+use lr1120::payload::GnssUplink;
+
+// Scan results (NAV message) and detected satellites list
+fuzz_target!(|data: &[u8]| {
+    if let Ok(nav) = GnssNav::try_from_slice(data) {
+        let _ = nav.data();
+    }
+    let mut payload: heapless::Vec<u8, { GnssUplink::max_encoded_len() }> = heapless::Vec::new();
+    let _ = GnssUplink::encode(data, &mut payload);
+    for sv in gnss_parse_sv_detected(data).flatten() {
+        let _ = (sv.sv(), sv.snr(), sv.doppler());
+    }
+});
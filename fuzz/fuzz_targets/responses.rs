@@ -0,0 +1,22 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use lr1120::cmd::cmd_gnss::*;
+use lr1120::cmd::cmd_system::*;
+use lr1120::cmd::cmd_wifi::*;
+
+// Fixed-size response parsers must reject any slice with the wrong length and decode any content
+fuzz_target!(|data: &[u8]| {
+    if let Ok(rsp) = StatusRsp::try_from_slice(data) {
+        let _ = rsp.intr();
+    }
+    if let Ok(rsp) = VersionRsp::try_from_slice(data) {
+        let _ = (rsp.hw_type(), rsp.major(), rsp.minor());
+    }
+    if let Ok(rsp) = GnssGetContextStatusRsp::try_from_slice(data) {
+        let _ = rsp.global_almanac_crc();
+    }
+    if let Ok(rsp) = WifiReadExtendedResultsRsp::try_from_slice(data) {
+        let _ = (rsp.mac0(), rsp.ssid(), rsp.timestamp(), rsp.channel_id());
+    }
+});
@@ -0,0 +1,35 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use lr1120::beacon::*;
+use lr1120::cmd::cmd_gnss::*;
+use lr1120::cmd::cmd_system::*;
+use lr1120::gnss::*;
+
+// Truncated responses and frames must be reported as errors, never panic
+// First two bytes select the beacon RFU lengths, remaining bytes are the data
+fuzz_target!(|data: &[u8]| {
+    let [rfu1_len, rfu2_len, data @ ..] = data else {
+        return;
+    };
+    let cfg = BeaconCfg { rfu1_len: rfu1_len & 0xF, rfu2_len: rfu2_len & 0xF, ..BeaconCfg::eu868() };
+    let beacon = Beacon::parse(data, &cfg);
+    if data.len() != cfg.frame_len() {
+        assert!(beacon.is_err());
+    }
+    for n in 0..data.len() {
+        let short = &data[..n];
+        if n < VersionRsp::RSP_LEN {
+            assert!(VersionRsp::try_from_slice(short).is_err());
+        }
+        if n < GnssGetContextStatusRsp::RSP_LEN {
+            assert!(GnssGetContextStatusRsp::try_from_slice(short).is_err());
+        }
+    }
+    if !data.len().is_multiple_of(GnssGetSvDetectedRsp::RSP_LEN) {
+        assert!(gnss_parse_sv_detected(data).last().is_some_and(|sv| sv.is_err()));
+    }
+    if !data.len().is_multiple_of(GnssGetSvVisibleDopplerRsp::RSP_LEN) {
+        assert!(gnss_parse_sv_visible_doppler(data).last().is_some_and(|sv| sv.is_err()));
+    }
+});
@@ -0,0 +1,24 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use lr1120::wifi_scan::*;
+
+// First byte is the number of results announced, remaining bytes are the results buffer
+fuzz_target!(|data: &[u8]| {
+    let Some((&nb, buffer)) = data.split_first() else {
+        return;
+    };
+    let filter = WifiApFilter::geoloc();
+    for res in filter.apply(WifiResultsIter::<WifiReadResultsRsp>::new(buffer, nb)).flatten() {
+        let _ = (res.mac(), res.rssi(), res.channel_id());
+    }
+    for res in filter.apply(WifiResultsIter::<WifiReadLongResultsRsp>::new(buffer, nb)).flatten() {
+        let _ = (res.mac(), res.timestamp());
+    }
+    for res in WifiResultsIter::<WifiReadExtendedResultsRsp>::new(buffer, nb).flatten() {
+        let _ = res.ssid();
+    }
+    for res in WifiResultsIter::<WifiReadCountryCodeResultsRsp>::new(buffer, nb).flatten() {
+        let _ = res.mac();
+    }
+});
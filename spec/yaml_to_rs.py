@@ -71,6 +71,12 @@ class Command:
     description: str
     parameters: list[Field]
     status_fields: list[Field]
+    # Response of variable length read by the driver (no response struct generated)
+    variable_rsp: bool = False
+    # Opcode catalog only: name shared by commands with the same opcode, variable request payload or response
+    catalog_name: str | None = None
+    catalog_req_variable: bool = False
+    catalog_rsp_variable: bool = False
 
 class ValidationError(Exception):
     pass
@@ -155,7 +161,15 @@ def parse_command(cmd_name: str, cmd_data: dict[str, Any]) -> Command:  # pyrigh
             field = parse_field(field_data, f"command '{cmd_name}' status_field")
             status_fields.append(field)
         
-        return Command(cmd_name, opcode, description, parameters, status_fields)
+        variable_rsp : bool = cmd_data.get('variable_rsp', False)
+
+        catalog : dict[str, str] = cmd_data.get('catalog', {})
+        for key, value in catalog.items():
+            if key not in ('name', 'req', 'rsp') or (key != 'name' and value != 'variable'):
+                raise ValidationError(f"invalid catalog property {key}: {value}")
+
+        return Command(cmd_name, opcode, description, parameters, status_fields, variable_rsp,
+                       catalog.get('name'), catalog.get('req') == 'variable', catalog.get('rsp') == 'variable')
         
     except KeyError as e:
         raise ValidationError(f"command '{cmd_name}': missing required property: {e}")
@@ -331,7 +345,7 @@ def gen_req(cmd: Command, _category: str, advanced: bool = False) -> str:
     if advanced:
         func_name += "_adv"
 
-    func_suffix = "_req" if cmd.status_fields else "_cmd"
+    func_suffix = "_req" if cmd.status_fields or cmd.variable_rsp else "_cmd"
     func_name += func_suffix
     
     # Filter parameters based on advanced flag
@@ -363,9 +377,9 @@ def gen_req(cmd: Command, _category: str, advanced: bool = False) -> str:
     desc = cmd.description.replace('[', '(').replace(']', ')')
     lines.append(f"/// {desc}")
     if param_list:
-        lines.append(f"pub fn {func_name}({', '.join(param_list)}) -> [u8; {buffer_size}] {{")
+        lines.append(f"pub const fn {func_name}({', '.join(param_list)}) -> [u8; {buffer_size}] {{")
     else:
-        lines.append(f"pub fn {func_name}() -> [u8; {buffer_size}] {{")
+        lines.append(f"pub const fn {func_name}() -> [u8; {buffer_size}] {{")
     
     # Generate opcode bytes
     opcode_msb = (cmd.opcode >> 8) & 0xFF
@@ -375,53 +389,60 @@ def gen_req(cmd: Command, _category: str, advanced: bool = False) -> str:
         lines.append(f"    [0x{opcode_msb:02X}, 0x{opcode_lsb:02X}]")
     else:
         # Generate parameter packing code
-        lines.append("    let mut cmd = [0u8; {}];".format(buffer_size))
-        lines.append(f"    cmd[0] = 0x{opcode_msb:02X};")
-        lines.append(f"    cmd[1] = 0x{opcode_lsb:02X};")
-        lines.append("")
-        
+        lines.append(f"    CmdWriter::new(0x{cmd.opcode:04X})")
+        cursor = 2
         for param in params:
             if param.bit_width == 0:
                 continue
 
-            need_cast_u8 = param.signed or param.bit_width > 8
-            mask = (1 << param.bit_width) - 1 if param.bit_width < 8 else 255
-            # Generate bit packing for each byte position
-            for pos in param.byte_positions:
-                _, lsb = pos.get_bit_range_tuple()
-                if param.bit_width == 1 and not param.enum:
-                    lines.append(f"    if {param.name} {{ cmd[{pos.byte_index}] |= {1<<lsb}; }}")
-                elif param.name == 'temp_format':
-                    lines.append(f"    cmd[2] |= 8; // Force format to Celsius")
-                else:
+            positions = [(pos.byte_index, *pos.get_bit_range_tuple()) for pos in param.byte_positions]
+            rust_type = get_rust_type(param)
+            # Full bytes in big-endian order: write the value directly
+            nb_bytes = len(positions)
+            is_full_be = (
+                not param.little_endian
+                and param.bit_width == 8 * nb_bytes
+                and all(msb == 7 and lsb == 0 for _, msb, lsb in positions)
+                and all(positions[i+1][0] == positions[i][0] + 1 for i in range(nb_bytes - 1))
+            )
+            if positions[0][0] < cursor:
+                raise ValidationError(f"{cmd.name}: field {param.name} overlaps previous field (byte {positions[0][0]})")
+            if positions[0][0] > cursor:
+                lines.append(f"        .skip({positions[0][0] - cursor})")
+                cursor = positions[0][0]
+            if param.name == 'temp_format':
+                lines.append("        .flag(3, true) // Force format to Celsius")
+            elif param.bit_width == 1 and not param.enum:
+                _, _, lsb = positions[0]
+                lines.append(f"        .flag({lsb}, {param.name})")
+            elif is_full_be:
+                value = param.name
+                if param.enum:
+                    value = f"{param.name} as u8"
+                elif param.signed:
+                    value = f"{param.name} as {rust_type.replace('i', 'u')}"
+                lines.append(f"        .push_u{param.bit_width}({value})")
+                cursor += nb_bytes
+            else:
+                # Generic packing, byte per byte
+                value = f"({param.name} as u8)" if param.enum else param.name
+                for i, (byte_index, msb, lsb) in enumerate(positions):
+                    if byte_index > cursor:
+                        lines.append(f"        .skip({byte_index - cursor})")
+                        cursor = byte_index
                     if param.little_endian:
-                        shift_right = sum((p_msb - p_lsb + 1) for p_pos in param.byte_positions[:param.byte_positions.index(pos)] for p_msb, p_lsb in [p_pos.get_bit_range_tuple()])
-                    else :
-                        shift_right = param.bit_width - sum((p_msb - p_lsb + 1) for p_pos in param.byte_positions[:param.byte_positions.index(pos)+1] for p_msb, p_lsb in [p_pos.get_bit_range_tuple()])
-                    l = f"    cmd[{pos.byte_index}] |= "
-                    if need_cast_u8:  l += '(';
-                    if lsb!= 0: l += '(';
-                    if shift_right!=0: l += '(';
-                    if param.enum:
-                        # print(f'Enum {param.name} : shift_right={shift_right}, lsb={lsb}, width={param.bit_width}')
-                        if shift_right==0 and lsb==0 and param.bit_width==8:
-                            l += f"{param.name} as u8"
-                        else:
-                            l += f"({param.name} as u8)"
+                        shift_right = sum(p_msb - p_lsb + 1 for _, p_msb, p_lsb in positions[:i])
                     else:
-                        l += param.name
-                    if shift_right!=0:
-                        l += f" >> {shift_right})"
-                    if param.bit_width!=8:
-                        l += f" & 0x{mask:X}"
-                    if lsb!= 0:
-                        l += f') << {lsb}'
-                    if need_cast_u8:
-                        l += ') as u8';
-                    l += ';'
-                    lines.append(l)
-        
-        lines.append("    cmd")
+                        shift_right = param.bit_width - sum(p_msb - p_lsb + 1 for _, p_msb, p_lsb in positions[:i+1])
+                    v = value.strip('()') if shift_right == 0 else f"({value} >> {shift_right})"
+                    if rust_type != 'u8' and not param.enum:
+                        v = f"{v} as u8"
+                    if msb == 7 and lsb == 0:
+                        lines.append(f"        .push_u8({v})")
+                        cursor += 1
+                    else:
+                        lines.append(f"        .bits({v}, {msb - lsb + 1}, {lsb})")
+        lines.append("        .finish()")
     
     lines.append("}")
     return '\n'.join(lines)
@@ -447,30 +468,36 @@ def gen_rsp(cmd: Command, _category: str, advanced: bool = False) -> str:
     generic_rsp = cmd.name != "GetStatus" and len(fields)>0 and len(fields[0].byte_positions)>0 and fields[0].byte_positions[0].byte_index == 0
     
     lines = [f"/// Response for {cmd.name} command"]
-    if not generic_rsp and struct_name != 'GnssReadAlmanacStatusRsp':
+    # Default can only be derived for arrays up to 32 elements
+    if not generic_rsp and buffer_size <= 32:
         lines.append("#[derive(Default)]")
     lines.append(f"pub struct {struct_name}([u8; {buffer_size}]);")
     lines.append("")
     lines.append(f"impl {struct_name} {{")
+    lines.append("    /// Response length in bytes")
+    lines.append(f"    pub const RSP_LEN : usize = {buffer_size};")
 
-    if generic_rsp:
+    lines.append("")
+    lines.append("    /// Create struct from existing response buffer")
+    lines.append("    /// Return an error if the buffer size does not match the response size")
+    lines.append("    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {")
+    lines.append(f"        let raw : [u8; {buffer_size}] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;")
+    lines.append("        Ok(Self(raw))")
+    lines.append("    }")
+    if not generic_rsp:
         lines.append("")
-        lines.append("    /// Create struct from existing response buffer")
-        lines.append("    pub fn from_slice(buffer: &[u8]) -> Self {")
-        lines.append(f"        let raw : [u8; {buffer_size}] = buffer.try_into().expect(\"Buffer size should match response size !\");")
-        lines.append("        Self(raw)")
-        lines.append("    }")
-    else:
         lines.append("    /// Create a new response buffer")
         lines.append("    pub fn new() -> Self {")
         lines.append("        Self::default()")
         lines.append("    }")
         lines.append("")
-        lines.append("    /// Return Status")
-        lines.append("    pub fn status(&mut self) -> Status {")
         if cmd.name == "GetStatus" :
-            lines.append("        Status::from_array([self.0[0], self.0[1]]) ")
+            lines.append("    /// Return Status")
+            lines.append("    pub fn status(&self) -> Status {")
+            lines.append("        Status::from_array([self.0[0], self.0[1]])")
         else :
+            lines.append("    /// Return Status (only the first byte is provided by a response)")
+            lines.append("    pub fn status(&self) -> Status {")
             lines.append("        self.0[0].into()")
         lines.append("    }")
 
@@ -498,6 +525,14 @@ def gen_rsp(cmd: Command, _category: str, advanced: bool = False) -> str:
                 lines.append('    }')
             continue
 
+        # MAC address fields are returned as a MacAddress
+        if name.startswith('mac') and field.bit_width == 48:
+            bytes_list = ', '.join(f'self.0[{pos.byte_index}]' for pos in field.byte_positions)
+            lines.append(f'    pub fn {name}(&self) -> MacAddress {{')
+            lines.append(f'        MacAddress::new([{bytes_list}])')
+            lines.append('    }')
+            continue
+
         # Implementation
         lines.append(f"    pub fn {name}(&self) -> {return_type} {{")
         l : str = '        '
@@ -623,11 +658,12 @@ def gen_rsp(cmd: Command, _category: str, advanced: bool = False) -> str:
         lines.append("        if self.rx_adc_offset() {defmt::write!(f, \"RxAdcOffset \")};")
         lines.append("    }")
         lines.append("}")
-    elif cmd.name == 'GnssReadAlmanacStatus':
-        lines.append("impl Default for GnssReadAlmanacStatusRsp {")
+
+    if not generic_rsp and buffer_size > 32:
+        lines.append("")
+        lines.append(f"impl Default for {struct_name} {{")
         lines.append("    fn default() -> Self {")
-        lines.append("        let content : [u8; 54] = core::array::repeat(0);")
-        lines.append("        Self(content)")
+        lines.append("        Self([0u8; Self::RSP_LEN])")
         lines.append("    }")
         lines.append("}")
     
@@ -643,6 +679,9 @@ def gen_file(category: str, commands: list[Command], output_dir: Path) -> None:
 
     if category=='system':
         lines.append("use crate::status::{Status,Intr};")
+    elif category=='wifi':
+        lines.append("use crate::status::Status;")
+        lines.append("use crate::wifi_scan::MacAddress;")
     elif has_rsp:
         lines.append("use crate::status::Status;")
     
@@ -715,6 +754,9 @@ def gen_file(category: str, commands: list[Command], output_dir: Path) -> None:
             lines.append(gen_req(cmd, category, advanced=True))
             lines.append("")
     
+    if any('CmdWriter::new' in l for l in lines):
+        lines.insert(1, "use super::CmdWriter;")
+
     # Generate response structs
     if has_rsp:
         lines.append("// Response structs")
@@ -750,6 +792,57 @@ def gen_file(category: str, commands: list[Command], output_dir: Path) -> None:
     with open(file_path, 'w') as f:
         _ = f.write('\n'.join(lines))
 
+def catalog_entry(cmd: Command) -> tuple[str, tuple[str, int], str]:
+    """Name, request length and response length of a command, as counted on the bus"""
+    name = cmd.catalog_name or cmd.name
+    # Optional parameters and variable payloads make the request longer than its mandatory part
+    mandatory = [p for p in cmd.parameters if not p.optional and p.bit_width > 0]
+    req_len = max([2] + [pos.byte_index + 1 for p in mandatory for pos in p.byte_positions])
+    variable_req = cmd.catalog_req_variable or len(mandatory) != len(cmd.parameters)
+    req = ('AtLeast' if variable_req else 'Fixed', req_len)
+    if cmd.variable_rsp or cmd.catalog_rsp_variable or any(f.bit_width == 0 for f in cmd.status_fields):
+        rsp = 'Variable'
+    elif cmd.status_fields:
+        rsp = f'Fixed({size_of(cmd.status_fields)})'
+    else:
+        rsp = 'None'
+    return (name, req, rsp)
+
+def gen_catalog(commands: list[Command], output_dir: Path) -> None:
+    """Generate the table of the opcode catalog, sorted by opcode"""
+    file_path = output_dir / "cmd_catalog.rs"
+
+    entries : dict[int, list[tuple[str, tuple[str, int], str]]] = {}
+    for cmd in commands:
+        # Opcode not yet known
+        if cmd.opcode < 0:
+            continue
+        entries.setdefault(cmd.opcode, []).append(catalog_entry(cmd))
+
+    lines = [
+        "// Opcode catalog generated from commands.yaml (types and decoding in catalog.rs)\n",
+        "use super::catalog::{OpcodeInfo, ReqLen, RspLen};",
+        "",
+        "/// All commands known by the driver, sorted by opcode",
+        "pub const OPCODES: &[OpcodeInfo] = &[",
+    ]
+    for opcode in sorted(entries):
+        variants = entries[opcode]
+        names = {name for name, _, _ in variants}
+        if len(names) > 1:
+            raise ValidationError(f"opcode 0x{opcode:04X} shared by {sorted(names)}: set a common catalog name")
+        # Same opcode for several packet types: keep the shortest request and a variable response if they differ
+        reqs = {req for _, req, _ in variants}
+        req_kind, req_len = reqs.pop() if len(reqs) == 1 else ('AtLeast', min(n for _, n in reqs))
+        rsps = {rsp for _, _, rsp in variants}
+        rsp = rsps.pop() if len(rsps) == 1 else 'Variable'
+        lines.append(f'    OpcodeInfo {{ opcode: 0x{opcode:04X}, name: "{names.pop()}", req: ReqLen::{req_kind}({req_len}), rsp: RspLen::{rsp} }},')
+    lines.append("];")
+    lines.append("")
+
+    with open(file_path, 'w') as f:
+        _ = f.write('\n'.join(lines))
+
 def main():
     
     yaml_path = Path(sys.argv[1]) if len(sys.argv) > 1 else Path("./commands.yaml")
@@ -765,6 +858,7 @@ def main():
                 data = yaml.safe_load(f)
             
             # Parse commands
+            all_commands : list[Command] = []
             for category, category_data in data.get('categories', {}).items():
                 print(f'Category {category}')
                 commands : list[Command] = []
@@ -776,7 +870,12 @@ def main():
                         print(f"Error in {yaml_path}:{cmd_name}: {e}", file=sys.stderr)
                         sys.exit(1)
             
-                gen_file(category, commands, output_dir)
+                all_commands.extend(commands)
+                # Commands written by hand are only listed for the opcode catalog
+                if not category_data.get('hand_written', False):
+                    gen_file(category, commands, output_dir)
+
+            gen_catalog(all_commands, output_dir)
 
         else:
             print(f"Error: {yaml_path} is not a file or directory", file=sys.stderr)
@@ -0,0 +1,198 @@
+//! # Resumable almanac update
+//!
+//! A full almanac update received over LoRaWAN is split over many downlinks, possibly across several days
+//! and device reboots. [`AlmanacWriter`] writes the SV blocks with [`gnss_updt_almanac`](Lr1120::gnss_updt_almanac)
+//! as they arrive and tracks which satellites were written. Its [`AlmanacWriterState`] is small (22 bytes)
+//! and can be saved in non-volatile memory after each chunk, then restored after a reboot to continue the update.
+//!
+//! Each chunk is validated before anything is sent to the chip: it must contain whole 20-byte SV blocks
+//! with a satellite identifier below [`ALMANAC_NB_SV`]. Blocks already written (e.g. repeated downlink) are skipped.
+//! The almanac header is sent again before the first block written after a restore.
+//!
+//! Once all satellites are written, [`finish`](AlmanacWriter::finish) compares the CRC computed by the chip
+//! with the one of the header: on mismatch (corrupted chunk, chip reset during the update, ...)
+//! the progress is cleared and the update must be restarted.
+//!
+//! ## Example
+//! ```rust,no_run
+//! let mut writer = match load_state() {
+//!     Some(state) => AlmanacWriter::restore(state),
+//!     None => AlmanacWriter::new(AlmanacHeader::new(date, crc)),
+//! };
+//! // On each almanac downlink
+//! writer.write_chunk(&mut lr1120, &downlink).await?;
+//! save_state(&writer.state().to_bytes());
+//! if writer.is_complete() && !writer.finish(&mut lr1120).await? {
+//!     request_almanac_again();
+//! }
+//! ```
+//!
+//! ## Available Methods
+//! - [`new`](AlmanacWriter::new) - Start a new almanac update
+//! - [`restore`](AlmanacWriter::restore) - Continue an almanac update from a saved state
+//! - [`state`](AlmanacWriter::state) - Progress of the update, to be saved
+//! - [`is_written`](AlmanacWriter::is_written) - Check if the block of a satellite was written
+//! - [`nb_written`](AlmanacWriter::nb_written) - Number of satellites written
+//! - [`is_complete`](AlmanacWriter::is_complete) - Check if all satellites were written
+//! - [`write_chunk`](AlmanacWriter::write_chunk) - Validate and write a chunk of SV blocks
+//! - [`finish`](AlmanacWriter::finish) - Check the almanac CRC once all satellites are written
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiBus;
+
+use super::{BusyPin, Lr1120, Lr1120Error};
+use super::gnss::{AlmanacHeader, AlmanacSv, ALMANAC_SV_SIZE};
+
+/// Number of SV blocks in a full almanac update
+pub const ALMANAC_NB_SV: usize = 128;
+
+/// Size of the serialized writer state
+pub const ALMANAC_WRITER_STATE_SIZE: usize = 22;
+
+/// Progress of an almanac update: header and satellites written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlmanacWriterState {
+    /// Almanac date (days since GPS epoch)
+    pub date: u16,
+    /// Expected almanac CRC
+    pub crc: u32,
+    /// Bitmap of the satellites written, indexed by satellite identifier
+    pub written: [u32; ALMANAC_NB_SV / 32],
+}
+
+impl AlmanacWriterState {
+    /// Serialize the state (big endian)
+    pub fn to_bytes(&self) -> [u8; ALMANAC_WRITER_STATE_SIZE] {
+        let mut bytes = [0; ALMANAC_WRITER_STATE_SIZE];
+        let src = self.date.to_be_bytes().into_iter()
+            .chain(self.crc.to_be_bytes())
+            .chain(self.written.iter().flat_map(|w| w.to_be_bytes()));
+        for (b, v) in bytes.iter_mut().zip(src) {
+            *b = v;
+        }
+        bytes
+    }
+
+    /// Deserialize a state saved with [`to_bytes`](AlmanacWriterState::to_bytes)
+    pub fn from_bytes(bytes: &[u8; ALMANAC_WRITER_STATE_SIZE]) -> Self {
+        let [d0, d1, c0, c1, c2, c3, bitmap @ ..] = *bytes;
+        let mut written = [0; ALMANAC_NB_SV / 32];
+        for (w, b) in written.iter_mut().zip(bitmap.chunks_exact(4)) {
+            *w = b.iter().fold(0, |acc, v| (acc << 8) | *v as u32);
+        }
+        Self {
+            date: u16::from_be_bytes([d0, d1]),
+            crc: u32::from_be_bytes([c0, c1, c2, c3]),
+            written,
+        }
+    }
+}
+
+/// Resumable writer of a full almanac update
+#[derive(Debug, Clone)]
+pub struct AlmanacWriter {
+    state: AlmanacWriterState,
+    /// Header sent to the chip since the writer was created
+    hdr_sent: bool,
+}
+
+impl AlmanacWriter {
+    /// Start a new almanac update
+    pub fn new(hdr: AlmanacHeader) -> Self {
+        Self::restore(AlmanacWriterState { date: hdr.date, crc: hdr.crc, written: [0; ALMANAC_NB_SV / 32] })
+    }
+
+    /// Continue an almanac update from a saved state
+    pub fn restore(state: AlmanacWriterState) -> Self {
+        Self { state, hdr_sent: false }
+    }
+
+    /// Progress of the update, to be saved to continue after a reboot
+    pub fn state(&self) -> AlmanacWriterState {
+        self.state
+    }
+
+    /// Almanac header of the update
+    pub fn header(&self) -> AlmanacHeader {
+        AlmanacHeader::new(self.state.date, self.state.crc)
+    }
+
+    /// Check if the block of a satellite was written
+    pub fn is_written(&self, sv_id: u8) -> bool {
+        let idx = sv_id as usize;
+        self.state.written.get(idx / 32).is_some_and(|w| w & (1 << (idx % 32)) != 0)
+    }
+
+    /// Number of satellites written
+    pub fn nb_written(&self) -> usize {
+        self.state.written.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Check if all satellites were written
+    pub fn is_complete(&self) -> bool {
+        self.nb_written() == ALMANAC_NB_SV
+    }
+
+    /// Validate a chunk of 20-byte SV blocks and write the ones not yet written
+    /// Return the number of blocks written, InvalidSize if the chunk is empty or does not contain whole blocks,
+    /// and InvalidParam if a satellite identifier is out of range (nothing is written in both cases).
+    /// Return LowBattery when the battery is too low for a flash write (see [`set_flash_guard`](Lr1120::set_flash_guard))
+    pub async fn write_chunk<O, SPI, M>(&mut self, lr: &mut Lr1120<O,SPI,M>, chunk: &[u8]) -> Result<usize, Lr1120Error>
+        where O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+    {
+        if chunk.is_empty() || !chunk.len().is_multiple_of(ALMANAC_SV_SIZE) {
+            return Err(Lr1120Error::InvalidSize);
+        }
+        let blocks = chunk.chunks_exact(ALMANAC_SV_SIZE)
+            .filter_map(|b| b.try_into().ok())
+            .map(AlmanacSv::from_bytes);
+        if blocks.clone().any(|sv| sv.sv_id as usize >= ALMANAC_NB_SV) {
+            return Err(Lr1120Error::InvalidParam);
+        }
+        // Skip blocks already written, and repeated in the chunk
+        let mut written = self.state.written;
+        let nb = blocks.clone().filter(|sv| set_bit(&mut written, sv.sv_id)).count();
+        if nb == 0 {
+            return Ok(0);
+        }
+        lr.check_flash_write().await?;
+        if !self.hdr_sent {
+            lr.gnss_updt_almanac_hdr(&self.header()).await?;
+            self.hdr_sent = true;
+        }
+        let mut sent = self.state.written;
+        lr.gnss_updt_almanac_sv(blocks.filter(|sv| set_bit(&mut sent, sv.sv_id)), |_| {}).await?;
+        self.state.written = written;
+        Ok(nb)
+    }
+
+    /// Check the almanac CRC computed by the chip once all satellites are written
+    /// Return InvalidConfig if some satellites are missing. On CRC mismatch the progress is cleared
+    /// so that the update restarts from the header, and false is returned
+    pub async fn finish<O, SPI, M>(&mut self, lr: &mut Lr1120<O,SPI,M>) -> Result<bool, Lr1120Error>
+        where O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+    {
+        if !self.is_complete() {
+            return Err(Lr1120Error::InvalidConfig);
+        }
+        let ok = lr.gnss_check_almanac_crc(self.state.crc).await?;
+        if !ok {
+            self.state.written = [0; ALMANAC_NB_SV / 32];
+            self.hdr_sent = false;
+        }
+        Ok(ok)
+    }
+}
+
+/// Set the bit of a satellite in a bitmap, return true if it was not set
+fn set_bit(bitmap: &mut [u32; ALMANAC_NB_SV / 32], sv_id: u8) -> bool {
+    let idx = sv_id as usize;
+    match bitmap.get_mut(idx / 32) {
+        Some(w) if *w & (1 << (idx % 32)) == 0 => {
+            *w |= 1 << (idx % 32);
+            true
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,212 @@
+//! # LoRaWAN Class-B beacon reception
+//!
+//! LoRaWAN Class-B gateways broadcast a beacon every 128s, starting 1.5ms after a GPS time multiple of 128s.
+//! The beacon uses fixed radio settings (LoRa SF9/BW125 at 869.525MHz in EU868): implicit header without PHY CRC,
+//! 10 symbols preamble and the public syncword. The payload carries the GPS time of the beacon protected by its own CRC,
+//! followed by optional gateway information (e.g. GPS coordinates).
+//!
+//! The LR1120 has no scheduled RX command: the reception window is opened at the right time using `embassy-time`,
+//! from a reference between GPS time and local time (e.g. a previous beacon or a GNSS time fetch).
+//!
+//! ## Example
+//! ```rust,no_run
+//! let cfg = BeaconCfg::eu868();
+//! lr1120.set_beacon_rx_cfg(&cfg).await?;
+//! let (time, at) = next_beacon(gps_now, Instant::now());
+//! lr1120.beacon_rx(&cfg, at, Duration::from_millis(20)).await?;
+//! // Wait for RxDone or Timeout on the IRQ pin
+//! let beacon = lr1120.get_beacon(&cfg, intr).await?;
+//! ```
+//!
+//! ## Available Methods
+//! - [`set_beacon_rx_cfg`](Lr1120::set_beacon_rx_cfg) - Configure the radio for beacon reception
+//! - [`beacon_rx`](Lr1120::beacon_rx) - Open the reception window around the expected beacon
+//! - [`get_beacon`](Lr1120::get_beacon) - Read and parse the beacon received
+//! - [`next_beacon`] - GPS time and local instant of the next beacon
+//! - [`Beacon::parse`] - Parse a beacon payload
+
+use embassy_time::{Duration, Instant, Timer};
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiBus;
+
+use super::{BusyPin, Lr1120, Lr1120Error};
+use super::gps_time::GpsTime;
+use super::lora::{HeaderType, LoraBw, LoraModulationParams, LoraPacketParams, Sf};
+use super::radio::PacketType;
+use super::status::Intr;
+use super::system::duration_to_lf_steps;
+
+/// Beacon period in seconds
+pub const BEACON_PERIOD_S: u32 = 128;
+/// Delay between the beacon period start and the beacon transmission
+pub const BEACON_TX_DELAY: Duration = Duration::from_micros(1500);
+/// Beacon preamble length in symbols
+pub const BEACON_PREAMBLE_LEN: u16 = 10;
+/// LoRaWAN public syncword
+const BEACON_SYNCWORD: u8 = 0x34;
+/// Size of the time field
+const BEACON_TIME_LEN: usize = 4;
+/// Size of the gateway specific field (info descriptor and info)
+const BEACON_GW_LEN: usize = 7;
+/// Size of a CRC field
+const BEACON_CRC_LEN: usize = 2;
+
+/// Beacon radio settings and frame format
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BeaconCfg {
+    /// RF frequency in Hz
+    pub freq: u32,
+    /// Spreading factor
+    pub sf: Sf,
+    /// Bandwidth
+    pub bw: LoraBw,
+    /// Number of RFU bytes before the time field
+    pub rfu1_len: u8,
+    /// Number of RFU bytes after the gateway specific field
+    pub rfu2_len: u8,
+}
+
+impl BeaconCfg {
+    /// EU868 beacon: SF9/BW125 at 869.525MHz (17 bytes)
+    pub fn eu868() -> Self {
+        Self { freq: 869_525_000, sf: Sf::Sf9, bw: LoraBw::Bw125, rfu1_len: 2, rfu2_len: 0 }
+    }
+
+    /// US915 beacon: SF12/BW500 (23 bytes), on one of the 8 beacon channels (923.3MHz + 600kHz*n)
+    pub fn us915(channel: u8) -> Self {
+        let freq = 923_300_000 + 600_000 * (channel & 7) as u32;
+        Self { freq, sf: Sf::Sf12, bw: LoraBw::Bw500, rfu1_len: 5, rfu2_len: 3 }
+    }
+
+    /// Beacon length in bytes
+    pub fn frame_len(&self) -> usize {
+        self.rfu1_len as usize + BEACON_TIME_LEN + BEACON_CRC_LEN
+            + BEACON_GW_LEN + self.rfu2_len as usize + BEACON_CRC_LEN
+    }
+
+    /// Modulation parameters (coding rate 4/5, LDRO based on SF/BW)
+    pub fn modulation(&self) -> LoraModulationParams {
+        LoraModulationParams::basic(self.sf, self.bw)
+    }
+
+    /// Packet parameters: implicit header without CRC
+    pub fn packet(&self) -> LoraPacketParams {
+        LoraPacketParams::new(BEACON_PREAMBLE_LEN, self.frame_len() as u8, HeaderType::Implicit, false, false)
+    }
+
+    /// Preamble duration
+    pub fn preamble_duration(&self) -> Duration {
+        let symb_us = (1_000_000u64 << (self.sf as u8)) / self.bw.to_hz() as u64;
+        Duration::from_micros(symb_us * BEACON_PREAMBLE_LEN as u64)
+    }
+}
+
+/// Gateway information carried by the beacon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BeaconGwInfo {
+    /// Information descriptor (0 to 2: GPS coordinates of the antenna 1 to 3)
+    pub info_desc: u8,
+    /// Latitude: 24-bit signed, degree = lat * 90 / 2^23
+    pub lat: i32,
+    /// Longitude: 24-bit signed, degree = lon * 180 / 2^23
+    pub lon: i32,
+}
+
+/// Beacon content
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Beacon {
+    /// GPS time of the beacon period start (modulo 2^32)
+    pub time: GpsTime,
+    /// Gateway information (None if its CRC is invalid)
+    pub gw: Option<BeaconGwInfo>,
+}
+
+/// CRC-16 CCITT (polynomial 0x1021, initial value 0) used by the beacon
+fn beacon_crc(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0u16, |crc, b| {
+        (0..8).fold(crc ^ ((*b as u16) << 8), |c, _| {
+            if c & 0x8000 != 0 {(c << 1) ^ 0x1021} else {c << 1}
+        })
+    })
+}
+
+/// Sign-extend a 24-bit little-endian value
+fn i24_le(bytes: [u8; 3]) -> i32 {
+    (i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]])) >> 8
+}
+
+/// First N bytes of a slice, InvalidSize if the slice is too short
+fn le_array<const N: usize>(bytes: &[u8]) -> Result<[u8; N], Lr1120Error> {
+    bytes.get(..N).and_then(|b| b.try_into().ok()).ok_or(Lr1120Error::InvalidSize)
+}
+
+impl Beacon {
+    /// Parse a beacon payload
+    /// Return InvalidSize if the length does not match the configuration and CrcError if the time CRC is invalid
+    pub fn parse(bytes: &[u8], cfg: &BeaconCfg) -> Result<Self, Lr1120Error> {
+        if bytes.len() != cfg.frame_len() {
+            return Err(Lr1120Error::InvalidSize);
+        }
+        let (common, gw) = bytes.split_at(cfg.rfu1_len as usize + BEACON_TIME_LEN + BEACON_CRC_LEN);
+        let (common, crc) = common.split_at(common.len() - BEACON_CRC_LEN);
+        if beacon_crc(common) != u16::from_le_bytes(le_array(crc)?) {
+            return Err(Lr1120Error::CrcError);
+        }
+        let time = common.get(cfg.rfu1_len as usize..).ok_or(Lr1120Error::InvalidSize)?;
+        let time = GpsTime(u32::from_le_bytes(le_array(time)?));
+        let (gw, crc) = gw.split_at(gw.len() - BEACON_CRC_LEN);
+        let gw_crc_ok = beacon_crc(gw) == u16::from_le_bytes(le_array(crc)?);
+        let gw = match gw {
+            [info_desc, lat0, lat1, lat2, lon0, lon1, lon2, ..] if gw_crc_ok => Some(BeaconGwInfo {
+                info_desc: *info_desc,
+                lat: i24_le([*lat0, *lat1, *lat2]),
+                lon: i24_le([*lon0, *lon1, *lon2]),
+            }),
+            _ => None,
+        };
+        Ok(Beacon { time, gw })
+    }
+}
+
+/// Return the GPS time of the next beacon and the local instant of its transmission start
+/// `gps_now` and `now` are the current GPS time and the corresponding local instant
+pub fn next_beacon(gps_now: GpsTime, now: Instant) -> (GpsTime, Instant) {
+    let next = (gps_now.0 / BEACON_PERIOD_S + 1).wrapping_mul(BEACON_PERIOD_S);
+    let at = now + Duration::from_secs(next.wrapping_sub(gps_now.0) as u64) + BEACON_TX_DELAY;
+    (GpsTime(next), at)
+}
+
+impl<O,SPI, M> Lr1120<O,SPI, M> where
+    O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+{
+
+    /// Configure the radio for beacon reception
+    pub async fn set_beacon_rx_cfg(&mut self, cfg: &BeaconCfg) -> Result<(), Lr1120Error> {
+        self.set_packet_type(PacketType::Lora).await?;
+        self.set_rf(cfg.freq).await?;
+        self.set_lora_modulation(&cfg.modulation()).await?;
+        self.set_lora_packet(&cfg.packet()).await?;
+        self.set_lora_syncword(BEACON_SYNCWORD).await
+    }
+
+    /// Wait until `guard` before the beacon expected at `at` (see [`next_beacon`]) and start the reception
+    /// The reception times out if no preamble is detected within `guard` after the expected beacon start
+    pub async fn beacon_rx(&mut self, cfg: &BeaconCfg, at: Instant, guard: Duration) -> Result<(), Lr1120Error> {
+        Timer::at(at.checked_sub(guard).unwrap_or(at)).await;
+        let window = guard * 2 + cfg.preamble_duration();
+        let timeout = duration_to_lf_steps(window).min(0xFFFFFE) as u32;
+        self.set_rx(timeout, false).await
+    }
+
+    /// Read and parse the beacon received
+    /// `intr` is the interrupt status read at the end of the reception
+    pub async fn get_beacon(&mut self, cfg: &BeaconCfg, intr: Intr) -> Result<Beacon, Lr1120Error> {
+        // No PHY CRC: integrity is checked by the beacon CRC
+        let payload = self.get_lora_payload(intr, true).await?;
+        Beacon::parse(payload.data, cfg)
+    }
+
+}
@@ -0,0 +1,166 @@
+//! # Bootloader mode
+//!
+//! After a reboot with [`enter_bootloader`](Lr1120::enter_bootloader), the chip runs its bootloader instead of the firmware
+//! and only accepts the bootloader command set (see [`cmd_bootloader`](crate::cmd::cmd_bootloader)):
+//! flash erase and write of an encrypted firmware image, hash of the image, and the device identifiers
+//! (PIN, ChipEui, JoinEui). The methods below first read the status and return `InvalidConfig`
+//! unless [`Status::context`](crate::status::Status::context) reports the bootloader.
+//!
+//! The image words come from a firmware update file parsed with [`fw_image`](crate::fw_image).
+//! The bootloader reports its own chip type: check the image target with the type read before entering the bootloader.
+//!
+//! ## Example
+//! ```rust,no_run
+//! let image = FwImage::parse(include_bytes!("lr1120_transceiver_0201.h"))?;
+//! image.info().check_target(lr1120.get_version().await?.hw_type())?;
+//! lr1120.enter_bootloader().await?;
+//! lr1120.bl_erase_flash().await?;
+//! lr1120.bl_write_image(&image).await?;
+//! lr1120.bl_reboot(false).await?;
+//! let version = lr1120.get_version().await?;
+//! ```
+//!
+//! ## Available Methods
+//! - [`enter_bootloader`](Lr1120::enter_bootloader) - Reboot the chip in bootloader mode
+//! - [`bl_erase_flash`](Lr1120::bl_erase_flash) - Erase the firmware image
+//! - [`bl_write_flash`](Lr1120::bl_write_flash) - Write a chunk of an encrypted firmware image
+//! - [`bl_write_image`](Lr1120::bl_write_image) / [`bl_write_image_from`](Lr1120::bl_write_image_from) - Write a complete firmware image from a parsed file held in memory or streamed from a reader
+//! - [`bl_get_hash`](Lr1120::bl_get_hash) - Read the hash of the firmware image
+//! - [`bl_get_pin`](Lr1120::bl_get_pin) - Read the device PIN
+//! - [`bl_get_chip_eui`](Lr1120::bl_get_chip_eui) / [`bl_get_join_eui`](Lr1120::bl_get_join_eui) - Read the EUIs
+//! - [`bl_reboot`](Lr1120::bl_reboot) - Leave the bootloader and start the firmware
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiBus;
+use embedded_io_async::Read;
+
+use super::{BusyPin, Lr1120, Lr1120Error};
+use super::cmd::cmd_bootloader::*;
+use super::cmd::cmd_system::{self, ChipEuiRsp, SemtechJoinEuiRsp, StayInBootloader};
+use super::fw_image::{FwImage, FwImageReader};
+use super::latency;
+use super::status::ExecutionContext;
+
+impl<O,SPI, M> Lr1120<O,SPI, M> where
+    O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+{
+
+    /// Reboot the chip in bootloader mode. The LF clock configuration is retained
+    pub async fn enter_bootloader(&mut self) -> Result<(), Lr1120Error> {
+        let req = cmd_system::reboot_cmd(StayInBootloader::BootloaderMode);
+        self.cmd_wr(&req).await?;
+        let lf_clk = self.lf_clk;
+        self.clear_chip_state();
+        self.lf_clk = lf_clk;
+        Ok(())
+    }
+
+    /// Return InvalidConfig unless the chip runs the bootloader
+    pub(crate) async fn check_bootloader(&mut self) -> Result<(), Lr1120Error> {
+        let (status, _) = self.get_status().await?;
+        match status.context() {
+            Some(ExecutionContext::Bootloader) => Ok(()),
+            _ => Err(Lr1120Error::InvalidConfig),
+        }
+    }
+
+    /// Erase the firmware image and wait for the end of the erase (see [`latency::FLASH_ERASE`])
+    pub async fn bl_erase_flash(&mut self) -> Result<(), Lr1120Error> {
+        self.check_bootloader().await?;
+        self.cmd_wr(&erase_flash_cmd()).await?;
+        self.wait_ready(latency::FLASH_ERASE).await
+    }
+
+    /// Write a chunk of an encrypted firmware image at a byte offset (multiple of 4)
+    /// Return InvalidSize if the chunk is empty or longer than [`FLASH_WRITE_WORDS_MAX`] words
+    pub async fn bl_write_flash(&mut self, offset: u32, words: &[u32]) -> Result<(), Lr1120Error> {
+        if words.is_empty() || words.len() > FLASH_WRITE_WORDS_MAX {
+            return Err(Lr1120Error::InvalidSize);
+        }
+        if !offset.is_multiple_of(4) {
+            return Err(Lr1120Error::InvalidParam);
+        }
+        self.check_bootloader().await?;
+        let mut data = [0u8; 4 * FLASH_WRITE_WORDS_MAX];
+        for (d, w) in data.chunks_exact_mut(4).zip(words) {
+            d.copy_from_slice(&w.to_be_bytes());
+        }
+        let req = write_flash_encrypted_cmd(offset);
+        self.cmd_data_wr(&req, data.get(..4 * words.len()).unwrap_or_default()).await
+    }
+
+    /// Write a complete firmware image held in memory, after [`bl_erase_flash`](Lr1120::bl_erase_flash)
+    /// Return the number of words written, or the parsing error of the image (the flash is then partially written)
+    pub async fn bl_write_image(&mut self, image: &FwImage<'_>) -> Result<u32, Lr1120Error> {
+        let mut words = image.words();
+        let mut chunk = [0u32; FLASH_WRITE_WORDS_MAX];
+        let mut nb_words = 0;
+        loop {
+            let mut len = 0;
+            for (c, w) in chunk.iter_mut().zip(words.by_ref()) {
+                *c = w?;
+                len += 1;
+            }
+            if len == 0 {
+                return Ok(nb_words);
+            }
+            self.bl_write_flash(4 * nb_words, chunk.get(..len).unwrap_or_default()).await?;
+            nb_words += len as u32;
+        }
+    }
+
+    /// Write a complete firmware image streamed from a reader, after [`bl_erase_flash`](Lr1120::bl_erase_flash)
+    /// Return the number of words written, or the parsing/reader error of the image (the flash is then partially written)
+    pub async fn bl_write_image_from<R: Read>(&mut self, image: &mut FwImageReader<R>) -> Result<u32, Lr1120Error> {
+        let mut chunk = [0u32; FLASH_WRITE_WORDS_MAX];
+        let mut nb_words = 0;
+        loop {
+            let len = image.read_words(&mut chunk).await?;
+            if len == 0 {
+                return Ok(nb_words);
+            }
+            self.bl_write_flash(4 * nb_words, chunk.get(..len).unwrap_or_default()).await?;
+            nb_words += len as u32;
+        }
+    }
+
+    /// Read the hash of the firmware image
+    pub async fn bl_get_hash(&mut self) -> Result<[u8; 16], Lr1120Error> {
+        self.check_bootloader().await?;
+        let mut rsp = HashRsp::new();
+        self.cmd_rd(&get_hash_req(), rsp.as_mut()).await?;
+        Ok(rsp.hash())
+    }
+
+    /// Read the PIN used to claim the device
+    pub async fn bl_get_pin(&mut self) -> Result<u32, Lr1120Error> {
+        self.check_bootloader().await?;
+        let mut rsp = PinRsp::new();
+        self.cmd_rd(&get_pin_req(), rsp.as_mut()).await?;
+        Ok(rsp.pin())
+    }
+
+    /// Read the Chip EUI
+    pub async fn bl_get_chip_eui(&mut self) -> Result<u64, Lr1120Error> {
+        self.check_bootloader().await?;
+        let mut rsp = ChipEuiRsp::new();
+        self.cmd_rd(&read_chip_eui_req(), rsp.as_mut()).await?;
+        Ok(rsp.chip_eui())
+    }
+
+    /// Read the Semtech Join EUI
+    pub async fn bl_get_join_eui(&mut self) -> Result<u64, Lr1120Error> {
+        self.check_bootloader().await?;
+        let mut rsp = SemtechJoinEuiRsp::new();
+        self.cmd_rd(&read_join_eui_req(), rsp.as_mut()).await?;
+        Ok(rsp.semtech_join_eui())
+    }
+
+    /// Leave the bootloader and start the firmware, or restart in the bootloader if `stay_in_bootloader` is set
+    pub async fn bl_reboot(&mut self, stay_in_bootloader: bool) -> Result<(), Lr1120Error> {
+        self.check_bootloader().await?;
+        let stay = if stay_in_bootloader {StayInBootloader::BootloaderMode} else {StayInBootloader::SoftwareRestart};
+        self.cmd_wr(&reboot_cmd(stay)).await
+    }
+
+}
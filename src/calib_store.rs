@@ -0,0 +1,241 @@
+//! # Per-board calibration storage
+//!
+//! Some calibrations are specific to a board design or to a device and are measured once (in production or on the field):
+//! RSSI gain offsets, ranging TxRx delay, crystal trimming and the image calibration bands of the deployment.
+//! The [`CalibrationStore`] trait loads and saves them as small blobs, so that a board support crate can
+//! plug its own non-volatile memory backend. [`load_calibration`](Lr1120::load_calibration) consults the store
+//! and applies the calibrations found: call it at init, after the reset and the TCXO configuration.
+//!
+//! Two implementations are provided: [`NoCalibrationStore`] (nothing stored, nothing applied)
+//! and [`RamCalibrationStore`] (kept in RAM, e.g. to prepare the blobs before writing them to flash, or for tests).
+//!
+//! | Calibration | Blob | Applied with |
+//! |-------------|------|--------------|
+//! | [`Rssi`](CalibrationId::Rssi) | [`RssiCalibration::to_bytes`] | [`set_rssi_calibration`](Lr1120::set_rssi_calibration) |
+//! | [`RangingDelay`](CalibrationId::RangingDelay) | u32 big endian | [`set_ranging_txrx_delay`](Lr1120::set_ranging_txrx_delay) |
+//! | [`XtalTrim`](CalibrationId::XtalTrim) | board specific value, up to 4 bytes big endian | Not applied: returned in [`CalibrationLoaded`] |
+//! | [`ImageBands`](CalibrationId::ImageBands) | (min, max) pairs, up to [`IMAGE_BANDS_MAX`] | [`calib_image`](Lr1120::calib_image) on each band |
+//!
+//! The LR1120 has no command to trim its crystal: the trim value is only stored, for board code
+//! driving an external trimming circuit.
+//!
+//! ## Example
+//! ```rust,no_run
+//! lr1120.reset().await?;
+//! lr1120.init_tcxo(TcxoVoltage::Tcxo1v8, Duration::from_millis(5)).await?;
+//! let loaded = lr1120.load_calibration(&mut board_nvm).await?;
+//! if !loaded.ranging_delay {
+//!     let delay = calibrate_ranging().await;
+//!     board_nvm.save_ranging_delay(delay)?;
+//! }
+//! ```
+//!
+//! ## Available Methods
+//! - [`load_calibration`](Lr1120::load_calibration) - Load the calibrations from a store and apply them
+//! - [`CalibrationStore::load`] / [`CalibrationStore::save`] - Load/save a raw calibration blob
+//! - [`save_rssi_calibration`](CalibrationStore::save_rssi_calibration), [`save_ranging_delay`](CalibrationStore::save_ranging_delay),
+//!   [`save_xtal_trim`](CalibrationStore::save_xtal_trim), [`save_image_bands`](CalibrationStore::save_image_bands) - Save a calibration in the blob format expected by the driver
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiBus;
+
+use super::{BusyPin, Lr1120, Lr1120Error};
+use super::radio::{RssiCalibration, RSSI_CALIBRATION_SIZE};
+use super::system::FreqBand;
+
+/// Maximum number of image calibration bands stored
+pub const IMAGE_BANDS_MAX: usize = 4;
+
+/// Maximum size of a calibration blob
+pub const CALIBRATION_BLOB_MAX: usize = 10;
+
+/// Number of calibration identifiers
+const NB_CALIBRATION: usize = 4;
+
+/// Calibration identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CalibrationId {
+    /// RSSI gain offsets of the board design
+    Rssi = 0,
+    /// Ranging TxRx delay
+    RangingDelay = 1,
+    /// Crystal trimming (board specific, not applied by the driver)
+    XtalTrim = 2,
+    /// Image calibration bands
+    ImageBands = 3,
+}
+
+impl CalibrationId {
+    /// Maximum size of the blob
+    pub const fn max_len(&self) -> usize {
+        match self {
+            CalibrationId::Rssi => RSSI_CALIBRATION_SIZE,
+            CalibrationId::RangingDelay => 4,
+            CalibrationId::XtalTrim => 4,
+            CalibrationId::ImageBands => 2 * IMAGE_BANDS_MAX,
+        }
+    }
+}
+
+/// Non-volatile storage of the calibration blobs
+pub trait CalibrationStore {
+    /// Load a calibration blob into `buf` (at least [`max_len`](CalibrationId::max_len) bytes)
+    /// Return the size of the blob, or None if it is not stored
+    fn load(&mut self, id: CalibrationId, buf: &mut [u8]) -> Result<Option<usize>, Lr1120Error>;
+
+    /// Save a calibration blob
+    fn save(&mut self, id: CalibrationId, data: &[u8]) -> Result<(), Lr1120Error>;
+
+    /// Save the RSSI calibration
+    fn save_rssi_calibration(&mut self, cal: &RssiCalibration) -> Result<(), Lr1120Error> {
+        self.save(CalibrationId::Rssi, &cal.to_bytes())
+    }
+
+    /// Save the ranging TxRx delay
+    fn save_ranging_delay(&mut self, delay: u32) -> Result<(), Lr1120Error> {
+        self.save(CalibrationId::RangingDelay, &delay.to_be_bytes())
+    }
+
+    /// Save the crystal trimming value
+    fn save_xtal_trim(&mut self, trim: u32) -> Result<(), Lr1120Error> {
+        self.save(CalibrationId::XtalTrim, &trim.to_be_bytes())
+    }
+
+    /// Save the image calibration bands
+    /// Return InvalidSize with more than [`IMAGE_BANDS_MAX`] bands
+    fn save_image_bands(&mut self, bands: &[FreqBand]) -> Result<(), Lr1120Error> {
+        if bands.len() > IMAGE_BANDS_MAX {
+            return Err(Lr1120Error::InvalidSize);
+        }
+        let mut blob = [0; 2 * IMAGE_BANDS_MAX];
+        for (b, band) in blob.chunks_exact_mut(2).zip(bands) {
+            b.copy_from_slice(&[band.min(), band.max()]);
+        }
+        self.save(CalibrationId::ImageBands, blob.get(..2 * bands.len()).unwrap_or_default())
+    }
+}
+
+/// Store without any calibration: nothing is applied and saves are dropped
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoCalibrationStore;
+
+impl CalibrationStore for NoCalibrationStore {
+    fn load(&mut self, _id: CalibrationId, _buf: &mut [u8]) -> Result<Option<usize>, Lr1120Error> {
+        Ok(None)
+    }
+
+    fn save(&mut self, _id: CalibrationId, _data: &[u8]) -> Result<(), Lr1120Error> {
+        Ok(())
+    }
+}
+
+/// Calibrations kept in RAM
+#[derive(Debug, Clone, Default)]
+pub struct RamCalibrationStore {
+    blobs: [Option<heapless::Vec<u8, CALIBRATION_BLOB_MAX>>; NB_CALIBRATION],
+}
+
+impl RamCalibrationStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Blob stored for a calibration
+    pub fn get(&self, id: CalibrationId) -> Option<&[u8]> {
+        self.blobs.get(id as usize)?.as_deref()
+    }
+
+    /// Remove a calibration
+    pub fn remove(&mut self, id: CalibrationId) {
+        if let Some(blob) = self.blobs.get_mut(id as usize) {
+            *blob = None;
+        }
+    }
+}
+
+impl CalibrationStore for RamCalibrationStore {
+    fn load(&mut self, id: CalibrationId, buf: &mut [u8]) -> Result<Option<usize>, Lr1120Error> {
+        let Some(blob) = self.get(id) else {
+            return Ok(None);
+        };
+        buf.get_mut(..blob.len())
+            .ok_or(Lr1120Error::InvalidSize)?
+            .copy_from_slice(blob);
+        Ok(Some(blob.len()))
+    }
+
+    /// Return InvalidSize if the blob is longer than [`max_len`](CalibrationId::max_len)
+    fn save(&mut self, id: CalibrationId, data: &[u8]) -> Result<(), Lr1120Error> {
+        if data.len() > id.max_len() {
+            return Err(Lr1120Error::InvalidSize);
+        }
+        let blob = heapless::Vec::from_slice(data).map_err(|_| Lr1120Error::InvalidSize)?;
+        let slot = self.blobs.get_mut(id as usize).ok_or(Lr1120Error::InvalidParam)?;
+        *slot = Some(blob);
+        Ok(())
+    }
+}
+
+/// Calibrations found in the store and applied
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CalibrationLoaded {
+    /// RSSI calibration applied
+    pub rssi: bool,
+    /// Ranging TxRx delay applied (never without the `ranging` feature)
+    pub ranging_delay: bool,
+    /// Number of image calibration bands calibrated
+    pub image_bands: u8,
+    /// Crystal trimming value, to be applied by the board
+    pub xtal_trim: Option<u32>,
+}
+
+impl<O,SPI, M> Lr1120<O,SPI, M> where
+    O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+{
+
+    /// Load the calibrations from a store and apply them (to be called at init, after the TCXO configuration)
+    /// The image calibration ends in standby RC. Return InvalidSize if a blob has an unexpected length
+    pub async fn load_calibration<S: CalibrationStore>(&mut self, store: &mut S) -> Result<CalibrationLoaded, Lr1120Error> {
+        let mut loaded = CalibrationLoaded::default();
+        let mut buf = [0; CALIBRATION_BLOB_MAX];
+
+        if let Some(len) = store.load(CalibrationId::Rssi, &mut buf)? {
+            let blob = buf.get(..len).and_then(|b| b.try_into().ok()).ok_or(Lr1120Error::InvalidSize)?;
+            self.set_rssi_calibration(&RssiCalibration::from_bytes(blob)).await?;
+            loaded.rssi = true;
+        }
+
+        #[cfg(feature = "ranging")]
+        if let Some(len) = store.load(CalibrationId::RangingDelay, &mut buf)? {
+            let blob = buf.get(..len).and_then(|b| b.try_into().ok()).ok_or(Lr1120Error::InvalidSize)?;
+            self.set_ranging_txrx_delay(u32::from_be_bytes(blob)).await?;
+            loaded.ranging_delay = true;
+        }
+
+        if let Some(len) = store.load(CalibrationId::XtalTrim, &mut buf)? {
+            if len > CalibrationId::XtalTrim.max_len() {
+                return Err(Lr1120Error::InvalidSize);
+            }
+            let blob = buf.get(..len).unwrap_or_default();
+            loaded.xtal_trim = Some(blob.iter().fold(0, |acc, b| (acc << 8) | *b as u32));
+        }
+
+        if let Some(len) = store.load(CalibrationId::ImageBands, &mut buf)? {
+            if !len.is_multiple_of(2) || len > CalibrationId::ImageBands.max_len() {
+                return Err(Lr1120Error::InvalidSize);
+            }
+            for band in buf.get(..len).unwrap_or_default().chunks_exact(2) {
+                if let [min, max] = *band {
+                    self.calib_image(FreqBand::new(min, max)).await?;
+                    loaded.image_bands += 1;
+                }
+            }
+        }
+
+        Ok(loaded)
+    }
+
+}
@@ -0,0 +1,132 @@
+//! # Opcode catalog
+//!
+//! Name and expected lengths of each command known by the driver, to turn raw SPI traces into readable logs
+//! (e.g. in host-side tooling capturing the bus, or when logging the commands sent).
+//! Lengths are counted on the bus: a request includes its 2-byte opcode and a response starts with the status byte.
+//! The table is generated from `commands.yaml` by `yaml_to_rs.py`, like the command builders.
+//!
+//! ## Example
+//! ```rust,no_run
+//! let cmd = decode(&[0x02, 0x0B, 0x33, 0xBC, 0xA1, 0x00])?;
+//! assert_eq!(cmd.name(), "SetRfFrequency");
+//! println!("{cmd}"); // SetRfFrequency (0x020B): 33 BC A1 00
+//! ```
+//!
+//! ## Available Methods
+//! - [`lookup`] - Return the catalog entry of an opcode
+//! - [`decode`] - Split a raw command into its catalog entry and parameters
+
+use core::fmt;
+
+use crate::Lr1120Error;
+
+/// Expected length of a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReqLen {
+    /// Fixed number of bytes
+    Fixed(u8),
+    /// Fixed header followed by a variable length payload (or length depending on the packet type)
+    AtLeast(u8),
+}
+
+impl ReqLen {
+    /// Check if a request length is valid
+    pub const fn accepts(&self, len: usize) -> bool {
+        match *self {
+            ReqLen::Fixed(n) => len == n as usize,
+            ReqLen::AtLeast(n) => len >= n as usize,
+        }
+    }
+}
+
+/// Expected length of a response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RspLen {
+    /// Write-only command
+    None,
+    /// Fixed number of bytes, including the status byte
+    Fixed(u8),
+    /// Length given by the request parameters, the packet type or a previous response
+    Variable,
+}
+
+/// Catalog entry of an opcode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OpcodeInfo {
+    /// 16-bit opcode
+    pub opcode: u16,
+    /// Command name from the specification
+    pub name: &'static str,
+    /// Expected request length
+    pub req: ReqLen,
+    /// Expected response length
+    pub rsp: RspLen,
+}
+
+/// All commands known by the driver, sorted by opcode (generated from `commands.yaml`)
+pub use super::cmd_catalog::OPCODES;
+
+/// Return the catalog entry of an opcode
+pub fn lookup(opcode: u16) -> Option<&'static OpcodeInfo> {
+    OPCODES.binary_search_by_key(&opcode, |info| info.opcode)
+        .ok()
+        .and_then(|idx| OPCODES.get(idx))
+}
+
+/// Raw command split into its opcode and parameters
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DecodedCmd<'a> {
+    /// 16-bit opcode
+    pub opcode: u16,
+    /// Catalog entry (None for an opcode unknown to the driver)
+    pub info: Option<&'static OpcodeInfo>,
+    /// Parameters following the opcode
+    pub params: &'a [u8],
+}
+
+impl DecodedCmd<'_> {
+    /// Command name, "Unknown" for an opcode not in the catalog
+    pub fn name(&self) -> &'static str {
+        self.info.map_or("Unknown", |info| info.name)
+    }
+
+    /// Check the request length against the catalog (false for an unknown opcode)
+    pub fn len_ok(&self) -> bool {
+        self.info.is_some_and(|info| info.req.accepts(self.params.len() + 2))
+    }
+
+    /// Expected response length (None for an unknown opcode)
+    pub fn rsp_len(&self) -> Option<RspLen> {
+        self.info.map(|info| info.rsp)
+    }
+}
+
+impl fmt::Display for DecodedCmd<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (0x{:04X})", self.name(), self.opcode)?;
+        if !self.params.is_empty() {
+            write!(f, ":")?;
+            for b in self.params {
+                write!(f, " {b:02X}")?;
+            }
+        }
+        if self.info.is_some() && !self.len_ok() {
+            write!(f, " [unexpected length {}]", self.params.len() + 2)?;
+        }
+        Ok(())
+    }
+}
+
+/// Split a raw command (as sent on the bus) into its catalog entry and parameters
+/// Return InvalidSize if the command is shorter than an opcode
+pub fn decode(bytes: &[u8]) -> Result<DecodedCmd<'_>, Lr1120Error> {
+    let [op0, op1, params @ ..] = bytes else {
+        return Err(Lr1120Error::InvalidSize);
+    };
+    let opcode = u16::from_be_bytes([*op0, *op1]);
+    Ok(DecodedCmd { opcode, info: lookup(opcode), params })
+}
@@ -0,0 +1,129 @@
+// Bootloader commands API
+// Written by hand (listed in commands.yaml for the opcode catalog only): only accepted when the chip runs the bootloader
+
+use super::CmdWriter;
+use super::cmd_system::StayInBootloader;
+use crate::status::Status;
+
+/// Maximum number of 32-bit words written by one WriteFlashEncrypted command
+pub const FLASH_WRITE_WORDS_MAX: usize = 64;
+
+/// Erases the whole flash (firmware image) before writing a new one. BUSY stays high during the erase.
+pub const fn erase_flash_cmd() -> [u8; 2] {
+    [0x80, 0x00]
+}
+
+/// Writes a chunk of the encrypted firmware image at a byte offset, followed by up to 64 words (big endian). The image is decrypted and checked by the bootloader.
+pub const fn write_flash_encrypted_cmd(offset: u32) -> [u8; 6] {
+    CmdWriter::new(0x8003)
+        .push_u32(offset)
+        .finish()
+}
+
+/// Reads the hash of the firmware image written in flash
+pub const fn get_hash_req() -> [u8; 2] {
+    [0x80, 0x04]
+}
+
+/// Leaves the bootloader and starts the firmware (0), or restarts in the bootloader (3)
+pub const fn reboot_cmd(stay_in_bootloader: StayInBootloader) -> [u8; 3] {
+    CmdWriter::new(0x8005)
+        .push_u8(stay_in_bootloader as u8)
+        .finish()
+}
+
+/// Reads the PIN used to claim the device on LoRa Cloud
+pub const fn get_pin_req() -> [u8; 2] {
+    [0x80, 0x0B]
+}
+
+/// Reads the ChipEui from the bootloader
+pub const fn read_chip_eui_req() -> [u8; 2] {
+    [0x80, 0x0C]
+}
+
+/// Reads the Semtech JoinEui from the bootloader
+pub const fn read_join_eui_req() -> [u8; 2] {
+    [0x80, 0x0D]
+}
+
+// Response structs
+
+/// Response for GetHash command
+#[derive(Default)]
+pub struct HashRsp([u8; 17]);
+
+impl HashRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 17;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 17] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
+    /// Create a new response buffer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
+        self.0[0].into()
+    }
+
+    /// 16-byte hash of the firmware image
+    pub fn hash(&self) -> [u8; 16] {
+        let mut hash = [0; 16];
+        hash.copy_from_slice(&self.0[1..]);
+        hash
+    }
+}
+
+impl AsMut<[u8]> for HashRsp {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+/// Response for GetPin command
+#[derive(Default)]
+pub struct PinRsp([u8; 5]);
+
+impl PinRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 5;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 5] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
+    /// Create a new response buffer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
+        self.0[0].into()
+    }
+
+    /// 4-byte PIN
+    pub fn pin(&self) -> u32 {
+        (self.0[4] as u32) |
+        ((self.0[3] as u32) << 8) |
+        ((self.0[2] as u32) << 16) |
+        ((self.0[1] as u32) << 24)
+    }
+}
+
+impl AsMut<[u8]> for PinRsp {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
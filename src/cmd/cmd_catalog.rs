@@ -0,0 +1,149 @@
+// Opcode catalog generated from commands.yaml (types and decoding in catalog.rs)
+
+use super::catalog::{OpcodeInfo, ReqLen, RspLen};
+
+/// All commands known by the driver, sorted by opcode
+pub const OPCODES: &[OpcodeInfo] = &[
+    OpcodeInfo { opcode: 0x0100, name: "GetStatus", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(6) },
+    OpcodeInfo { opcode: 0x0101, name: "GetVersion", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(5) },
+    OpcodeInfo { opcode: 0x0105, name: "WriteRegMem32", req: ReqLen::AtLeast(10), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0106, name: "ReadRegMem32", req: ReqLen::Fixed(7), rsp: RspLen::Variable },
+    OpcodeInfo { opcode: 0x0109, name: "WriteBuffer8", req: ReqLen::AtLeast(2), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x010A, name: "ReadBuffer8", req: ReqLen::Fixed(4), rsp: RspLen::Variable },
+    OpcodeInfo { opcode: 0x010B, name: "ClearRxBuffer", req: ReqLen::Fixed(2), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x010C, name: "WriteRegMemMask32", req: ReqLen::Fixed(14), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x010D, name: "GetErrors", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(3) },
+    OpcodeInfo { opcode: 0x010E, name: "ClearErrors", req: ReqLen::Fixed(2), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x010F, name: "Calibrate", req: ReqLen::Fixed(3), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0110, name: "SetRegMode", req: ReqLen::Fixed(3), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0111, name: "CalibImage", req: ReqLen::Fixed(4), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0112, name: "SetDioAsRfSwitch", req: ReqLen::Fixed(10), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0113, name: "SetDioIrqParams", req: ReqLen::Fixed(10), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0114, name: "ClearIrq", req: ReqLen::Fixed(6), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0116, name: "ConfigLfClock", req: ReqLen::Fixed(3), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0117, name: "SetTcxoMode", req: ReqLen::Fixed(6), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0118, name: "Reboot", req: ReqLen::Fixed(3), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0119, name: "GetVbat", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(2) },
+    OpcodeInfo { opcode: 0x011A, name: "GetTemp", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(3) },
+    OpcodeInfo { opcode: 0x011B, name: "SetSleep", req: ReqLen::Fixed(7), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x011C, name: "SetStandby", req: ReqLen::Fixed(3), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x011D, name: "SetFs", req: ReqLen::Fixed(2), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0120, name: "GetRandomNumber", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(5) },
+    OpcodeInfo { opcode: 0x0125, name: "GetChipEui", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(9) },
+    OpcodeInfo { opcode: 0x0126, name: "GetSemtechJoinEui", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(9) },
+    OpcodeInfo { opcode: 0x0128, name: "EnableSpiCrc", req: ReqLen::Fixed(4), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x012A, name: "DriveDiosInSleepMode", req: ReqLen::Fixed(3), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0200, name: "ResetStats", req: ReqLen::Fixed(2), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0201, name: "GetStats", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(9) },
+    OpcodeInfo { opcode: 0x0202, name: "GetPacketType", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(2) },
+    OpcodeInfo { opcode: 0x0203, name: "GetRxBufferStatus", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(3) },
+    OpcodeInfo { opcode: 0x0204, name: "GetPacketStatus", req: ReqLen::Fixed(2), rsp: RspLen::Variable },
+    OpcodeInfo { opcode: 0x0205, name: "GetRssiInst", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(2) },
+    OpcodeInfo { opcode: 0x0206, name: "SetFskSyncWord", req: ReqLen::Fixed(10), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0209, name: "SetRx", req: ReqLen::Fixed(5), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x020A, name: "SetTx", req: ReqLen::Fixed(5), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x020B, name: "SetRfFrequency", req: ReqLen::Fixed(6), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x020C, name: "AutoTxRx", req: ReqLen::Fixed(9), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x020D, name: "SetLoraCadParams", req: ReqLen::Fixed(9), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x020E, name: "SetPacketType", req: ReqLen::Fixed(3), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x020F, name: "SetModulationParams", req: ReqLen::AtLeast(6), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0210, name: "SetPacketParams", req: ReqLen::AtLeast(8), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0211, name: "SetTxParams", req: ReqLen::Fixed(4), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0212, name: "SetFskAddress", req: ReqLen::Fixed(4), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0213, name: "SetRxTxFallbackMode", req: ReqLen::Fixed(3), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0214, name: "SetRxDutyCycle", req: ReqLen::Fixed(9), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0215, name: "SetPaConfig", req: ReqLen::Fixed(6), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0217, name: "StopTimeoutOnPreamble", req: ReqLen::Fixed(3), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0218, name: "SetLoraCad", req: ReqLen::Fixed(2), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0219, name: "SetTxCw", req: ReqLen::Fixed(2), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x021A, name: "SetTxInfinitePreamble", req: ReqLen::Fixed(2), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x021B, name: "SetLoraSynchTimeout", req: ReqLen::Fixed(3), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x021C, name: "SetRangingAddr", req: ReqLen::Fixed(7), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x021D, name: "SetRangingReqAddr", req: ReqLen::Fixed(6), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x021E, name: "GetRangingResult", req: ReqLen::Fixed(3), rsp: RspLen::Variable },
+    OpcodeInfo { opcode: 0x021F, name: "SetRangingTxRxDelay", req: ReqLen::Fixed(6), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0224, name: "SetFskCrcParams", req: ReqLen::Fixed(10), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0225, name: "SetFskWhitParams", req: ReqLen::Fixed(4), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0227, name: "SetRxBoosted", req: ReqLen::Fixed(3), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0228, name: "SetRangingParameter", req: ReqLen::Fixed(4), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0229, name: "SetRssiCalibration", req: ReqLen::Fixed(12), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x022B, name: "SetLoraSyncword", req: ReqLen::Fixed(3), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x022C, name: "LrFhssBuildFrame", req: ReqLen::AtLeast(11), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x022D, name: "LrFhssSetSyncWord", req: ReqLen::Fixed(6), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0230, name: "GetLoraRxHeaderInfos", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(2) },
+    OpcodeInfo { opcode: 0x0300, name: "WifiScan", req: ReqLen::Fixed(11), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0301, name: "WifiScanTimeLimit", req: ReqLen::Fixed(11), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0302, name: "WifiCountryCode", req: ReqLen::Fixed(9), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0303, name: "WifiCountryCodeTimeLimit", req: ReqLen::Fixed(9), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0305, name: "WifiGetNbResults", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(2) },
+    OpcodeInfo { opcode: 0x0306, name: "WifiReadResults", req: ReqLen::Fixed(5), rsp: RspLen::Variable },
+    OpcodeInfo { opcode: 0x0307, name: "WifiResetCumulTimings", req: ReqLen::Fixed(2), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0308, name: "WifiReadCumulTimings", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(17) },
+    OpcodeInfo { opcode: 0x0309, name: "WifiGetNbCountryCodeResults", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(2) },
+    OpcodeInfo { opcode: 0x030A, name: "WifiReadCountryCodeResults", req: ReqLen::Fixed(4), rsp: RspLen::Variable },
+    OpcodeInfo { opcode: 0x030B, name: "WifiCfgTimestampAPphone", req: ReqLen::Fixed(6), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0320, name: "WifiReadVersion", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(3) },
+    OpcodeInfo { opcode: 0x0400, name: "GnssSetConstellationToUse", req: ReqLen::Fixed(3), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0401, name: "GnssReadConstellationToUse", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(2) },
+    OpcodeInfo { opcode: 0x0402, name: "GnssSetAlmanacUpdate", req: ReqLen::Fixed(9), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0403, name: "GnssReadAlmanacUpdate", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(2) },
+    OpcodeInfo { opcode: 0x0406, name: "GnssReadVersion", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(3) },
+    OpcodeInfo { opcode: 0x0407, name: "GnssReadSupportedConstellations", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(2) },
+    OpcodeInfo { opcode: 0x0408, name: "GnssSetMode", req: ReqLen::Fixed(3), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0409, name: "GnssAutonomous", req: ReqLen::Fixed(9), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x040A, name: "GnssAssisted", req: ReqLen::Fixed(9), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x040B, name: "GnssScan", req: ReqLen::Fixed(5), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x040C, name: "GnssGetResultSize", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(3) },
+    OpcodeInfo { opcode: 0x040D, name: "GnssReadResults", req: ReqLen::Fixed(2), rsp: RspLen::Variable },
+    OpcodeInfo { opcode: 0x040E, name: "GnssAlmanacFullUpdate", req: ReqLen::AtLeast(2), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x040F, name: "GnssAlmanacReadAddrSize", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(7) },
+    OpcodeInfo { opcode: 0x0410, name: "GnssSetAssistancePosition", req: ReqLen::Fixed(6), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0411, name: "GnssReadAssistancePosition", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(5) },
+    OpcodeInfo { opcode: 0x0414, name: "GnssPushSolverMsg", req: ReqLen::AtLeast(2), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0415, name: "GnssPushDmMsg", req: ReqLen::AtLeast(2), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0416, name: "GnssGetContextStatus", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(10) },
+    OpcodeInfo { opcode: 0x0417, name: "GnssGetNbSvDetected", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(2) },
+    OpcodeInfo { opcode: 0x0418, name: "GnssGetSvDetected", req: ReqLen::Fixed(2), rsp: RspLen::Variable },
+    OpcodeInfo { opcode: 0x0419, name: "GnssGetConsumption", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(9) },
+    OpcodeInfo { opcode: 0x041A, name: "GnssReadAlmanacPerSatellite", req: ReqLen::Fixed(4), rsp: RspLen::Variable },
+    OpcodeInfo { opcode: 0x041F, name: "GnssGetSvVisible", req: ReqLen::Fixed(11), rsp: RspLen::Fixed(2) },
+    OpcodeInfo { opcode: 0x0420, name: "GnssGetSvVisibleDoppler", req: ReqLen::Fixed(2), rsp: RspLen::Variable },
+    OpcodeInfo { opcode: 0x0426, name: "GnssReadLastScanModeLaunched", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(2) },
+    OpcodeInfo { opcode: 0x0432, name: "GnssFetchTime", req: ReqLen::Fixed(4), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0434, name: "GnssReadTime", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(9) },
+    OpcodeInfo { opcode: 0x0435, name: "GnssResetTime", req: ReqLen::Fixed(2), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0437, name: "GnssResetPosition", req: ReqLen::Fixed(2), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x044B, name: "GnssSetTime", req: ReqLen::Fixed(8), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x044F, name: "GnssReadDopplerSolverRes", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(19) },
+    OpcodeInfo { opcode: 0x0453, name: "GnssReadDelayResetAP", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(4) },
+    OpcodeInfo { opcode: 0x0455, name: "GnssAlmanacUpdateFromSat", req: ReqLen::Fixed(4), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0457, name: "GnssReadAlmanacStatus", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(54) },
+    OpcodeInfo { opcode: 0x0463, name: "GnssConfigAlmanacUpdatePeriod", req: ReqLen::Fixed(6), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0464, name: "GnssReadAlmanacUpdatePeriod", req: ReqLen::Fixed(4), rsp: RspLen::Fixed(3) },
+    OpcodeInfo { opcode: 0x0465, name: "GnssConfigDelayResetAP", req: ReqLen::Fixed(5), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0466, name: "GnssGetSvWarmStart", req: ReqLen::Fixed(3), rsp: RspLen::Variable },
+    OpcodeInfo { opcode: 0x0467, name: "GnssReadWNRollover", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(3) },
+    OpcodeInfo { opcode: 0x0469, name: "GnssReadWarmStartStatus", req: ReqLen::Fixed(3), rsp: RspLen::Fixed(6) },
+    OpcodeInfo { opcode: 0x0472, name: "GnssWriteBitMaskSatActivated", req: ReqLen::AtLeast(7), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0502, name: "CryptoSetKey", req: ReqLen::Fixed(19), rsp: RspLen::Fixed(2) },
+    OpcodeInfo { opcode: 0x0503, name: "CryptoDeriveKey", req: ReqLen::Fixed(20), rsp: RspLen::Fixed(2) },
+    OpcodeInfo { opcode: 0x0504, name: "CryptoProcessJoinAccept", req: ReqLen::AtLeast(5), rsp: RspLen::Variable },
+    OpcodeInfo { opcode: 0x0505, name: "CryptoComputeAesCmac", req: ReqLen::AtLeast(3), rsp: RspLen::Fixed(6) },
+    OpcodeInfo { opcode: 0x0506, name: "CryptoVerifyAesCmac", req: ReqLen::AtLeast(7), rsp: RspLen::Fixed(2) },
+    OpcodeInfo { opcode: 0x0507, name: "CryptoAesEncrypt01", req: ReqLen::AtLeast(3), rsp: RspLen::Variable },
+    OpcodeInfo { opcode: 0x0508, name: "CryptoAesEncrypt", req: ReqLen::AtLeast(3), rsp: RspLen::Variable },
+    OpcodeInfo { opcode: 0x0509, name: "CryptoAesDecrypt", req: ReqLen::AtLeast(3), rsp: RspLen::Variable },
+    OpcodeInfo { opcode: 0x050A, name: "CryptoStoreToFlash", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(2) },
+    OpcodeInfo { opcode: 0x050B, name: "CryptoRestoreFromFlash", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(2) },
+    OpcodeInfo { opcode: 0x050D, name: "CryptoSetParam", req: ReqLen::Fixed(7), rsp: RspLen::Fixed(2) },
+    OpcodeInfo { opcode: 0x050E, name: "CryptoGetParam", req: ReqLen::Fixed(3), rsp: RspLen::Fixed(6) },
+    OpcodeInfo { opcode: 0x050F, name: "CryptoCheckEncryptedFirmwareImage", req: ReqLen::AtLeast(6), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x0510, name: "CryptoCheckEncryptedFirmwareImageResult", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(2) },
+    OpcodeInfo { opcode: 0x8000, name: "EraseFlash", req: ReqLen::Fixed(2), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x8003, name: "WriteFlashEncrypted", req: ReqLen::AtLeast(6), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x8004, name: "GetHash", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(17) },
+    OpcodeInfo { opcode: 0x8005, name: "RebootBootloader", req: ReqLen::Fixed(3), rsp: RspLen::None },
+    OpcodeInfo { opcode: 0x800B, name: "GetPin", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(5) },
+    OpcodeInfo { opcode: 0x800C, name: "ReadChipEui", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(9) },
+    OpcodeInfo { opcode: 0x800D, name: "ReadJoinEui", req: ReqLen::Fixed(2), rsp: RspLen::Fixed(9) },
+];
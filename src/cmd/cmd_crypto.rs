@@ -1,5 +1,6 @@
 // Crypto commands API
 
+use super::CmdWriter;
 use crate::status::Status;
 
 /// Key identifier
@@ -98,173 +99,101 @@ pub enum LorawanVersion {
 }
 
 /// Sets a specific Key identified by KeyID into Crypto Engine. Key is 16-byte AES-128 key as defined in FIPS-197.
-pub fn crypto_set_key_req(key_id: KeyId, key: u128) -> [u8; 19] {
-    let mut cmd = [0u8; 19];
-    cmd[0] = 0x05;
-    cmd[1] = 0x02;
-
-    cmd[2] |= key_id as u8;
-    cmd[3] |= ((key >> 120) & 0xFF) as u8;
-    cmd[4] |= ((key >> 112) & 0xFF) as u8;
-    cmd[5] |= ((key >> 104) & 0xFF) as u8;
-    cmd[6] |= ((key >> 96) & 0xFF) as u8;
-    cmd[7] |= ((key >> 88) & 0xFF) as u8;
-    cmd[8] |= ((key >> 80) & 0xFF) as u8;
-    cmd[9] |= ((key >> 72) & 0xFF) as u8;
-    cmd[10] |= ((key >> 64) & 0xFF) as u8;
-    cmd[11] |= ((key >> 56) & 0xFF) as u8;
-    cmd[12] |= ((key >> 48) & 0xFF) as u8;
-    cmd[13] |= ((key >> 40) & 0xFF) as u8;
-    cmd[14] |= ((key >> 32) & 0xFF) as u8;
-    cmd[15] |= ((key >> 24) & 0xFF) as u8;
-    cmd[16] |= ((key >> 16) & 0xFF) as u8;
-    cmd[17] |= ((key >> 8) & 0xFF) as u8;
-    cmd[18] |= (key & 0xFF) as u8;
-    cmd
+pub const fn crypto_set_key_req(key_id: KeyId, key: u128) -> [u8; 19] {
+    CmdWriter::new(0x0502)
+        .push_u8(key_id as u8)
+        .push_u128(key)
+        .finish()
 }
 
 /// Derives (encrypts) input value into destination Key using source Key. Generated key stored in Crypto Engine RAM - use CryptoStoreToFlash to persist. See LoRaWAN key derivation schemes in Ch 16.3-16.4.
-pub fn crypto_derive_key_req(src_key_id: KeyId, dst_key_id: KeyId, input: u128) -> [u8; 20] {
-    let mut cmd = [0u8; 20];
-    cmd[0] = 0x05;
-    cmd[1] = 0x03;
-
-    cmd[2] |= src_key_id as u8;
-    cmd[3] |= dst_key_id as u8;
-    cmd[4] |= ((input >> 120) & 0xFF) as u8;
-    cmd[5] |= ((input >> 112) & 0xFF) as u8;
-    cmd[6] |= ((input >> 104) & 0xFF) as u8;
-    cmd[7] |= ((input >> 96) & 0xFF) as u8;
-    cmd[8] |= ((input >> 88) & 0xFF) as u8;
-    cmd[9] |= ((input >> 80) & 0xFF) as u8;
-    cmd[10] |= ((input >> 72) & 0xFF) as u8;
-    cmd[11] |= ((input >> 64) & 0xFF) as u8;
-    cmd[12] |= ((input >> 56) & 0xFF) as u8;
-    cmd[13] |= ((input >> 48) & 0xFF) as u8;
-    cmd[14] |= ((input >> 40) & 0xFF) as u8;
-    cmd[15] |= ((input >> 32) & 0xFF) as u8;
-    cmd[16] |= ((input >> 24) & 0xFF) as u8;
-    cmd[17] |= ((input >> 16) & 0xFF) as u8;
-    cmd[18] |= ((input >> 8) & 0xFF) as u8;
-    cmd[19] |= (input & 0xFF) as u8;
-    cmd
+pub const fn crypto_derive_key_req(src_key_id: KeyId, dst_key_id: KeyId, input: u128) -> [u8; 20] {
+    CmdWriter::new(0x0503)
+        .push_u8(src_key_id as u8)
+        .push_u8(dst_key_id as u8)
+        .push_u128(input)
+        .finish()
 }
 
 /// Decrypts join accept message (using AES-ECB encrypt per LoRaWAN spec) on Data and Header, then verifies MIC. Returns decrypted data if MIC verification successful.
-pub fn crypto_process_join_accept_req(dec_key_id: KeyId, ver_key_id: KeyId, lorawan_version: LorawanVersion) -> [u8; 5] {
-    let mut cmd = [0u8; 5];
-    cmd[0] = 0x05;
-    cmd[1] = 0x04;
-
-    cmd[2] |= dec_key_id as u8;
-    cmd[3] |= ver_key_id as u8;
-    cmd[4] |= lorawan_version as u8;
-    cmd
+pub const fn crypto_process_join_accept_req(dec_key_id: KeyId, ver_key_id: KeyId, lorawan_version: LorawanVersion) -> [u8; 5] {
+    CmdWriter::new(0x0504)
+        .push_u8(dec_key_id as u8)
+        .push_u8(ver_key_id as u8)
+        .push_u8(lorawan_version as u8)
+        .finish()
 }
 
 /// Computes AES CMAC of provided data using specified Key and returns MIC (first 4 bytes of CMAC). Maximum data size 256 bytes.
-pub fn crypto_compute_aes_cmac_req(key_id: KeyId) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x05;
-    cmd[1] = 0x05;
-
-    cmd[2] |= key_id as u8;
-    cmd
+pub const fn crypto_compute_aes_cmac_req(key_id: KeyId) -> [u8; 3] {
+    CmdWriter::new(0x0505)
+        .push_u8(key_id as u8)
+        .finish()
 }
 
 /// Computes AES CMAC of provided data using specified Key and compares with provided MIC. Returns SUCCESS if MICs match, FAIL_CMAC otherwise. Maximum data size 256 bytes.
-pub fn crypto_verify_aes_cmac_req(key_id: KeyId, expected_mic: u32) -> [u8; 7] {
-    let mut cmd = [0u8; 7];
-    cmd[0] = 0x05;
-    cmd[1] = 0x06;
-
-    cmd[2] |= key_id as u8;
-    cmd[3] |= ((expected_mic >> 24) & 0xFF) as u8;
-    cmd[4] |= ((expected_mic >> 16) & 0xFF) as u8;
-    cmd[5] |= ((expected_mic >> 8) & 0xFF) as u8;
-    cmd[6] |= (expected_mic & 0xFF) as u8;
-    cmd
+pub const fn crypto_verify_aes_cmac_req(key_id: KeyId, expected_mic: u32) -> [u8; 7] {
+    CmdWriter::new(0x0506)
+        .push_u8(key_id as u8)
+        .push_u32(expected_mic)
+        .finish()
 }
 
 /// Encrypts provided data using specified Key and returns encrypted data. Cannot be used on key indexes 2-11 (prevents re-calculating session keys). For LoRaWAN encryption operations.
-pub fn crypto_aes_encrypt01_req(key_id: KeyId) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x05;
-    cmd[1] = 0x07;
-
-    cmd[2] |= key_id as u8;
-    cmd
+pub const fn crypto_aes_encrypt01_req(key_id: KeyId) -> [u8; 3] {
+    CmdWriter::new(0x0507)
+        .push_u8(key_id as u8)
+        .finish()
 }
 
 /// Encrypts provided data using specified Key and returns encrypted data. For generic non-LoRaWAN operations using Crypto Engine as hardware accelerator. Only for General Purpose keys (26-27).
-pub fn crypto_aes_encrypt_req(key_id: KeyId) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x05;
-    cmd[1] = 0x08;
-
-    cmd[2] |= key_id as u8;
-    cmd
+pub const fn crypto_aes_encrypt_req(key_id: KeyId) -> [u8; 3] {
+    CmdWriter::new(0x0508)
+        .push_u8(key_id as u8)
+        .finish()
 }
 
 /// Decrypts provided data using specified Key and returns decrypted data. For non-LoRaWAN security tasks using Crypto Engine as standalone hardware accelerator.
-pub fn crypto_aes_decrypt_req(key_id: KeyId) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x05;
-    cmd[1] = 0x09;
-
-    cmd[2] |= key_id as u8;
-    cmd
+pub const fn crypto_aes_decrypt_req(key_id: KeyId) -> [u8; 3] {
+    CmdWriter::new(0x0509)
+        .push_u8(key_id as u8)
+        .finish()
 }
 
 /// Stores all Keys and Parameters from Crypto Engine RAM into flash memory for persistence
-pub fn crypto_store_to_flash_req() -> [u8; 2] {
+pub const fn crypto_store_to_flash_req() -> [u8; 2] {
     [0x05, 0x0A]
 }
 
 /// Restores all Keys and Parameters from flash memory into Crypto Engine RAM
-pub fn crypto_restore_from_flash_req() -> [u8; 2] {
+pub const fn crypto_restore_from_flash_req() -> [u8; 2] {
     [0x05, 0x0B]
 }
 
 /// Sets a specific Parameter into Crypto Engine RAM
-pub fn crypto_set_param_req(param_id: u8, data: u32) -> [u8; 7] {
-    let mut cmd = [0u8; 7];
-    cmd[0] = 0x05;
-    cmd[1] = 0x0D;
-
-    cmd[2] |= param_id;
-    cmd[3] |= ((data >> 24) & 0xFF) as u8;
-    cmd[4] |= ((data >> 16) & 0xFF) as u8;
-    cmd[5] |= ((data >> 8) & 0xFF) as u8;
-    cmd[6] |= (data & 0xFF) as u8;
-    cmd
+pub const fn crypto_set_param_req(param_id: u8, data: u32) -> [u8; 7] {
+    CmdWriter::new(0x050D)
+        .push_u8(param_id)
+        .push_u32(data)
+        .finish()
 }
 
 /// Gets a specific Parameter from Crypto Engine RAM
-pub fn crypto_get_param_req(param_id: u8) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x05;
-    cmd[1] = 0x0E;
-
-    cmd[2] |= param_id;
-    cmd
+pub const fn crypto_get_param_req(param_id: u8) -> [u8; 3] {
+    CmdWriter::new(0x050E)
+        .push_u8(param_id)
+        .finish()
 }
 
 /// Adds a chunk to encrypted firmware image to be checked. Call multiple times until complete image sent. Takes max 64x32-bit words (256 bytes) per call, except last segment may be shorter. BUSY released when ready for next chunk. Call CryptoCheckEncryptedFirmwareImageResult to get final result.
-pub fn crypto_check_encrypted_firmware_image_cmd(offset: u32) -> [u8; 6] {
-    let mut cmd = [0u8; 6];
-    cmd[0] = 0x05;
-    cmd[1] = 0x0F;
-
-    cmd[2] |= ((offset >> 24) & 0xFF) as u8;
-    cmd[3] |= ((offset >> 16) & 0xFF) as u8;
-    cmd[4] |= ((offset >> 8) & 0xFF) as u8;
-    cmd[5] |= (offset & 0xFF) as u8;
-    cmd
+pub const fn crypto_check_encrypted_firmware_image_cmd(offset: u32) -> [u8; 6] {
+    CmdWriter::new(0x050F)
+        .push_u32(offset)
+        .finish()
 }
 
 /// Gets result of encrypted firmware image check after all chunks sent via CryptoCheckEncryptedFirmwareImage
-pub fn crypto_check_encrypted_firmware_image_result_req() -> [u8; 2] {
+pub const fn crypto_check_encrypted_firmware_image_result_req() -> [u8; 2] {
     [0x05, 0x10]
 }
 
@@ -275,13 +204,23 @@ pub fn crypto_check_encrypted_firmware_image_result_req() -> [u8; 2] {
 pub struct CryptoSetKeyRsp([u8; 2]);
 
 impl CryptoSetKeyRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -302,13 +241,23 @@ impl AsMut<[u8]> for CryptoSetKeyRsp {
 pub struct CryptoDeriveKeyRsp([u8; 2]);
 
 impl CryptoDeriveKeyRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -329,13 +278,23 @@ impl AsMut<[u8]> for CryptoDeriveKeyRsp {
 pub struct CryptoProcessJoinAcceptRsp([u8; 2]);
 
 impl CryptoProcessJoinAcceptRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -356,13 +315,23 @@ impl AsMut<[u8]> for CryptoProcessJoinAcceptRsp {
 pub struct CryptoComputeAesCmacRsp([u8; 6]);
 
 impl CryptoComputeAesCmacRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 6;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 6] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -391,13 +360,23 @@ impl AsMut<[u8]> for CryptoComputeAesCmacRsp {
 pub struct CryptoVerifyAesCmacRsp([u8; 2]);
 
 impl CryptoVerifyAesCmacRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -418,13 +397,23 @@ impl AsMut<[u8]> for CryptoVerifyAesCmacRsp {
 pub struct CryptoAesEncrypt01Rsp([u8; 2]);
 
 impl CryptoAesEncrypt01Rsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -445,13 +434,23 @@ impl AsMut<[u8]> for CryptoAesEncrypt01Rsp {
 pub struct CryptoAesEncryptRsp([u8; 2]);
 
 impl CryptoAesEncryptRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -472,13 +471,23 @@ impl AsMut<[u8]> for CryptoAesEncryptRsp {
 pub struct CryptoAesDecryptRsp([u8; 2]);
 
 impl CryptoAesDecryptRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -499,13 +508,23 @@ impl AsMut<[u8]> for CryptoAesDecryptRsp {
 pub struct CryptoStoreToFlashRsp([u8; 2]);
 
 impl CryptoStoreToFlashRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -526,13 +545,23 @@ impl AsMut<[u8]> for CryptoStoreToFlashRsp {
 pub struct CryptoRestoreFromFlashRsp([u8; 2]);
 
 impl CryptoRestoreFromFlashRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -553,13 +582,23 @@ impl AsMut<[u8]> for CryptoRestoreFromFlashRsp {
 pub struct CryptoSetParamRsp([u8; 2]);
 
 impl CryptoSetParamRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -580,13 +619,23 @@ impl AsMut<[u8]> for CryptoSetParamRsp {
 pub struct CryptoGetParamRsp([u8; 6]);
 
 impl CryptoGetParamRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 6;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 6] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -615,13 +664,23 @@ impl AsMut<[u8]> for CryptoGetParamRsp {
 pub struct CryptoCheckEncryptedFirmwareImageResultRsp([u8; 2]);
 
 impl CryptoCheckEncryptedFirmwareImageResultRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -1,5 +1,6 @@
 // Fsk commands API
 
+use super::CmdWriter;
 use crate::status::Status;
 
 /// Bit rate precision: HIGH indicates 8 fractional bits precision, while BASIC indicates no fractional bits
@@ -99,102 +100,65 @@ pub enum DcFree {
 }
 
 /// Configures (G)FSK modulation parameters. Returns CMD_FAIL if packet type is not (G)FSK. Bandwidth must satisfy: (2*Fdev + BR) < Bandwidth. Special register settings required for 0.6kbps and 1.2kbps bit rates (see section 8.4.5).
-pub fn set_fsk_modulation_params_cmd(precision: Precision, bitrate: u32, pulse_shape: PulseShape, rx_bw: RxBw, fdev: u32) -> [u8; 12] {
-    let mut cmd = [0u8; 12];
-    cmd[0] = 0x02;
-    cmd[1] = 0x0F;
-
-    cmd[2] |= (precision as u8) & 0x1;
-    cmd[2] |= ((bitrate >> 24) & 0xFF) as u8;
-    cmd[3] |= ((bitrate >> 16) & 0xFF) as u8;
-    cmd[4] |= ((bitrate >> 8) & 0xFF) as u8;
-    cmd[5] |= (bitrate & 0xFF) as u8;
-    cmd[6] |= pulse_shape as u8;
-    cmd[7] |= rx_bw as u8;
-    cmd[8] |= ((fdev >> 24) & 0xFF) as u8;
-    cmd[9] |= ((fdev >> 16) & 0xFF) as u8;
-    cmd[10] |= ((fdev >> 8) & 0xFF) as u8;
-    cmd[11] |= (fdev & 0xFF) as u8;
-    cmd
+pub const fn set_fsk_modulation_params_cmd(precision: Precision, bitrate: u32, pulse_shape: PulseShape, rx_bw: RxBw, fdev: u32) -> [u8; 12] {
+    CmdWriter::new(0x020F)
+        .bits(precision as u8, 1, 0)
+        .push_u32(bitrate)
+        .push_u8(pulse_shape as u8)
+        .push_u8(rx_bw as u8)
+        .push_u32(fdev)
+        .finish()
 }
 
 #[allow(clippy::too_many_arguments)]
 /// Configures (G)FSK RF packet parameters. Preamble recommended minimum 16 bits. PblDetect must be < SyncWordLen. For SX128x compatibility: PacketType 0x02, CRC 0/1/2 bytes, SyncWordLen 8/16/24/32/40 bits, AddrComp disabled, DcFree 0x00 or 0x03 only.
-pub fn set_fsk_packet_params_cmd(pbl_len_tx: u16, pbl_len_detect: PblLenDetect, sync_word_len: u8, addr_comp: AddrComp, fsk_pkt_format: FskPktFormat, pld_len: u8, crc: Crc, dc_free: DcFree) -> [u8; 11] {
-    let mut cmd = [0u8; 11];
-    cmd[0] = 0x02;
-    cmd[1] = 0x10;
-
-    cmd[2] |= ((pbl_len_tx >> 8) & 0xFF) as u8;
-    cmd[3] |= (pbl_len_tx & 0xFF) as u8;
-    cmd[4] |= pbl_len_detect as u8;
-    cmd[5] |= sync_word_len;
-    cmd[6] |= (addr_comp as u8) & 0x3;
-    cmd[7] |= (fsk_pkt_format as u8) & 0x3;
-    cmd[8] |= pld_len;
-    cmd[9] |= crc as u8;
-    cmd[10] |= dc_free as u8;
-    cmd
+pub const fn set_fsk_packet_params_cmd(pbl_len_tx: u16, pbl_len_detect: PblLenDetect, sync_word_len: u8, addr_comp: AddrComp, fsk_pkt_format: FskPktFormat, pld_len: u8, crc: Crc, dc_free: DcFree) -> [u8; 11] {
+    CmdWriter::new(0x0210)
+        .push_u16(pbl_len_tx)
+        .push_u8(pbl_len_detect as u8)
+        .push_u8(sync_word_len)
+        .bits(addr_comp as u8, 2, 0)
+        .skip(1)
+        .bits(fsk_pkt_format as u8, 2, 0)
+        .skip(1)
+        .push_u8(pld_len)
+        .push_u8(crc as u8)
+        .push_u8(dc_free as u8)
+        .finish()
 }
 
 /// Configures (G)FSK syncword. Default 0x9723522556536564. For RX only: syncword must be multiple of 8 bits. If not, configure as next multiple of 8 and add filler bits at beginning (e.g., 30 bits -> configure as 32 bits with '01b' or '10b' prefix).
-pub fn set_fsk_sync_word_cmd(syncword: u64) -> [u8; 10] {
-    let mut cmd = [0u8; 10];
-    cmd[0] = 0x02;
-    cmd[1] = 0x06;
-
-    cmd[2] |= ((syncword >> 56) & 0xFF) as u8;
-    cmd[3] |= ((syncword >> 48) & 0xFF) as u8;
-    cmd[4] |= ((syncword >> 40) & 0xFF) as u8;
-    cmd[5] |= ((syncword >> 32) & 0xFF) as u8;
-    cmd[6] |= ((syncword >> 24) & 0xFF) as u8;
-    cmd[7] |= ((syncword >> 16) & 0xFF) as u8;
-    cmd[8] |= ((syncword >> 8) & 0xFF) as u8;
-    cmd[9] |= (syncword & 0xFF) as u8;
-    cmd
+pub const fn set_fsk_sync_word_cmd(syncword: u64) -> [u8; 10] {
+    CmdWriter::new(0x0206)
+        .push_u64(syncword)
+        .finish()
 }
 
 /// Sets Node and Broadcast addresses for (G)FSK packet filtering when addr_comp enabled (0x01 or 0x02 in SetGfskPacketParams). Reception aborted with adrsErr flag if address comparison fails.
-pub fn set_fsk_address_cmd(addr_node: u8, addr_bcast: u8) -> [u8; 4] {
-    let mut cmd = [0u8; 4];
-    cmd[0] = 0x02;
-    cmd[1] = 0x12;
-
-    cmd[2] |= addr_node;
-    cmd[3] |= addr_bcast;
-    cmd
+pub const fn set_fsk_address_cmd(addr_node: u8, addr_bcast: u8) -> [u8; 4] {
+    CmdWriter::new(0x0212)
+        .push_u8(addr_node)
+        .push_u8(addr_bcast)
+        .finish()
 }
 
 /// Configures CRC polynomial and initial value for flexible CRC configuration. Examples: IBM CRC (InitValue=0xFFFF, Poly=0x8005, CrcType=CRC_2_BYTE), CCITT CRC (InitValue=0x1D0F, Poly=0x1021, CrcType=CRC_2_BYTE_INV)
-pub fn set_fsk_crc_params_cmd(init: u32, polynom: u32) -> [u8; 10] {
-    let mut cmd = [0u8; 10];
-    cmd[0] = 0x02;
-    cmd[1] = 0x24;
-
-    cmd[2] |= ((init >> 24) & 0xFF) as u8;
-    cmd[3] |= ((init >> 16) & 0xFF) as u8;
-    cmd[4] |= ((init >> 8) & 0xFF) as u8;
-    cmd[5] |= (init & 0xFF) as u8;
-    cmd[6] |= ((polynom >> 24) & 0xFF) as u8;
-    cmd[7] |= ((polynom >> 16) & 0xFF) as u8;
-    cmd[8] |= ((polynom >> 8) & 0xFF) as u8;
-    cmd[9] |= (polynom & 0xFF) as u8;
-    cmd
+pub const fn set_fsk_crc_params_cmd(init: u32, polynom: u32) -> [u8; 10] {
+    CmdWriter::new(0x0224)
+        .push_u32(init)
+        .push_u32(polynom)
+        .finish()
 }
 
 /// Sets whitening seed. Polynomial: x^9+x^5+1 (sub-GHz), x^7+x^4+1 (HF). Limits consecutive 1's/0's to 9. Seed must match on all peer devices. Only needed if data has high correlation with long 0/1 strings.
-pub fn set_fsk_whit_params_cmd(seed: u16) -> [u8; 4] {
-    let mut cmd = [0u8; 4];
-    cmd[0] = 0x02;
-    cmd[1] = 0x25;
-
-    cmd[2] |= ((seed >> 8) & 0xFF) as u8;
-    cmd[3] |= (seed & 0xFF) as u8;
-    cmd
+pub const fn set_fsk_whit_params_cmd(seed: u16) -> [u8; 4] {
+    CmdWriter::new(0x0225)
+        .push_u16(seed)
+        .finish()
 }
 
 /// Returns link quality informations on last received packet
-pub fn get_fsk_packet_status_req() -> [u8; 2] {
+pub const fn get_fsk_packet_status_req() -> [u8; 2] {
     [0x02, 0x04]
 }
 
@@ -205,13 +169,23 @@ pub fn get_fsk_packet_status_req() -> [u8; 2] {
 pub struct FskPacketStatusRsp([u8; 5]);
 
 impl FskPacketStatusRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 5;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 5] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
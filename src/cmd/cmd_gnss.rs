@@ -1,5 +1,6 @@
 // Gnss commands API
 
+use super::CmdWriter;
 use crate::status::Status;
 
 /// 0x00: Legacy (single) scanning, 0x03: Advanced (multiple) scanning, other: RFU
@@ -176,358 +177,306 @@ pub enum BeidouType {
 }
 
 /// Configures GNSS scanning for selected constellation (GPS/BeiDou). If both selected, GPS scans first, then BeiDou after delay (4s fixed for FW ≤01.02, variable 1s steps for FW 02.01+). Requires 32.768kHz clock for dual constellation. BUSY high until both scans complete.
-pub fn gnss_set_constellation_to_use_cmd(gps_en: bool, beidou_en: bool) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x04;
-    cmd[1] = 0x00;
-
-    if gps_en { cmd[2] |= 1; }
-    if beidou_en { cmd[2] |= 2; }
-    cmd
+pub const fn gnss_set_constellation_to_use_cmd(gps_en: bool, beidou_en: bool) -> [u8; 3] {
+    CmdWriter::new(0x0400)
+        .flag(0, gps_en)
+        .flag(1, beidou_en)
+        .finish()
 }
 
 /// Reads selected constellation (GPS/BeiDou)
-pub fn gnss_read_constellation_to_use_req() -> [u8; 2] {
+pub const fn gnss_read_constellation_to_use_req() -> [u8; 2] {
     [0x04, 0x01]
 }
 
 /// Reads supported constellations
-pub fn gnss_read_supported_constellations_req() -> [u8; 2] {
+pub const fn gnss_read_supported_constellations_req() -> [u8; 2] {
     [0x04, 0x07]
 }
 
 /// Configures GNSS for Legacy (single) or Advanced (multiple) scanning. Advanced performs multiple captures and averages them for increased precision, at expense of longer duration and higher energy. FW 02.01+ NAV message format differs from earlier versions.
-pub fn gnss_set_mode_cmd(gnss_scan_mode: GnssScanMode) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x04;
-    cmd[1] = 0x08;
-
-    cmd[2] |= gnss_scan_mode as u8;
-    cmd
+pub const fn gnss_set_mode_cmd(gnss_scan_mode: GnssScanMode) -> [u8; 3] {
+    CmdWriter::new(0x0408)
+        .push_u8(gnss_scan_mode as u8)
+        .finish()
 }
 
 /// Captures GNSS signals in autonomous mode (no assistance info available). NOT supported in FW 02.01+, replaced by GnssScan. Resets previous GNSS results. BUSY high during scan, GNSSDone IRQ when complete.
-pub fn gnss_autonomous_cmd(time: u32, best_effort: bool, pseudo_range_en: bool, doppler_info_en: bool, bit_changes_en: bool, nb_sv_max: u8) -> [u8; 9] {
-    let mut cmd = [0u8; 9];
-    cmd[0] = 0x04;
-    cmd[1] = 0x09;
-
-    cmd[2] |= ((time >> 24) & 0xFF) as u8;
-    cmd[3] |= ((time >> 16) & 0xFF) as u8;
-    cmd[4] |= ((time >> 8) & 0xFF) as u8;
-    cmd[5] |= (time & 0xFF) as u8;
-    if best_effort { cmd[6] |= 1; }
-    if pseudo_range_en { cmd[7] |= 1; }
-    if doppler_info_en { cmd[7] |= 2; }
-    if bit_changes_en { cmd[7] |= 4; }
-    cmd[8] |= nb_sv_max;
-    cmd
+pub const fn gnss_autonomous_cmd(time: u32, best_effort: bool, pseudo_range_en: bool, doppler_info_en: bool, bit_changes_en: bool, nb_sv_max: u8) -> [u8; 9] {
+    CmdWriter::new(0x0409)
+        .push_u32(time)
+        .flag(0, best_effort)
+        .skip(1)
+        .flag(0, pseudo_range_en)
+        .flag(1, doppler_info_en)
+        .flag(2, bit_changes_en)
+        .skip(1)
+        .push_u8(nb_sv_max)
+        .finish()
 }
 
 /// Captures GNSS signals using assistance data (time, position, almanac). NOT supported in FW 02.01+, replaced by GnssScan. Resets previous GNSS results. BUSY high during scan, GNSSDone IRQ when complete.
-pub fn gnss_assisted_cmd(time: u32, best_effort: bool, pseudo_range_en: bool, doppler_info_en: bool, bit_changes_en: bool, nb_sv_max: u8) -> [u8; 9] {
-    let mut cmd = [0u8; 9];
-    cmd[0] = 0x04;
-    cmd[1] = 0x0A;
-
-    cmd[2] |= ((time >> 24) & 0xFF) as u8;
-    cmd[3] |= ((time >> 16) & 0xFF) as u8;
-    cmd[4] |= ((time >> 8) & 0xFF) as u8;
-    cmd[5] |= (time & 0xFF) as u8;
-    if best_effort { cmd[6] |= 1; }
-    if pseudo_range_en { cmd[7] |= 1; }
-    if doppler_info_en { cmd[7] |= 2; }
-    if bit_changes_en { cmd[7] |= 4; }
-    cmd[8] |= nb_sv_max;
-    cmd
+pub const fn gnss_assisted_cmd(time: u32, best_effort: bool, pseudo_range_en: bool, doppler_info_en: bool, bit_changes_en: bool, nb_sv_max: u8) -> [u8; 9] {
+    CmdWriter::new(0x040A)
+        .push_u32(time)
+        .flag(0, best_effort)
+        .skip(1)
+        .flag(0, pseudo_range_en)
+        .flag(1, doppler_info_en)
+        .flag(2, bit_changes_en)
+        .skip(1)
+        .push_u8(nb_sv_max)
+        .finish()
 }
 
 /// Captures GNSS signals independent of assistance data availability. FW 02.01+ only. Two types: Cold start (no time/position, determines via demod/2D solving) or Assisted (time+position known, searches 12 strongest visible satellites). Use sleep with retention to preserve assistance data. Can be aborted by sending 0x0 on SPI while BUSY active (max 2.9s abort delay).
-pub fn gnss_scan_cmd(best_effort: bool, pseudo_range_en: bool, doppler_info_en: bool, bit_changes_en: bool, nb_sv_max: u8) -> [u8; 5] {
-    let mut cmd = [0u8; 5];
-    cmd[0] = 0x04;
-    cmd[1] = 0x0B;
-
-    if best_effort { cmd[2] |= 1; }
-    if pseudo_range_en { cmd[3] |= 1; }
-    if doppler_info_en { cmd[3] |= 2; }
-    if bit_changes_en { cmd[3] |= 4; }
-    cmd[4] |= nb_sv_max;
-    cmd
+pub const fn gnss_scan_cmd(best_effort: bool, pseudo_range_en: bool, doppler_info_en: bool, bit_changes_en: bool, nb_sv_max: u8) -> [u8; 5] {
+    CmdWriter::new(0x040B)
+        .flag(0, best_effort)
+        .skip(1)
+        .flag(0, pseudo_range_en)
+        .flag(1, doppler_info_en)
+        .flag(2, bit_changes_en)
+        .skip(1)
+        .push_u8(nb_sv_max)
+        .finish()
 }
 
 /// Configures approximate position for GNSS assisted mode. FW 02.01+ uses integrated 2D solving to determine Assistance Position, replacing this configured value.
-pub fn gnss_set_assistance_position_cmd(latitude: u16, longitude: u16) -> [u8; 6] {
-    let mut cmd = [0u8; 6];
-    cmd[0] = 0x04;
-    cmd[1] = 0x10;
-
-    cmd[2] |= ((latitude >> 8) & 0xFF) as u8;
-    cmd[3] |= (latitude & 0xFF) as u8;
-    cmd[4] |= ((longitude >> 8) & 0xFF) as u8;
-    cmd[5] |= (longitude & 0xFF) as u8;
-    cmd
+pub const fn gnss_set_assistance_position_cmd(latitude: u16, longitude: u16) -> [u8; 6] {
+    CmdWriter::new(0x0410)
+        .push_u16(latitude)
+        .push_u16(longitude)
+        .finish()
 }
 
 /// Reads assistance position
-pub fn gnss_read_assistance_position_req() -> [u8; 2] {
+pub const fn gnss_read_assistance_position_req() -> [u8; 2] {
     [0x04, 0x11]
 }
 
 /// Reads GNSS context status including firmware version, almanac CRC, error codes, and frequency search space
-pub fn gnss_get_context_status_req() -> [u8; 2] {
+pub const fn gnss_get_context_status_req() -> [u8; 2] {
     [0x04, 0x16]
 }
 
 /// Returns internal GNSS firmware version and almanac version
-pub fn gnss_read_version_req() -> [u8; 2] {
+pub const fn gnss_read_version_req() -> [u8; 2] {
     [0x04, 0x06]
 }
 
 /// Configures constellation almanac information to be updated. By default both constellations activated.
-pub fn gnss_set_almanac_update_cmd(gps_en: bool, beidou_en: bool) -> [u8; 9] {
-    let mut cmd = [0u8; 9];
-    cmd[0] = 0x04;
-    cmd[1] = 0x02;
-
-    if gps_en { cmd[8] |= 2; }
-    if beidou_en { cmd[8] |= 4; }
-    cmd
+pub const fn gnss_set_almanac_update_cmd(gps_en: bool, beidou_en: bool) -> [u8; 9] {
+    CmdWriter::new(0x0402)
+        .skip(6)
+        .flag(1, gps_en)
+        .flag(2, beidou_en)
+        .finish()
 }
 
 /// Reads almanac update information
-pub fn gnss_read_almanac_update_req() -> [u8; 2] {
+pub const fn gnss_read_almanac_update_req() -> [u8; 2] {
     [0x04, 0x03]
 }
 
 /// Reads size in bytes of byte stream containing available GNSS results. Must call before GnssReadResults.
-pub fn gnss_get_result_size_req() -> [u8; 2] {
+pub const fn gnss_get_result_size_req() -> [u8; 2] {
     [0x04, 0x0C]
 }
 
 /// Retrieves last GNSS results. Variable length message: DestinationID (1 byte) + Payload. DestinationID: 0x00=Status to Host, 0x01=NAV to GNSS Solver, 0x02=Almanac update to DM service. Must call GnssGetResultSize first.
-pub fn gnss_read_results_cmd() -> [u8; 2] {
+pub const fn gnss_read_results_cmd() -> [u8; 2] {
     [0x04, 0x0D]
 }
 
 /// Pushes messages from GNSS solver to LR1120 (e.g., assistance position update)
-pub fn gnss_push_solver_msg_cmd() -> [u8; 2] {
+pub const fn gnss_push_solver_msg_cmd() -> [u8; 2] {
     [0x04, 0x14]
 }
 
 /// Pushes messages from LoRaWAN network to LR1120. FW 02.01+ only.
-pub fn gnss_push_dm_msg_cmd() -> [u8; 2] {
+pub const fn gnss_push_dm_msg_cmd() -> [u8; 2] {
     [0x04, 0x15]
 }
 
 /// Returns number of Satellite Vehicles detected during last GNSS scan
-pub fn gnss_get_nb_sv_detected_req() -> [u8; 2] {
+pub const fn gnss_get_nb_sv_detected_req() -> [u8; 2] {
     [0x04, 0x17]
 }
 
 /// Returns ID, SNR and Doppler of Satellite Vehicles detected during last GNSS scan. SNR in dB, add 31dB to convert to C/N0.
-pub fn gnss_get_sv_detected_req() -> [u8; 2] {
+pub const fn gnss_get_sv_detected_req() -> [u8; 2] {
     [0x04, 0x18]
 }
 
 /// Reads duration of Radio capture and CPU processing phases of GNSS scan in microseconds. Used to determine GNSS power consumption.
-pub fn gnss_get_consumption_req() -> [u8; 2] {
+pub const fn gnss_get_consumption_req() -> [u8; 2] {
     [0x04, 0x19]
 }
 
 /// Returns number of visible satellites for given time, position, and constellation
-pub fn gnss_get_sv_visible_req(time: u32, latitude: u16, longitude: u16, gps_en: bool, beidou_en: bool) -> [u8; 11] {
-    let mut cmd = [0u8; 11];
-    cmd[0] = 0x04;
-    cmd[1] = 0x1F;
-
-    cmd[2] |= ((time >> 24) & 0xFF) as u8;
-    cmd[3] |= ((time >> 16) & 0xFF) as u8;
-    cmd[4] |= ((time >> 8) & 0xFF) as u8;
-    cmd[5] |= (time & 0xFF) as u8;
-    cmd[6] |= ((latitude >> 8) & 0xFF) as u8;
-    cmd[7] |= (latitude & 0xFF) as u8;
-    cmd[8] |= ((longitude >> 8) & 0xFF) as u8;
-    cmd[9] |= (longitude & 0xFF) as u8;
-    if gps_en { cmd[10] |= 1; }
-    if beidou_en { cmd[10] |= 2; }
-    cmd
+pub const fn gnss_get_sv_visible_req(time: u32, latitude: u16, longitude: u16, gps_en: bool, beidou_en: bool) -> [u8; 11] {
+    CmdWriter::new(0x041F)
+        .push_u32(time)
+        .push_u16(latitude)
+        .push_u16(longitude)
+        .flag(0, gps_en)
+        .flag(1, beidou_en)
+        .finish()
 }
 
-/// Configures delay after which LR1120 resets Assistance Position and switches from assisted to cold start scan (if time elapsed since last AP update exceeds delay AND no SV detected). FW 02.01+ only.
-pub fn gnss_config_delay_reset_ap_cmd(delay: u32) -> [u8; 5] {
-    let mut cmd = [0u8; 5];
-    cmd[0] = 0x04;
-    cmd[1] = 0x65;
+/// Returns ID, predicted Doppler and Doppler error of the satellites visible for the time, position and constellation given in the last GnssGetSvVisible command.
+pub const fn gnss_get_sv_visible_doppler_req() -> [u8; 2] {
+    [0x04, 0x20]
+}
 
-    cmd[2] |= ((delay >> 16) & 0xFF) as u8;
-    cmd[3] |= ((delay >> 8) & 0xFF) as u8;
-    cmd[4] |= (delay & 0xFF) as u8;
-    cmd
+/// Configures delay after which LR1120 resets Assistance Position and switches from assisted to cold start scan (if time elapsed since last AP update exceeds delay AND no SV detected). FW 02.01+ only.
+pub const fn gnss_config_delay_reset_ap_cmd(delay: u32) -> [u8; 5] {
+    CmdWriter::new(0x0465)
+        .push_u24(delay)
+        .finish()
 }
 
 /// Returns type of scan launched during last scan. FW 02.01+ only.
-pub fn gnss_read_last_scan_mode_launched_req() -> [u8; 2] {
+pub const fn gnss_read_last_scan_mode_launched_req() -> [u8; 2] {
     [0x04, 0x26]
 }
 
 /// Configures LR1120 to determine time by demodulating satellite signals. FW 02.01+ only. Can be aborted.
-pub fn gnss_fetch_time_cmd(best_effort: bool, fetch_time_mode: FetchTimeMode) -> [u8; 4] {
-    let mut cmd = [0u8; 4];
-    cmd[0] = 0x04;
-    cmd[1] = 0x32;
-
-    if best_effort { cmd[2] |= 1; }
-    cmd[3] |= fetch_time_mode as u8;
-    cmd
+pub const fn gnss_fetch_time_cmd(best_effort: bool, fetch_time_mode: FetchTimeMode) -> [u8; 4] {
+    CmdWriter::new(0x0432)
+        .flag(0, best_effort)
+        .skip(1)
+        .push_u8(fetch_time_mode as u8)
+        .finish()
 }
 
 /// Returns GPS time. FW 02.01+ only.
-pub fn gnss_read_time_req() -> [u8; 2] {
+pub const fn gnss_read_time_req() -> [u8; 2] {
     [0x04, 0x34]
 }
 
 /// Resets GPS time. FW 02.01+ only.
-pub fn gnss_reset_time_cmd() -> [u8; 2] {
+pub const fn gnss_reset_time_cmd() -> [u8; 2] {
     [0x04, 0x35]
 }
 
 /// Resets Assistance Position. FW 02.01+ only.
-pub fn gnss_reset_position_cmd() -> [u8; 2] {
+pub const fn gnss_reset_position_cmd() -> [u8; 2] {
     [0x04, 0x37]
 }
 
 /// Allows Host MCU to set LR1120 GPS time. FW 02.01+ only.
-pub fn gnss_set_time_cmd(gps_time: u32, time_accuracy: u16) -> [u8; 8] {
-    let mut cmd = [0u8; 8];
-    cmd[0] = 0x04;
-    cmd[1] = 0x4B;
-
-    cmd[2] |= ((gps_time >> 24) & 0xFF) as u8;
-    cmd[3] |= ((gps_time >> 16) & 0xFF) as u8;
-    cmd[4] |= ((gps_time >> 8) & 0xFF) as u8;
-    cmd[5] |= (gps_time & 0xFF) as u8;
-    cmd[6] |= ((time_accuracy >> 8) & 0xFF) as u8;
-    cmd[7] |= (time_accuracy & 0xFF) as u8;
-    cmd
+pub const fn gnss_set_time_cmd(gps_time: u32, time_accuracy: u16) -> [u8; 8] {
+    CmdWriter::new(0x044B)
+        .push_u32(gps_time)
+        .push_u16(time_accuracy)
+        .finish()
 }
 
 /// Reads Assistance Position calculated by LR1120 2D Solver during GnssScan/GnssAlmanacUpdateFromSat or updated by GnssComputeAssistancePosition. All 18 bytes must be read. FW 02.01+ only.
-pub fn gnss_read_doppler_solver_res_req() -> [u8; 2] {
+pub const fn gnss_read_doppler_solver_res_req() -> [u8; 2] {
     [0x04, 0x4F]
 }
 
 /// Reads delay before Assistance Position reset configured in GnssConfigDelayResetAP. FW 02.01+ only.
-pub fn gnss_read_delay_reset_ap_req() -> [u8; 2] {
+pub const fn gnss_read_delay_reset_ap_req() -> [u8; 2] {
     [0x04, 0x53]
 }
 
 /// Reads number of GPS time Week Number rollover (every 1024 weeks). In 2023, value is 2. Can be changed by GnssSetTime, GnssFetchTime, or GnssScan. FW 02.01+ only.
-pub fn gnss_read_wn_rollover_req() -> [u8; 2] {
+pub const fn gnss_read_wn_rollover_req() -> [u8; 2] {
     [0x04, 0x67]
 }
 
 /// Reads number of visible satellites and time elapsed since last update of detected satellite list for this constellation. FW 02.01+ only.
-pub fn gnss_read_warm_start_status_req(gps_en: bool, beidou_en: bool) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x04;
-    cmd[1] = 0x69;
-
-    if gps_en { cmd[2] |= 1; }
-    if beidou_en { cmd[2] |= 2; }
-    cmd
+pub const fn gnss_read_warm_start_status_req(gps_en: bool, beidou_en: bool) -> [u8; 3] {
+    CmdWriter::new(0x0469)
+        .flag(0, gps_en)
+        .flag(1, beidou_en)
+        .finish()
 }
 
 /// Returns list of satellites for next keep sync scan. Must call GnssReadWarmStartStatus first to know how many satellites in list. FW 02.01+ only.
-pub fn gnss_get_sv_warm_start_req(gps_en: bool, beidou_en: bool) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x04;
-    cmd[1] = 0x66;
-
-    if gps_en { cmd[2] |= 1; }
-    if beidou_en { cmd[2] |= 2; }
-    cmd
+pub const fn gnss_get_sv_warm_start_req(gps_en: bool, beidou_en: bool) -> [u8; 3] {
+    CmdWriter::new(0x0466)
+        .flag(0, gps_en)
+        .flag(1, beidou_en)
+        .finish()
 }
 
 /// Configures LR1120 to search for Almanacs for each satellite. For GPS: 32-bit mask for satellites 1-32. For BeiDou: two 32-bit masks for satellites 1-32 and 33-63. FW 02.01+ only.
-pub fn gnss_write_bit_mask_sat_activated_cmd(gps_en: bool, beidou_en: bool, bit_mask_activated_0: u32) -> [u8; 7] {
-    let mut cmd = [0u8; 7];
-    cmd[0] = 0x04;
-    cmd[1] = 0x72;
-
-    if gps_en { cmd[2] |= 1; }
-    if beidou_en { cmd[2] |= 2; }
-    cmd[3] |= ((bit_mask_activated_0 >> 24) & 0xFF) as u8;
-    cmd[4] |= ((bit_mask_activated_0 >> 16) & 0xFF) as u8;
-    cmd[5] |= ((bit_mask_activated_0 >> 8) & 0xFF) as u8;
-    cmd[6] |= (bit_mask_activated_0 & 0xFF) as u8;
-    cmd
+pub const fn gnss_write_bit_mask_sat_activated_cmd(gps_en: bool, beidou_en: bool, bit_mask_activated_0: u32) -> [u8; 7] {
+    CmdWriter::new(0x0472)
+        .flag(0, gps_en)
+        .flag(1, beidou_en)
+        .skip(1)
+        .push_u32(bit_mask_activated_0)
+        .finish()
 }
 
 /// Configures LR1120 to search for Almanacs for each satellite. For GPS: 32-bit mask for satellites 1-32. For BeiDou: two 32-bit masks for satellites 1-32 and 33-63. FW 02.01+ only.
-pub fn gnss_write_bit_mask_sat_activated_adv_cmd(gps_en: bool, beidou_en: bool, bit_mask_activated_0: u32, bit_mask_activated_1: u32) -> [u8; 11] {
-    let mut cmd = [0u8; 11];
-    cmd[0] = 0x04;
-    cmd[1] = 0x72;
-
-    if gps_en { cmd[2] |= 1; }
-    if beidou_en { cmd[2] |= 2; }
-    cmd[3] |= ((bit_mask_activated_0 >> 24) & 0xFF) as u8;
-    cmd[4] |= ((bit_mask_activated_0 >> 16) & 0xFF) as u8;
-    cmd[5] |= ((bit_mask_activated_0 >> 8) & 0xFF) as u8;
-    cmd[6] |= (bit_mask_activated_0 & 0xFF) as u8;
-    cmd[7] |= ((bit_mask_activated_1 >> 24) & 0xFF) as u8;
-    cmd[8] |= ((bit_mask_activated_1 >> 16) & 0xFF) as u8;
-    cmd[9] |= ((bit_mask_activated_1 >> 8) & 0xFF) as u8;
-    cmd[10] |= (bit_mask_activated_1 & 0xFF) as u8;
-    cmd
+pub const fn gnss_write_bit_mask_sat_activated_adv_cmd(gps_en: bool, beidou_en: bool, bit_mask_activated_0: u32, bit_mask_activated_1: u32) -> [u8; 11] {
+    CmdWriter::new(0x0472)
+        .flag(0, gps_en)
+        .flag(1, beidou_en)
+        .skip(1)
+        .push_u32(bit_mask_activated_0)
+        .push_u32(bit_mask_activated_1)
+        .finish()
 }
 
 /// Launches GNSS scan to download Almanac parameters from satellite signal (subframe 4/5) for one constellation. Must be sent at precise time matching Almanac data availability - use GnssReadAlmanacStatus. Default: Almanac in RAM, written to flash when >6 satellites available or >half almanacs to update available. Can be aborted. FW 02.01+ only.
-pub fn gnss_almanac_update_from_sat_cmd(best_effort: bool, gps_en: bool, beidou_en: bool) -> [u8; 4] {
-    let mut cmd = [0u8; 4];
-    cmd[0] = 0x04;
-    cmd[1] = 0x55;
-
-    if best_effort { cmd[2] |= 1; }
-    if gps_en { cmd[3] |= 1; }
-    if beidou_en { cmd[3] |= 2; }
-    cmd
+pub const fn gnss_almanac_update_from_sat_cmd(best_effort: bool, gps_en: bool, beidou_en: bool) -> [u8; 4] {
+    CmdWriter::new(0x0455)
+        .flag(0, best_effort)
+        .skip(1)
+        .flag(0, gps_en)
+        .flag(1, beidou_en)
+        .finish()
 }
 
 /// Returns detailed almanac update status for both GPS and BeiDou constellations including which satellites need update, next subframe timing, and activation status. Updated when SV almanac demodulated and stored in retention memory or flash. All 53 bytes must be read. FW 02.01+ only.
-pub fn gnss_read_almanac_status_req() -> [u8; 2] {
+pub const fn gnss_read_almanac_status_req() -> [u8; 2] {
     [0x04, 0x57]
 }
 
 /// Configures Almanac update period (days) after which application notified via GnssReadAlmanacStatus. Age compared with Period. Defaults: GPS 31 days, BeiDou MEO 60 days, BeiDou IGSO 30 days. FW 02.01+ only.
-pub fn gnss_config_almanac_update_period_cmd(gps_en: bool, beidou_en: bool, beidou_type: BeidouType, period: u16) -> [u8; 6] {
-    let mut cmd = [0u8; 6];
-    cmd[0] = 0x04;
-    cmd[1] = 0x63;
-
-    if gps_en { cmd[2] |= 1; }
-    if beidou_en { cmd[2] |= 2; }
-    cmd[3] |= beidou_type as u8;
-    cmd[4] |= ((period >> 8) & 0xFF) as u8;
-    cmd[5] |= (period & 0xFF) as u8;
-    cmd
+pub const fn gnss_config_almanac_update_period_cmd(gps_en: bool, beidou_en: bool, beidou_type: BeidouType, period: u16) -> [u8; 6] {
+    CmdWriter::new(0x0463)
+        .flag(0, gps_en)
+        .flag(1, beidou_en)
+        .skip(1)
+        .push_u8(beidou_type as u8)
+        .push_u16(period)
+        .finish()
 }
 
 /// Reads Almanac update period for constellation and SV type. FW 02.01+ only.
-pub fn gnss_read_almanac_update_period_req(gps_en: bool, beidou_en: bool, beidou_type: BeidouType) -> [u8; 4] {
-    let mut cmd = [0u8; 4];
-    cmd[0] = 0x04;
-    cmd[1] = 0x64;
+pub const fn gnss_read_almanac_update_period_req(gps_en: bool, beidou_en: bool, beidou_type: BeidouType) -> [u8; 4] {
+    CmdWriter::new(0x0464)
+        .flag(0, gps_en)
+        .flag(1, beidou_en)
+        .skip(1)
+        .push_u8(beidou_type as u8)
+        .finish()
+}
+
+/// Returns the start address and size of the almanac storage, allowing to read it back with ReadRegMem32.
+pub const fn gnss_almanac_read_addr_size_req() -> [u8; 2] {
+    [0x04, 0x0F]
+}
 
-    if gps_en { cmd[2] |= 1; }
-    if beidou_en { cmd[2] |= 2; }
-    cmd[3] |= beidou_type as u8;
-    cmd
+/// Reads the almanac of nb_sv consecutive satellites starting from sv_id. Each satellite record is 22 bytes.
+pub const fn gnss_read_almanac_per_satellite_req(sv_id: u8, nb_sv: u8) -> [u8; 4] {
+    CmdWriter::new(0x041A)
+        .push_u8(sv_id)
+        .push_u8(nb_sv)
+        .finish()
 }
 
 /// Updates all Almanac data for all satellites. Each constellation updated separately. Total 2580 bytes: 20-byte header + 128 satellites * 20 bytes. Max 512 bytes per SPI transaction - requires multiple transactions. Two approaches: (1) 129 transactions of 20 bytes each (min memory), (2) 5 transactions of 500 bytes + 1 of 80 bytes (min transactions). Almanac stored in flash, kept after power off/sleep without retention.
-pub fn gnss_almanac_full_update_cmd() -> [u8; 2] {
+pub const fn gnss_almanac_full_update_cmd() -> [u8; 2] {
     [0x04, 0x0E]
 }
 
@@ -538,13 +487,23 @@ pub fn gnss_almanac_full_update_cmd() -> [u8; 2] {
 pub struct GnssReadConstellationToUseRsp([u8; 2]);
 
 impl GnssReadConstellationToUseRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -570,13 +529,23 @@ impl AsMut<[u8]> for GnssReadConstellationToUseRsp {
 pub struct GnssReadSupportedConstellationsRsp([u8; 2]);
 
 impl GnssReadSupportedConstellationsRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -602,13 +571,23 @@ impl AsMut<[u8]> for GnssReadSupportedConstellationsRsp {
 pub struct GnssReadAssistancePositionRsp([u8; 5]);
 
 impl GnssReadAssistancePositionRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 5;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 5] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -636,13 +615,23 @@ impl AsMut<[u8]> for GnssReadAssistancePositionRsp {
 pub struct GnssGetContextStatusRsp([u8; 10]);
 
 impl GnssGetContextStatusRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 10;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 10] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -692,13 +681,23 @@ impl AsMut<[u8]> for GnssGetContextStatusRsp {
 pub struct GnssReadVersionRsp([u8; 3]);
 
 impl GnssReadVersionRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 3;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 3] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -724,13 +723,23 @@ impl AsMut<[u8]> for GnssReadVersionRsp {
 pub struct GnssReadAlmanacUpdateRsp([u8; 2]);
 
 impl GnssReadAlmanacUpdateRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -756,13 +765,23 @@ impl AsMut<[u8]> for GnssReadAlmanacUpdateRsp {
 pub struct GnssGetResultSizeRsp([u8; 3]);
 
 impl GnssGetResultSizeRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 3;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 3] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -784,13 +803,23 @@ impl AsMut<[u8]> for GnssGetResultSizeRsp {
 pub struct GnssGetNbSvDetectedRsp([u8; 2]);
 
 impl GnssGetNbSvDetectedRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -810,11 +839,14 @@ impl AsMut<[u8]> for GnssGetNbSvDetectedRsp {
 pub struct GnssGetSvDetectedRsp([u8; 4]);
 
 impl GnssGetSvDetectedRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 4;
 
     /// Create struct from existing response buffer
-    pub fn from_slice(buffer: &[u8]) -> Self {
-        let raw : [u8; 4] = buffer.try_into().expect("Buffer size should match response size !");
-        Self(raw)
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 4] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
     }
 
     /// Identifier
@@ -846,13 +878,23 @@ impl AsMut<[u8]> for GnssGetSvDetectedRsp {
 pub struct GnssGetConsumptionRsp([u8; 9]);
 
 impl GnssGetConsumptionRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 9;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 9] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -884,13 +926,23 @@ impl AsMut<[u8]> for GnssGetConsumptionRsp {
 pub struct GnssGetSvVisibleRsp([u8; 2]);
 
 impl GnssGetSvVisibleRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -906,18 +958,67 @@ impl AsMut<[u8]> for GnssGetSvVisibleRsp {
     }
 }
 
+/// Response for GnssGetSvVisibleDoppler command
+pub struct GnssGetSvVisibleDopplerRsp([u8; 5]);
+
+impl GnssGetSvVisibleDopplerRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 5;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 5] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
+    /// Identifier
+    pub fn sv_id(&self) -> u8 {
+        self.0[0]
+    }
+
+    /// Predicted Doppler in Hz
+    pub fn doppler(&self) -> i16 {
+        let raw = (self.0[2] as u16) |
+            ((self.0[1] as u16) << 8);
+        raw as i16
+    }
+
+    /// Doppler error in Hz
+    pub fn doppler_error(&self) -> u16 {
+        (self.0[4] as u16) |
+        ((self.0[3] as u16) << 8)
+    }
+}
+
+impl AsMut<[u8]> for GnssGetSvVisibleDopplerRsp {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
 /// Response for GnssReadLastScanModeLaunched command
 #[derive(Default)]
 pub struct GnssReadLastScanModeLaunchedRsp([u8; 2]);
 
 impl GnssReadLastScanModeLaunchedRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -938,13 +1039,23 @@ impl AsMut<[u8]> for GnssReadLastScanModeLaunchedRsp {
 pub struct GnssReadTimeRsp([u8; 9]);
 
 impl GnssReadTimeRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 9;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 9] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -976,13 +1087,23 @@ impl AsMut<[u8]> for GnssReadTimeRsp {
 pub struct GnssReadDopplerSolverResRsp([u8; 19]);
 
 impl GnssReadDopplerSolverResRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 19;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 19] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -1056,13 +1177,23 @@ impl AsMut<[u8]> for GnssReadDopplerSolverResRsp {
 pub struct GnssReadDelayResetAPRsp([u8; 4]);
 
 impl GnssReadDelayResetAPRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 4;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 4] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -1085,13 +1216,23 @@ impl AsMut<[u8]> for GnssReadDelayResetAPRsp {
 pub struct GnssReadWNRolloverRsp([u8; 3]);
 
 impl GnssReadWNRolloverRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 3;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 3] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -1117,13 +1258,23 @@ impl AsMut<[u8]> for GnssReadWNRolloverRsp {
 pub struct GnssReadWarmStartStatusRsp([u8; 6]);
 
 impl GnssReadWarmStartStatusRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 6;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 6] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -1152,13 +1303,23 @@ impl AsMut<[u8]> for GnssReadWarmStartStatusRsp {
 pub struct GnssGetSvWarmStartRsp([u8; 2]);
 
 impl GnssGetSvWarmStartRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
     // TODO: Implement accessor for variable length field 'sv_list'
@@ -1174,13 +1335,23 @@ impl AsMut<[u8]> for GnssGetSvWarmStartRsp {
 pub struct GnssReadAlmanacStatusRsp([u8; 54]);
 
 impl GnssReadAlmanacStatusRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 54;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 54] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -1323,10 +1494,10 @@ impl AsMut<[u8]> for GnssReadAlmanacStatusRsp {
         &mut self.0
     }
 }
+
 impl Default for GnssReadAlmanacStatusRsp {
     fn default() -> Self {
-        let content : [u8; 54] = core::array::repeat(0);
-        Self(content)
+        Self([0u8; Self::RSP_LEN])
     }
 }
 
@@ -1335,13 +1506,23 @@ impl Default for GnssReadAlmanacStatusRsp {
 pub struct GnssReadAlmanacUpdatePeriodRsp([u8; 3]);
 
 impl GnssReadAlmanacUpdatePeriodRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 3;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 3] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -1357,3 +1538,49 @@ impl AsMut<[u8]> for GnssReadAlmanacUpdatePeriodRsp {
         &mut self.0
     }
 }
+
+/// Response for GnssAlmanacReadAddrSize command
+#[derive(Default)]
+pub struct GnssAlmanacReadAddrSizeRsp([u8; 7]);
+
+impl GnssAlmanacReadAddrSizeRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 7;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 7] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
+    /// Create a new response buffer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
+        self.0[0].into()
+    }
+
+    /// Start address of the almanac storage
+    pub fn addr(&self) -> u32 {
+        (self.0[4] as u32) |
+        ((self.0[3] as u32) << 8) |
+        ((self.0[2] as u32) << 16) |
+        ((self.0[1] as u32) << 24)
+    }
+
+    /// Size of the almanac storage in bytes
+    pub fn size(&self) -> u16 {
+        (self.0[6] as u16) |
+        ((self.0[5] as u16) << 8)
+    }
+}
+
+impl AsMut<[u8]> for GnssAlmanacReadAddrSizeRsp {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
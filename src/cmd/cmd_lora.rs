@@ -1,5 +1,6 @@
 // Lora commands API
 
+use super::CmdWriter;
 use crate::status::Status;
 
 /// Spreading factor
@@ -179,145 +180,105 @@ pub enum RangingResKind {
 }
 
 /// Configures LoRa modulation parameters (SF, BW, CR, LDRO). Returns CMD_FAIL if packet type is not LoRa. SF5/SF6 compatible with SX126x. SF6 can be made compatible with SX127x in implicit mode via register setting.
-pub fn set_lora_modulation_params_cmd(sf: Sf, lora_bw: LoraBw, lora_cr: LoraCr, ldro: Ldro) -> [u8; 6] {
-    let mut cmd = [0u8; 6];
-    cmd[0] = 0x02;
-    cmd[1] = 0x0F;
-
-    cmd[2] |= sf as u8;
-    cmd[3] |= lora_bw as u8;
-    cmd[4] |= (lora_cr as u8) & 0x7;
-    cmd[5] |= (ldro as u8) & 0x3;
-    cmd
+pub const fn set_lora_modulation_params_cmd(sf: Sf, lora_bw: LoraBw, lora_cr: LoraCr, ldro: Ldro) -> [u8; 6] {
+    CmdWriter::new(0x020F)
+        .push_u8(sf as u8)
+        .push_u8(lora_bw as u8)
+        .bits(lora_cr as u8, 3, 0)
+        .skip(1)
+        .bits(ldro as u8, 2, 0)
+        .finish()
 }
 
 /// Configures LoRa RF packet parameters. Fails if no packet type set.
-pub fn set_lora_packet_params_cmd(pbl_len: u16, header_type: HeaderType, pld_len: u8, crc_en: bool, invert_iq: bool) -> [u8; 8] {
-    let mut cmd = [0u8; 8];
-    cmd[0] = 0x02;
-    cmd[1] = 0x10;
-
-    cmd[2] |= ((pbl_len >> 8) & 0xFF) as u8;
-    cmd[3] |= (pbl_len & 0xFF) as u8;
-    cmd[4] |= header_type as u8;
-    cmd[5] |= pld_len;
-    if crc_en { cmd[6] |= 1; }
-    if invert_iq { cmd[7] |= 1; }
-    cmd
+pub const fn set_lora_packet_params_cmd(pbl_len: u16, header_type: HeaderType, pld_len: u8, crc_en: bool, invert_iq: bool) -> [u8; 8] {
+    CmdWriter::new(0x0210)
+        .push_u16(pbl_len)
+        .push_u8(header_type as u8)
+        .push_u8(pld_len)
+        .flag(0, crc_en)
+        .skip(1)
+        .flag(0, invert_iq)
+        .finish()
 }
 
 /// Activates Channel Activity Detection (CAD) feature. Searches for LoRa preamble signal in both preamble and payload fields. Triggers CADdone IRQ when complete, CadDetected IRQ if valid signal found. Returns to STDBY_RC after completion. Minimum 2 symbols recommended.
-pub fn set_lora_cad_cmd() -> [u8; 2] {
+pub const fn set_lora_cad_cmd() -> [u8; 2] {
     [0x02, 0x18]
 }
 
 /// Defines LoRa CAD parameters. DetPeak/DetMin depend on SF, BW, and symbol count - must be carefully tested for good sensitivity and minimal false detections.
-pub fn set_lora_cad_params_cmd(nb_symbols: u8, det_peak: u8, det_min: u8, exit_mode: ExitMode, timeout: u32) -> [u8; 9] {
-    let mut cmd = [0u8; 9];
-    cmd[0] = 0x02;
-    cmd[1] = 0x0D;
-
-    cmd[2] |= nb_symbols;
-    cmd[3] |= det_peak;
-    cmd[4] |= det_min;
-    cmd[5] |= exit_mode as u8;
-    cmd[6] |= ((timeout >> 16) & 0xFF) as u8;
-    cmd[7] |= ((timeout >> 8) & 0xFF) as u8;
-    cmd[8] |= (timeout & 0xFF) as u8;
-    cmd
+pub const fn set_lora_cad_params_cmd(nb_symbols: u8, det_peak: u8, det_min: u8, exit_mode: ExitMode, timeout: u32) -> [u8; 9] {
+    CmdWriter::new(0x020D)
+        .push_u8(nb_symbols)
+        .push_u8(det_peak)
+        .push_u8(det_min)
+        .push_u8(exit_mode as u8)
+        .push_u24(timeout)
+        .finish()
 }
 
 /// Configures LoRa modem to issue RX timeout after exactly SymbolNum symbols if no packet detected
-pub fn set_lora_synch_timeout_cmd(symbol_num: u8) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x02;
-    cmd[1] = 0x1B;
-
-    cmd[2] |= symbol_num;
-    cmd
+pub const fn set_lora_synch_timeout_cmd(symbol_num: u8) -> [u8; 3] {
+    CmdWriter::new(0x021B)
+        .push_u8(symbol_num)
+        .finish()
 }
 
 /// Sets the LoRa syncword. Valid for all spreading factors.
-pub fn set_lora_syncword_cmd(syncword: u8) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x02;
-    cmd[1] = 0x2B;
-
-    cmd[2] |= syncword;
-    cmd
+pub const fn set_lora_syncword_cmd(syncword: u8) -> [u8; 3] {
+    CmdWriter::new(0x022B)
+        .push_u8(syncword)
+        .finish()
 }
 
 /// Returns information coded in last received packet header (explicit header mode) or configured coding_rate and crc_type settings
-pub fn get_lora_rx_header_infos_req() -> [u8; 2] {
+pub const fn get_lora_rx_header_infos_req() -> [u8; 2] {
     [0x02, 0x30]
 }
 
 /// Returns link quality informations on last received packet
-pub fn get_lora_packet_status_req() -> [u8; 2] {
+pub const fn get_lora_packet_status_req() -> [u8; 2] {
     [0x02, 0x04]
 }
 
 /// Sets the ranging ID for this slave device. Defines which address bytes are checked against master's request.
-pub fn set_ranging_addr_cmd(addr: u32, check_length: CheckLength) -> [u8; 7] {
-    let mut cmd = [0u8; 7];
-    cmd[0] = 0x02;
-    cmd[1] = 0x1C;
-
-    cmd[2] |= ((addr >> 24) & 0xFF) as u8;
-    cmd[3] |= ((addr >> 16) & 0xFF) as u8;
-    cmd[4] |= ((addr >> 8) & 0xFF) as u8;
-    cmd[5] |= (addr & 0xFF) as u8;
-    cmd[6] |= (check_length as u8) & 0x7;
-    cmd
+pub const fn set_ranging_addr_cmd(addr: u32, check_length: CheckLength) -> [u8; 7] {
+    CmdWriter::new(0x021C)
+        .push_u32(addr)
+        .bits(check_length as u8, 3, 0)
+        .finish()
 }
 
 /// Sets the address requested by the Master in the ranging request. Must match receiving Slave's ranging address.
-pub fn set_ranging_req_addr_cmd(req_addr: u32) -> [u8; 6] {
-    let mut cmd = [0u8; 6];
-    cmd[0] = 0x02;
-    cmd[1] = 0x1D;
-
-    cmd[2] |= ((req_addr >> 24) & 0xFF) as u8;
-    cmd[3] |= ((req_addr >> 16) & 0xFF) as u8;
-    cmd[4] |= ((req_addr >> 8) & 0xFF) as u8;
-    cmd[5] |= (req_addr & 0xFF) as u8;
-    cmd
+pub const fn set_ranging_req_addr_cmd(req_addr: u32) -> [u8; 6] {
+    CmdWriter::new(0x021D)
+        .push_u32(req_addr)
+        .finish()
 }
 
 /// Reads ranging results in Master based on Type. Distance formula: Round Trip Distance (m) = Res * 3e8 / (2^12 * BW), where BW is LoRa bandwidth in Hz. RSSI formula: RSSI (dB) = Res / 2
-pub fn get_ranging_result_req(ranging_res_kind: RangingResKind) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x02;
-    cmd[1] = 0x1E;
-
-    cmd[2] |= ranging_res_kind as u8;
-    cmd
+pub const fn get_ranging_result_req(ranging_res_kind: RangingResKind) -> [u8; 3] {
+    CmdWriter::new(0x021E)
+        .push_u8(ranging_res_kind as u8)
+        .finish()
 }
 
 
 
 /// Sets TxRx delay for ranging calibration. Compensates for deterministic fixed delay in processing ranging response/request for accurate range estimation. Same value must be written in both Master and Slave. Value depends on LoRa BW/SF used.
-pub fn set_ranging_tx_rx_delay_cmd(delay: u32) -> [u8; 6] {
-    let mut cmd = [0u8; 6];
-    cmd[0] = 0x02;
-    cmd[1] = 0x1F;
-
-    cmd[2] |= ((delay >> 24) & 0xFF) as u8;
-    cmd[3] |= ((delay >> 16) & 0xFF) as u8;
-    cmd[4] |= ((delay >> 8) & 0xFF) as u8;
-    cmd[5] |= (delay & 0xFF) as u8;
-    cmd
+pub const fn set_ranging_tx_rx_delay_cmd(delay: u32) -> [u8; 6] {
+    CmdWriter::new(0x021F)
+        .push_u32(delay)
+        .finish()
 }
 
 /// Defines number of symbols used during synchronization. Value of 15 recommended for good compromise between accuracy and time on air/energy. Increasing symbols improves accuracy at expense of longer time on air.
-pub fn set_ranging_parameter_cmd(reserved: u8, symb_nb: u8) -> [u8; 4] {
-    let mut cmd = [0u8; 4];
-    cmd[0] = 0x02;
-    cmd[1] = 0x28;
-
-    cmd[2] |= reserved;
-    cmd[3] |= symb_nb;
-    cmd
+pub const fn set_ranging_parameter_cmd(reserved: u8, symb_nb: u8) -> [u8; 4] {
+    CmdWriter::new(0x0228)
+        .push_u8(reserved)
+        .push_u8(symb_nb)
+        .finish()
 }
 
 // Response structs
@@ -327,13 +288,23 @@ pub fn set_ranging_parameter_cmd(reserved: u8, symb_nb: u8) -> [u8; 4] {
 pub struct LoraRxHeaderInfosRsp([u8; 2]);
 
 impl LoraRxHeaderInfosRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -359,13 +330,23 @@ impl AsMut<[u8]> for LoraRxHeaderInfosRsp {
 pub struct LoraPacketStatusRsp([u8; 4]);
 
 impl LoraPacketStatusRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 4;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 4] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -396,13 +377,23 @@ impl AsMut<[u8]> for LoraPacketStatusRsp {
 pub struct RangingResultRsp([u8; 4]);
 
 impl RangingResultRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 4;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 4] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -425,13 +416,23 @@ impl AsMut<[u8]> for RangingResultRsp {
 pub struct RangingRssiRsp([u8; 2]);
 
 impl RangingRssiRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
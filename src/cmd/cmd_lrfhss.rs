@@ -1,5 +1,6 @@
 // Lrfhss commands API
 
+use super::CmdWriter;
 
 /// Coding rate
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -45,32 +46,24 @@ pub enum LrfhssBw {
 
 #[allow(clippy::too_many_arguments)]
 /// Encodes payload and configures internal hopping table. Returns CMD_OK if valid, CMD_PERR if invalid. Does NOT send packet - use SetTx to transmit. Max coded packet 255 bytes. See payload length table for user payload limits by CR and HeaderCount. FCC use case: BW 0x08/0x09, Hopping 0x01, Grid 0x00. If configured, LrFhssHop IRQ asserted at each hop after PA ramp-up.
-pub fn lr_fhss_build_frame_cmd(sync_header_cnt: u8, lrfhss_cr: LrfhssCr, mod_type: u8, grid: Grid, hopping: Hopping, lrfhss_bw: LrfhssBw, hop_sequence: u16, device_offset: i8) -> [u8; 11] {
-    let mut cmd = [0u8; 11];
-    cmd[0] = 0x02;
-    cmd[1] = 0x2C;
-
-    cmd[2] |= sync_header_cnt;
-    cmd[3] |= lrfhss_cr as u8;
-    cmd[4] |= mod_type;
-    cmd[5] |= grid as u8;
-    cmd[6] |= hopping as u8;
-    cmd[7] |= lrfhss_bw as u8;
-    cmd[8] |= ((hop_sequence >> 8) & 0xFF) as u8;
-    cmd[9] |= (hop_sequence & 0xFF) as u8;
-    cmd[10] |= (device_offset) as u8;
-    cmd
+pub const fn lr_fhss_build_frame_cmd(sync_header_cnt: u8, lrfhss_cr: LrfhssCr, mod_type: u8, grid: Grid, hopping: Hopping, lrfhss_bw: LrfhssBw, hop_sequence: u16, device_offset: i8) -> [u8; 11] {
+    CmdWriter::new(0x022C)
+        .push_u8(sync_header_cnt)
+        .push_u8(lrfhss_cr as u8)
+        .push_u8(mod_type)
+        .push_u8(grid as u8)
+        .push_u8(hopping as u8)
+        .push_u8(lrfhss_bw as u8)
+        .bits((hop_sequence >> 8) as u8, 1, 0)
+        .skip(1)
+        .push_u8(hop_sequence as u8)
+        .push_u8(device_offset as u8)
+        .finish()
 }
 
 /// Sets LR-FHSS syncword (4 bytes). Default: {0x2C, 0x0F, 0x79, 0x95}
-pub fn lr_fhss_set_sync_word_cmd(syncword: u32) -> [u8; 6] {
-    let mut cmd = [0u8; 6];
-    cmd[0] = 0x02;
-    cmd[1] = 0x2D;
-
-    cmd[2] |= ((syncword >> 24) & 0xFF) as u8;
-    cmd[3] |= ((syncword >> 16) & 0xFF) as u8;
-    cmd[4] |= ((syncword >> 8) & 0xFF) as u8;
-    cmd[5] |= (syncword & 0xFF) as u8;
-    cmd
+pub const fn lr_fhss_set_sync_word_cmd(syncword: u32) -> [u8; 6] {
+    CmdWriter::new(0x022D)
+        .push_u32(syncword)
+        .finish()
 }
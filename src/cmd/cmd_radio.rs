@@ -1,5 +1,6 @@
 // Radio commands API
 
+use super::CmdWriter;
 use crate::status::Status;
 
 /// Device mode between TX and RX modes
@@ -96,199 +97,157 @@ impl From<u8> for PacketType {
 }
 
 /// Sets the RF (PLL) frequency of the radio in Hz. Sub-GHz path selected for frequencies ≤1.50GHz, HF path for higher frequencies. All frequency dependent parameters automatically recomputed.
-pub fn set_rf_frequency_cmd(rf_freq: u32) -> [u8; 6] {
-    let mut cmd = [0u8; 6];
-    cmd[0] = 0x02;
-    cmd[1] = 0x0B;
-
-    cmd[2] |= ((rf_freq >> 24) & 0xFF) as u8;
-    cmd[3] |= ((rf_freq >> 16) & 0xFF) as u8;
-    cmd[4] |= ((rf_freq >> 8) & 0xFF) as u8;
-    cmd[5] |= (rf_freq & 0xFF) as u8;
-    cmd
+pub const fn set_rf_frequency_cmd(rf_freq: u32) -> [u8; 6] {
+    CmdWriter::new(0x020B)
+        .push_u32(rf_freq)
+        .finish()
 }
 
 /// Sets radio in RX mode. Sub-GHz path for ≤1.50GHz, HF path above. After timeout, returns to Standby RC. BUSY goes low after entering RX mode. Fails if no packet type configured or packet type doesn't allow RX.
-pub fn set_rx_cmd(rx_timeout: u32) -> [u8; 5] {
-    let mut cmd = [0u8; 5];
-    cmd[0] = 0x02;
-    cmd[1] = 0x09;
-
-    cmd[2] |= ((rx_timeout >> 16) & 0xFF) as u8;
-    cmd[3] |= ((rx_timeout >> 8) & 0xFF) as u8;
-    cmd[4] |= (rx_timeout & 0xFF) as u8;
-    cmd
+pub const fn set_rx_cmd(rx_timeout: u32) -> [u8; 5] {
+    CmdWriter::new(0x0209)
+        .push_u24(rx_timeout)
+        .finish()
 }
 
 /// Sets radio in TX mode, triggers RF packet transmission with RTC timeout. After TX_DONE or TIMEOUT, returns to STBY_RC (default), STBY_XOSC or FS per FallBackMode config. BUSY goes low after entering TX mode. Fails if no packet type configured or packet type doesn't allow TX.
-pub fn set_tx_cmd(tx_timeout: u32) -> [u8; 5] {
-    let mut cmd = [0u8; 5];
-    cmd[0] = 0x02;
-    cmd[1] = 0x0A;
-
-    cmd[2] |= ((tx_timeout >> 16) & 0xFF) as u8;
-    cmd[3] |= ((tx_timeout >> 8) & 0xFF) as u8;
-    cmd[4] |= (tx_timeout & 0xFF) as u8;
-    cmd
+pub const fn set_tx_cmd(tx_timeout: u32) -> [u8; 5] {
+    CmdWriter::new(0x020A)
+        .push_u24(tx_timeout)
+        .finish()
 }
 
 /// Sets device in TX continuous wave mode (unmodulated carrier). Immediately enters TX CW mode. Operating frequency and PA configuration commands (including RF output power) must be called PRIOR to this command. Used for ETSI D-M1 test (unmodulated carrier) and FCC Part 15.247 compliance testing.
-pub fn set_tx_cw_cmd() -> [u8; 2] {
+pub const fn set_tx_cw_cmd() -> [u8; 2] {
     [0x02, 0x19]
 }
 
 /// Transmits infinite preamble sequence. Immediately starts transmission. Operating frequency, PA configuration commands (including RF output power), and packet type must be called PRIOR to this command. Used for ETSI D-M2 test (continuously modulated signal with greatest occupied RF bandwidth).
-pub fn set_tx_infinite_preamble_cmd() -> [u8; 2] {
+pub const fn set_tx_infinite_preamble_cmd() -> [u8; 2] {
     [0x02, 0x1A]
 }
 
 /// Automatically performs transition to RX after TX or TX after RX. After second mode, returns to Standby RC. Not used if Rx Duty Cycle is started.
-pub fn auto_tx_rx_cmd(delay: u32, intermediary_mode: IntermediaryMode, timeout: u32) -> [u8; 9] {
-    let mut cmd = [0u8; 9];
-    cmd[0] = 0x02;
-    cmd[1] = 0x0C;
-
-    cmd[2] |= ((delay >> 16) & 0xFF) as u8;
-    cmd[3] |= ((delay >> 8) & 0xFF) as u8;
-    cmd[4] |= (delay & 0xFF) as u8;
-    cmd[5] |= intermediary_mode as u8;
-    cmd[6] |= ((timeout >> 16) & 0xFF) as u8;
-    cmd[7] |= ((timeout >> 8) & 0xFF) as u8;
-    cmd[8] |= (timeout & 0xFF) as u8;
-    cmd
+pub const fn auto_tx_rx_cmd(delay: u32, intermediary_mode: IntermediaryMode, timeout: u32) -> [u8; 9] {
+    CmdWriter::new(0x020C)
+        .push_u24(delay)
+        .push_u8(intermediary_mode as u8)
+        .push_u24(timeout)
+        .finish()
 }
 
 /// Defines device mode after packet transmission or reception. Not used if Rx Duty Cycle started or AutoRxTx configured. After RX timeout, device returns to Standby RC regardless of fallback config.
-pub fn set_rx_tx_fallback_mode_cmd(fallback_mode: FallbackMode) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x02;
-    cmd[1] = 0x13;
-
-    cmd[2] |= fallback_mode as u8;
-    cmd
+pub const fn set_rx_tx_fallback_mode_cmd(fallback_mode: FallbackMode) -> [u8; 3] {
+    CmdWriter::new(0x0213)
+        .push_u8(fallback_mode as u8)
+        .finish()
 }
 
 /// Periodically opens RX windows. Between windows, device goes to Sleep mode with retention. Configure 32kHz clock source before entering Duty Cycle. AutoRxTx and SetRxTxFallback not used in this mode. Returns CMD_FAIL if packet type not set.
-pub fn set_rx_duty_cycle_cmd(rx_period: u32, sleep_period: u32, use_lora_cad: bool) -> [u8; 9] {
-    let mut cmd = [0u8; 9];
-    cmd[0] = 0x02;
-    cmd[1] = 0x14;
-
-    cmd[2] |= ((rx_period >> 16) & 0xFF) as u8;
-    cmd[3] |= ((rx_period >> 8) & 0xFF) as u8;
-    cmd[4] |= (rx_period & 0xFF) as u8;
-    cmd[5] |= ((sleep_period >> 16) & 0xFF) as u8;
-    cmd[6] |= ((sleep_period >> 8) & 0xFF) as u8;
-    cmd[7] |= (sleep_period & 0xFF) as u8;
-    if use_lora_cad { cmd[8] |= 1; }
-    cmd
+pub const fn set_rx_duty_cycle_cmd(rx_period: u32, sleep_period: u32, use_lora_cad: bool) -> [u8; 9] {
+    CmdWriter::new(0x0214)
+        .push_u24(rx_period)
+        .push_u24(sleep_period)
+        .flag(0, use_lora_cad)
+        .finish()
 }
 
 /// Defines if RX timeout should be stopped on Syncword/Header detection or Preamble detection
-pub fn stop_timeout_on_preamble_cmd(stop_on_preamble: bool) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x02;
-    cmd[1] = 0x17;
-
-    if stop_on_preamble { cmd[2] |= 1; }
-    cmd
+pub const fn stop_timeout_on_preamble_cmd(stop_on_preamble: bool) -> [u8; 3] {
+    CmdWriter::new(0x0217)
+        .flag(0, stop_on_preamble)
+        .finish()
 }
 
 /// Returns instantaneous RSSI value at the time command is sent. If no RF packet present, returns RF noise. Formula: RSSI (dBm) = -Rssi/2
-pub fn get_rssi_inst_req() -> [u8; 2] {
+pub const fn get_rssi_inst_req() -> [u8; 2] {
     [0x02, 0x05]
 }
 
 /// Returns internal statistics of received RF packets. Statistics reset on Power On Reset, power down, or ResetStats command.
-pub fn get_stats_req() -> [u8; 2] {
+pub const fn get_stats_req() -> [u8; 2] {
     [0x02, 0x01]
 }
 
 /// Resets the internal statistics of received RF packets
-pub fn reset_stats_cmd() -> [u8; 2] {
+pub const fn reset_stats_cmd() -> [u8; 2] {
     [0x02, 0x00]
 }
 
 /// Returns the length of last packet received and offset in RX buffer of first byte received
-pub fn get_rx_buffer_status_req() -> [u8; 2] {
+pub const fn get_rx_buffer_status_req() -> [u8; 2] {
     [0x02, 0x03]
 }
 
 /// Sets device in RX Boosted mode, allowing ~2dB increased sensitivity at expense of ~2mA higher RX current consumption
-pub fn set_rx_boosted_cmd(rx_boosted: RxBoosted) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x02;
-    cmd[1] = 0x27;
-
-    cmd[2] |= rx_boosted as u8;
-    cmd
+pub const fn set_rx_boosted_cmd(rx_boosted: RxBoosted) -> [u8; 3] {
+    CmdWriter::new(0x0227)
+        .push_u8(rx_boosted as u8)
+        .finish()
 }
 
 /// Selects which PA to use and configures the PA supply. Must be called before SetTxParams. No automatic frequency limitation during PA selection - frequency must match external matching network capability.
-pub fn set_pa_config_cmd(pa_sel: PaSel, reg_pa_supply: RegPaSupply, pa_duty_cycle: u8, pa_hp_sel: u8) -> [u8; 6] {
-    let mut cmd = [0u8; 6];
-    cmd[0] = 0x02;
-    cmd[1] = 0x15;
-
-    cmd[2] |= pa_sel as u8;
-    cmd[3] |= reg_pa_supply as u8;
-    cmd[4] |= pa_duty_cycle;
-    cmd[5] |= pa_hp_sel;
-    cmd
+pub const fn set_pa_config_cmd(pa_sel: PaSel, reg_pa_supply: RegPaSupply, pa_duty_cycle: u8, pa_hp_sel: u8) -> [u8; 6] {
+    CmdWriter::new(0x0215)
+        .push_u8(pa_sel as u8)
+        .push_u8(reg_pa_supply as u8)
+        .push_u8(pa_duty_cycle)
+        .push_u8(pa_hp_sel)
+        .finish()
 }
 
 /// Sets TX power and ramp time of selected PA. SetPaConfig must be sent prior to this command. 48us ramp time recommended for best trade-off between fast RF establishment and minimum spurious emissions.
-pub fn set_tx_params_cmd(tx_power: i8, ramp_time: RampTime) -> [u8; 4] {
-    let mut cmd = [0u8; 4];
-    cmd[0] = 0x02;
-    cmd[1] = 0x11;
-
-    cmd[2] |= (tx_power) as u8;
-    cmd[3] |= ramp_time as u8;
-    cmd
+pub const fn set_tx_params_cmd(tx_power: i8, ramp_time: RampTime) -> [u8; 4] {
+    CmdWriter::new(0x0211)
+        .push_u8(tx_power as u8)
+        .push_u8(ramp_time as u8)
+        .finish()
 }
 
 #[allow(clippy::too_many_arguments)]
 /// Sets gain offset for on-chip power estimation. LR1120 internal LNA has predefined gains (G4-G13 with sub-gains). RSSI must be calibrated per hardware type, not per device. Default calibration for 868-915MHz on EVK.
-pub fn set_rssi_calibration_cmd(tune_g4: u8, tune_g5: u8, tune_g6: u8, tune_g7: u8, tune_g8: u8, tune_g9: u8, tune_g10: u8, tune_g11: u8, tune_g12: u8, tune_g13: u8, tune_g13_hp1: u8, tune_g13_hp2: u8, tune_g13_hp3: u8, tune_g13_hp4: u8, tune_g13_hp5: u8, tune_g13_hp6: u8, tune_g13_hp7: u8, gain_offset: u16) -> [u8; 12] {
-    let mut cmd = [0u8; 12];
-    cmd[0] = 0x02;
-    cmd[1] = 0x29;
-
-    cmd[2] |= (tune_g4 & 0xF) << 4;
-    cmd[2] |= tune_g5 & 0xF;
-    cmd[3] |= (tune_g6 & 0xF) << 4;
-    cmd[3] |= tune_g7 & 0xF;
-    cmd[4] |= (tune_g8 & 0xF) << 4;
-    cmd[4] |= tune_g9 & 0xF;
-    cmd[5] |= (tune_g10 & 0xF) << 4;
-    cmd[5] |= tune_g11 & 0xF;
-    cmd[6] |= (tune_g12 & 0xF) << 4;
-    cmd[6] |= tune_g13 & 0xF;
-    cmd[7] |= (tune_g13_hp1 & 0xF) << 4;
-    cmd[7] |= tune_g13_hp2 & 0xF;
-    cmd[8] |= (tune_g13_hp3 & 0xF) << 4;
-    cmd[8] |= tune_g13_hp4 & 0xF;
-    cmd[9] |= (tune_g13_hp5 & 0xF) << 4;
-    cmd[9] |= tune_g13_hp6 & 0xF;
-    cmd[10] |= (tune_g13_hp7 & 0xF) << 4;
-    cmd[10] |= ((gain_offset >> 8) & 0xFF) as u8;
-    cmd[11] |= (gain_offset & 0xFF) as u8;
-    cmd
+pub const fn set_rssi_calibration_cmd(tune_g4: u8, tune_g5: u8, tune_g6: u8, tune_g7: u8, tune_g8: u8, tune_g9: u8, tune_g10: u8, tune_g11: u8, tune_g12: u8, tune_g13: u8, tune_g13_hp1: u8, tune_g13_hp2: u8, tune_g13_hp3: u8, tune_g13_hp4: u8, tune_g13_hp5: u8, tune_g13_hp6: u8, tune_g13_hp7: u8, gain_offset: u16) -> [u8; 12] {
+    CmdWriter::new(0x0229)
+        .bits(tune_g4, 4, 4)
+        .bits(tune_g5, 4, 0)
+        .skip(1)
+        .bits(tune_g6, 4, 4)
+        .bits(tune_g7, 4, 0)
+        .skip(1)
+        .bits(tune_g8, 4, 4)
+        .bits(tune_g9, 4, 0)
+        .skip(1)
+        .bits(tune_g10, 4, 4)
+        .bits(tune_g11, 4, 0)
+        .skip(1)
+        .bits(tune_g12, 4, 4)
+        .bits(tune_g13, 4, 0)
+        .skip(1)
+        .bits(tune_g13_hp1, 4, 4)
+        .bits(tune_g13_hp2, 4, 0)
+        .skip(1)
+        .bits(tune_g13_hp3, 4, 4)
+        .bits(tune_g13_hp4, 4, 0)
+        .skip(1)
+        .bits(tune_g13_hp5, 4, 4)
+        .bits(tune_g13_hp6, 4, 0)
+        .skip(1)
+        .bits(tune_g13_hp7, 4, 4)
+        .bits((gain_offset >> 8) as u8, 4, 0)
+        .skip(1)
+        .push_u8(gain_offset as u8)
+        .finish()
 }
 
 /// Defines which modem to use for next RF transactions. First command to call before RX/TX and before defining modulation/packet parameters. Only works in Standby RC, Standby Xosc or FS mode, otherwise returns CMD_FAIL.
-pub fn set_packet_type_cmd(packet_type: PacketType) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x02;
-    cmd[1] = 0x0E;
-
-    cmd[2] |= packet_type as u8;
-    cmd
+pub const fn set_packet_type_cmd(packet_type: PacketType) -> [u8; 3] {
+    CmdWriter::new(0x020E)
+        .push_u8(packet_type as u8)
+        .finish()
 }
 
 /// Returns current protocol of the radio
-pub fn get_packet_type_req() -> [u8; 2] {
+pub const fn get_packet_type_req() -> [u8; 2] {
     [0x02, 0x02]
 }
 
@@ -299,13 +258,23 @@ pub fn get_packet_type_req() -> [u8; 2] {
 pub struct RssiInstRsp([u8; 2]);
 
 impl RssiInstRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -326,13 +295,23 @@ impl AsMut<[u8]> for RssiInstRsp {
 pub struct StatsRsp([u8; 9]);
 
 impl StatsRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 9;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 9] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -372,13 +351,23 @@ impl AsMut<[u8]> for StatsRsp {
 pub struct RxBufferStatusRsp([u8; 3]);
 
 impl RxBufferStatusRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 3;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 3] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -404,13 +393,23 @@ impl AsMut<[u8]> for RxBufferStatusRsp {
 pub struct PacketTypeRsp([u8; 2]);
 
 impl PacketTypeRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -1,56 +1,30 @@
 // Regmem commands API
 
+use super::CmdWriter;
 use crate::status::Status;
 /// Writes blocks of 32-bit words in register/memory space starting at a specific address. Address must be 32-bit aligned and data length must be a multiple of 4. Maximum N is 64.
-pub fn write_reg_mem32_cmd(addr: u32, data: u32) -> [u8; 9] {
-    let mut cmd = [0u8; 9];
-    cmd[0] = 0x01;
-    cmd[1] = 0x05;
-
-    cmd[2] |= ((addr >> 24) & 0xFF) as u8;
-    cmd[3] |= ((addr >> 16) & 0xFF) as u8;
-    cmd[4] |= ((addr >> 8) & 0xFF) as u8;
-    cmd[5] |= (addr & 0xFF) as u8;
-    cmd[5] |= ((data >> 24) & 0xFF) as u8;
-    cmd[6] |= ((data >> 16) & 0xFF) as u8;
-    cmd[7] |= ((data >> 8) & 0xFF) as u8;
-    cmd[8] |= (data & 0xFF) as u8;
-    cmd
+pub const fn write_reg_mem32_cmd(addr: u32, data: u32) -> [u8; 10] {
+    CmdWriter::new(0x0105)
+        .push_u32(addr)
+        .push_u32(data)
+        .finish()
 }
 
 /// Reads blocks of 32-bit words in register/memory space starting at a specific address. Address must be 32-bit aligned. Maximum len is 64 words.
-pub fn read_reg_mem32_req(addr: u32, len: u8) -> [u8; 7] {
-    let mut cmd = [0u8; 7];
-    cmd[0] = 0x01;
-    cmd[1] = 0x06;
-
-    cmd[2] |= ((addr >> 24) & 0xFF) as u8;
-    cmd[3] |= ((addr >> 16) & 0xFF) as u8;
-    cmd[4] |= ((addr >> 8) & 0xFF) as u8;
-    cmd[5] |= (addr & 0xFF) as u8;
-    cmd[6] |= len;
-    cmd
+pub const fn read_reg_mem32_req(addr: u32, len: u8) -> [u8; 7] {
+    CmdWriter::new(0x0106)
+        .push_u32(addr)
+        .push_u8(len)
+        .finish()
 }
 
 /// Reads/modifies/writes the masked bits (Mask bits = 1) of a single 32-bit word in register/memory space at the specified address. Address must be 32-bit aligned.
-pub fn write_reg_mem_mask32_cmd(addr: u32, mask: u32, data: u32) -> [u8; 14] {
-    let mut cmd = [0u8; 14];
-    cmd[0] = 0x01;
-    cmd[1] = 0x0C;
-
-    cmd[2] |= ((addr >> 24) & 0xFF) as u8;
-    cmd[3] |= ((addr >> 16) & 0xFF) as u8;
-    cmd[4] |= ((addr >> 8) & 0xFF) as u8;
-    cmd[5] |= (addr & 0xFF) as u8;
-    cmd[6] |= ((mask >> 24) & 0xFF) as u8;
-    cmd[7] |= ((mask >> 16) & 0xFF) as u8;
-    cmd[8] |= ((mask >> 8) & 0xFF) as u8;
-    cmd[9] |= (mask & 0xFF) as u8;
-    cmd[10] |= ((data >> 24) & 0xFF) as u8;
-    cmd[11] |= ((data >> 16) & 0xFF) as u8;
-    cmd[12] |= ((data >> 8) & 0xFF) as u8;
-    cmd[13] |= (data & 0xFF) as u8;
-    cmd
+pub const fn write_reg_mem_mask32_cmd(addr: u32, mask: u32, data: u32) -> [u8; 14] {
+    CmdWriter::new(0x010C)
+        .push_u32(addr)
+        .push_u32(mask)
+        .push_u32(data)
+        .finish()
 }
 
 // Response structs
@@ -60,13 +34,23 @@ pub fn write_reg_mem_mask32_cmd(addr: u32, mask: u32, data: u32) -> [u8; 14] {
 pub struct ReadRegMem32Rsp([u8; 6]);
 
 impl ReadRegMem32Rsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 6;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 6] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -1,5 +1,6 @@
 // System commands API
 
+use super::CmdWriter;
 use crate::status::{Status,Intr};
 
 /// Device type
@@ -71,237 +72,182 @@ pub enum RegMode {
 }
 
 /// Returns status of device including stat1, stat2, and interrupt flags. Clears the stat2 ResetStatus field.
-pub fn get_status_req() -> [u8; 2] {
+pub const fn get_status_req() -> [u8; 2] {
     [0x01, 0x00]
 }
 
 /// Returns the pending errors that occurred since the last ClearErrors() or circuit startup
-pub fn get_errors_req() -> [u8; 2] {
+pub const fn get_errors_req() -> [u8; 2] {
     [0x01, 0x0D]
 }
 
 /// Clears all error flags pending in the device. Error flags cannot be cleared individually.
-pub fn clear_errors_cmd() -> [u8; 2] {
+pub const fn clear_errors_cmd() -> [u8; 2] {
     [0x01, 0x0E]
 }
 
 /// Writes a block of bytes into the radio TX buffer (up to 255 bytes)
-pub fn write_buffer8_cmd() -> [u8; 2] {
+pub const fn write_buffer8_cmd() -> [u8; 2] {
     [0x01, 0x09]
 }
 
 /// Reads a block of bytes from the radio RX buffer starting at a specific offset. RX buffer must be implemented as a ring buffer.
-pub fn read_buffer8_cmd(offset: u8, len: u8) -> [u8; 4] {
-    let mut cmd = [0u8; 4];
-    cmd[0] = 0x01;
-    cmd[1] = 0x0A;
-
-    cmd[2] |= offset;
-    cmd[3] |= len;
-    cmd
+pub const fn read_buffer8_cmd(offset: u8, len: u8) -> [u8; 4] {
+    CmdWriter::new(0x010A)
+        .push_u8(offset)
+        .push_u8(len)
+        .finish()
 }
 
 /// Clears all data in the radio RX buffer by writing '0' over the whole buffer. Mainly used for debug purposes.
-pub fn clear_rx_buffer_cmd() -> [u8; 2] {
+pub const fn clear_rx_buffer_cmd() -> [u8; 2] {
     [0x01, 0x0B]
 }
 
 /// Gets a 32-bit random number. Not for security purposes.
-pub fn get_random_number_req() -> [u8; 2] {
+pub const fn get_random_number_req() -> [u8; 2] {
     [0x01, 0x20]
 }
 
 /// Enables/disables an 8-bit CRC on the SPI interface. CRC uses polynomial 0x65 (reversed reciprocal), initial value 0xFF. This command is always protected by CRC.
-pub fn enable_spi_crc_cmd(enable: u8, crc: u8) -> [u8; 4] {
-    let mut cmd = [0u8; 4];
-    cmd[0] = 0x01;
-    cmd[1] = 0x28;
-
-    cmd[2] |= enable;
-    cmd[3] |= crc;
-    cmd
+pub const fn enable_spi_crc_cmd(enable: u8, crc: u8) -> [u8; 4] {
+    CmdWriter::new(0x0128)
+        .push_u8(enable)
+        .push_u8(crc)
+        .finish()
 }
 
 /// Returns the version of the LR1120 hardware and firmware
-pub fn get_version_req() -> [u8; 2] {
+pub const fn get_version_req() -> [u8; 2] {
     [0x01, 0x01]
 }
 
 /// Calibrates the requested blocks. Command operates in any mode and returns to Standby RC after completion. Note: PLL_TX calibration required before first Bluetooth Low Energy transmission.
-pub fn calibrate_cmd(lf_rc: bool, hf_rc: bool, pll: bool, adc: bool, img: bool, pll_tx: bool) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x01;
-    cmd[1] = 0x0F;
-
-    if lf_rc { cmd[2] |= 1; }
-    if hf_rc { cmd[2] |= 2; }
-    if pll { cmd[2] |= 4; }
-    if adc { cmd[2] |= 8; }
-    if img { cmd[2] |= 16; }
-    if pll_tx { cmd[2] |= 32; }
-    cmd
+pub const fn calibrate_cmd(lf_rc: bool, hf_rc: bool, pll: bool, adc: bool, img: bool, pll_tx: bool) -> [u8; 3] {
+    CmdWriter::new(0x010F)
+        .flag(0, lf_rc)
+        .flag(1, hf_rc)
+        .flag(2, pll)
+        .flag(3, adc)
+        .flag(4, img)
+        .flag(5, pll_tx)
+        .finish()
 }
 
 /// Launches image calibration for given frequency range on RFI_N/P_LF sub-GHz path. Frequencies in 4MHz steps. Operates in any mode, returns to Standby RC. Image calibration advised after large temperature variations (>10°C) or frequency changes (>10MHz).
-pub fn calib_image_cmd(freq1: u8, freq2: u8) -> [u8; 4] {
-    let mut cmd = [0u8; 4];
-    cmd[0] = 0x01;
-    cmd[1] = 0x11;
-
-    cmd[2] |= freq1;
-    cmd[3] |= freq2;
-    cmd
+pub const fn calib_image_cmd(freq1: u8, freq2: u8) -> [u8; 4] {
+    CmdWriter::new(0x0111)
+        .push_u8(freq1)
+        .push_u8(freq2)
+        .finish()
 }
 
 /// Configures the 32kHz clock source
-pub fn config_lf_clock_cmd(lf_clock: LfClock, busy_release: bool) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x01;
-    cmd[1] = 0x16;
-
-    cmd[2] |= (lf_clock as u8) & 0x3;
-    if busy_release { cmd[2] |= 4; }
-    cmd
+pub const fn config_lf_clock_cmd(lf_clock: LfClock, busy_release: bool) -> [u8; 3] {
+    CmdWriter::new(0x0116)
+        .bits(lf_clock as u8, 2, 0)
+        .flag(2, busy_release)
+        .finish()
 }
 
 /// Configures the chip for a connected TCXO. Must be called before GetTemp() if TCXO is used. Only operates in Standby RC mode, otherwise returns CMD_FAIL. Complete chip reset required to return to normal XOSC operation.
-pub fn set_tcxo_mode_cmd(tcxo_voltage: TcxoVoltage, delay: u32) -> [u8; 6] {
-    let mut cmd = [0u8; 6];
-    cmd[0] = 0x01;
-    cmd[1] = 0x17;
-
-    cmd[2] |= tcxo_voltage as u8;
-    cmd[3] |= ((delay >> 16) & 0xFF) as u8;
-    cmd[4] |= ((delay >> 8) & 0xFF) as u8;
-    cmd[5] |= (delay & 0xFF) as u8;
-    cmd
+pub const fn set_tcxo_mode_cmd(tcxo_voltage: TcxoVoltage, delay: u32) -> [u8; 6] {
+    CmdWriter::new(0x0117)
+        .push_u8(tcxo_voltage as u8)
+        .push_u24(delay)
+        .finish()
 }
 
 /// Triggers a restart of the LR1120 firmware. 32kHz clock configuration is retained.
-pub fn reboot_cmd(stay_in_bootloader: StayInBootloader) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x01;
-    cmd[1] = 0x18;
-
-    cmd[2] |= stay_in_bootloader as u8;
-    cmd
+pub const fn reboot_cmd(stay_in_bootloader: StayInBootloader) -> [u8; 3] {
+    CmdWriter::new(0x0118)
+        .push_u8(stay_in_bootloader as u8)
+        .finish()
 }
 
 /// Puts device in Power Down or Sleep mode with optional automatic wake-up. Device exits on NSS falling edge. BUSY=1, all MISO and DIOs in Hi-Z. After wake-up, performs firmware restart and goes to Standby RC when BUSY goes low.
-pub fn set_sleep_cmd(wakeup_rtc: bool, ret_en: bool, sleep_time: u32) -> [u8; 7] {
-    let mut cmd = [0u8; 7];
-    cmd[0] = 0x01;
-    cmd[1] = 0x1B;
-
-    if wakeup_rtc { cmd[2] |= 2; }
-    if ret_en { cmd[2] |= 1; }
-    cmd[3] |= ((sleep_time >> 24) & 0xFF) as u8;
-    cmd[4] |= ((sleep_time >> 16) & 0xFF) as u8;
-    cmd[5] |= ((sleep_time >> 8) & 0xFF) as u8;
-    cmd[6] |= (sleep_time & 0xFF) as u8;
-    cmd
+pub const fn set_sleep_cmd(wakeup_rtc: bool, ret_en: bool, sleep_time: u32) -> [u8; 7] {
+    CmdWriter::new(0x011B)
+        .flag(1, wakeup_rtc)
+        .flag(0, ret_en)
+        .skip(1)
+        .push_u32(sleep_time)
+        .finish()
 }
 
 /// Sets the device in standby mode with chosen 32MHz oscillator. RC is default for all automatic mode transitions. Crystal/TCXO allows faster transitions at expense of higher power.
-pub fn set_standby_cmd(standby_mode: StandbyMode) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x01;
-    cmd[1] = 0x1C;
-
-    cmd[2] |= standby_mode as u8;
-    cmd
+pub const fn set_standby_cmd(standby_mode: StandbyMode) -> [u8; 3] {
+    CmdWriter::new(0x011C)
+        .push_u8(standby_mode as u8)
+        .finish()
 }
 
 /// Sets chip into Frequency Synthesis (FS) mode
-pub fn set_fs_cmd() -> [u8; 2] {
+pub const fn set_fs_cmd() -> [u8; 2] {
     [0x01, 0x1D]
 }
 
 /// Configures which interrupt signals should be activated on the DIO9 (IRQ pin 1) and/or DIO11 (IRQ pin 2) interrupt pins
-pub fn set_dio_irq_params_cmd(irq1_to_enable: u32, irq2_to_enable: u32) -> [u8; 10] {
-    let mut cmd = [0u8; 10];
-    cmd[0] = 0x01;
-    cmd[1] = 0x13;
-
-    cmd[2] |= ((irq1_to_enable >> 24) & 0xFF) as u8;
-    cmd[3] |= ((irq1_to_enable >> 16) & 0xFF) as u8;
-    cmd[4] |= ((irq1_to_enable >> 8) & 0xFF) as u8;
-    cmd[5] |= (irq1_to_enable & 0xFF) as u8;
-    cmd[6] |= ((irq2_to_enable >> 24) & 0xFF) as u8;
-    cmd[7] |= ((irq2_to_enable >> 16) & 0xFF) as u8;
-    cmd[8] |= ((irq2_to_enable >> 8) & 0xFF) as u8;
-    cmd[9] |= (irq2_to_enable & 0xFF) as u8;
-    cmd
+pub const fn set_dio_irq_params_cmd(irq1_to_enable: u32, irq2_to_enable: u32) -> [u8; 10] {
+    CmdWriter::new(0x0113)
+        .push_u32(irq1_to_enable)
+        .push_u32(irq2_to_enable)
+        .finish()
 }
 
 /// Clears the selected interrupt signals by writing a 1 in the respective bit. IrqToClear mapping is identical to IrqToEnable.
-pub fn clear_irq_cmd(irq_to_clear: u32) -> [u8; 6] {
-    let mut cmd = [0u8; 6];
-    cmd[0] = 0x01;
-    cmd[1] = 0x14;
-
-    cmd[2] |= ((irq_to_clear >> 24) & 0xFF) as u8;
-    cmd[3] |= ((irq_to_clear >> 16) & 0xFF) as u8;
-    cmd[4] |= ((irq_to_clear >> 8) & 0xFF) as u8;
-    cmd[5] |= (irq_to_clear & 0xFF) as u8;
-    cmd
+pub const fn clear_irq_cmd(irq_to_clear: u32) -> [u8; 6] {
+    CmdWriter::new(0x0114)
+        .push_u32(irq_to_clear)
+        .finish()
 }
 
 #[allow(clippy::too_many_arguments)]
 /// Configures DIO5, DIO6, DIO7, DIO8 and DIO10 to control external RF switches or LNAs on the Sub-GHz, GNSS, Wi-Fi and RFIO_HF RF paths. Only works in Standby RC mode, otherwise returns CMD_FAIL. Only lowest 5 bits of all configurations are used.
-pub fn set_dio_as_rf_switch_cmd(rfsw_enable: u8, rfsw_stby_cfg: u8, rfsw_rx_cfg: u8, rfsw_tx_cfg: u8, rfsw_tx_hp_cfg: u8, rfsw_tx_hf_cfg: u8, rfsw_gnss_cfg: u8, rfsw_wifi_cfg: u8) -> [u8; 10] {
-    let mut cmd = [0u8; 10];
-    cmd[0] = 0x01;
-    cmd[1] = 0x12;
-
-    cmd[2] |= rfsw_enable;
-    cmd[3] |= rfsw_stby_cfg;
-    cmd[4] |= rfsw_rx_cfg;
-    cmd[5] |= rfsw_tx_cfg;
-    cmd[6] |= rfsw_tx_hp_cfg;
-    cmd[7] |= rfsw_tx_hf_cfg;
-    cmd[8] |= rfsw_gnss_cfg;
-    cmd[9] |= rfsw_wifi_cfg;
-    cmd
+pub const fn set_dio_as_rf_switch_cmd(rfsw_enable: u8, rfsw_stby_cfg: u8, rfsw_rx_cfg: u8, rfsw_tx_cfg: u8, rfsw_tx_hp_cfg: u8, rfsw_tx_hf_cfg: u8, rfsw_gnss_cfg: u8, rfsw_wifi_cfg: u8) -> [u8; 10] {
+    CmdWriter::new(0x0112)
+        .push_u8(rfsw_enable)
+        .push_u8(rfsw_stby_cfg)
+        .push_u8(rfsw_rx_cfg)
+        .push_u8(rfsw_tx_cfg)
+        .push_u8(rfsw_tx_hp_cfg)
+        .push_u8(rfsw_tx_hf_cfg)
+        .push_u8(rfsw_gnss_cfg)
+        .push_u8(rfsw_wifi_cfg)
+        .finish()
 }
 
 /// Enables or disables pull-up/down resistors on configured RF switch and IRQ line DIOs when in sleep mode. Saves power when RF switches are supplied by LR1120 DIOs.
-pub fn drive_dios_in_sleep_mode_cmd(enable: u8) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x01;
-    cmd[1] = 0x2A;
-
-    cmd[2] |= enable;
-    cmd
+pub const fn drive_dios_in_sleep_mode_cmd(enable: u8) -> [u8; 3] {
+    CmdWriter::new(0x012A)
+        .push_u8(enable)
+        .finish()
 }
 
 /// Returns the temperature measurement from the built-in temperature sensor. Uses XOSC mode, so SetTcxoMode must be called first if TCXO is connected.
-pub fn get_temp_req() -> [u8; 2] {
+pub const fn get_temp_req() -> [u8; 2] {
     [0x01, 0x1A]
 }
 
 /// Sets whether DC-DC converter is enabled for XOSC, FS, RX or TX modes. Only works in Standby RC mode, otherwise returns CMD_FAIL.
-pub fn set_reg_mode_cmd(reg_mode: RegMode) -> [u8; 3] {
-    let mut cmd = [0u8; 3];
-    cmd[0] = 0x01;
-    cmd[1] = 0x10;
-
-    cmd[2] |= reg_mode as u8;
-    cmd
+pub const fn set_reg_mode_cmd(reg_mode: RegMode) -> [u8; 3] {
+    CmdWriter::new(0x0110)
+        .push_u8(reg_mode as u8)
+        .finish()
 }
 
 /// Returns the battery supply voltage (VBAT) as a function of reference voltage. Formula: VBAT (V) = (5 * (Vbat(7:0) / 255)) / (1 - (1 / 1.35V))
-pub fn get_vbat_req() -> [u8; 2] {
+pub const fn get_vbat_req() -> [u8; 2] {
     [0x01, 0x19]
 }
 
 /// Reads LR1120's pre-provisioned ChipEui. Globally-unique number assigned by Semtech in production using Semtech's IEEE assigned EUIs. Stored in persistent memory. Can be used as LoRaWAN DeviceEui or user can provide their own.
-pub fn get_chip_eui_req() -> [u8; 2] {
+pub const fn get_chip_eui_req() -> [u8; 2] {
     [0x01, 0x25]
 }
 
 /// Reads LR1120's pre-programmed JoinEui installed in production by Semtech. LoRaWAN root keys (AppKey, NwkKey) derived from this JoinEui. In standard use, should be used as LoRaWAN JoinEui field in Join Request frame. Stored in persistent memory.
-pub fn get_semtech_join_eui_req() -> [u8; 2] {
+pub const fn get_semtech_join_eui_req() -> [u8; 2] {
     [0x01, 0x26]
 }
 
@@ -312,14 +258,24 @@ pub fn get_semtech_join_eui_req() -> [u8; 2] {
 pub struct StatusRsp([u8; 6]);
 
 impl StatusRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 6;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 6] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
     /// Return Status
-    pub fn status(&mut self) -> Status {
-        Status::from_array([self.0[0], self.0[1]]) 
+    pub fn status(&self) -> Status {
+        Status::from_array([self.0[0], self.0[1]])
     }
 
     /// IRQ status register
@@ -339,13 +295,23 @@ impl AsMut<[u8]> for StatusRsp {
 pub struct ErrorsRsp([u8; 3]);
 
 impl ErrorsRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 3;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 3] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -434,13 +400,23 @@ impl defmt::Format for ErrorsRsp {
 pub struct RandomNumberRsp([u8; 5]);
 
 impl RandomNumberRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 5;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 5] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -464,13 +440,23 @@ impl AsMut<[u8]> for RandomNumberRsp {
 pub struct VersionRsp([u8; 5]);
 
 impl VersionRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 5;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 5] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -512,13 +498,23 @@ impl defmt::Format for VersionRsp {
 pub struct TempRsp([u8; 3]);
 
 impl TempRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 3;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 3] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -540,13 +536,23 @@ impl AsMut<[u8]> for TempRsp {
 pub struct VbatRsp([u8; 2]);
 
 impl VbatRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -567,13 +573,23 @@ impl AsMut<[u8]> for VbatRsp {
 pub struct ChipEuiRsp([u8; 9]);
 
 impl ChipEuiRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 9;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 9] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -601,13 +617,23 @@ impl AsMut<[u8]> for ChipEuiRsp {
 pub struct SemtechJoinEuiRsp([u8; 9]);
 
 impl SemtechJoinEuiRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 9;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 9] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
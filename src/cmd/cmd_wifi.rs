@@ -1,6 +1,8 @@
 // Wifi commands API
 
+use super::CmdWriter;
 use crate::status::Status;
+use crate::wifi_scan::MacAddress;
 
 /// 802.11 standard selection: B (1), G (2), N (3) or All (4)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -83,88 +85,63 @@ impl From<u8> for FrameType {
 }
 
 /// Captures Wi-Fi packets on RFIO_HF pin. BUSY signal high during scan (few hundred ms). IRQ signal high at end if WifiScanDone interrupt enabled.
-pub fn wifi_scan_cmd(wifi_standard: WifiStandard, chan_mask: u16, acq_mode: AcqMode, nb_max_res: u8, nb_scan_per_chan: u8, timeout: u16, abort_on_timeout: bool) -> [u8; 11] {
-    let mut cmd = [0u8; 11];
-    cmd[0] = 0x03;
-    cmd[1] = 0x00;
-
-    cmd[2] |= wifi_standard as u8;
-    cmd[3] |= ((chan_mask >> 8) & 0xFF) as u8;
-    cmd[4] |= (chan_mask & 0xFF) as u8;
-    cmd[5] |= acq_mode as u8;
-    cmd[6] |= nb_max_res;
-    cmd[7] |= nb_scan_per_chan;
-    cmd[8] |= ((timeout >> 8) & 0xFF) as u8;
-    cmd[9] |= (timeout & 0xFF) as u8;
-    if abort_on_timeout { cmd[10] |= 1; }
-    cmd
+pub const fn wifi_scan_cmd(wifi_standard: WifiStandard, chan_mask: u16, acq_mode: AcqMode, nb_max_res: u8, nb_scan_per_chan: u8, timeout: u16, abort_on_timeout: bool) -> [u8; 11] {
+    CmdWriter::new(0x0300)
+        .push_u8(wifi_standard as u8)
+        .push_u16(chan_mask)
+        .push_u8(acq_mode as u8)
+        .push_u8(nb_max_res)
+        .push_u8(nb_scan_per_chan)
+        .push_u16(timeout)
+        .flag(0, abort_on_timeout)
+        .finish()
 }
 
 /// Searches for Wi-Fi MAC addresses during configurable maximal time. Duration may be exceeded due to crystal drift and last signal detection. T_max = N_channel x ((1 + Xtal_precision) x Timeout + T_offset)
-pub fn wifi_scan_time_limit_cmd(wifi_standard: WifiStandard, chan_mask: u16, acq_mode: AcqMode, nb_max_res: u8, scan_time_per_channel: u16, timeout_per_scan: u16) -> [u8; 11] {
-    let mut cmd = [0u8; 11];
-    cmd[0] = 0x03;
-    cmd[1] = 0x01;
-
-    cmd[2] |= wifi_standard as u8;
-    cmd[3] |= ((chan_mask >> 8) & 0xFF) as u8;
-    cmd[4] |= (chan_mask & 0xFF) as u8;
-    cmd[5] |= acq_mode as u8;
-    cmd[6] |= nb_max_res;
-    cmd[7] |= ((scan_time_per_channel >> 8) & 0xFF) as u8;
-    cmd[8] |= (scan_time_per_channel & 0xFF) as u8;
-    cmd[9] |= ((timeout_per_scan >> 8) & 0xFF) as u8;
-    cmd[10] |= (timeout_per_scan & 0xFF) as u8;
-    cmd
+pub const fn wifi_scan_time_limit_cmd(wifi_standard: WifiStandard, chan_mask: u16, acq_mode: AcqMode, nb_max_res: u8, scan_time_per_channel: u16, timeout_per_scan: u16) -> [u8; 11] {
+    CmdWriter::new(0x0301)
+        .push_u8(wifi_standard as u8)
+        .push_u16(chan_mask)
+        .push_u8(acq_mode as u8)
+        .push_u8(nb_max_res)
+        .push_u16(scan_time_per_channel)
+        .push_u16(timeout_per_scan)
+        .finish()
 }
 
 /// Extracts Country code from Beacon or Probe Response. Only Wi-Fi b signals searched. Results filtered for duplicates by MAC address. Returns CMD_PERR if parameter range not respected, CMD_FAIL for radio config errors.
-pub fn wifi_country_code_cmd(chan_mask: u16, nb_max_res: u8, nb_scan_per_channel: u8, timeout: u16, abort_on_timeout: bool) -> [u8; 9] {
-    let mut cmd = [0u8; 9];
-    cmd[0] = 0x03;
-    cmd[1] = 0x02;
-
-    cmd[2] |= ((chan_mask >> 8) & 0xFF) as u8;
-    cmd[3] |= (chan_mask & 0xFF) as u8;
-    cmd[4] |= nb_max_res;
-    cmd[5] |= nb_scan_per_channel;
-    cmd[6] |= ((timeout >> 8) & 0xFF) as u8;
-    cmd[7] |= (timeout & 0xFF) as u8;
-    if abort_on_timeout { cmd[8] |= 1; }
-    cmd
+pub const fn wifi_country_code_cmd(chan_mask: u16, nb_max_res: u8, nb_scan_per_channel: u8, timeout: u16, abort_on_timeout: bool) -> [u8; 9] {
+    CmdWriter::new(0x0302)
+        .push_u16(chan_mask)
+        .push_u8(nb_max_res)
+        .push_u8(nb_scan_per_channel)
+        .push_u16(timeout)
+        .flag(0, abort_on_timeout)
+        .finish()
 }
 
 /// Searches for Wi-Fi MAC addresses during configurable maximal time for country code extraction. T_max = N_channel x ((1 + Xtal_precision) x Timeout + T_offset). T_offset always 9.59ms.
-pub fn wifi_country_code_time_limit_cmd(chan_mask: u16, nb_max_res: u8, scan_time_per_channel: u16, timeout_per_scan: u16) -> [u8; 9] {
-    let mut cmd = [0u8; 9];
-    cmd[0] = 0x03;
-    cmd[1] = 0x03;
-
-    cmd[2] |= ((chan_mask >> 8) & 0xFF) as u8;
-    cmd[3] |= (chan_mask & 0xFF) as u8;
-    cmd[4] |= nb_max_res;
-    cmd[5] |= ((scan_time_per_channel >> 8) & 0xFF) as u8;
-    cmd[6] |= (scan_time_per_channel & 0xFF) as u8;
-    cmd[7] |= ((timeout_per_scan >> 8) & 0xFF) as u8;
-    cmd[8] |= (timeout_per_scan & 0xFF) as u8;
-    cmd
+pub const fn wifi_country_code_time_limit_cmd(chan_mask: u16, nb_max_res: u8, scan_time_per_channel: u16, timeout_per_scan: u16) -> [u8; 9] {
+    CmdWriter::new(0x0303)
+        .push_u16(chan_mask)
+        .push_u8(nb_max_res)
+        .push_u16(scan_time_per_channel)
+        .push_u16(timeout_per_scan)
+        .finish()
 }
 
 /// Returns the number of Wi-Fi Scanning results (8 bits). Read at next SPI transaction.
-pub fn wifi_get_nb_results_req() -> [u8; 2] {
+pub const fn wifi_get_nb_results_req() -> [u8; 2] {
     [0x03, 0x05]
 }
 
 /// Reads byte stream of Wi-Fi Passive Scanning results from given index in requested format. Must call WifiGetNbResults first. Issue NOP bytes (0x00) to read back. Max 1020 bytes per command - split into multiple requests if needed. Format 0x01: 22 bytes/MAC (modes 0x01, 0x02) or 79 bytes/MAC (mode 0x04). Format 0x04: 9 bytes/MAC.
-pub fn wifi_read_results_req(index: u8, nb_results: u8, wifi_result_format: WifiResultFormat) -> [u8; 5] {
-    let mut cmd = [0u8; 5];
-    cmd[0] = 0x03;
-    cmd[1] = 0x06;
-
-    cmd[2] |= index;
-    cmd[3] |= nb_results;
-    cmd[4] |= wifi_result_format as u8;
-    cmd
+pub const fn wifi_read_results_req(index: u8, nb_results: u8, wifi_result_format: WifiResultFormat) -> [u8; 5] {
+    CmdWriter::new(0x0306)
+        .push_u8(index)
+        .push_u8(nb_results)
+        .push_u8(wifi_result_format as u8)
+        .finish()
 }
 
 
@@ -172,46 +149,37 @@ pub fn wifi_read_results_req(index: u8, nb_results: u8, wifi_result_format: Wifi
 
 
 /// Resets Wi-Fi Passive Scanning cumulative timings. Must be called prior to executing Wi-Fi Passive Scanning if timings are to be read.
-pub fn wifi_reset_cumul_timings_cmd() -> [u8; 2] {
+pub const fn wifi_reset_cumul_timings_cmd() -> [u8; 2] {
     [0x03, 0x07]
 }
 
 /// Reads Wi-Fi Passive Scanning cumulative timings (16 bytes). Represents total time in various modes during WifiScan, summed for all acquisitions over different parameters. Times in microseconds. All 16 bytes must be read. Must be reset by host.
-pub fn wifi_read_cumul_timings_req() -> [u8; 2] {
+pub const fn wifi_read_cumul_timings_req() -> [u8; 2] {
     [0x03, 0x08]
 }
 
 /// Returns number of results after Country Code scanning by WifiCountryCode or WifiCountryCodeTimeLimit
-pub fn wifi_get_nb_country_code_results_req() -> [u8; 2] {
+pub const fn wifi_get_nb_country_code_results_req() -> [u8; 2] {
     [0x03, 0x09]
 }
 
 /// Reads byte stream of Wi-Fi Passive Scanning Country Code results from given index. Must call WifiGetNbCountryCodeResults first. Issue NOP bytes to read back. One result is 10 bytes.
-pub fn wifi_read_country_code_results_req(index: u8, nb_results: u8) -> [u8; 4] {
-    let mut cmd = [0u8; 4];
-    cmd[0] = 0x03;
-    cmd[1] = 0x0A;
-
-    cmd[2] |= index;
-    cmd[3] |= nb_results;
-    cmd
+pub const fn wifi_read_country_code_results_req(index: u8, nb_results: u8) -> [u8; 4] {
+    CmdWriter::new(0x030A)
+        .push_u8(index)
+        .push_u8(nb_results)
+        .finish()
 }
 
 /// Configures timestamp threshold to discriminate mobile access point from gateways. Default 1 day. If timestamp from beacon/probe response exceeds limit, MAC validation indicates probable gateway not mobile device.
-pub fn wifi_cfg_timestamp_a_pphone_cmd(timestamp: u32) -> [u8; 6] {
-    let mut cmd = [0u8; 6];
-    cmd[0] = 0x03;
-    cmd[1] = 0x0B;
-
-    cmd[2] |= ((timestamp >> 24) & 0xFF) as u8;
-    cmd[3] |= ((timestamp >> 16) & 0xFF) as u8;
-    cmd[4] |= ((timestamp >> 8) & 0xFF) as u8;
-    cmd[5] |= (timestamp & 0xFF) as u8;
-    cmd
+pub const fn wifi_cfg_timestamp_a_pphone_cmd(timestamp: u32) -> [u8; 6] {
+    CmdWriter::new(0x030B)
+        .push_u32(timestamp)
+        .finish()
 }
 
 /// Returns internal Wi-Fi firmware version major and minor numbers
-pub fn wifi_read_version_req() -> [u8; 2] {
+pub const fn wifi_read_version_req() -> [u8; 2] {
     [0x03, 0x20]
 }
 
@@ -222,13 +190,23 @@ pub fn wifi_read_version_req() -> [u8; 2] {
 pub struct WifiGetNbResultsRsp([u8; 2]);
 
 impl WifiGetNbResultsRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -248,11 +226,14 @@ impl AsMut<[u8]> for WifiGetNbResultsRsp {
 pub struct WifiReadResultsRsp([u8; 9]);
 
 impl WifiReadResultsRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 9;
 
     /// Create struct from existing response buffer
-    pub fn from_slice(buffer: &[u8]) -> Self {
-        let raw : [u8; 9] = buffer.try_into().expect("Buffer size should match response size !");
-        Self(raw)
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 9] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
     }
 
     /// 802.11 standard selection: B (1), G (2), N (3) or All (4)
@@ -275,19 +256,14 @@ impl WifiReadResultsRsp {
         (self.0[1] >> 6) & 0x1 != 0
     }
 
-    /// RSSI value of captured signal (-dBm)
+    /// RSSI value of captured signal (dBm, signed byte), see rssi_dbm
     pub fn rssi(&self) -> u8 {
         self.0[2]
     }
 
     /// MAC Address
-    pub fn mac(&self) -> u64 {
-        (self.0[8] as u64) |
-        ((self.0[7] as u64) << 8) |
-        ((self.0[6] as u64) << 16) |
-        ((self.0[5] as u64) << 24) |
-        ((self.0[4] as u64) << 32) |
-        ((self.0[3] as u64) << 40)
+    pub fn mac(&self) -> MacAddress {
+        MacAddress::new([self.0[3], self.0[4], self.0[5], self.0[6], self.0[7], self.0[8]])
     }
 }
 
@@ -301,11 +277,14 @@ impl AsMut<[u8]> for WifiReadResultsRsp {
 pub struct WifiReadLongResultsRsp([u8; 22]);
 
 impl WifiReadLongResultsRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 22;
 
     /// Create struct from existing response buffer
-    pub fn from_slice(buffer: &[u8]) -> Self {
-        let raw : [u8; 22] = buffer.try_into().expect("Buffer size should match response size !");
-        Self(raw)
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 22] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
     }
 
     /// 802.11 standard selection: B (1), G (2), N (3) or All (4)
@@ -328,7 +307,7 @@ impl WifiReadLongResultsRsp {
         (self.0[1] >> 6) & 0x1 != 0
     }
 
-    /// RSSI value of captured signal (-dBm)
+    /// RSSI value of captured signal (dBm, signed byte), see rssi_dbm
     pub fn rssi(&self) -> u8 {
         self.0[2]
     }
@@ -339,13 +318,8 @@ impl WifiReadLongResultsRsp {
     }
 
     /// MAC Address
-    pub fn mac(&self) -> u64 {
-        (self.0[9] as u64) |
-        ((self.0[8] as u64) << 8) |
-        ((self.0[7] as u64) << 16) |
-        ((self.0[6] as u64) << 24) |
-        ((self.0[5] as u64) << 32) |
-        ((self.0[4] as u64) << 40)
+    pub fn mac(&self) -> MacAddress {
+        MacAddress::new([self.0[4], self.0[5], self.0[6], self.0[7], self.0[8], self.0[9]])
     }
 
     /// Phase offset (used to compute frequency offset)
@@ -383,11 +357,14 @@ impl AsMut<[u8]> for WifiReadLongResultsRsp {
 pub struct WifiReadExtendedResultsRsp([u8; 79]);
 
 impl WifiReadExtendedResultsRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 79;
 
     /// Create struct from existing response buffer
-    pub fn from_slice(buffer: &[u8]) -> Self {
-        let raw : [u8; 79] = buffer.try_into().expect("Buffer size should match response size !");
-        Self(raw)
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 79] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
     }
 
     /// 802.11 standard selection: B (1), G (2), N (3) or All (4)
@@ -410,7 +387,7 @@ impl WifiReadExtendedResultsRsp {
         (self.0[1] >> 6) & 0x1 != 0
     }
 
-    /// RSSI value of captured signal (-dBm)
+    /// RSSI value of captured signal (dBm, signed byte), see rssi_dbm
     pub fn rssi(&self) -> u8 {
         self.0[2]
     }
@@ -439,33 +416,18 @@ impl WifiReadExtendedResultsRsp {
     }
 
     /// MAC Address 0
-    pub fn mac0(&self) -> u64 {
-        (self.0[15] as u64) |
-        ((self.0[14] as u64) << 8) |
-        ((self.0[13] as u64) << 16) |
-        ((self.0[12] as u64) << 24) |
-        ((self.0[11] as u64) << 32) |
-        ((self.0[10] as u64) << 40)
+    pub fn mac0(&self) -> MacAddress {
+        MacAddress::new([self.0[10], self.0[11], self.0[12], self.0[13], self.0[14], self.0[15]])
     }
 
     /// MAC Address 1
-    pub fn mac1(&self) -> u64 {
-        (self.0[21] as u64) |
-        ((self.0[20] as u64) << 8) |
-        ((self.0[19] as u64) << 16) |
-        ((self.0[18] as u64) << 24) |
-        ((self.0[17] as u64) << 32) |
-        ((self.0[16] as u64) << 40)
+    pub fn mac1(&self) -> MacAddress {
+        MacAddress::new([self.0[16], self.0[17], self.0[18], self.0[19], self.0[20], self.0[21]])
     }
 
     /// MAC Address 2
-    pub fn mac2(&self) -> u64 {
-        (self.0[27] as u64) |
-        ((self.0[26] as u64) << 8) |
-        ((self.0[25] as u64) << 16) |
-        ((self.0[24] as u64) << 24) |
-        ((self.0[23] as u64) << 32) |
-        ((self.0[22] as u64) << 40)
+    pub fn mac2(&self) -> MacAddress {
+        MacAddress::new([self.0[22], self.0[23], self.0[24], self.0[25], self.0[26], self.0[27]])
     }
 
     /// AP uptime in us
@@ -536,13 +498,23 @@ impl AsMut<[u8]> for WifiReadExtendedResultsRsp {
 pub struct WifiReadCumulTimingsRsp([u8; 17]);
 
 impl WifiReadCumulTimingsRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 17;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 17] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -590,13 +562,23 @@ impl AsMut<[u8]> for WifiReadCumulTimingsRsp {
 pub struct WifiGetNbCountryCodeResultsRsp([u8; 2]);
 
 impl WifiGetNbCountryCodeResultsRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 2;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 2] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -616,11 +598,14 @@ impl AsMut<[u8]> for WifiGetNbCountryCodeResultsRsp {
 pub struct WifiReadCountryCodeResultsRsp([u8; 10]);
 
 impl WifiReadCountryCodeResultsRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 10;
 
     /// Create struct from existing response buffer
-    pub fn from_slice(buffer: &[u8]) -> Self {
-        let raw : [u8; 10] = buffer.try_into().expect("Buffer size should match response size !");
-        Self(raw)
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 10] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
     }
 
     /// Country code in ASCII
@@ -650,13 +635,8 @@ impl WifiReadCountryCodeResultsRsp {
     }
 
     /// MAC Address
-    pub fn mac(&self) -> u64 {
-        (self.0[9] as u64) |
-        ((self.0[8] as u64) << 8) |
-        ((self.0[7] as u64) << 16) |
-        ((self.0[6] as u64) << 24) |
-        ((self.0[5] as u64) << 32) |
-        ((self.0[4] as u64) << 40)
+    pub fn mac(&self) -> MacAddress {
+        MacAddress::new([self.0[4], self.0[5], self.0[6], self.0[7], self.0[8], self.0[9]])
     }
 }
 
@@ -671,13 +651,23 @@ impl AsMut<[u8]> for WifiReadCountryCodeResultsRsp {
 pub struct WifiReadVersionRsp([u8; 3]);
 
 impl WifiReadVersionRsp {
+    /// Response length in bytes
+    pub const RSP_LEN : usize = 3;
+
+    /// Create struct from existing response buffer
+    /// Return an error if the buffer size does not match the response size
+    pub fn try_from_slice(buffer: &[u8]) -> Result<Self, crate::Lr1120Error> {
+        let raw : [u8; 3] = buffer.try_into().map_err(|_| crate::Lr1120Error::InvalidSize)?;
+        Ok(Self(raw))
+    }
+
     /// Create a new response buffer
     pub fn new() -> Self {
         Self::default()
     }
 
-    /// Return Status
-    pub fn status(&mut self) -> Status {
+    /// Return Status (only the first byte is provided by a response)
+    pub fn status(&self) -> Status {
         self.0[0].into()
     }
 
@@ -1,13 +1,135 @@
 //! # Collection of functions to create SPI commands (byte arrays) and hold SPI responses
 //!
 //! All commands are automatically generated from the `commands.yaml` file which is extracted from the official specification.
+//! Command builders pack their fields with the internal `CmdWriter`, which is also the way to add new opcodes by hand.
+//! The [`catalog`] lists the name and expected lengths of each opcode, to decode raw SPI traces.
 //!
+//! Command builders are `const fn`: fixed command sequences (e.g. an init script) can be built at compile time
+//! and stored in flash with [`cmd_seq!`](crate::cmd_seq), then sent with [`cmd_wr_batch`](crate::Lr1120::cmd_wr_batch).
+//! ```rust,no_run
+//! static INIT: &[&[u8]] = cmd_seq![
+//!     set_standby_cmd(StandbyMode::Rc),
+//!     set_packet_type_cmd(PacketType::Lora),
+//!     set_rf_frequency_cmd(868_100_000),
+//! ];
+//! lr1120.cmd_wr_batch(INIT, BatchCheck::EndOnly).await?;
+//! ```
+//!
+pub mod catalog;
+pub mod cmd_bootloader;
+mod cmd_catalog;
+#[cfg(feature = "crypto")]
 pub mod cmd_crypto;
+#[cfg(feature = "fsk")]
 pub mod cmd_fsk;
+#[cfg(feature = "gnss")]
 pub mod cmd_gnss;
 pub mod cmd_lora;
+#[cfg(feature = "lrfhss")]
 pub mod cmd_lrfhss;
 pub mod cmd_radio;
 pub mod cmd_regmem;
 pub mod cmd_system;
+#[cfg(feature = "wifi")]
 pub mod cmd_wifi;
+
+/// Build a static sequence of commands (`&'static [&'static [u8]]`) from command builders
+/// Each command is evaluated at compile time: the builders and their arguments must be usable in a const context
+#[macro_export]
+macro_rules! cmd_seq {
+    ($($cmd:expr),* $(,)?) => {
+        &[$(&const { $cmd } as &[u8]),*]
+    };
+}
+
+/// Writer packing the fields of a command in a fixed-size buffer
+///
+/// The opcode is written first, then fields are added in order:
+/// multi-byte values are OR-ed big-endian and advance the position (some fields share a byte with the next one),
+/// while sub-byte fields (`bits`, `flag`) are OR-ed in the current byte and require an explicit `skip` to move to the next byte.
+/// All methods are `const` and panic at compile time when used in a const context with a buffer too small.
+pub(crate) struct CmdWriter<const N: usize> {
+    buf: [u8; N],
+    pos: usize,
+}
+
+// Positions are bounded by the generated command layout, which also sets N
+#[allow(clippy::indexing_slicing)]
+impl<const N: usize> CmdWriter<N> {
+    /// Start a command with its 16b opcode
+    pub(crate) const fn new(opcode: u16) -> Self {
+        let mut buf = [0u8; N];
+        buf[0] = (opcode >> 8) as u8;
+        buf[1] = opcode as u8;
+        Self { buf, pos: 2 }
+    }
+
+    /// Advance the position by n bytes (reserved bytes or end of a byte with sub-byte fields)
+    pub(crate) const fn skip(mut self, n: usize) -> Self {
+        self.pos += n;
+        self
+    }
+
+    /// OR the n least significant bytes of a value (big-endian)
+    const fn push_be(mut self, value: u128, n: usize) -> Self {
+        let mut i = 0;
+        while i < n {
+            self.buf[self.pos + i] |= (value >> (8 * (n - 1 - i))) as u8;
+            i += 1;
+        }
+        self.pos += n;
+        self
+    }
+
+    /// Write one byte
+    pub(crate) const fn push_u8(self, value: u8) -> Self {
+        self.push_be(value as u128, 1)
+    }
+
+    /// Write a 16b value
+    pub(crate) const fn push_u16(self, value: u16) -> Self {
+        self.push_be(value as u128, 2)
+    }
+
+    /// Write the 24 least significant bits of a value
+    pub(crate) const fn push_u24(self, value: u32) -> Self {
+        self.push_be(value as u128, 3)
+    }
+
+    /// Write a 32b value
+    pub(crate) const fn push_u32(self, value: u32) -> Self {
+        self.push_be(value as u128, 4)
+    }
+
+    /// Write a 64b value
+    #[cfg(feature = "fsk")]
+    pub(crate) const fn push_u64(self, value: u64) -> Self {
+        self.push_be(value as u128, 8)
+    }
+
+    /// Write a 128b value
+    #[cfg(feature = "crypto")]
+    pub(crate) const fn push_u128(self, value: u128) -> Self {
+        self.push_be(value, 16)
+    }
+
+    /// OR a field of `width` bits at bit `lsb` of the current byte
+    pub(crate) const fn bits(mut self, value: u8, width: u8, lsb: u8) -> Self {
+        let mask = ((1u16 << width) - 1) as u8;
+        self.buf[self.pos] |= (value & mask) << lsb;
+        self
+    }
+
+    /// Set bit `bit` of the current byte if `value` is true
+    pub(crate) const fn flag(mut self, bit: u8, value: bool) -> Self {
+        if value {
+            self.buf[self.pos] |= 1 << bit;
+        }
+        self
+    }
+
+    /// Return the command bytes
+    pub(crate) const fn finish(self) -> [u8; N] {
+        self.buf
+    }
+}
@@ -0,0 +1,149 @@
+//! # RX duty cycle coexistence with GNSS/WiFi scans
+//!
+//! A GNSS or WiFi scan uses the same chip as the radio: launching one while the RX duty cycle is active
+//! stops the duty cycle without any notice. The driver records the duty cycle started with
+//! [`set_rx_duty_cycle`](Lr1120::set_rx_duty_cycle) (until another mode is set or the chip is reset)
+//! and the scan commands return `InvalidConfig` while it is active.
+//!
+//! [`with_rx_duty_cycle_paused`](Lr1120::with_rx_duty_cycle_paused) arbitrates between them: it stops the duty cycle,
+//! runs the GNSS/WiFi operation, then restarts the duty cycle and reports the RX windows missed
+//! during the operation ([`RxWindowsMissed`]), e.g. to let the application extend its listening or warn its peers.
+//! The pause can also be handled manually with [`pause_rx_duty_cycle`](Lr1120::pause_rx_duty_cycle)
+//! and [`resume_rx_duty_cycle`](Lr1120::resume_rx_duty_cycle).
+//!
+//! The chip ends the duty cycle on a packet reception: the driver forgets it when
+//! [`get_status`](Lr1120::get_status) reports RxDone.
+//!
+//! ## Example
+//! ```rust,no_run
+//! lr1120.set_rx_duty_cycle(listen, cycle, false).await?;
+//! // ...
+//! let (timing, missed) = lr1120.with_rx_duty_cycle_paused(async |lr| {
+//!     lr.gnss_scan_timed(cfg, latency::GNSS_SCAN, Duration::from_millis(500), |_| BusyProgress::Continue).await
+//! }).await?;
+//! if let Some(missed) = missed {
+//!     info!("{} RX windows missed during the scan", missed.windows);
+//! }
+//! ```
+//!
+//! ## Available Methods
+//! - [`rx_duty_cycle`](Lr1120::rx_duty_cycle) - Return the RX duty cycle currently active
+//! - [`pause_rx_duty_cycle`](Lr1120::pause_rx_duty_cycle) - Stop the RX duty cycle before another operation
+//! - [`resume_rx_duty_cycle`](Lr1120::resume_rx_duty_cycle) - Restart the RX duty cycle and report the windows missed
+//! - [`with_rx_duty_cycle_paused`](Lr1120::with_rx_duty_cycle_paused) - Run an operation with the RX duty cycle paused
+
+use embassy_time::{Duration, Instant};
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiBus;
+
+use super::{BusyPin, Lr1120, Lr1120Error};
+use super::system::ChipMode;
+use super::status::Intr;
+
+/// RX duty cycle parameters (in LF clock steps)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RxDutyCycle {
+    /// Listening time of each window
+    pub listen_time: u32,
+    /// Period of the windows
+    pub cycle_time: u32,
+    /// Windows performing a LoRa CAD instead of a reception
+    pub use_lora_cad: bool,
+}
+
+impl RxDutyCycle {
+    /// Period of the windows with an LF clock at `lf_clk_hz`
+    pub fn period(&self, lf_clk_hz: u32) -> Duration {
+        Duration::from_micros(self.cycle_time as u64 * 1_000_000 / lf_clk_hz.max(1) as u64)
+    }
+}
+
+/// RX duty cycle stopped by [`pause_rx_duty_cycle`](Lr1120::pause_rx_duty_cycle)
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RxDutyCyclePause {
+    /// Duty cycle to restart
+    pub cfg: RxDutyCycle,
+    /// Instant the duty cycle was stopped
+    pub at: Instant,
+}
+
+/// Event reported when the RX duty cycle is restarted after a pause
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RxWindowsMissed {
+    /// Duration of the pause
+    pub paused: Duration,
+    /// Number of RX windows missed during the pause
+    pub windows: u32,
+}
+
+impl<O,SPI, M> Lr1120<O,SPI, M> where
+    O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+{
+
+    /// Return the RX duty cycle currently active (as recorded by the driver)
+    pub fn rx_duty_cycle(&self) -> Option<RxDutyCycle> {
+        self.rx_duty_cycle
+    }
+
+    /// Forget the RX duty cycle ended by the chip on a packet reception (called by `get_status`)
+    pub(crate) fn record_rx_duty_cycle(&mut self, intr: Intr) {
+        if intr.rx_done() {
+            self.rx_duty_cycle = None;
+        }
+    }
+
+    /// Return InvalidConfig while the RX duty cycle is active
+    #[cfg(any(feature = "gnss", feature = "wifi"))]
+    pub(crate) fn check_no_rx_duty_cycle(&self) -> Result<(), Lr1120Error> {
+        match self.rx_duty_cycle {
+            Some(_) => Err(Lr1120Error::InvalidConfig),
+            None => Ok(()),
+        }
+    }
+
+    /// Stop the RX duty cycle (chip in standby RC) before another operation
+    /// Return None if no duty cycle is active
+    pub async fn pause_rx_duty_cycle(&mut self) -> Result<Option<RxDutyCyclePause>, Lr1120Error> {
+        let Some(cfg) = self.rx_duty_cycle else {
+            return Ok(None);
+        };
+        self.set_chip_mode(ChipMode::StandbyRc).await?;
+        Ok(Some(RxDutyCyclePause { cfg, at: Instant::now() }))
+    }
+
+    /// Restart an RX duty cycle paused with [`pause_rx_duty_cycle`](Lr1120::pause_rx_duty_cycle)
+    /// and return the RX windows missed during the pause
+    pub async fn resume_rx_duty_cycle(&mut self, pause: RxDutyCyclePause) -> Result<RxWindowsMissed, Lr1120Error> {
+        let paused = pause.at.elapsed();
+        let cfg = pause.cfg;
+        self.set_rx_duty_cycle(cfg.listen_time, cfg.cycle_time, cfg.use_lora_cad).await?;
+        let period = cfg.period(self.lf_clk_hz).as_ticks().max(1);
+        let windows = (paused.as_ticks() / period).min(u32::MAX as u64) as u32;
+        #[cfg(feature = "defmt")]
+        if windows > 0 {
+            defmt::warn!("{} RX windows missed during {}ms", windows, paused.as_millis());
+        }
+        Ok(RxWindowsMissed { paused, windows })
+    }
+
+    /// Run an operation (e.g. GNSS/WiFi scan and its results) with the RX duty cycle paused
+    /// The duty cycle is restarted even if the operation fails. Return the result of the operation
+    /// and the RX windows missed (None if no duty cycle was active).
+    /// An error of the operation takes precedence over an error restarting the duty cycle
+    pub async fn with_rx_duty_cycle_paused<F, T>(&mut self, op: F) -> Result<(T, Option<RxWindowsMissed>), Lr1120Error>
+        where F: AsyncFnOnce(&mut Self) -> Result<T, Lr1120Error>
+    {
+        let pause = self.pause_rx_duty_cycle().await?;
+        let res = op(self).await;
+        let missed = match pause {
+            Some(pause) => Some(self.resume_rx_duty_cycle(pause).await),
+            None => None,
+        };
+        let v = res?;
+        Ok((v, missed.transpose()?))
+    }
+
+}
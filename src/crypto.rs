@@ -7,6 +7,15 @@
 //! ### Key Handling
 //! - [`ce_set_key`](Lr1120::ce_set_key) - Set an encryption key
 //! - [`ce_derive_key`](Lr1120::ce_derive_key) - Derives input value from the source key into the destination key
+//! - [`ce_derive_lifetime_keys`](Lr1120::ce_derive_lifetime_keys) - Derive the LoRaWAN 1.1 lifetime keys (JSEncKey/JSIntKey) from the network key
+//!
+//! ### Provisioning
+//! - [`ce_provision`](Lr1120::ce_provision) - Set keys, derive lifetime keys, store to flash and check the restore from flash
+//! - [`ce_key_fingerprint`](Lr1120::ce_key_fingerprint) - CMAC of a fixed block, to check a key without reading it back
+//!
+//! The crypto engine does not provide any key-lock or provisioning status: keys are write-only.
+//! A device can be checked by comparing key fingerprints with the ones computed offline from the expected keys
+//! (AES-CMAC of [`KEY_FINGERPRINT_BLOCK`]). Only the network key, JSIntKey and unicast keys can be used for a CMAC.
 //!
 //! ### Computation
 //! - [`ce_process_join_accept`](Lr1120::ce_process_join_accept) - Return decryption status and decrypted payload
@@ -23,16 +32,90 @@
 //! - [`ce_get_param`](Lr1120::ce_get_param) - Get a parameter by ID
 //! - [`ce_check_fw_image`](Lr1120::ce_check_fw_image) - Check if the firmware image is valid
 //! - [`ce_fw_image_ok`](Lr1120::ce_fw_image_ok) - Return true if the all previous calls to all chunks of the fimrware image were correct
+//! - [`verify_firmware_image`](Lr1120::verify_firmware_image) - Check a complete firmware image streamed from any `embedded_io_async::Read` source (e.g. external flash)
 //!
 
-use embassy_time::Duration;
 use embedded_hal::digital::OutputPin;
 use embedded_hal_async::spi::SpiBus;
+use embedded_io_async::{Read, ReadExactError};
 
 use super::{BusyPin, Lr1120, Lr1120Error};
 
 pub use crate::cmd::cmd_crypto::*;
 
+/// Block used to compute a key fingerprint
+pub const KEY_FINGERPRINT_BLOCK: [u8; 16] = *b"LR1120-KEY-CHECK";
+
+/// Keys to provision in the crypto engine (no Debug implementation to avoid logging keys)
+#[derive(Clone, Copy)]
+pub struct CeProvisioning {
+    /// Network root key (NwkKey)
+    pub nwk_key: u128,
+    /// Application root key (AppKey)
+    pub app_key: u128,
+    /// DevEUI used to derive the LoRaWAN 1.1 lifetime keys (None for LoRaWAN 1.0)
+    pub dev_eui: Option<u64>,
+}
+
+/// Provisioning step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ProvisionStep {
+    SetNwkKey,
+    SetAppKey,
+    DeriveLifetimeKeys,
+    StoreToFlash,
+    RestoreFromFlash,
+    /// Key fingerprints differ after the restore from flash
+    Verify,
+}
+
+/// Result of a provisioning
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ProvisionReport {
+    /// First step which failed with the crypto engine status
+    pub failed: Option<(ProvisionStep, CeStatus)>,
+    /// Fingerprint of the network key
+    pub nwk_fingerprint: Option<u32>,
+    /// Fingerprint of the JSIntKey (only when lifetime keys are derived)
+    pub js_int_fingerprint: Option<u32>,
+}
+
+impl ProvisionReport {
+    /// True when all steps succeeded, including the check after the restore from flash
+    pub fn is_provisioned(&self) -> bool {
+        self.failed.is_none()
+    }
+}
+
+/// Derivation input of a LoRaWAN 1.1 lifetime key: prefix | DevEUI (little endian) | padding
+fn lifetime_key_input(prefix: u8, dev_eui: u64) -> u128 {
+    ((prefix as u128) << 120) | ((dev_eui.swap_bytes() as u128) << 56)
+}
+
+/// Maximum chunk size for the firmware image check
+pub const FW_CHECK_CHUNK_LEN: usize = 256;
+
+/// Description of a firmware image to verify
+/// The encrypted image does not expose its version to the crypto engine: the version and target of an update file
+/// are checked with [`FwImageInfo`](crate::fw_image::FwImageInfo) instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FwManifest {
+    /// Image length in bytes (multiple of 4)
+    pub len: u32,
+    /// Expected verification result (true for a genuine image)
+    pub expected: bool,
+}
+
+impl FwManifest {
+    /// Manifest of a genuine image
+    pub fn new(len: u32) -> Self {
+        Self { len, expected: true }
+    }
+}
+
 #[derive(Debug, Clone)]
 /// Struct holding result from an encryption/decryption
 pub struct CeDataRes<'a> {
@@ -46,6 +129,12 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
 {
 
+    /// Split a response of `len` bytes read in the internal buffer into the CE status and the data
+    fn ce_data_res(&self, len: usize) -> Result<CeDataRes<'_>, Lr1120Error> {
+        let (status, data) = self.rsp_data(len)?.split_first().ok_or(Lr1120Error::InvalidSize)?;
+        Ok(CeDataRes{status: (*status).into(), data})
+    }
+
     /// Set an encryption key
     pub async fn ce_set_key(&mut self, id: KeyId, key: u128) -> Result<CeStatus, Lr1120Error> {
         let req = crypto_set_key_req(id, key);
@@ -70,6 +159,84 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         Ok(rsp.ce_status())
     }
 
+    /// Derive the LoRaWAN 1.1 lifetime keys from the network key:
+    /// JSEncKey = aes128_encrypt(NwkKey, 0x05 | DevEUI | pad16) and JSIntKey = aes128_encrypt(NwkKey, 0x06 | DevEUI | pad16)
+    pub async fn ce_derive_lifetime_keys(&mut self, dev_eui: u64) -> Result<CeStatus, Lr1120Error> {
+        let status = self.ce_derive_key(KeyId::Nwk, KeyId::JsEnc, lifetime_key_input(0x05, dev_eui)).await?;
+        if status != CeStatus::Success {
+            return Ok(status);
+        }
+        self.ce_derive_key(KeyId::Nwk, KeyId::JsInt, lifetime_key_input(0x06, dev_eui)).await
+    }
+
+    /// Return the AES-CMAC of [`KEY_FINGERPRINT_BLOCK`] with a key (network key, JSIntKey or unicast key)
+    pub async fn ce_key_fingerprint(&mut self, key: KeyId) -> Result<Result<u32, CeStatus>, Lr1120Error> {
+        let rsp = self.ce_compute_cmac(key, &KEY_FINGERPRINT_BLOCK).await?;
+        Ok(match rsp.ce_status() {
+            CeStatus::Success => Ok(rsp.mic()),
+            status => Err(status),
+        })
+    }
+
+    /// Provision the root keys, derive the lifetime keys (if a DevEUI is provided) and store them in flash.
+    /// The restore from flash is then checked by comparing key fingerprints before and after.
+    /// SPI/pin errors are returned as error, crypto engine failures are reported in the [`ProvisionReport`].
+    pub async fn ce_provision(&mut self, cfg: &CeProvisioning) -> Result<ProvisionReport, Lr1120Error> {
+        let mut report = ProvisionReport { failed: None, nwk_fingerprint: None, js_int_fingerprint: None };
+        let steps = [
+            (ProvisionStep::SetNwkKey, self.ce_set_key(KeyId::Nwk, cfg.nwk_key).await?),
+            (ProvisionStep::SetAppKey, self.ce_set_key(KeyId::App, cfg.app_key).await?),
+        ];
+        if let Some(failed) = steps.into_iter().find(|(_, s)| *s != CeStatus::Success) {
+            report.failed = Some(failed);
+            return Ok(report);
+        }
+        if let Some(dev_eui) = cfg.dev_eui {
+            let status = self.ce_derive_lifetime_keys(dev_eui).await?;
+            if status != CeStatus::Success {
+                report.failed = Some((ProvisionStep::DeriveLifetimeKeys, status));
+                return Ok(report);
+            }
+        }
+        let (nwk, js_int) = match self.provision_fingerprints(cfg.dev_eui.is_some()).await? {
+            Ok(fp) => fp,
+            Err(status) => {
+                report.failed = Some((ProvisionStep::Verify, status));
+                return Ok(report);
+            }
+        };
+        report.nwk_fingerprint = Some(nwk);
+        report.js_int_fingerprint = js_int;
+        let status = self.ce_store_to_flash().await?;
+        if status != CeStatus::Success {
+            report.failed = Some((ProvisionStep::StoreToFlash, status));
+            return Ok(report);
+        }
+        let status = self.ce_restore_from_flash().await?;
+        if status != CeStatus::Success {
+            report.failed = Some((ProvisionStep::RestoreFromFlash, status));
+            return Ok(report);
+        }
+        match self.provision_fingerprints(cfg.dev_eui.is_some()).await? {
+            Ok(fp) if fp == (nwk, js_int) => {}
+            Ok(_) => report.failed = Some((ProvisionStep::Verify, CeStatus::FailCmac)),
+            Err(status) => report.failed = Some((ProvisionStep::Verify, status)),
+        }
+        Ok(report)
+    }
+
+    /// Fingerprints of the network key and optionally of JSIntKey
+    async fn provision_fingerprints(&mut self, js_int: bool) -> Result<Result<(u32, Option<u32>), CeStatus>, Lr1120Error> {
+        let nwk = match self.ce_key_fingerprint(KeyId::Nwk).await? {
+            Ok(fp) => fp,
+            Err(status) => return Ok(Err(status)),
+        };
+        if !js_int {
+            return Ok(Ok((nwk, None)));
+        }
+        Ok(self.ce_key_fingerprint(KeyId::JsInt).await?.map(|fp| (nwk, Some(fp))))
+    }
+
     /// Decrypt a join/accept LoRaWAN message using two keys (one for decryption, one for integrity check)
     /// Input data buffer contains header (1 or 12 bytes) followed by 16 or 32 bytes of encrypted payload
     /// Return decryption status and decrypted payload
@@ -85,11 +252,10 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         }
         let req = crypto_process_join_accept_req(dec, mic, lorawan);
         self.cmd_data_wr(&req, data).await?;
-        self.wait_ready(Duration::from_millis(100)).await?;
-        self.rsp_rd(rsp_len).await?;
-        let status : CeStatus = self.buffer()[0].into();
-        let payload = &self.buffer()[1..rsp_len+1];
-        Ok(CeDataRes{status, data:payload})
+        self.wait_ready(self.timeouts.cmd).await?;
+        // Response is the CE status followed by the decrypted data
+        self.rsp_rd(rsp_len + 1).await?;
+        self.ce_data_res(rsp_len + 1)
     }
 
     /// Compute AES CMAC of the provided data
@@ -125,9 +291,7 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         self.cmd_data_wr(&req, din).await?;
         let rsp_len = din.len() + 1;
         self.rsp_rd(rsp_len).await?;
-        let status : CeStatus = self.buffer()[0].into();
-        let data = &self.buffer()[1..rsp_len];
-        Ok(CeDataRes{status, data})
+        self.ce_data_res(rsp_len)
     }
 
     /// Encrypt data for non-LoRaWAN operation
@@ -136,9 +300,7 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         self.cmd_data_wr(&req, din).await?;
         let rsp_len = din.len() + 1;
         self.rsp_rd(rsp_len).await?;
-        let status : CeStatus = self.buffer()[0].into();
-        let data = &self.buffer()[1..rsp_len];
-        Ok(CeDataRes{status, data})
+        self.ce_data_res(rsp_len)
     }
 
     /// Encrypt data for non-LoRaWAN operation
@@ -147,13 +309,13 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         self.cmd_data_wr(&req, din).await?;
         let rsp_len = din.len() + 1;
         self.rsp_rd(rsp_len).await?;
-        let status : CeStatus = self.buffer()[0].into();
-        let data = &self.buffer()[1..rsp_len];
-        Ok(CeDataRes{status, data})
+        self.ce_data_res(rsp_len)
     }
 
     /// Store all keys and parameters from Crypto Engine into falsh memory
+    /// Return LowBattery when the battery is below the low battery threshold (see [`set_flash_guard`](Lr1120::set_flash_guard))
     pub async fn ce_store_to_flash(&mut self) -> Result<CeStatus, Lr1120Error> {
+        self.check_flash_write().await?;
         let req = crypto_store_to_flash_req();
         let mut rsp = CryptoStoreToFlashRsp::new();
         self.cmd_rd(&req, rsp.as_mut()).await?;
@@ -198,4 +360,26 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         Ok(rsp.success())
     }
 
+    /// Stream a firmware image from `reader` to the crypto engine by chunks of 256 bytes and return its verification result
+    /// Return true when the result matches the one expected by the manifest,
+    /// InvalidParam if the image length is not a multiple of 4, InvalidSize if the reader ends before the image length and Io on reader error
+    pub async fn verify_firmware_image<R: Read>(&mut self, manifest: &FwManifest, reader: &mut R) -> Result<bool, Lr1120Error> {
+        if !manifest.len.is_multiple_of(4) {
+            return Err(Lr1120Error::InvalidParam);
+        }
+        let mut chunk = [0u8; FW_CHECK_CHUNK_LEN];
+        let mut offset = 0;
+        while offset < manifest.len {
+            let len = (manifest.len - offset).min(FW_CHECK_CHUNK_LEN as u32) as usize;
+            let data = chunk.get_mut(..len).ok_or(Lr1120Error::InvalidSize)?;
+            reader.read_exact(data).await.map_err(|e| match e {
+                ReadExactError::UnexpectedEof => Lr1120Error::InvalidSize,
+                ReadExactError::Other(_) => Lr1120Error::Io,
+            })?;
+            self.ce_check_fw_image(offset, data).await?;
+            offset += len as u32;
+        }
+        Ok(self.ce_fw_image_ok().await? == manifest.expected)
+    }
+
 }
\ No newline at end of file
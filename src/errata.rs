@@ -0,0 +1,160 @@
+//! # Errata workarounds
+//!
+//! Workarounds for known chip behaviours are applied automatically by the driver in the relevant flows.
+//! Each workaround can be enabled or disabled individually with [`set_errata`](Lr1120::set_errata),
+//! and [`Errata::for_version`] selects the workarounds relevant for a chip type and firmware version
+//! (as returned by [`get_version`](Lr1120::get_version)) from [`ERRATA_TABLE`]. All workarounds are enabled by default.
+//!
+//! | Workaround | Flow | Chips | Firmware |
+//! |------------|------|-------|----------|
+//! | [`TCXO_START_ERR`](Errata::TCXO_START_ERR) | [`init_tcxo`](Lr1120::init_tcxo) | All | All |
+//! | [`TCXO_COLD_WAKEUP`](Errata::TCXO_COLD_WAKEUP) | [`wake_up`](Lr1120::wake_up) after a sleep without retention | All | All |
+//!
+//! Only behaviours described in the command specification of this driver are covered: no workaround is applied
+//! after a GNSS scan, nor any register-level fix from the vendor errata sheets. Such a fix is added as a new
+//! bit with its own entry in [`ERRATA_TABLE`], giving the chips and firmware versions affected.
+//!
+//! ## Example
+//! ```rust,no_run
+//! let version = lr1120.get_version().await?;
+//! lr1120.set_errata(Errata::for_version(version.hw_type(), (version.major(), version.minor())));
+//! ```
+//!
+//! ## Available Methods
+//! - [`set_errata`](Lr1120::set_errata) - Select the workarounds applied by the driver
+//! - [`errata`](Lr1120::errata) - Return the workarounds applied by the driver
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiBus;
+
+use super::{BusyPin, Lr1120, Lr1120Error};
+use super::system::{CalibrateBlocks, HwType};
+
+/// Set of errata workarounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Errata(u8);
+
+impl Errata {
+    /// With a TCXO the HF oscillator start error is raised at power-up, before the TCXO is configured:
+    /// the error is cleared after the TCXO configuration, so that later errors are not masked by it
+    pub const TCXO_START_ERR : Errata = Errata(0x01);
+
+    /// The firmware restarts when waking up from a sleep without retention and loses the TCXO configuration:
+    /// the startup calibration then fails as at power-up. The TCXO configuration set before the sleep is
+    /// restored on wake-up, the start error cleared and all blocks calibrated again
+    pub const TCXO_COLD_WAKEUP : Errata = Errata(0x02);
+
+    /// No workaround
+    pub fn empty() -> Self {
+        Self(0)
+    }
+
+    /// All workarounds defined in [`ERRATA_TABLE`]
+    pub fn all() -> Self {
+        ERRATA_TABLE.iter().fold(Self::empty(), |acc, e| acc | e.errata)
+    }
+
+    /// Workarounds relevant for a chip type and firmware version (major, minor)
+    pub fn for_version(hw_type: HwType, fw_version: (u8, u8)) -> Self {
+        ERRATA_TABLE.iter()
+            .filter(|e| e.applies(hw_type, fw_version))
+            .fold(Self::empty(), |acc, e| acc | e.errata)
+    }
+
+    /// Raw mask value
+    pub fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// True when all workarounds from other are selected
+    pub fn contains(&self, other: Errata) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+/// Chips and firmware versions affected by a workaround
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ErrataEntry {
+    /// Workaround
+    pub errata: Errata,
+    /// Chips affected (a running bootloader is never affected)
+    pub hw_types: &'static [HwType],
+    /// First firmware version affected (major, minor)
+    pub fw_min: (u8, u8),
+    /// Last firmware version affected (major, minor)
+    pub fw_max: (u8, u8),
+}
+
+impl ErrataEntry {
+    /// True when the workaround applies to a chip type and firmware version
+    pub fn applies(&self, hw_type: HwType, fw_version: (u8, u8)) -> bool {
+        self.hw_types.contains(&hw_type) && fw_version >= self.fw_min && fw_version <= self.fw_max
+    }
+}
+
+/// Chips running the transceiver firmware
+const ALL_CHIPS : &[HwType] = &[HwType::Lr1110, HwType::Lr1120, HwType::Lr1121];
+
+/// Workarounds with the chips and firmware versions they apply to
+pub const ERRATA_TABLE : &[ErrataEntry] = &[
+    ErrataEntry { errata: Errata::TCXO_START_ERR, hw_types: ALL_CHIPS, fw_min: (0, 0), fw_max: (0xFF, 0xFF) },
+    ErrataEntry { errata: Errata::TCXO_COLD_WAKEUP, hw_types: ALL_CHIPS, fw_min: (0, 0), fw_max: (0xFF, 0xFF) },
+];
+
+impl Default for Errata {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl core::ops::BitOr for Errata {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl core::ops::Sub for Errata {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 & !rhs.0)
+    }
+}
+
+impl<O,SPI, M> Lr1120<O,SPI, M> where
+    O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+{
+
+    /// Select the workarounds applied by the driver
+    pub fn set_errata(&mut self, errata: Errata) {
+        self.errata = errata;
+    }
+
+    /// Return the workarounds applied by the driver
+    pub fn errata(&self) -> Errata {
+        self.errata
+    }
+
+    /// Workarounds applied after the TCXO configuration
+    pub(crate) async fn errata_after_tcxo(&mut self) -> Result<(), Lr1120Error> {
+        if self.errata.contains(Errata::TCXO_START_ERR) {
+            self.clear_errors().await?;
+        }
+        Ok(())
+    }
+
+    /// Workarounds applied on wake-up
+    pub(crate) async fn errata_after_wakeup(&mut self) -> Result<(), Lr1120Error> {
+        let cold = core::mem::take(&mut self.cold_start);
+        if let Some((volt, start_time)) = self.tcxo
+            && cold && self.errata.contains(Errata::TCXO_COLD_WAKEUP) {
+            self.set_tcxo(volt, start_time).await?;
+            self.clear_errors().await?;
+            self.calibrate_blocks(CalibrateBlocks::all()).await?;
+        }
+        Ok(())
+    }
+
+}
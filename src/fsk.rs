@@ -11,19 +11,14 @@
 //!
 //! ```rust,no_run
 //! use lr1120::radio::PacketType;
-//! use lr1120::fsk::{PblLenDetect, AddrComp, FskPktFormat, Crc, DcFree};
-//! use lr1120::{PulseShape, RxBw};
+//! use lr1120::fsk::{PblLenDetect, AddrComp, FskPktFormat, Crc, DcFree, FskModulationParams};
 //!
 //! // Set packet type to FSK Legacy (compatible with SX126x/SX127x/LR11xx)
 //! lr1120.set_packet_type(PacketType::FskLegacy).await.expect("Setting packet type");
 //!
-//! // Configure FSK modulation (250kbps, BT=0.5 pulse shaping, 444kHz bandwidth, 62.5kHz deviation)
-//! lr1120.set_fsk_modulation(
-//!     250_000,                // Bitrate: 250 kbps
-//!     PulseShape::Bt0p5,     // Pulse shaping: BT=0.5 Gaussian filter
-//!     RxBw::Bw444,           // RX bandwidth: 444 kHz
-//!     62500                  // Frequency deviation: 62.5 kHz
-//! ).await.expect("Setting FSK modulation");
+//! // Configure FSK modulation (250kbps, BT=0.5 pulse shaping, 467kHz bandwidth, 62.5kHz deviation)
+//! let modulation = FskModulationParams::fsk_250k();
+//! lr1120.set_fsk_modulation_params(&modulation).await.expect("Setting FSK modulation");
 //!
 //! // Configure syncword (64-bit value, syncword length configured separately in packet params)
 //! lr1120.set_fsk_syncword(0xCD05DEADC0FE1337).await.expect("Setting syncword");
@@ -44,15 +39,233 @@
 //! ## Available Methods
 //!
 //! - [`set_fsk_modulation`](Lr1120::set_fsk_modulation) - Configure bitrate, pulse shaping, bandwidth, and frequency deviation
+//! - [`set_fsk_modulation_params`](Lr1120::set_fsk_modulation_params) - Configure modulation from a validated [`FskModulationParams`]
 //! - [`set_fsk_packet`](Lr1120::set_fsk_packet) - Set packet parameters (preamble, length format, CRC, addressing, whitening)
+//! - [`set_fsk_packet_params`](Lr1120::set_fsk_packet_params) - Set packet parameters from a [`FskPacketParams`]
 //! - [`set_fsk_syncword`](Lr1120::set_fsk_syncword) - Configure synchronization word value
+//! - [`set_fsk_syncword_bits`](Lr1120::set_fsk_syncword_bits) - Configure a syncword of any bit length, padding it and updating the syncword length
 //! - [`get_fsk_packet_status`](Lr1120::get_fsk_packet_status) - Read FSK packet status: RSSI, packet length, error source (address, CRC, length, ...)
+//! - [`get_fsk_packet_status_filtered`](Lr1120::get_fsk_packet_status_filtered) - Read FSK packet status and filter its RSSI on the host across packets ([`FskRssiFilter`])
 
 use embedded_hal::digital::OutputPin;
 use embedded_hal_async::spi::SpiBus;
 
 pub use super::cmd::cmd_fsk::*;
 use super::{BusyPin, Lr1120, Lr1120Error};
+use super::radio::{rssi_to_dbm, PacketType};
+
+impl FskPacketStatusRsp {
+    /// RSSI captured after synchronisation in dBm
+    pub fn rssi_sync_dbm(&self) -> i16 {
+        rssi_to_dbm(self.rssi_sync())
+    }
+
+    /// RSSI averaged on the whole packet in dBm
+    pub fn rssi_avg_dbm(&self) -> i16 {
+        rssi_to_dbm(self.rssi_avg())
+    }
+}
+
+impl RxBw {
+    /// Bandwidth in Hz
+    pub fn hz(&self) -> u32 {
+        match self {
+            RxBw::Bw4800   => 4800,
+            RxBw::Bw5800   => 5800,
+            RxBw::Bw7300   => 7300,
+            RxBw::Bw9700   => 9700,
+            RxBw::Bw11700  => 11700,
+            RxBw::Bw14600  => 14600,
+            RxBw::Bw19500  => 19500,
+            RxBw::Bw23400  => 23400,
+            RxBw::Bw29300  => 29300,
+            RxBw::Bw39000  => 39000,
+            RxBw::Bw46900  => 46900,
+            RxBw::Bw58600  => 58600,
+            RxBw::Bw78200  => 78200,
+            RxBw::Bw93800  => 93800,
+            RxBw::Bw117300 => 117300,
+            RxBw::Bw156200 => 156200,
+            RxBw::Bw187200 => 187200,
+            RxBw::Bw234300 => 234300,
+            RxBw::Bw312000 => 312000,
+            RxBw::Bw373600 => 373600,
+            RxBw::Bw467000 => 467000,
+        }
+    }
+}
+
+/// Minimum bitrate supported (bps)
+const FSK_BITRATE_MIN : u32 = 600;
+/// Maximum bitrate supported (bps)
+const FSK_BITRATE_MAX : u32 = 300_000;
+
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// FSK Modulation parameters: bitrate, frequency deviation, pulse shaping and RX bandwidth
+pub struct FskModulationParams {
+    /// Bitrate in bps
+    pub bitrate: u32,
+    /// Frequency deviation in Hz
+    pub fdev: u32,
+    /// Pulse shaping
+    pub pulse_shape: PulseShape,
+    /// RX Bandwidth (double side-band)
+    pub rx_bw: RxBw,
+}
+
+impl FskModulationParams {
+    /// Create modulation parameters
+    pub fn new(bitrate: u32, fdev: u32, pulse_shape: PulseShape, rx_bw: RxBw) -> Self {
+        Self {bitrate, fdev, pulse_shape, rx_bw}
+    }
+
+    /// 100kbps with 50kHz deviation (similar to wM-Bus T/C-mode)
+    pub fn wmbus_100k() -> Self {
+        Self::new(100_000, 50_000, PulseShape::Bt0p5, RxBw::Bw234300)
+    }
+
+    /// 50kbps with 25kHz deviation, as used by the LoRaWAN FSK channel
+    pub fn lorawan_50k() -> Self {
+        Self::new(50_000, 25_000, PulseShape::Bt1p0, RxBw::Bw117300)
+    }
+
+    /// 250kbps with 62.5kHz deviation (MSK-like)
+    pub fn fsk_250k() -> Self {
+        Self::new(250_000, 62_500, PulseShape::Bt0p5, RxBw::Bw467000)
+    }
+
+    /// Modulation index (2*fdev/bitrate) in percent
+    pub fn mod_index_pct(&self) -> u32 {
+        (200 * self.fdev as u64 / self.bitrate.max(1) as u64) as u32
+    }
+
+    /// Check parameters consistency:
+    ///  - Bitrate in range 0.6 to 300kbps
+    ///  - Signal bandwidth (2*fdev + bitrate) strictly smaller than the RX bandwidth
+    ///  - Modulation index between 0.5 and 10
+    pub fn validate(&self) -> Result<(), Lr1120Error> {
+        if !(FSK_BITRATE_MIN..=FSK_BITRATE_MAX).contains(&self.bitrate) {
+            return Err(Lr1120Error::InvalidParam);
+        }
+        if self.fdev.saturating_mul(2).saturating_add(self.bitrate) >= self.rx_bw.hz() {
+            return Err(Lr1120Error::InvalidParam);
+        }
+        if !(50..=1000).contains(&self.mod_index_pct()) {
+            return Err(Lr1120Error::InvalidParam);
+        }
+        Ok(())
+    }
+}
+
+/// FSK packet parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FskPacketParams {
+    /// TX preamble length in bits
+    pub pbl_len_tx: u16,
+    /// Preamble detection length
+    pub pbl_len_detect: PblLenDetect,
+    /// Syncword length in bits (up to 64)
+    pub sw_len: u8,
+    /// Address filtering
+    pub addr_comp: AddrComp,
+    /// Fixed or variable length packet
+    pub fsk_pkt_format: FskPktFormat,
+    /// Payload length (maximum length for variable length packet)
+    pub pld_len: u8,
+    /// CRC configuration
+    pub crc: Crc,
+    /// DC-free encoding
+    pub dc_free: DcFree,
+}
+
+/// Maximum syncword length in bits
+const FSK_SYNCWORD_MAX_BITS : u8 = 64;
+
+/// Pad a syncword whose length is not a multiple of 8 bits, as required by the chip in RX:
+/// filler bits alternating like the preamble are added before the syncword, the last one being the inverse of the first syncword bit.
+/// `bits` holds the syncword MSB first (first `len_bits` bits are used).
+/// Return the syncword aligned on the MSB, as expected by [`set_fsk_syncword`](Lr1120::set_fsk_syncword), and the padded length in bits
+pub fn fsk_syncword_pad(bits: &[u8], len_bits: u8) -> Result<(u64, u8), Lr1120Error> {
+    if len_bits == 0 || len_bits > FSK_SYNCWORD_MAX_BITS {
+        return Err(Lr1120Error::InvalidParam);
+    }
+    let nb_bytes = (len_bits as usize).div_ceil(8);
+    let bytes = bits.get(..nb_bytes).ok_or(Lr1120Error::InvalidSize)?;
+    let raw = bytes.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64) << (64 - 8 * nb_bytes);
+    let sw = raw >> (64 - len_bits);
+    let padded_len = 8 * nb_bytes as u8;
+    let nb_fill = padded_len - len_bits;
+    // Filler ends with the inverse of the first syncword bit: 0b10 pattern repeated if the syncword starts with 1, 0b01 otherwise
+    let first = (sw >> (len_bits - 1)) & 1;
+    let pattern = if first == 1 {0xAAu64} else {0x55u64};
+    let filler = pattern & ((1 << nb_fill) - 1);
+    let padded = if nb_fill == 0 {sw} else {(filler << len_bits) | sw};
+    Ok((padded << (64 - padded_len), padded_len))
+}
+
+/// Fractional bits of the filtered RSSI
+const RSSI_FILTER_FRAC: u32 = 4;
+
+/// Host-side filter of the packet RSSI across the FSK packets received
+///
+/// The chip averages the RSSI over a single packet: at low bitrates the RX bandwidth is narrow and
+/// few packets are received, so the packet RSSI fluctuates from one packet to the next.
+/// No register configuring the averaging window of the chip is documented in the command specification,
+/// so the values returned by [`get_fsk_packet_status`](Lr1120::get_fsk_packet_status) cannot be tuned:
+/// this exponential moving average, updated by [`get_fsk_packet_status_filtered`](Lr1120::get_fsk_packet_status_filtered),
+/// is computed by the driver to give a stable value for link-quality decisions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FskRssiFilter {
+    /// Smoothing factor: each new packet has a weight of 1/2^n (0 disables the filtering, n clamped to 15)
+    pub smoothing: u8,
+    /// Filtered RSSI in 0.5dBm with RSSI_FILTER_FRAC fractional bits
+    avg: Option<i32>,
+}
+
+impl FskRssiFilter {
+    /// Filter with each new packet weighted 1/2^smoothing
+    pub const fn new(smoothing: u8) -> Self {
+        Self { smoothing, avg: None }
+    }
+
+    /// Default filter for a bitrate (bps): 1/8 below 10kbps, 1/4 below 100kbps, 1/2 above
+    pub const fn for_bitrate(bitrate: u32) -> Self {
+        let smoothing = if bitrate < 10_000 {3} else if bitrate < 100_000 {2} else {1};
+        Self::new(smoothing)
+    }
+
+    /// Add the RSSI of a packet status (ignored if no packet was received or the reception was aborted)
+    pub fn add(&mut self, status: &FskPacketStatusRsp) {
+        if !status.pkt_rcvd() || status.abort_err() {
+            return;
+        }
+        let sample = -(status.rssi_avg() as i32) << RSSI_FILTER_FRAC;
+        let shift = self.smoothing.min(15);
+        self.avg = Some(match self.avg {
+            Some(v) => v + ((sample - v) >> shift),
+            None => sample,
+        });
+    }
+
+    /// Filtered RSSI in dBm (None before the first packet)
+    pub fn rssi_dbm(&self) -> Option<i16> {
+        self.avg.map(|v| (v >> (RSSI_FILTER_FRAC + 1)) as i16)
+    }
+
+    /// Restart the filter, e.g. after a change of modulation or channel
+    pub fn reset(&mut self) {
+        self.avg = None;
+    }
+}
+
+impl Default for FskRssiFilter {
+    fn default() -> Self {
+        Self::for_bitrate(FSK_BITRATE_MAX)
+    }
+}
 
 impl<O,SPI, M> Lr1120<O,SPI, M> where
     O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
@@ -60,16 +273,35 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
 
     /// Set Modulation parameters: raw bitrate, pulse shaping, Bandwidth and fdev
     pub async fn set_fsk_modulation(&mut self, bitrate: u32, pulse_shape: PulseShape, rx_bw: RxBw, fdev: u32) -> Result<(), Lr1120Error> {
+        self.check_packet_type(PacketType::Gfsk).await?;
         let req = set_fsk_modulation_params_cmd(Precision::Basic, bitrate, pulse_shape, rx_bw, fdev);
-        self.cmd_wr(&req).await
+        self.cmd_wr(&req).await?;
+        #[cfg(feature = "shadow")] {self.shadow.fsk_modulation = Some(FskModulationParams::new(bitrate, fdev, pulse_shape, rx_bw));}
+        Ok(())
+    }
+
+    /// Set Modulation parameters after checking their consistency
+    pub async fn set_fsk_modulation_params(&mut self, params: &FskModulationParams) -> Result<(), Lr1120Error> {
+        params.validate()?;
+        self.set_fsk_modulation(params.bitrate, params.pulse_shape, params.rx_bw, params.fdev).await
     }
 
     // TODO: add dedicated struct and find a good default set of values
     #[allow(clippy::too_many_arguments)]
     /// Set packet parameters (preamble, length format, CRC, addressing, whitening)
     pub async fn set_fsk_packet(&mut self, pbl_len_tx: u16, pbl_len_detect: PblLenDetect, sw_len: u8, addr_comp: AddrComp, fsk_pkt_format: FskPktFormat, pld_len: u8, crc: Crc, dc_free: DcFree) -> Result<(), Lr1120Error> {
-        let req = set_fsk_packet_params_cmd(pbl_len_tx, pbl_len_detect, sw_len, addr_comp, fsk_pkt_format, pld_len, crc, dc_free);
-        self.cmd_wr(&req).await
+        let params = FskPacketParams{pbl_len_tx, pbl_len_detect, sw_len, addr_comp, fsk_pkt_format, pld_len, crc, dc_free};
+        self.set_fsk_packet_params(&params).await
+    }
+
+    /// Set packet parameters from a [`FskPacketParams`]
+    pub async fn set_fsk_packet_params(&mut self, params: &FskPacketParams) -> Result<(), Lr1120Error> {
+        self.check_packet_type(PacketType::Gfsk).await?;
+        let req = set_fsk_packet_params_cmd(params.pbl_len_tx, params.pbl_len_detect, params.sw_len, params.addr_comp, params.fsk_pkt_format, params.pld_len, params.crc, params.dc_free);
+        self.cmd_wr(&req).await?;
+        self.fsk_packet = Some(*params);
+        #[cfg(feature = "shadow")] {self.shadow.fsk_packet = Some(*params);}
+        Ok(())
     }
 
     /// Configure syncword
@@ -78,6 +310,20 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         self.cmd_wr(&req).await
     }
 
+    /// Configure a syncword of any length (1 to 64 bits) given MSB first in `bits`
+    /// The syncword is padded to a multiple of 8 bits (see [`fsk_syncword_pad`]) and the syncword length
+    /// of the packet parameters is updated accordingly: packet parameters must have been set before (InvalidConfig otherwise)
+    pub async fn set_fsk_syncword_bits(&mut self, bits: &[u8], len_bits: u8) -> Result<(), Lr1120Error> {
+        let (syncword, sw_len) = fsk_syncword_pad(bits, len_bits)?;
+        let mut params = self.fsk_packet.ok_or(Lr1120Error::InvalidConfig)?;
+        self.set_fsk_syncword(syncword).await?;
+        if params.sw_len != sw_len {
+            params.sw_len = sw_len;
+            self.set_fsk_packet_params(&params).await?;
+        }
+        Ok(())
+    }
+
     /// Read FSK packet status: RSSI, packet length, error source (address, CRC, length, ...)
     pub async fn get_fsk_packet_status(&mut self) -> Result<FskPacketStatusRsp, Lr1120Error> {
         let req = get_fsk_packet_status_req();
@@ -86,4 +332,11 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         Ok(rsp)
     }
 
+    /// Read FSK packet status and add its RSSI to a host-side filter (see [`FskRssiFilter`])
+    pub async fn get_fsk_packet_status_filtered(&mut self, filter: &mut FskRssiFilter) -> Result<FskPacketStatusRsp, Lr1120Error> {
+        let rsp = self.get_fsk_packet_status().await?;
+        filter.add(&rsp);
+        Ok(rsp)
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,412 @@
+//! # Firmware update files
+//!
+//! Semtech ships the LR11xx firmware updates as C header files (e.g. `lr1120_transceiver_0201.h`):
+//! ```c
+//! #define LR11XX_FIRMWARE_VERSION 0x0201
+//! #define LR11XX_FIRMWARE_UPDATE_TO LR1120_FIRMWARE_UPDATE_TO_TRX
+//! #define LR11XX_FIRMWARE_IMAGE_SIZE 61320
+//! const uint32_t lr11xx_firmware_image[] = { 0x..., 0x..., ... };
+//! ```
+//! The parser extracts the firmware version, the target chip ([`FwImageInfo`]) and the encrypted image
+//! as 32-bit words, ready to be written by the bootloader. The file can be given as a byte slice ([`FwImage`])
+//! or streamed from any `embedded_io_async::Read` source such as an external flash ([`FwImageReader`]),
+//! without loading it entirely in RAM. Comments are skipped and other definitions ignored.
+//!
+//! The image size (in 32-bit words) is checked against the number of words of the array when the file defines it.
+//! [`FwImageInfo::check_target`] refuses an image built for another chip variant, or for the LR1110 modem firmware
+//! which this driver does not support. The chip type must be read with [`get_version`](crate::Lr1120::get_version)
+//! before rebooting in the bootloader: the bootloader reports its own type.
+//!
+//! ## Example
+//! ```rust,no_run
+//! let image = FwImage::parse(include_bytes!("lr1120_transceiver_0201.h"))?;
+//! image.info().check_target(lr1120.get_version().await?.hw_type())?;
+//! for word in image.words() {
+//!     let word = word?;
+//!     // ...
+//! }
+//! ```
+//!
+//! ## Available Methods
+//! - [`FwImage::parse`] - Parse the header of a firmware file held in memory
+//! - [`FwImage::words`] - Iterate over the image words
+//! - [`FwImageReader::new`] - Parse the header of a firmware file streamed from a reader
+//! - [`FwImageReader::read_words`] - Read the next image words from the reader
+//! - [`FwImageInfo::check_target`] - Check that the image is a transceiver firmware for a chip type
+
+use embedded_io_async::Read;
+
+use super::Lr1120Error;
+use super::system::HwType;
+
+/// Maximum length of a token (identifier or number) in a firmware file
+const TOKEN_MAX: usize = 48;
+
+/// Read buffer of the streaming parser
+const READ_CHUNK: usize = 64;
+
+/// Firmware image description from the file header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FwImageInfo {
+    /// Firmware version (major in the MSB, minor in the LSB)
+    pub version: u16,
+    /// Chip the image is built for
+    pub hw_type: HwType,
+    /// Modem firmware (LR1110 LoRa Basics Modem) instead of the transceiver firmware
+    pub modem: bool,
+    /// Image size in 32-bit words (when defined by the file)
+    pub size: Option<u32>,
+}
+
+impl FwImageInfo {
+    /// Firmware version (major, minor)
+    pub fn version(&self) -> (u8, u8) {
+        ((self.version >> 8) as u8, self.version as u8)
+    }
+
+    /// Check that the image is a transceiver firmware for a chip type (as read with `get_version` before rebooting in the bootloader)
+    /// Return WrongHwType for another chip or a modem firmware, and InvalidParam if the chip type is the bootloader
+    pub fn check_target(&self, hw_type: HwType) -> Result<(), Lr1120Error> {
+        if hw_type == HwType::Bootloader {
+            return Err(Lr1120Error::InvalidParam);
+        }
+        if self.hw_type != hw_type || self.modem {
+            return Err(Lr1120Error::WrongHwType { image: self.hw_type, chip: hw_type });
+        }
+        Ok(())
+    }
+}
+
+/// Target of an update (value of `LR11XX_FIRMWARE_UPDATE_TO`): chip type and modem flag
+fn parse_target(value: &[u8]) -> Option<(HwType, bool)> {
+    let (chip, kind) = match value {
+        [b'L', b'R', b'1', b'1', n0, n1, b'_', b'F', b'I', b'R', b'M', b'W', b'A', b'R', b'E', b'_', rest @ ..] =>
+            ([*n0, *n1], rest.strip_prefix(b"UPDATE_TO_")?),
+        _ => return None,
+    };
+    let hw_type = match &chip {
+        b"10" => HwType::Lr1110,
+        b"20" => HwType::Lr1120,
+        b"21" => HwType::Lr1121,
+        _ => return None,
+    };
+    match kind {
+        b"TRX" => Some((hw_type, false)),
+        b"MODEM" => Some((hw_type, true)),
+        _ => None,
+    }
+}
+
+/// Parse a C integer literal (decimal or hexadecimal, with optional unsigned/long suffix)
+fn parse_int(token: &[u8]) -> Option<u32> {
+    let end = token.iter().rposition(|c| !matches!(c, b'u' | b'U' | b'l' | b'L'))?;
+    let digits = token.get(..=end)?;
+    let (digits, radix) = match digits {
+        [b'0', b'x' | b'X', hex @ ..] => (hex, 16),
+        _ => (digits, 10),
+    };
+    let s = core::str::from_utf8(digits).ok()?;
+    u32::from_str_radix(s, radix).ok()
+}
+
+/// Lexer state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Lex {
+    Code,
+    /// '/' seen, possible comment start
+    Slash,
+    LineComment,
+    BlockComment,
+    /// '*' seen in a block comment, possible comment end
+    BlockStar,
+}
+
+/// Parser state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    /// Header: definitions before the array
+    Header,
+    /// `#define` seen, waiting for the name
+    DefineName,
+    /// Waiting for the value of a definition
+    DefineValue(Define),
+    /// Inside the image array
+    Array,
+    /// Array closed
+    Done,
+}
+
+/// Definitions used by the parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Define {
+    Version,
+    Target,
+    Size,
+    Other,
+}
+
+/// Incremental parser of a firmware file
+#[derive(Debug, Clone)]
+struct Parser {
+    lex: Lex,
+    section: Section,
+    token: heapless::Vec<u8, TOKEN_MAX>,
+    version: Option<u16>,
+    target: Option<(HwType, bool)>,
+    size: Option<u32>,
+    nb_words: u32,
+}
+
+impl Parser {
+    fn new() -> Self {
+        Self {
+            lex: Lex::Code,
+            section: Section::Header,
+            token: heapless::Vec::new(),
+            version: None,
+            target: None,
+            size: None,
+            nb_words: 0,
+        }
+    }
+
+    /// Process one byte of the file, returning an image word when one is complete
+    fn push(&mut self, c: u8) -> Result<Option<u32>, Lr1120Error> {
+        match self.lex {
+            Lex::LineComment => {
+                if c == b'\n' {
+                    self.lex = Lex::Code;
+                }
+                return Ok(None);
+            }
+            Lex::BlockComment => {
+                if c == b'*' {
+                    self.lex = Lex::BlockStar;
+                }
+                return Ok(None);
+            }
+            Lex::BlockStar => {
+                self.lex = match c {
+                    b'/' => Lex::Code,
+                    b'*' => Lex::BlockStar,
+                    _ => Lex::BlockComment,
+                };
+                return Ok(None);
+            }
+            Lex::Slash => {
+                self.lex = match c {
+                    b'/' => Lex::LineComment,
+                    b'*' => Lex::BlockComment,
+                    _ => Lex::Code,
+                };
+                if self.lex != Lex::Code {
+                    return Ok(None);
+                }
+            }
+            Lex::Code => {}
+        }
+        if c.is_ascii_alphanumeric() || c == b'_' || c == b'#' {
+            return self.token.push(c).map(|_| None).map_err(|_| Lr1120Error::InvalidParam);
+        }
+        let word = self.end_token()?;
+        match c {
+            b'/' => self.lex = Lex::Slash,
+            b'{' if self.section == Section::Header => self.section = Section::Array,
+            b'}' if self.section == Section::Array => self.section = Section::Done,
+            // Definition without value
+            b'\n' if matches!(self.section, Section::DefineName | Section::DefineValue(_)) => self.section = Section::Header,
+            _ => {}
+        }
+        Ok(word)
+    }
+
+    /// Process the token completed by a delimiter
+    fn end_token(&mut self) -> Result<Option<u32>, Lr1120Error> {
+        if self.token.is_empty() {
+            return Ok(None);
+        }
+        let token = core::mem::take(&mut self.token);
+        match self.section {
+            Section::Header if token == b"#define" => self.section = Section::DefineName,
+            Section::Header | Section::Done => {}
+            Section::DefineName => {
+                let define = match token.as_slice() {
+                    b"LR11XX_FIRMWARE_VERSION" => Define::Version,
+                    b"LR11XX_FIRMWARE_UPDATE_TO" => Define::Target,
+                    b"LR11XX_FIRMWARE_IMAGE_SIZE" => Define::Size,
+                    _ => Define::Other,
+                };
+                self.section = Section::DefineValue(define);
+            }
+            Section::DefineValue(define) => {
+                match define {
+                    Define::Version => {
+                        let version = parse_int(&token).ok_or(Lr1120Error::InvalidParam)?;
+                        self.version = Some(u16::try_from(version).map_err(|_| Lr1120Error::InvalidParam)?);
+                    }
+                    Define::Target => self.target = Some(parse_target(&token).ok_or(Lr1120Error::InvalidParam)?),
+                    Define::Size => self.size = Some(parse_int(&token).ok_or(Lr1120Error::InvalidParam)?),
+                    Define::Other => {}
+                }
+                self.section = Section::Header;
+            }
+            Section::Array => {
+                let word = parse_int(&token).ok_or(Lr1120Error::InvalidParam)?;
+                self.nb_words += 1;
+                if self.size.is_some_and(|size| self.nb_words > size) {
+                    return Err(Lr1120Error::InvalidSize);
+                }
+                return Ok(Some(word));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Description of the image once the array is reached
+    /// Return InvalidParam if the version or target is missing
+    fn info(&self) -> Result<FwImageInfo, Lr1120Error> {
+        let (Some(version), Some((hw_type, modem))) = (self.version, self.target) else {
+            return Err(Lr1120Error::InvalidParam);
+        };
+        Ok(FwImageInfo { version, hw_type, modem, size: self.size })
+    }
+
+    /// Check the end of the image: array closed with the expected number of words
+    fn check_end(&self) -> Result<(), Lr1120Error> {
+        let complete = self.section == Section::Done && self.size.is_none_or(|size| size == self.nb_words);
+        if complete {Ok(())} else {Err(Lr1120Error::InvalidSize)}
+    }
+}
+
+/// Firmware file held in memory
+#[derive(Debug, Clone)]
+pub struct FwImage<'a> {
+    info: FwImageInfo,
+    /// Parser positioned at the start of the array
+    parser: Parser,
+    /// File content after the start of the array
+    body: &'a [u8],
+}
+
+impl<'a> FwImage<'a> {
+    /// Parse the header of a firmware file
+    /// Return InvalidParam if the file has no image array or does not define the version and target
+    pub fn parse(file: &'a [u8]) -> Result<Self, Lr1120Error> {
+        let mut parser = Parser::new();
+        for (idx, c) in file.iter().enumerate() {
+            parser.push(*c)?;
+            if parser.section == Section::Array {
+                let body = file.get(idx + 1..).unwrap_or_default();
+                return Ok(Self { info: parser.info()?, parser, body });
+            }
+        }
+        Err(Lr1120Error::InvalidParam)
+    }
+
+    /// Description of the image
+    pub fn info(&self) -> &FwImageInfo {
+        &self.info
+    }
+
+    /// Iterate over the image words
+    /// The last item is an error if the array is malformed, truncated or does not match the size defined
+    pub fn words(&self) -> FwImageWords<'a> {
+        FwImageWords { parser: self.parser.clone(), body: self.body.iter(), end: false }
+    }
+}
+
+/// Iterator over the words of a firmware image (see [`FwImage::words`])
+#[derive(Debug, Clone)]
+pub struct FwImageWords<'a> {
+    parser: Parser,
+    body: core::slice::Iter<'a, u8>,
+    end: bool,
+}
+
+impl Iterator for FwImageWords<'_> {
+    type Item = Result<u32, Lr1120Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.end {
+            return None;
+        }
+        while self.parser.section != Section::Done {
+            let Some(c) = self.body.next() else {
+                break;
+            };
+            match self.parser.push(*c) {
+                Ok(Some(word)) => return Some(Ok(word)),
+                Ok(None) => {}
+                Err(e) => {
+                    self.end = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        self.end = true;
+        self.parser.check_end().err().map(Err)
+    }
+}
+
+/// Firmware file streamed from a reader
+pub struct FwImageReader<R> {
+    reader: R,
+    info: FwImageInfo,
+    parser: Parser,
+    buf: [u8; READ_CHUNK],
+    /// Range of `buf` not yet parsed
+    pos: usize,
+    len: usize,
+}
+
+impl<R: Read> FwImageReader<R> {
+    /// Read and parse the header of a firmware file
+    /// Return InvalidParam if the file has no image array or does not define the version and target, and Io on reader error
+    pub async fn new(reader: R) -> Result<Self, Lr1120Error> {
+        let mut fw = Self { reader, info: FwImageInfo { version: 0, hw_type: HwType::Lr1120, modem: false, size: None }, parser: Parser::new(), buf: [0; READ_CHUNK], pos: 0, len: 0 };
+        while fw.parser.section != Section::Array {
+            let c = fw.next_byte().await?.ok_or(Lr1120Error::InvalidParam)?;
+            fw.parser.push(c)?;
+        }
+        fw.info = fw.parser.info()?;
+        Ok(fw)
+    }
+
+    /// Description of the image
+    pub fn info(&self) -> &FwImageInfo {
+        &self.info
+    }
+
+    /// Next byte of the file (None at end of file)
+    async fn next_byte(&mut self) -> Result<Option<u8>, Lr1120Error> {
+        if self.pos == self.len {
+            self.len = self.reader.read(&mut self.buf).await.map_err(|_| Lr1120Error::Io)?;
+            self.pos = 0;
+        }
+        let c = self.buf.get(self.pos).filter(|_| self.pos < self.len).copied();
+        self.pos += 1;
+        Ok(c)
+    }
+
+    /// Read the next image words into `words`, return the number of words read (0 once the image is complete)
+    /// Return InvalidParam on a malformed array, InvalidSize if it is truncated or does not match the size defined, and Io on reader error
+    pub async fn read_words(&mut self, words: &mut [u32]) -> Result<usize, Lr1120Error> {
+        let mut nb = 0;
+        while nb < words.len() && self.parser.section != Section::Done {
+            let Some(c) = self.next_byte().await? else {
+                break;
+            };
+            if let Some(word) = self.parser.push(c)? {
+                if let Some(w) = words.get_mut(nb) {
+                    *w = word;
+                }
+                nb += 1;
+            }
+        }
+        if nb < words.len() {
+            self.parser.check_end()?;
+        }
+        Ok(nb)
+    }
+}
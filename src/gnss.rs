@@ -6,20 +6,33 @@
 //!
 //! ### Configuration
 //! - [`gnss_set_constellation`](Lr1120::gnss_set_constellation) - Requires 32.768kHz clock for dual constellation. BUSY high until both scans complete.
+//! - [`set_gnss_dual_policy`](Lr1120::set_gnss_dual_policy) - Reject dual constellation or fall back to GPS only when the LF clock is the RC oscillator
+//! - [`gnss_constellation_fallback`](Lr1120::gnss_constellation_fallback) - True if the last constellation configuration fell back to GPS only
 //! - [`gnss_get_constellation`](Lr1120::gnss_get_constellation) - Reads selected constellation (GPS/BeiDou)
 //! - [`gnss_supported_constellation`](Lr1120::gnss_supported_constellation) - Reads supported constellation (GPS/BeiDou)
 //! - [`gnss_set_mode`](Lr1120::gnss_set_mode) - Configures GNSS scanning mode (single or multi)
+//! - [`gnss_update_mode`](Lr1120::gnss_update_mode) - Change the GNSS scanning mode between scans, only sending the command when the mode differs
+//! - [`gnss_apply_mode_advice`](Lr1120::gnss_apply_mode_advice) - Measure the battery and switch to the scanning mode recommended by [`GnssModeAdvice`]
 //! - [`gnss_set_assist_pos`](Lr1120::gnss_set_assist_pos) - Configures approximate position for GNSS assisted mode.
 //! - [`gnss_get_assist_pos`](Lr1120::gnss_get_assist_pos) - Reads approximate position used for GNSS assisted mode.
+//! - [`gnss_seed_position`](Lr1120::gnss_seed_position) - Seed the assisted scan with a [`CoarsePosition`] and set the delay to reset it from its accuracy
+//! - [`gnss_apply_solver_position`](Lr1120::gnss_apply_solver_position) - Use the filtered position of the last solver result as assistance position
 //! - [`gnss_set_delay_reset_assist`](Lr1120::gnss_set_delay_reset_assist) - Configures delay after which LR1120 resets Assistance Position and switches from assisted to cold start
 //! - [`gnss_get_delay_reset_assist`](Lr1120::gnss_get_delay_reset_assist) - Return reset delay configuration for assistance position
 //! - [`gnss_reset_assist`](Lr1120::gnss_reset_assist) - Reset Assist position
 //!
 //! ### Scan
 //! - [`gnss_scan`](Lr1120::gnss_scan) - Captures GNSS signals independant of assistance data availability
+//!   Use [`wait_ready_progress`](Lr1120::wait_ready_progress) to observe (or abort) the scan while BUSY is high.
+//! - [`gnss_scan_timed`](Lr1120::gnss_scan_timed) - Run a scan with a progress callback and return its [`GnssScanTiming`]
+//!   Scans (including time fetch and almanac update) fail with `InvalidConfig` when an RF switch configuration without GNSS switch was set.
 //! - [`gnss_get_result_size`](Lr1120::gnss_get_result_size) - Return result size in byte
+//! - [`gnss_read_results`](Lr1120::gnss_read_results) - Read result of last scan (NAV message)
+//! - [`gnss_read_results_into`](Lr1120::gnss_read_results_into) - Stream the result of last scan into an `embedded_io_async::Write` (e.g. uplink queue), whatever its size
 //! - [`gnss_get_nb_sv`](Lr1120::gnss_get_nb_sv) - Return number of satellite vehicles detected during last scan
+//! - [`gnss_get_sv_detected`](Lr1120::gnss_get_sv_detected) - Return ID, SNR and Doppler of satellite vehicles detected during last scan
 //! - [`gnss_get_nb_sv_filt`](Lr1120::gnss_get_nb_sv_filt) - Return number of satellite vehicles detected for a given time position and constellation
+//! - [`gnss_get_sv_visible_doppler`](Lr1120::gnss_get_sv_visible_doppler) - Return ID, predicted Doppler and Doppler error of the visible satellites (see [`GnssGetSvVisibleDopplerRsp::matches`] to cross-check scan results)
 //! - [`gnss_get_scan_type`](Lr1120::gnss_get_scan_type) - Returns type of scan launched during last scan.
 //! - [`gnss_get_doppler](Lr1120::gnss_get_doppler) - Reads Assistance Position calculated by 2D Solver
 //! - [`gnss_get_wn_rollover](Lr1120::gnss_get_wn_rollover) - Reads number of GPS time Week Number rollover (every 1024 weeks).
@@ -31,6 +44,10 @@
 //! - [`gnss_get_time`](Lr1120::gnss_get_time) - Return GPS Time
 //! - [`gnss_reset_time`](Lr1120::gnss_reset_time) - Reset GPS Time
 //! - [`gnss_set_time`](Lr1120::gnss_set_time) - Allows MCU host to set GPS Time
+//! - [`sync_rtc_from_gnss`](Lr1120::sync_rtc_from_gnss) - Fetch the time from satellites and return it with its uncertainty ([`GnssTimeSync`])
+//! - [`GpsTime`] - GPS time with conversion from/to week number, time of week and calendar date
+//! - [`AlmanacDate`] - Almanac date (days since GPS epoch) with conversion from/to calendar date
+//! - [`AlmanacHeader::almanac_age_days`] - Number of days elapsed since the almanac date
 //!
 //! ### Almanac
 //! - [`gnss_set_almanac_update`](Lr1120::gnss_set_almanac_update) - Enable Almanac update for constellation GPS/Beidou
@@ -40,23 +57,36 @@
 //! - [`gnss_updt_almanac_from_sat`](Lr1120::gnss_updt_almanac_from_sat) - Launches GNSS scan to download Almanac parameters from satellite signal (subframe 4/5) for one constellation.
 //! - [`gnss_set_almanac_updt_period`](Lr1120::gnss_set_almanac_updt_period) - Configures Almanac update period (days) after which application notified via GnssReadAlmanacStatus.
 //! - [`gnss_get_almanac_updt_period`](Lr1120::gnss_get_almanac_updt_period) - Read Almanac update period (days)
+//! - [`gnss_updt_almanac`](Lr1120::gnss_updt_almanac) - Write an almanac (header and satellite blocks) without verification
+//! - [`gnss_updt_almanac_checked`](Lr1120::gnss_updt_almanac_checked) - Write an almanac with a progress callback per command, then check the CRC computed by the chip against the header (`AlmanacCrc` error on mismatch)
+//! - [`gnss_check_almanac_crc`](Lr1120::gnss_check_almanac_crc) - Compare almanac CRC computed by the chip with an expected value (see [`almanac_crc`])
+//! - [`gnss_read_almanac_sv`](Lr1120::gnss_read_almanac_sv) - Read back the almanac record of one satellite
+//! - [`gnss_almanac_addr_size`](Lr1120::gnss_almanac_addr_size) - Return address and size of the almanac storage to read it with [`rd_mem`](Lr1120::rd_mem)
 //! - [`gnss_get_almanac_status`](Lr1120::gnss_get_almanac_status) - Returns detailed almanac update status for both GPS and BeiDou constellations including which satellites need update, next subframe timing, and activation status.
 //!
 //! ### Message
 //! - [`gnss_push_solver_msg`](Lr1120::gnss_push_solver_msg) Pushes messages from GNSS solver to LR1120 (e.g., assistance position update)
-//! - [`gnss_push_dm_msg`](Lr1120::gnss_push_dm_msg) Pushes messages from LoRaWAN network to LR1120
+//! - [`gnss_push_dm_msg`](Lr1120::gnss_push_dm_msg) Pushes messages from LoRaWAN network to LR1120 (one whole message of up to [`GNSS_PUSH_MSG_MAX`] bytes per call)
 //!
 //! ### Misc
 //! - [`gnss_get_version`](Lr1120::gnss_get_version) - Get the firmware and almanac version
-//! - [`gnss_get_consumption`](Lr1120::gnss_get_consumption) - Return result size in byte
+//! - [`gnss_capabilities`](Lr1120::gnss_capabilities) - Read GNSS capabilities and enable runtime check of firmware 02.01 methods
+//! - [`gnss_get_consumption`](Lr1120::gnss_get_consumption) - Return the duration of the radio capture and CPU processing of the last scan
+//! - [`gnss_get_scan_timing`](Lr1120::gnss_get_scan_timing) - Return the timing of the last scan as a [`GnssScanTiming`], with an energy estimate from a [`GnssPowerProfile`]
 //!
 
 
 use embedded_hal::digital::OutputPin;
 use embedded_hal_async::spi::SpiBus;
-use embassy_time::Duration;
+use embedded_io_async::Write;
+use embassy_time::{Duration, Instant};
 
-use super::{BusyPin, Lr1120, Lr1120Error};
+use super::{BusyPin, Lr1120, Lr1120Error, RSP_MAX_LEN};
+#[cfg(not(feature = "gnss_v1"))]
+use super::BusyProgress;
+use super::system::{HwType, LfClock, RfPath};
+use super::gps_time::{civil_from_days, gps_days_from_calendar, GPS_EPOCH_UNIX_DAYS};
+pub use super::gps_time::{GpsTime, SECS_PER_DAY, SECS_PER_WEEK};
 
 pub use crate::cmd::cmd_gnss::*;
 
@@ -88,8 +118,108 @@ impl GnssScanCfg {
     }
 }
 
+/// Default GPS satellites searched for almanac update: all satellites 1 to 32
+pub const GPS_SAT_MASK_DEFAULT: u32 = 0xFFFF_FFFF;
+/// Default BeiDou satellites 1 to 32 searched for almanac update: all except 15, 17, 18 and 31
+pub const BEIDOU_SAT_MASK0_DEFAULT: u32 = 0xBFFC_BFFF;
+/// Default BeiDou satellites 33 to 63 searched for almanac update: 33 to 43, 59 and 60
+/// The value is 28-bit wide: bit 31 (satellite 64) does not exist
+pub const BEIDOU_SAT_MASK1_DEFAULT: u32 = 0x0C00_07FF;
+/// Highest BeiDou satellite identifier
+pub const BEIDOU_SAT_MAX: u8 = 63;
+
+const _ : () = assert!(GPS_SAT_MASK_DEFAULT.count_ones() == 32);
+const _ : () = assert!(BEIDOU_SAT_MASK0_DEFAULT.count_ones() == 28);
+const _ : () = assert!(BEIDOU_SAT_MASK1_DEFAULT.count_ones() == 13);
+
+/// Build a GPS satellite mask from a list of satellite identifiers (1 to 32)
+/// Return InvalidParam for an identifier out of range
+pub fn gps_sat_mask(svs: &[u8]) -> Result<u32, Lr1120Error> {
+    svs.iter().try_fold(0u32, |mask, &sv| match sv {
+        1..=32 => Ok(mask | 1 << (sv - 1)),
+        _ => Err(Lr1120Error::InvalidParam),
+    })
+}
+
+/// Build the two BeiDou satellite masks (satellites 1-32 and 33-63) from a list of satellite identifiers (1 to 63)
+/// Return InvalidParam for an identifier out of range
+pub fn beidou_sat_mask(svs: &[u8]) -> Result<(u32, u32), Lr1120Error> {
+    svs.iter().try_fold((0u32, 0u32), |(m0, m1), &sv| match sv {
+        1..=32 => Ok((m0 | 1 << (sv - 1), m1)),
+        33..=BEIDOU_SAT_MAX => Ok((m0, m1 | 1 << (sv - 33))),
+        _ => Err(Lr1120Error::InvalidParam),
+    })
+}
+
+/// Almanac date: number of days elapsed since the GPS epoch (1980/01/06)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlmanacDate(pub u16);
+
+impl AlmanacDate {
+    /// Create almanac date from a calendar date
+    /// Return None if the date is before the GPS epoch or after 2159/06/11
+    pub fn from_calendar(year: u16, month: u8, day: u8) -> Option<Self> {
+        let days = gps_days_from_calendar(year, month, day)?;
+        u16::try_from(days).ok().map(AlmanacDate)
+    }
+
+    /// Calendar date (year, month, day)
+    pub fn to_calendar(&self) -> (u16, u8, u8) {
+        civil_from_days(self.0 as i64 + GPS_EPOCH_UNIX_DAYS)
+    }
+
+    /// GPS time at midnight of the almanac date
+    /// Return None after 2116/02/07 (32 bits overflow)
+    pub fn to_gps_time(&self) -> Option<GpsTime> {
+        (self.0 as u32).checked_mul(SECS_PER_DAY).map(GpsTime)
+    }
+
+    /// Number of days elapsed between the almanac date and a GPS time (0 if the date is in the future)
+    pub fn age_days(&self, now: GpsTime) -> u32 {
+        now.days().saturating_sub(self.0 as u32)
+    }
+}
+
+impl From<GpsTime> for AlmanacDate {
+    /// Convert a GPS time into the almanac date of the same day
+    /// The date saturates at 0xFFFF (2159/06/11)
+    fn from(time: GpsTime) -> Self {
+        AlmanacDate(time.days().min(u16::MAX as u32) as u16)
+    }
+}
+
+impl GnssReadTimeRsp {
+    /// GPS time as [`GpsTime`]
+    pub fn time(&self) -> GpsTime {
+        GpsTime(self.gps_time())
+    }
+}
+
+/// GPS time read from the chip, with its uncertainty and the local instant it was read
+/// Allows to discipline a host RTC
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GnssTimeSync {
+    /// GPS time
+    pub time: GpsTime,
+    /// Time accuracy reported by the chip
+    pub accuracy: Duration,
+    /// Local instant when the time was read
+    pub at: Instant,
+}
+
+impl GnssTimeSync {
+    /// GPS time at a later local instant (rounded down to the second)
+    pub fn time_at(&self, now: Instant) -> GpsTime {
+        let elapsed = now.checked_duration_since(self.at).unwrap_or(Duration::from_ticks(0)).as_secs();
+        GpsTime(self.time.0.saturating_add(elapsed.min(u32::MAX as u64) as u32))
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 /// Almanac header used for update
+/// The date is the number of days since the GPS epoch (see [`AlmanacDate`])
 pub struct AlmanacHeader {
     pub date: u16,
     pub crc: u32,
@@ -101,6 +231,17 @@ impl AlmanacHeader {
         AlmanacHeader { date, crc }
     }
 
+    /// Almanac date
+    pub fn almanac_date(&self) -> AlmanacDate {
+        AlmanacDate(self.date)
+    }
+
+    /// Number of days elapsed since the almanac date
+    /// Allows to decide when an almanac update is needed
+    pub fn almanac_age_days(&self, now: GpsTime) -> u32 {
+        self.almanac_date().age_days(now)
+    }
+
     /// Copy struct in buffer of bytes
     /// Must be 20 byte long to get the whole structure
     pub fn to_bytes(&self, buffer: &mut [u8]) {
@@ -134,18 +275,471 @@ impl AlmanacSv {
     /// Copy struct in buffer of bytes
     /// Must be 20 byte long to get the whole structure
     pub fn to_bytes(&self, buffer: &mut [u8]) {
-        for (i,b) in buffer.iter_mut().take(20).enumerate() {
-            match i {
-                0 => *b = self.sv_id,
-                ci if (1..=15).contains(&ci) => *b = self.content[ci],
-                16 => *b = (self.ca_code>>8) as u8,
-                17 => *b = (self.ca_code&0xFF) as u8,
-                18 => *b = self.modulation,
-                19 => *b = self.const_id,
-                _ => *b = 0,
+        let bytes = [self.sv_id].into_iter()
+            .chain(self.content)
+            .chain(self.ca_code.to_be_bytes())
+            .chain([self.modulation, self.const_id]);
+        for (b, v) in buffer.iter_mut().zip(bytes) {
+            *b = v;
+        }
+    }
+
+    /// Parse a 20-byte block as written by `gnss_updt_almanac` (e.g. received in a downlink)
+    pub fn from_bytes(bytes: &[u8; ALMANAC_SV_SIZE]) -> Self {
+        let [sv_id, content @ .., ca_h, ca_l, modulation, const_id] = *bytes;
+        Self {
+            sv_id,
+            content,
+            ca_code: u16::from_be_bytes([ca_h, ca_l]),
+            modulation,
+            const_id,
+        }
+    }
+}
+
+/// Size of one satellite almanac block
+pub const ALMANAC_SV_SIZE : usize = 20;
+
+/// Maximum length of an SPI transaction of the GNSS engine (opcode included)
+pub const GNSS_SPI_TRANSACTION_MAX : usize = 512;
+
+/// Maximum length of a message pushed with [`gnss_push_solver_msg`](Lr1120::gnss_push_solver_msg) or [`gnss_push_dm_msg`](Lr1120::gnss_push_dm_msg)
+pub const GNSS_PUSH_MSG_MAX : usize = GNSS_SPI_TRANSACTION_MAX - 2;
+
+/// Maximum number of SV blocks per almanac update command
+#[cfg(not(feature = "gnss_v1"))]
+const ALMANAC_SV_PER_CMD : usize = (GNSS_SPI_TRANSACTION_MAX - 2) / ALMANAC_SV_SIZE;
+
+/// Opcode of the manual almanac update (header and SV blocks)
+#[cfg(not(feature = "gnss_v1"))]
+const ALMANAC_UPDT_OPCODE: [u8; 2] = [0x04, 0x0E];
+
+/// Check the length of a message pushed to the GNSS engine
+fn check_push_msg(msg: &[u8]) -> Result<(), Lr1120Error> {
+    if msg.is_empty() || msg.len() > GNSS_PUSH_MSG_MAX {
+        return Err(Lr1120Error::InvalidSize);
+    }
+    Ok(())
+}
+
+/// Size of one satellite almanac record read from the chip
+pub const ALMANAC_SV_READ_SIZE : usize = 22;
+
+/// Almanac record of one satellite read from the chip
+/// The record read is longer than the one written (see [`AlmanacSv`]) and is kept raw
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlmanacSvRecord(pub [u8; ALMANAC_SV_READ_SIZE]);
+
+impl AlmanacSvRecord {
+    /// Satellite identifier
+    pub fn sv_id(&self) -> u8 {
+        self.0[0]
+    }
+
+    /// Raw record
+    pub fn bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// CRC-32 (IEEE 802.3, reflected polynomial 0xEDB88320) used to check almanac images
+#[derive(Debug, Clone, Copy)]
+pub struct AlmanacCrc(u32);
+
+impl Default for AlmanacCrc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AlmanacCrc {
+    /// Start a new CRC computation
+    pub fn new() -> Self {
+        Self(0xFFFFFFFF)
+    }
+
+    /// Update CRC with some bytes
+    pub fn update(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u32;
+            for _ in 0..8 {
+                let mask = (self.0 & 1).wrapping_neg();
+                self.0 = (self.0 >> 1) ^ (0xEDB88320 & mask);
             }
         }
     }
+
+    /// Return final CRC value
+    pub fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+/// Progress of a manual almanac update (see [`gnss_updt_almanac_checked`](Lr1120::gnss_updt_almanac_checked))
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AlmanacProgress {
+    /// Number of satellite blocks written
+    pub written: usize,
+    /// Total number of satellite blocks of the update
+    pub total: usize,
+}
+
+/// Compute the CRC of an almanac image on the host, i.e. over all SV blocks as written by `gnss_updt_almanac`
+/// Allows to check a downloaded almanac before writing it to the chip
+pub fn almanac_crc(sv_list: &[AlmanacSv]) -> u32 {
+    let mut crc = AlmanacCrc::new();
+    let mut bytes = [0u8; ALMANAC_SV_SIZE];
+    for sv in sv_list {
+        sv.to_bytes(&mut bytes);
+        crc.update(&bytes);
+    }
+    crc.finish()
+}
+
+/// Settings of the scan mode recommendation
+/// Multi mode (advanced scanning) improves accuracy at the cost of a longer scan and more energy:
+/// it is only recommended when the battery allows it and the last accuracy does not reach the target.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GnssModeAdvice {
+    /// Accuracy target, in the unit of the doppler solver accuracy
+    pub target_accuracy: u16,
+    /// Minimum battery voltage (mV) to allow the multi mode
+    pub min_vbat_mv: u16,
+}
+
+impl GnssModeAdvice {
+    /// Create the recommendation settings
+    pub fn new(target_accuracy: u16, min_vbat_mv: u16) -> Self {
+        Self { target_accuracy, min_vbat_mv }
+    }
+
+    /// Recommend a scan mode from the battery voltage (mV) and the accuracy of the last solver result
+    /// Multi mode is recommended when the battery is above the threshold and the last accuracy
+    /// is unknown (no fix yet) or worse than the target
+    pub fn recommend(&self, vbat_mv: u16, last_accuracy: Option<u16>) -> GnssScanMode {
+        let accurate = last_accuracy.is_some_and(|a| a <= self.target_accuracy);
+        if vbat_mv < self.min_vbat_mv || accurate {
+            GnssScanMode::Single
+        } else {
+            GnssScanMode::Multi
+        }
+    }
+}
+
+/// Behavior when dual constellation is requested without the accurate 32.768kHz LF clock (XTAL or external)
+/// With the RC oscillator the BeiDou scan following the GPS scan is not reliable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DualConstellationPolicy {
+    /// Return InvalidConfig
+    Reject,
+    /// Configure GPS only and raise the fallback flag
+    GpsOnly,
+}
+
+/// Timing of the last GNSS scan
+/// The chip reports the cumulated duration of the radio capture and of the CPU processing of all constellations scanned:
+/// the time spent between captures (e.g. delay between the GPS and BeiDou scans) is only known from the host
+/// measurement of the BUSY period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GnssScanTiming {
+    /// Radio capture duration
+    pub radio: Duration,
+    /// CPU processing duration
+    pub computation: Duration,
+    /// Duration of the BUSY period measured by the host (None when not measured)
+    pub busy: Option<Duration>,
+}
+
+impl GnssScanTiming {
+    /// Create the scan timing from the chip consumption report and the BUSY duration measured by the host
+    pub fn new(rsp: &GnssGetConsumptionRsp, busy: Option<Duration>) -> Self {
+        Self {
+            radio: Duration::from_micros(rsp.radio_ms() as u64),
+            computation: Duration::from_micros(rsp.computation_ms() as u64),
+            busy,
+        }
+    }
+
+    /// Time spent in capture and processing
+    pub fn active(&self) -> Duration {
+        self.radio + self.computation
+    }
+
+    /// Time of the BUSY period spent neither in capture nor in processing (waiting between captures)
+    pub fn idle(&self) -> Option<Duration> {
+        self.busy.map(|b| b.checked_sub(self.active()).unwrap_or(Duration::from_ticks(0)))
+    }
+
+    /// Estimate the energy (µJ) of the scan with the currents of a power profile
+    pub fn energy_uj(&self, profile: &GnssPowerProfile) -> u64 {
+        let charge_pc = self.radio.as_micros() * profile.radio_ua as u64
+            + self.computation.as_micros() * profile.computation_ua as u64
+            + self.idle().map_or(0, |d| d.as_micros()) * profile.idle_ua as u64;
+        // pC * mV = 1e-15 J
+        charge_pc * profile.vbat_mv as u64 / 1_000_000_000
+    }
+}
+
+/// Supply voltage and currents of the scan phases, to estimate the energy of a scan
+/// Values depend on the board (DC-DC or LDO, TCXO, LNA): they should be measured for each design
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GnssPowerProfile {
+    /// Supply voltage (mV)
+    pub vbat_mv: u16,
+    /// Current during the radio capture (µA)
+    pub radio_ua: u32,
+    /// Current during the CPU processing (µA)
+    pub computation_ua: u32,
+    /// Current between captures (µA)
+    pub idle_ua: u32,
+}
+
+/// Encode an angle in micro-degrees with a full scale `scale_udeg` on 12 bits (rounded to nearest)
+fn assist_angle(udeg: i32, scale_udeg: i64) -> u16 {
+    let v = udeg as i64 * 2048;
+    let half = if v < 0 {-scale_udeg / 2} else {scale_udeg / 2};
+    ((v + half) / scale_udeg).clamp(-2048, 2047) as i16 as u16
+}
+
+/// Encode a latitude in micro-degrees to the assistance position format (degrees * 2048/90)
+pub fn assist_latitude(udeg: i32) -> u16 {
+    assist_angle(udeg, 90_000_000)
+}
+
+/// Encode a longitude in micro-degrees to the assistance position format (degrees * 2048/180)
+pub fn assist_longitude(udeg: i32) -> u16 {
+    assist_angle(udeg, 180_000_000)
+}
+
+/// Maximum delay (s) before the assistance position reset (24-bit value)
+pub const DELAY_RESET_AP_MAX_S: u32 = 0xFF_FFFF;
+
+/// Coarse position used to seed the assisted scan, e.g. from a WiFi or cell geolocation resolved by a server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CoarsePosition {
+    /// Latitude in micro-degrees
+    pub latitude: i32,
+    /// Longitude in micro-degrees
+    pub longitude: i32,
+    /// Accuracy radius in meters
+    pub accuracy_m: u32,
+}
+
+impl CoarsePosition {
+    /// Create a coarse position from latitude/longitude in micro-degrees and an accuracy radius in meters
+    pub fn new(latitude: i32, longitude: i32, accuracy_m: u32) -> Self {
+        Self { latitude, longitude, accuracy_m }
+    }
+
+    /// Latitude and longitude in the assistance position format
+    pub fn to_assist(&self) -> (u16, u16) {
+        (assist_latitude(self.latitude), assist_longitude(self.longitude))
+    }
+}
+
+/// Filtered position of the doppler solver applied as assistance position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SolverPosition {
+    /// Latitude, in the format of the assistance position
+    pub latitude: u16,
+    /// Longitude, in the format of the assistance position
+    pub longitude: u16,
+    /// Accuracy estimate of the filtered position
+    pub accuracy: u16,
+    /// Number of dopplers used by the solver
+    pub nb_sv: u8,
+}
+
+/// Settings of the assistance position seeding
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GnssSeedCfg {
+    /// Maximum position error (m) for the assisted scan to remain useful
+    pub max_error_m: u32,
+    /// Maximum speed of the device (m/s), used to estimate how long the position stays valid
+    pub speed_mps: u32,
+}
+
+impl Default for GnssSeedCfg {
+    /// 100km maximum error for a device moving at up to 30m/s
+    fn default() -> Self {
+        Self { max_error_m: 100_000, speed_mps: 30 }
+    }
+}
+
+impl GnssSeedCfg {
+    /// Duration (s) during which a position stays within the maximum error,
+    /// or None if its accuracy is already worse than the maximum error
+    pub fn validity_s(&self, pos: &CoarsePosition) -> Option<u32> {
+        let margin = self.max_error_m.checked_sub(pos.accuracy_m)?;
+        Some(margin.checked_div(self.speed_mps).unwrap_or(u32::MAX))
+    }
+}
+
+/// Constellation (and BeiDou satellite type) of an almanac update period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AlmanacConstellation {
+    /// GPS satellites
+    Gps,
+    /// BeiDou satellites of the given type (MEO or IGSO)
+    Beidou(BeidouType),
+}
+
+impl AlmanacConstellation {
+    /// Default almanac update period in days (GPS 31 days, BeiDou MEO 60 days, BeiDou IGSO 30 days)
+    pub fn default_period(&self) -> u16 {
+        match self {
+            AlmanacConstellation::Gps => 31,
+            AlmanacConstellation::Beidou(BeidouType::Meo) => 60,
+            AlmanacConstellation::Beidou(BeidouType::Igso) => 30,
+        }
+    }
+
+    /// Command fields: GPS enable, BeiDou enable and BeiDou type (ignored for GPS)
+    #[cfg(not(feature = "gnss_v1"))]
+    fn fields(&self) -> (bool, bool, BeidouType) {
+        match *self {
+            AlmanacConstellation::Gps => (true, false, BeidouType::Meo),
+            AlmanacConstellation::Beidou(t) => (false, true, t),
+        }
+    }
+}
+
+/// GNSS capabilities based on the chip type and firmware versions
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GnssCapabilities {
+    /// Chip type
+    pub hw_type: HwType,
+    /// Chip firmware version (major, minor)
+    pub fw_version: (u8,u8),
+    /// GNSS firmware version
+    pub gnss_version: u8,
+    /// Almanac version
+    pub almanac_version: u8,
+}
+
+impl GnssCapabilities {
+    /// True if the chip supports GNSS scanning at all (not the case for LR1121 or when running the bootloader)
+    pub fn has_gnss(&self) -> bool {
+        matches!(self.hw_type, HwType::Lr1110 | HwType::Lr1120)
+    }
+
+    /// True if the firmware supports the new GNSS API (firmware 02.01 on LR1120, 04.01 on LR1110):
+    /// GnssScan, time/almanac update from satellites, warm start, ...
+    pub fn has_v2_api(&self) -> bool {
+        match self.hw_type {
+            HwType::Lr1110 => self.fw_version >= (4,1),
+            HwType::Lr1120 => self.fw_version >= (2,1),
+            _ => false,
+        }
+    }
+}
+
+/// Offset of BeiDou satellites in the SV ID space
+const SV_ID_BEIDOU_OFFSET : u8 = 64;
+
+/// Satellite identifier with its constellation and PRN number (starting at 1)
+/// The chip encodes SV ID as 0-63 for GPS and 64-127 for BeiDou.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SvId {
+    Gps(u8),
+    Beidou(u8),
+}
+
+impl SvId {
+    /// Return the PRN number of the satellite
+    pub fn prn(&self) -> u8 {
+        match self {
+            SvId::Gps(prn) |
+            SvId::Beidou(prn) => *prn,
+        }
+    }
+
+    /// Return the raw SV ID as used by the chip
+    pub fn raw(&self) -> u8 {
+        match self {
+            SvId::Gps(prn) => prn.saturating_sub(1),
+            SvId::Beidou(prn) => prn.saturating_sub(1) + SV_ID_BEIDOU_OFFSET,
+        }
+    }
+}
+
+impl From<u8> for SvId {
+    fn from(value: u8) -> Self {
+        if value >= SV_ID_BEIDOU_OFFSET {
+            SvId::Beidou(value - SV_ID_BEIDOU_OFFSET + 1)
+        } else {
+            SvId::Gps(value + 1)
+        }
+    }
+}
+
+impl From<SvId> for u8 {
+    fn from(value: SvId) -> Self {
+        value.raw()
+    }
+}
+
+impl core::fmt::Display for SvId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SvId::Gps(prn) => write!(f, "G{prn:02}"),
+            SvId::Beidou(prn) => write!(f, "B{prn:02}"),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for SvId {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            SvId::Gps(prn) => defmt::write!(f, "G{=u8:02}", prn),
+            SvId::Beidou(prn) => defmt::write!(f, "B{=u8:02}", prn),
+        }
+    }
+}
+
+impl GnssGetSvDetectedRsp {
+    /// Satellite identifier with constellation
+    pub fn sv(&self) -> SvId {
+        self.sv_id().into()
+    }
+}
+
+/// Parse the response of GnssGetSvDetected
+/// A trailing partial entry yields an `InvalidSize` error
+pub fn gnss_parse_sv_detected(bytes: &[u8]) -> impl Iterator<Item=Result<GnssGetSvDetectedRsp, Lr1120Error>> + '_ {
+    bytes.chunks(GnssGetSvDetectedRsp::RSP_LEN).map(GnssGetSvDetectedRsp::try_from_slice)
+}
+
+impl GnssGetSvVisibleDopplerRsp {
+    /// Satellite identifier with constellation
+    pub fn sv(&self) -> SvId {
+        self.sv_id().into()
+    }
+
+    /// Check a satellite detected during a scan against this prediction:
+    /// true if it is the same satellite and its Doppler is within the predicted error
+    pub fn matches(&self, detected: &GnssGetSvDetectedRsp) -> bool {
+        let diff = (detected.doppler() as i32 - self.doppler() as i32).unsigned_abs();
+        detected.sv_id() == self.sv_id() && diff <= self.doppler_error() as u32
+    }
+}
+
+/// Parse the response of GnssGetSvVisibleDoppler
+/// A trailing partial entry yields an `InvalidSize` error
+pub fn gnss_parse_sv_visible_doppler(bytes: &[u8]) -> impl Iterator<Item=Result<GnssGetSvVisibleDopplerRsp, Lr1120Error>> + '_ {
+    bytes.chunks(GnssGetSvVisibleDopplerRsp::RSP_LEN).map(GnssGetSvVisibleDopplerRsp::try_from_slice)
 }
 
 
@@ -156,9 +750,30 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// Configures GNSS scanning for selected constellation (GPS/BeiDou).
     /// If both selected, GPS scans first, then BeiDou after delay (4s fixed for FW ≤01.02, variable 1s steps for FW 02.01+).
     /// Requires 32.768kHz clock for dual constellation. BUSY high until both scans complete.
+    /// When the LF clock is known to be the RC oscillator (set by [`set_lf_clk`](Lr1120::set_lf_clk) or after reset),
+    /// dual constellation returns InvalidConfig, or falls back to GPS only with [`DualConstellationPolicy::GpsOnly`]
+    /// (see [`gnss_constellation_fallback`](Lr1120::gnss_constellation_fallback)).
     pub async fn gnss_set_constellation(&mut self, gps: bool, beidou: bool) -> Result<(), Lr1120Error> {
-        let req = gnss_set_constellation_to_use_cmd(gps, beidou);
-        self.cmd_wr(&req).await
+        let lf_rc = self.lf_clk == Some(LfClock::Rc);
+        let fallback = gps && beidou && lf_rc;
+        if fallback && self.gnss_dual_policy == DualConstellationPolicy::Reject {
+            return Err(Lr1120Error::InvalidConfig);
+        }
+        let req = gnss_set_constellation_to_use_cmd(gps, beidou && !fallback);
+        self.cmd_wr(&req).await?;
+        self.gnss_fallback = fallback;
+        Ok(())
+    }
+
+    /// Select the behavior of [`gnss_set_constellation`](Lr1120::gnss_set_constellation)
+    /// when dual constellation is requested while the LF clock is the RC oscillator
+    pub fn set_gnss_dual_policy(&mut self, policy: DualConstellationPolicy) {
+        self.gnss_dual_policy = policy;
+    }
+
+    /// True if the last constellation configuration fell back to GPS only because of the LF clock
+    pub fn gnss_constellation_fallback(&self) -> bool {
+        self.gnss_fallback
     }
 
     /// Reads selected constellation (GPS/BeiDou)
@@ -180,7 +795,30 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// Configures GNSS scanning mode (single or multi)
     pub async fn gnss_set_mode(&mut self, mode: GnssScanMode) -> Result<(), Lr1120Error> {
         let req = gnss_set_mode_cmd(mode);
-        self.cmd_wr(&req).await
+        self.gnss_mode = None;
+        self.cmd_wr(&req).await?;
+        self.gnss_mode = Some(mode);
+        Ok(())
+    }
+
+    /// Change the GNSS scanning mode between two scans
+    /// The command is only sent when the mode differs from the last one configured (or is unknown, e.g. after a reset).
+    /// Use [`gnss_set_mode`](Lr1120::gnss_set_mode) to force it, e.g. after a sleep without retention.
+    pub async fn gnss_update_mode(&mut self, mode: GnssScanMode) -> Result<(), Lr1120Error> {
+        if self.gnss_mode == Some(mode) {
+            return Ok(());
+        }
+        self.gnss_set_mode(mode).await
+    }
+
+    /// Measure the battery voltage and switch to the scanning mode recommended from it
+    /// and from the accuracy of the last solver result (None if no position was computed yet)
+    /// Return the mode selected
+    pub async fn gnss_apply_mode_advice(&mut self, advice: &GnssModeAdvice, last_accuracy: Option<u16>) -> Result<GnssScanMode, Lr1120Error> {
+        let vbat_mv = self.get_vbat_mv().await?;
+        let mode = advice.recommend(vbat_mv, last_accuracy);
+        self.gnss_update_mode(mode).await?;
+        Ok(mode)
     }
 
     /// Captures GNSS signals in autonomous mode (no assistance info available)
@@ -189,6 +827,8 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     #[cfg(feature = "gnss_v1")]
     #[doc(cfg(feature = "gnss_v1"))]
     pub async fn gnss_autonomous(&mut self, time: u32, cfg: GnssScanCfg) -> Result<(), Lr1120Error> {
+        self.check_rf_switch(RfPath::Gnss)?;
+        self.check_no_rx_duty_cycle()?;
         let req = gnss_autonomous_cmd(time, cfg.best_effort, cfg.pseudo_range, cfg.doppler_info, cfg.bit_changes, cfg.max_sv);
         self.cmd_wr(&req).await
     }
@@ -199,6 +839,8 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     #[cfg(feature = "gnss_v1")]
     #[doc(cfg(feature = "gnss_v1"))]
     pub async fn gnss_assisted(&mut self, time: u32, cfg: GnssScanCfg) -> Result<(), Lr1120Error> {
+        self.check_rf_switch(RfPath::Gnss)?;
+        self.check_no_rx_duty_cycle()?;
         let req = gnss_assisted_cmd(time, cfg.best_effort, cfg.pseudo_range, cfg.doppler_info, cfg.bit_changes, cfg.max_sv);
         self.cmd_wr(&req).await
     }
@@ -208,6 +850,9 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// Use sleep with retention to preserve assistance data
     #[cfg(not(feature = "gnss_v1"))]
     pub async fn gnss_scan(&mut self, cfg: GnssScanCfg) -> Result<(), Lr1120Error> {
+        self.gnss_check_v2()?;
+        self.check_rf_switch(RfPath::Gnss)?;
+        self.check_no_rx_duty_cycle()?;
         let req = gnss_scan_cmd(cfg.best_effort, cfg.pseudo_range, cfg.doppler_info, cfg.bit_changes, cfg.max_sv);
         self.cmd_wr(&req).await
     }
@@ -218,6 +863,41 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         self.cmd_wr(&req).await
     }
 
+    /// Seed the assisted scan with a coarse position (e.g. WiFi or cell geolocation sent by downlink)
+    /// The assistance position is set, which restarts the assistance position age, and the delay after which
+    /// the chip drops it (falling back to cold start) is set to the duration the position remains valid
+    /// (from its accuracy and the maximum speed, limited to the 24-bit range of the command).
+    /// Return InvalidParam when the accuracy is worse than the maximum error of the configuration.
+    pub async fn gnss_seed_position(&mut self, pos: &CoarsePosition, cfg: &GnssSeedCfg) -> Result<(), Lr1120Error> {
+        let validity = cfg.validity_s(pos).ok_or(Lr1120Error::InvalidParam)?;
+        let (latitude, longitude) = pos.to_assist();
+        self.gnss_set_assist_pos(latitude, longitude).await?;
+        #[cfg(not(feature = "gnss_v1"))]
+        self.gnss_set_delay_reset_assist(Duration::from_secs(validity.min(DELAY_RESET_AP_MAX_S) as u64)).await?;
+        #[cfg(feature = "gnss_v1")]
+        let _ = validity;
+        Ok(())
+    }
+
+    /// Read the last doppler solver result and, when the solver succeeded with a filtered accuracy
+    /// not worse than `max_accuracy` (in the unit of the solver accuracy), use the filtered position as assistance position.
+    /// Return the position applied, or None when the result was rejected
+    #[cfg(not(feature = "gnss_v1"))]
+    pub async fn gnss_apply_solver_position(&mut self, max_accuracy: u16) -> Result<Option<SolverPosition>, Lr1120Error> {
+        let rsp = self.gnss_get_doppler().await?;
+        if rsp.solver_error() != SolverError::None || rsp.filtered_accuracy() > max_accuracy {
+            return Ok(None);
+        }
+        let pos = SolverPosition {
+            latitude: rsp.filtered_latitude(),
+            longitude: rsp.filtered_longitude(),
+            accuracy: rsp.filtered_accuracy(),
+            nb_sv: rsp.nb_sv_used(),
+        };
+        self.gnss_set_assist_pos(pos.latitude, pos.longitude).await?;
+        Ok(Some(pos))
+    }
+
     /// Reads approximate position used for GNSS assisted mode.
     pub async fn gnss_get_assist_pos(&mut self) -> Result<GnssReadAssistancePositionRsp, Lr1120Error> {
         let req = gnss_read_assistance_position_req();
@@ -228,12 +908,38 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
 
     /// Reads GNSS context status including firmware version, almanac CRC, error codes, and frequency search space
     pub async fn gnss_get_context_status(&mut self) -> Result<GnssGetContextStatusRsp, Lr1120Error> {
-        let req = gnss_get_consumption_req();
+        let req = gnss_get_context_status_req();
         let mut rsp = GnssGetContextStatusRsp::new();
         self.cmd_rd(&req, rsp.as_mut()).await?;
         Ok(rsp)
     }
 
+    /// Read chip and GNSS firmware versions to determine GNSS capabilities
+    /// The result is kept in the driver: methods requiring firmware 02.01 then return Unsupported early on older firmware
+    pub async fn gnss_capabilities(&mut self) -> Result<GnssCapabilities, Lr1120Error> {
+        let version = self.get_version().await?;
+        let hw_type = version.hw_type();
+        let fw_version = (version.major(), version.minor());
+        let mut caps = GnssCapabilities{hw_type, fw_version, gnss_version: 0, almanac_version: 0};
+        if caps.has_gnss() {
+            let gnss_version = self.gnss_get_version().await?;
+            caps.gnss_version = gnss_version.firmware_version();
+            caps.almanac_version = gnss_version.almanac_version();
+        }
+        self.gnss_caps = Some(caps);
+        Ok(caps)
+    }
+
+    /// Check that the firmware supports the GNSS API introduced in firmware 02.01
+    /// Always succeed if capabilities were never read
+    #[cfg(not(feature = "gnss_v1"))]
+    fn gnss_check_v2(&self) -> Result<(), Lr1120Error> {
+        match self.gnss_caps {
+            Some(caps) if !caps.has_v2_api() => Err(Lr1120Error::Unsupported),
+            _ => Ok(()),
+        }
+    }
+
     /// Get the firmware and almanac version
     pub async fn gnss_get_version(&mut self) -> Result<GnssReadVersionRsp, Lr1120Error> {
         let req = gnss_read_version_req();
@@ -260,18 +966,57 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     pub async fn gnss_get_result_size(&mut self) -> Result<u16, Lr1120Error> {
         let req = gnss_get_result_size_req();
         let mut rsp = GnssGetResultSizeRsp::new();
-        self.cmd_rd(&req, rsp.as_mut()).await?;
+        self.with_spi_retry(async |lr| lr.cmd_rd(&req, rsp.as_mut()).await).await?;
         Ok(rsp.result_size())
     }
 
+    /// Read the result of the last scan (NAV message for the solver)
+    /// First byte indicates the destination of the message (host, solver or device management)
+    pub async fn gnss_read_results(&mut self) -> Result<&[u8], Lr1120Error> {
+        let rsp_len = self.gnss_get_result_size().await? as usize;
+        if rsp_len > RSP_MAX_LEN {
+            return Err(Lr1120Error::InvalidSize);
+        }
+        let req = gnss_read_results_cmd();
+        self.with_spi_retry(async |lr| {
+            lr.cmd_wr(&req).await?;
+            lr.wait_ready(lr.timeouts.rsp).await?;
+            lr.rsp_rd(rsp_len).await
+        }).await?;
+        self.rsp_data(rsp_len)
+    }
+
+    /// Stream the result of the last scan (NAV message) into a writer by chunks, without the size limit of the driver buffer
+    /// Return the number of bytes written, and Io on writer error (the message is then incomplete)
+    pub async fn gnss_read_results_into<W: Write>(&mut self, writer: &mut W) -> Result<usize, Lr1120Error> {
+        let rsp_len = self.gnss_get_result_size().await? as usize;
+        let req = gnss_read_results_cmd();
+        self.cmd_wr(&req).await?;
+        self.wait_ready(self.timeouts.rsp).await?;
+        self.rsp_rd_into(rsp_len, writer).await?;
+        writer.flush().await.map_err(|_| Lr1120Error::Io)?;
+        Ok(rsp_len)
+    }
+
     /// Return number of satellite vehicles detected during last scan
     pub async fn gnss_get_nb_sv(&mut self) -> Result<u8, Lr1120Error> {
         let req = gnss_get_nb_sv_detected_req();
         let mut rsp = GnssGetNbSvDetectedRsp::new();
-        self.cmd_rd(&req, rsp.as_mut()).await?;
+        self.with_spi_retry(async |lr| lr.cmd_rd(&req, rsp.as_mut()).await).await?;
         Ok(rsp.nb_sv())
     }
 
+    /// Return ID, SNR and Doppler of satellite vehicles detected during last scan
+    /// Must call gnss_get_nb_sv first to know how many satellites are available
+    pub async fn gnss_get_sv_detected(&mut self, nb_sv: u8) -> Result<impl Iterator<Item=Result<GnssGetSvDetectedRsp, Lr1120Error>>, Lr1120Error> {
+        let req = gnss_get_sv_detected_req();
+        self.cmd_wr(&req).await?;
+        self.wait_ready(self.timeouts.rsp).await?;
+        let rsp_len = nb_sv as usize * GnssGetSvDetectedRsp::RSP_LEN;
+        self.rsp_rd(rsp_len).await?;
+        Ok(gnss_parse_sv_detected(self.buffer().get(..rsp_len).unwrap_or_default()))
+    }
+
     /// Return number of satellite vehicles detected for a given time position and constellation
     pub async fn gnss_get_nb_sv_filt(&mut self, time: u32, latitude: u16, longitude: u16, gps: bool, beidou: bool) -> Result<u8, Lr1120Error> {
         let req = gnss_get_sv_visible_req(time, latitude, longitude, gps, beidou);
@@ -280,45 +1025,71 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         Ok(rsp.nb_sv_visible())
     }
 
+    /// Return ID, predicted Doppler and Doppler error of the satellites visible
+    /// for the time, position and constellation given in the last [`gnss_get_nb_sv_filt`](Lr1120::gnss_get_nb_sv_filt),
+    /// which must be called first to know how many satellites are available
+    pub async fn gnss_get_sv_visible_doppler(&mut self, nb_sv: u8) -> Result<impl Iterator<Item=Result<GnssGetSvVisibleDopplerRsp, Lr1120Error>>, Lr1120Error> {
+        let req = gnss_get_sv_visible_doppler_req();
+        self.cmd_wr(&req).await?;
+        self.wait_ready(self.timeouts.rsp).await?;
+        let rsp_len = nb_sv as usize * GnssGetSvVisibleDopplerRsp::RSP_LEN;
+        self.rsp_rd(rsp_len).await?;
+        Ok(gnss_parse_sv_visible_doppler(self.buffer().get(..rsp_len).unwrap_or_default()))
+    }
+
 
     /// Configures delay after which LR1120 resets Assistance Position and switches from assisted to cold start
+    /// The delay is rounded up to the second: return InvalidParam above [`DELAY_RESET_AP_MAX_S`] seconds
     #[cfg(not(feature = "gnss_v1"))]
-    pub async fn gnss_set_delay_reset_assist(&mut self, delay: u32) -> Result<(), Lr1120Error> {
-        let req = gnss_config_delay_reset_ap_cmd(delay);
+    pub async fn gnss_set_delay_reset_assist(&mut self, delay: Duration) -> Result<(), Lr1120Error> {
+        self.gnss_check_v2()?;
+        let secs = delay.as_micros().div_ceil(1_000_000);
+        if secs > DELAY_RESET_AP_MAX_S as u64 {
+            return Err(Lr1120Error::InvalidParam);
+        }
+        let req = gnss_config_delay_reset_ap_cmd(secs as u32);
         self.cmd_wr(&req).await
     }
 
     /// Return reset delay configuration for assistance position
     #[cfg(not(feature = "gnss_v1"))]
-    pub async fn gnss_get_delay_reset_assist(&mut self) -> Result<u32, Lr1120Error> {
+    pub async fn gnss_get_delay_reset_assist(&mut self) -> Result<Duration, Lr1120Error> {
+        self.gnss_check_v2()?;
         let req = gnss_read_delay_reset_ap_req();
         let mut rsp = GnssReadDelayResetAPRsp::new();
         self.cmd_rd(&req, rsp.as_mut()).await?;
-        Ok(rsp.delay())
+        Ok(Duration::from_secs(rsp.delay() as u64))
     }
 
     /// Reset Assist position
     #[cfg(not(feature = "gnss_v1"))]
     pub async fn gnss_reset_assist(&mut self) -> Result<(), Lr1120Error> {
+        self.gnss_check_v2()?;
         let req = gnss_reset_position_cmd();
         self.cmd_wr(&req).await
     }
 
 
     /// Pushes messages from GNSS solver to LR1120 (e.g., assistance position update)
+    /// A message is forwarded in a single transaction: it cannot be split since its format does not define
+    /// any continuation. Return InvalidSize for an empty message or one longer than [`GNSS_PUSH_MSG_MAX`]
     pub async fn gnss_push_solver_msg(&mut self, msg: &[u8]) -> Result<(), Lr1120Error> {
+        check_push_msg(msg)?;
         let req = gnss_push_solver_msg_cmd();
         self.cmd_data_wr(&req, msg).await
     }
 
     /// Pushes messages from LoRaWAN network to LR1120
+    /// Same constraints as [`gnss_push_solver_msg`](Lr1120::gnss_push_solver_msg): one whole message per call, up to [`GNSS_PUSH_MSG_MAX`] bytes
     #[cfg(not(feature = "gnss_v1"))]
     pub async fn gnss_push_dm_msg(&mut self, msg: &[u8]) -> Result<(), Lr1120Error> {
+        self.gnss_check_v2()?;
+        check_push_msg(msg)?;
         let req = gnss_push_dm_msg_cmd();
         self.cmd_data_wr(&req, msg).await
     }
 
-    /// Return result size in byte
+    /// Return the duration (in µs) of the radio capture and CPU processing of the last scan
     pub async fn gnss_get_consumption(&mut self) -> Result<GnssGetConsumptionRsp, Lr1120Error> {
         let req = gnss_get_consumption_req();
         let mut rsp = GnssGetConsumptionRsp::new();
@@ -326,9 +1097,29 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         Ok(rsp)
     }
 
+    /// Return the timing of the last scan, with the BUSY duration measured by the host if available
+    pub async fn gnss_get_scan_timing(&mut self, busy: Option<Duration>) -> Result<GnssScanTiming, Lr1120Error> {
+        let rsp = self.gnss_get_consumption().await?;
+        Ok(GnssScanTiming::new(&rsp, busy))
+    }
+
+    /// Run a scan, wait for its end while calling `on_progress` every `period` (see [`wait_ready_progress`](Lr1120::wait_ready_progress))
+    /// and return its timing, including the BUSY duration
+    #[cfg(not(feature = "gnss_v1"))]
+    pub async fn gnss_scan_timed<F>(&mut self, cfg: GnssScanCfg, timeout: Duration, period: Duration, on_progress: F) -> Result<GnssScanTiming, Lr1120Error>
+        where F: FnMut(Duration) -> BusyProgress
+    {
+        self.gnss_scan(cfg).await?;
+        let start = Instant::now();
+        self.wait_ready_progress(timeout, period, on_progress).await?;
+        let busy = start.elapsed();
+        self.gnss_get_scan_timing(Some(busy)).await
+    }
+
     /// Returns type of scan launched during last scan.
     #[cfg(not(feature = "gnss_v1"))]
     pub async fn gnss_get_scan_type(&mut self) -> Result<GnssScanType, Lr1120Error> {
+        self.gnss_check_v2()?;
         let req = gnss_read_last_scan_mode_launched_req();
         let mut rsp = GnssReadLastScanModeLaunchedRsp::new();
         self.cmd_rd(&req, rsp.as_mut()).await?;
@@ -338,6 +1129,9 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// Determine time by demodulating satellite signals
     #[cfg(not(feature = "gnss_v1"))]
     pub async fn gnss_fetch_time(&mut self, best_effort: bool, mode: FetchTimeMode) -> Result<(), Lr1120Error> {
+        self.gnss_check_v2()?;
+        self.check_rf_switch(RfPath::Gnss)?;
+        self.check_no_rx_duty_cycle()?;
         let req = gnss_fetch_time_cmd(best_effort, mode);
         self.cmd_wr(&req).await
     }
@@ -345,15 +1139,37 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// Return GPS Time
     #[cfg(not(feature = "gnss_v1"))]
     pub async fn gnss_get_time(&mut self) -> Result<GnssReadTimeRsp, Lr1120Error> {
+        self.gnss_check_v2()?;
         let req = gnss_read_time_req();
         let mut rsp = GnssReadTimeRsp::new();
         self.cmd_rd(&req, rsp.as_mut()).await?;
         Ok(rsp)
     }
 
+    /// Fetch the time from satellites and return it with its accuracy, to discipline the host RTC
+    /// The week number is fetched too when the chip does not know the time yet.
+    /// Wait up to `max_wait` for the end of the fetch and return None if no time could be demodulated
+    #[cfg(not(feature = "gnss_v1"))]
+    pub async fn sync_rtc_from_gnss(&mut self, max_wait: Duration) -> Result<Option<GnssTimeSync>, Lr1120Error> {
+        let mode = if self.gnss_get_time().await?.gps_time() == 0 {FetchTimeMode::TowWn} else {FetchTimeMode::TowOnly};
+        self.gnss_fetch_time(false, mode).await?;
+        self.wait_ready(max_wait).await?;
+        let rsp = self.gnss_get_time().await?;
+        let at = Instant::now();
+        if rsp.gps_time() == 0 {
+            return Ok(None);
+        }
+        Ok(Some(GnssTimeSync {
+            time: rsp.time(),
+            accuracy: Duration::from_millis(rsp.accuracy() as u64),
+            at,
+        }))
+    }
+
     /// Reset GPS Time
     #[cfg(not(feature = "gnss_v1"))]
     pub async fn gnss_reset_time(&mut self) -> Result<(), Lr1120Error> {
+        self.gnss_check_v2()?;
         let req = gnss_reset_time_cmd();
         self.cmd_wr(&req).await
     }
@@ -361,6 +1177,7 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// Allows MCU host to set GPS Time
     #[cfg(not(feature = "gnss_v1"))]
     pub async fn gnss_set_time(&mut self, time: u32, accuracy: u16) -> Result<(), Lr1120Error> {
+        self.gnss_check_v2()?;
         let req = gnss_set_time_cmd(time, accuracy);
         self.cmd_wr(&req).await
     }
@@ -368,6 +1185,7 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// Reads Assistance Position calculated by 2D Solver
     #[cfg(not(feature = "gnss_v1"))]
     pub async fn gnss_get_doppler(&mut self) -> Result<GnssReadDopplerSolverResRsp, Lr1120Error> {
+        self.gnss_check_v2()?;
         let req = gnss_read_doppler_solver_res_req();
         let mut rsp = GnssReadDopplerSolverResRsp::new();
         self.cmd_rd(&req, rsp.as_mut()).await?;
@@ -378,6 +1196,7 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// In 2025, value is 4. Can be changed by GnssSetTime, GnssFetchTime, or GnssScan.
     #[cfg(not(feature = "gnss_v1"))]
     pub async fn gnss_get_wn_rollover(&mut self) -> Result<GnssReadWNRolloverRsp, Lr1120Error> {
+        self.gnss_check_v2()?;
         let req = gnss_read_wn_rollover_req();
         let mut rsp = GnssReadWNRolloverRsp::new();
         self.cmd_rd(&req, rsp.as_mut()).await?;
@@ -387,6 +1206,7 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// Reads number of visible satellites and time elapsed since last update of detected satellite list for this constellation.
     #[cfg(not(feature = "gnss_v1"))]
     pub async fn gnss_get_warm_start_status(&mut self, gps: bool, beidou: bool) -> Result<GnssReadWarmStartStatusRsp, Lr1120Error> {
+        self.gnss_check_v2()?;
         let req = gnss_read_warm_start_status_req(gps, beidou);
         let mut rsp = GnssReadWarmStartStatusRsp::new();
         self.cmd_rd(&req, rsp.as_mut()).await?;
@@ -396,83 +1216,158 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// Returns list of satellites ID for next keep sync scan.
     /// Must call GnssReadWarmStartStatus first to know how many satellites in list (1 byte per satellites).
     #[cfg(not(feature = "gnss_v1"))]
-    pub async fn gnss_get_warm_start_sv(&mut self, gps: bool, beidou: bool, nb_sv: u8) -> Result<&[u8], Lr1120Error> {
+    pub async fn gnss_get_warm_start_sv(&mut self, gps: bool, beidou: bool, nb_sv: u8) -> Result<impl Iterator<Item=SvId>, Lr1120Error> {
+        self.gnss_check_v2()?;
         let req = gnss_get_sv_warm_start_req(gps, beidou);
         self.cmd_wr(&req).await?;
-        self.wait_ready(Duration::from_millis(1)).await?;
+        self.wait_ready(self.timeouts.rsp).await?;
         let rsp_len = nb_sv as usize;
         self.rsp_rd(rsp_len).await?;
-        Ok(&self.buffer()[..rsp_len])
+        Ok(self.rsp_data(rsp_len)?.iter().map(|&id| SvId::from(id)))
     }
 
     /// Configures LR1120 to search for Almanacs for each GPS satellite enabled by the mask
-    /// If mask is none, use default value (0xFFFFFFFF)
+    /// If mask is none, use default value ([`GPS_SAT_MASK_DEFAULT`]). See [`gps_sat_mask`] to build a mask.
     #[cfg(not(feature = "gnss_v1"))]
     pub async fn gnss_set_gps_sat_bitmask(&mut self, mask: Option<u32>) -> Result<(), Lr1120Error> {
-        let mask = mask.unwrap_or(0xFFFFFFFF);
+        self.gnss_check_v2()?;
+        let mask = mask.unwrap_or(GPS_SAT_MASK_DEFAULT);
         let req = gnss_write_bit_mask_sat_activated_cmd(true, false, mask);
         self.cmd_wr(&req).await
     }
 
-    /// Configures LR1120 to search for Almanacs for each Beidou satellite enabled by the masks
-    /// If mask is none, use default value (0xBFFCBFFF, 0xC0007FF)
+    /// Configures LR1120 to search for Almanacs for each Beidou satellite enabled by the masks (satellites 1-32 and 33-63)
+    /// If mask is none, use default value ([`BEIDOU_SAT_MASK0_DEFAULT`], [`BEIDOU_SAT_MASK1_DEFAULT`]). See [`beidou_sat_mask`] to build the masks.
+    /// Return InvalidParam if the second mask enables a satellite above 63
     #[cfg(not(feature = "gnss_v1"))]
     pub async fn gnss_set_beidou_sat_bitmask(&mut self, mask: Option<(u32,u32)>) -> Result<(), Lr1120Error> {
-        let (mask0,mask1) = mask.unwrap_or((0xBFFCBFFF, 0xC0007FF));
-        let req = gnss_write_bit_mask_sat_activated_adv_cmd(true, false, mask0, mask1);
+        self.gnss_check_v2()?;
+        let (mask0,mask1) = mask.unwrap_or((BEIDOU_SAT_MASK0_DEFAULT, BEIDOU_SAT_MASK1_DEFAULT));
+        if mask1 >> (BEIDOU_SAT_MAX - 32) != 0 {
+            return Err(Lr1120Error::InvalidParam);
+        }
+        let req = gnss_write_bit_mask_sat_activated_adv_cmd(false, true, mask0, mask1);
         self.cmd_wr(&req).await
     }
 
     /// Launches GNSS scan to download Almanac parameters from satellite signal (subframe 4/5) for one constellation.
     /// Must be sent at precise time matching Almanac data availability - use GnssReadAlmanacStatus.
     /// Default: Almanac in RAM, written to flash when >6 satellites available or >half almanacs to update available.
+    /// Return LowBattery when the battery is below the low battery threshold (see [`set_flash_guard`](Lr1120::set_flash_guard))
     #[cfg(not(feature = "gnss_v1"))]
     pub async fn gnss_updt_almanac_from_sat(&mut self, best_effort: bool, gps: bool) -> Result<(), Lr1120Error> {
+        self.gnss_check_v2()?;
+        self.check_rf_switch(RfPath::Gnss)?;
+        self.check_no_rx_duty_cycle()?;
+        self.check_flash_write().await?;
         let req = gnss_almanac_update_from_sat_cmd(best_effort, gps, !gps);
         self.cmd_wr(&req).await
     }
 
     /// Manually update the almanac
+    /// Return LowBattery when the battery is below the low battery threshold (see [`set_flash_guard`](Lr1120::set_flash_guard))
     #[cfg(not(feature = "gnss_v1"))]
     pub async fn gnss_updt_almanac(&mut self, hdr: AlmanacHeader, sv_list: &[AlmanacSv]) -> Result<(), Lr1120Error> {
-        let buffer = self.buffer.as_mut();
-        buffer[0] = 0x04;
-        buffer[1] = 0x0E;
-        // Send Header
-        hdr.to_bytes(&mut buffer[2..22]);
-        self.cmd_buf_wr(22).await?;
-        // Send SV params
-        for sv_chunks in sv_list.chunks(25) {
-            let buffer = self.buffer.as_mut();
-            buffer[0] = 0x04;
-            buffer[1] = 0x0E;
-            let mut offset = 2;
-            for sv in sv_chunks {
-                let offset_next = offset + 20;
-                sv.to_bytes(&mut buffer[offset..offset_next]);
-                offset = offset_next;
-            }
-            self.cmd_buf_wr(offset).await?;
+        self.check_flash_write().await?;
+        self.gnss_updt_almanac_hdr(&hdr).await?;
+        self.gnss_updt_almanac_sv(sv_list.iter().cloned(), |_| {}).await
+    }
+
+    /// Manually update the almanac, reporting the progress after each command,
+    /// then check the CRC computed by the chip against the CRC of the header
+    /// Return AlmanacCrc on mismatch, or LowBattery when the battery is below the low battery threshold
+    #[cfg(not(feature = "gnss_v1"))]
+    pub async fn gnss_updt_almanac_checked<F>(&mut self, hdr: AlmanacHeader, sv_list: &[AlmanacSv], mut on_progress: F) -> Result<(), Lr1120Error>
+        where F: FnMut(AlmanacProgress)
+    {
+        self.check_flash_write().await?;
+        self.gnss_updt_almanac_hdr(&hdr).await?;
+        let total = sv_list.len();
+        let mut written = 0;
+        self.gnss_updt_almanac_sv(sv_list.iter().cloned(), |nb| {
+            written += nb;
+            on_progress(AlmanacProgress { written, total });
+        }).await?;
+        let actual = self.gnss_get_context_status().await?.global_almanac_crc();
+        if actual != hdr.crc {
+            return Err(Lr1120Error::AlmanacCrc { expected: hdr.crc, actual });
         }
         Ok(())
     }
 
+    /// Send the header of a manual almanac update
+    #[cfg(not(feature = "gnss_v1"))]
+    pub(crate) async fn gnss_updt_almanac_hdr(&mut self, hdr: &AlmanacHeader) -> Result<(), Lr1120Error> {
+        self.gnss_check_v2()?;
+        let (opcode, data) = self.buffer.as_mut().split_at_mut(2);
+        opcode.copy_from_slice(&ALMANAC_UPDT_OPCODE);
+        hdr.to_bytes(data);
+        self.cmd_buf_wr(22).await
+    }
+
+    /// Send SV blocks of a manual almanac update, up to 25 per command (512-byte transaction)
+    /// `on_chunk` is called with the number of blocks sent by each command
+    #[cfg(not(feature = "gnss_v1"))]
+    pub(crate) async fn gnss_updt_almanac_sv(&mut self, sv_list: impl Iterator<Item=AlmanacSv>, mut on_chunk: impl FnMut(usize)) -> Result<(), Lr1120Error> {
+        self.gnss_check_v2()?;
+        let mut sv_list = sv_list.peekable();
+        while sv_list.peek().is_some() {
+            let (opcode, data) = self.buffer.as_mut().split_at_mut(2);
+            opcode.copy_from_slice(&ALMANAC_UPDT_OPCODE);
+            let nb = data.chunks_exact_mut(ALMANAC_SV_SIZE)
+                .take(ALMANAC_SV_PER_CMD)
+                .zip(sv_list.by_ref())
+                .map(|(dst, sv)| sv.to_bytes(dst))
+                .count();
+            self.cmd_buf_wr(2 + ALMANAC_SV_SIZE * nb).await?;
+            on_chunk(nb);
+        }
+        Ok(())
+    }
+
+    /// Compare the almanac CRC computed by the chip with an expected value (e.g. from [`almanac_crc`])
+    /// Return true when the almanac stored in the chip matches
+    pub async fn gnss_check_almanac_crc(&mut self, expected: u32) -> Result<bool, Lr1120Error> {
+        let status = self.gnss_get_context_status().await?;
+        Ok(status.global_almanac_crc() == expected)
+    }
+
+    /// Read back the almanac record of one satellite
+    /// Allows to verify the almanac content or cache it on the host
+    pub async fn gnss_read_almanac_sv(&mut self, sv_id: u8) -> Result<AlmanacSvRecord, Lr1120Error> {
+        let req = gnss_read_almanac_per_satellite_req(sv_id, 1);
+        self.cmd_wr(&req).await?;
+        self.wait_ready(self.timeouts.rsp).await?;
+        self.rsp_rd(ALMANAC_SV_READ_SIZE).await?;
+        let record = self.rsp_data(ALMANAC_SV_READ_SIZE)?.try_into().map_err(|_| Lr1120Error::InvalidSize)?;
+        Ok(AlmanacSvRecord(record))
+    }
+
+    /// Return the start address and size (in bytes) of the almanac storage
+    /// The raw almanac can then be read with [`rd_mem`](Lr1120::rd_mem)
+    pub async fn gnss_almanac_addr_size(&mut self) -> Result<(u32, u16), Lr1120Error> {
+        let req = gnss_almanac_read_addr_size_req();
+        let mut rsp = GnssAlmanacReadAddrSizeRsp::new();
+        self.cmd_rd(&req, rsp.as_mut()).await?;
+        Ok((rsp.addr(), rsp.size()))
+    }
+
     /// Configures Almanac update period (days) after which application notified via GnssReadAlmanacStatus.
-    /// If beidou_type is none, constellation is GPS.
+    /// The period is set for GPS or for one type of BeiDou satellites (see [`AlmanacConstellation::default_period`])
     #[cfg(not(feature = "gnss_v1"))]
-    pub async fn gnss_set_almanac_updt_period(&mut self, beidou_type: Option<BeidouType>, period: u16) -> Result<(), Lr1120Error> {
-        let beidou_en = beidou_type.is_some();
-        let beidou_type = beidou_type.unwrap_or(BeidouType::Meo);
-        let req = gnss_config_almanac_update_period_cmd(!beidou_en, beidou_en, beidou_type, period);
+    pub async fn gnss_set_almanac_updt_period(&mut self, constellation: AlmanacConstellation, period: u16) -> Result<(), Lr1120Error> {
+        self.gnss_check_v2()?;
+        let (gps, beidou, beidou_type) = constellation.fields();
+        let req = gnss_config_almanac_update_period_cmd(gps, beidou, beidou_type, period);
         self.cmd_wr(&req).await
     }
 
-    /// Read Almanac update period (days)
+    /// Read Almanac update period (days) of GPS or of one type of BeiDou satellites
     #[cfg(not(feature = "gnss_v1"))]
-    pub async fn gnss_get_almanac_updt_period(&mut self, beidou_type: Option<BeidouType>) -> Result<u16, Lr1120Error> {
-        let beidou_en = beidou_type.is_some();
-        let beidou_type = beidou_type.unwrap_or(BeidouType::Meo);
-        let req = gnss_read_almanac_update_period_req(!beidou_en, beidou_en, beidou_type);
+    pub async fn gnss_get_almanac_updt_period(&mut self, constellation: AlmanacConstellation) -> Result<u16, Lr1120Error> {
+        self.gnss_check_v2()?;
+        let (gps, beidou, beidou_type) = constellation.fields();
+        let req = gnss_read_almanac_update_period_req(gps, beidou, beidou_type);
         let mut rsp = GnssReadAlmanacUpdatePeriodRsp::new();
         self.cmd_rd(&req, rsp.as_mut()).await?;
         Ok(rsp.period())
@@ -482,6 +1377,7 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// Updated when SV almanac demodulated and stored in retention memory or flash.
     #[cfg(not(feature = "gnss_v1"))]
     pub async fn gnss_get_almanac_status(&mut self) -> Result<GnssReadAlmanacStatusRsp, Lr1120Error> {
+        self.gnss_check_v2()?;
         let req = gnss_read_almanac_status_req();
         let mut rsp = GnssReadAlmanacStatusRsp::new();
         self.cmd_rd(&req, rsp.as_mut()).await?;
@@ -0,0 +1,335 @@
+//! # Continuous GNSS tracking
+//!
+//! [`GnssTracker`] is a small state machine running GNSS scans periodically and publishing
+//! the results (NAV message, position from the doppler solver, time synchronisation, almanac update)
+//! on an `embassy-sync` channel.
+//!
+//! The scan type (KeepSync or Assisted) is selected by the chip from its context:
+//! the tracker uses a short period when satellites are still tracked (KeepSync possible)
+//! and a longer one otherwise (Assisted scan), to limit the energy spent without a fix.
+//! When the chip reports that almanac data will soon be broadcast, an almanac update from satellite
+//! is scheduled opportunistically.
+//! When a low battery threshold is configured, the battery voltage is checked before each step.
+//! With a scan mode advice configured, the scan mode (single or multi) is selected before each scan
+//! from the battery voltage and the accuracy of the last position (see [`GnssModeAdvice`]).
+//!
+//! ## Example
+//! ```rust,no_run
+//! static GNSS_EVENTS: Channel<CriticalSectionRawMutex, GnssEvent, 4> = Channel::new();
+//!
+//! #[embassy_executor::task]
+//! async fn gnss_task(mut lr1120: MyLr1120) {
+//!     let mut tracker = GnssTracker::new(GnssTrackerCfg::default());
+//!     tracker.run(&mut lr1120, GNSS_EVENTS.sender()).await
+//! }
+//! ```
+//!
+//! ## Available Methods
+//! - [`new`](GnssTracker::new) - Create a tracker, starting by a time fetch
+//! - [`state`](GnssTracker::state) - Current state of the tracker
+//! - [`last_timing`](GnssTracker::last_timing) - Timing of the last scan
+//! - [`step`](GnssTracker::step) - Run one step of the state machine and return the delay before the next one
+//! - [`run`](GnssTracker::run) - Run the tracker forever
+//! - [`run_with_motion`](GnssTracker::run_with_motion) - Run the tracker forever with a scan period driven by motion events (see [`motion`](crate::motion))
+
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embassy_sync::channel::Sender;
+use embassy_time::{Duration, Instant, Timer};
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiBus;
+
+use super::motion::{MotionHook, ScanCadence};
+use super::gnss::{AlmanacStatus, FetchTimeMode, GnssModeAdvice, GnssScanCfg, GnssScanTiming, GnssScanType, GpsTime, SolverError};
+use super::payload::ScanTimestamp;
+use super::{latency, BusyPin, Lr1120, Lr1120Error};
+
+/// Maximum size of a NAV message forwarded by the tracker
+pub const GNSS_NAV_MAX_LEN: usize = 256;
+
+/// Delay between the almanac update command and the start of the subframe broadcast
+const ALMANAC_LEAD: Duration = Duration::from_secs(2);
+
+/// GNSS tracker configuration
+#[derive(Debug, Clone)]
+pub struct GnssTrackerCfg {
+    /// Scan configuration
+    pub scan: GnssScanCfg,
+    /// Scan period when satellites are still tracked (KeepSync)
+    pub keep_sync_period: Duration,
+    /// Scan period without satellites tracked (Assisted), also used after an error
+    pub assisted_period: Duration,
+    /// Maximum duration of a scan or time fetch
+    pub scan_timeout: Duration,
+    /// Enable almanac update from satellites
+    pub almanac_update: bool,
+    /// Maximum delay to wait for almanac data to be broadcast
+    pub almanac_window: Duration,
+    /// Select the scan mode before each scan (None to keep the mode configured by the application)
+    pub mode_advice: Option<GnssModeAdvice>,
+}
+
+impl Default for GnssTrackerCfg {
+    fn default() -> Self {
+        Self {
+            scan: GnssScanCfg::new(false),
+            keep_sync_period: Duration::from_secs(60),
+            assisted_period: Duration::from_secs(600),
+            scan_timeout: latency::GNSS_SCAN,
+            almanac_update: true,
+            almanac_window: Duration::from_secs(60),
+            mode_advice: None,
+        }
+    }
+}
+
+/// NAV message read after a scan
+#[derive(Debug, Clone)]
+pub struct GnssNav(heapless::Vec<u8, GNSS_NAV_MAX_LEN>);
+
+impl GnssNav {
+    /// Copy a NAV message
+    /// Return InvalidSize if the message is longer than GNSS_NAV_MAX_LEN
+    pub fn try_from_slice(bytes: &[u8]) -> Result<Self, Lr1120Error> {
+        let data = heapless::Vec::from_slice(bytes).map_err(|_| Lr1120Error::InvalidSize)?;
+        Ok(Self(data))
+    }
+
+    /// NAV message content
+    pub fn data(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Event published by the tracker
+/// The NAV message is stored inline (no allocation in no_std), which makes this enum large
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum GnssEvent {
+    /// Scan completed with a NAV message
+    Nav { scan_type: GnssScanType, nb_sv: u8, nav: GnssNav, timestamp: ScanTimestamp },
+    /// Scan completed without any satellite detected
+    NoFix { scan_type: GnssScanType },
+    /// Position computed by the doppler solver
+    Position { latitude: u16, longitude: u16, accuracy: u16, nb_sv: u8, timestamp: ScanTimestamp },
+    /// GPS time read from the chip, with the drift (ms) of the GPS time compared to the local time since last synchronisation
+    TimeSync { gps_time: u32, accuracy: u32, drift_ms: i32 },
+    /// Almanac update from satellite completed for GPS or BeiDou
+    AlmanacUpdate { gps: bool },
+    /// Battery voltage (mV) below the low battery threshold (see [`set_low_bat_threshold_mv`](Lr1120::set_low_bat_threshold_mv))
+    LowBattery { vbat_mv: u16 },
+    /// An operation failed: the tracker retries after the assisted period
+    Error(Lr1120Error),
+}
+
+/// State of the tracker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GnssTrackerState {
+    /// Time unknown: fetch it from satellites
+    FetchTime,
+    /// Run a scan
+    Scan,
+    /// Download almanac from satellites for GPS or BeiDou
+    AlmanacUpdate { gps: bool },
+}
+
+/// Continuous GNSS tracking state machine
+pub struct GnssTracker {
+    cfg: GnssTrackerCfg,
+    state: GnssTrackerState,
+    /// Last GPS time read with the corresponding local time
+    last_sync: Option<(u32, Instant)>,
+    /// Next scan when an almanac update was inserted
+    next_scan: Instant,
+    /// Accuracy of the last position computed
+    last_accuracy: Option<u16>,
+    /// Timing of the last scan
+    last_timing: Option<GnssScanTiming>,
+    /// Scan period replacing the KeepSync period of the configuration (set by the motion cadence)
+    cadence_period: Option<Duration>,
+}
+
+impl GnssTracker {
+    /// Create a tracker, starting by a time fetch
+    pub fn new(cfg: GnssTrackerCfg) -> Self {
+        Self { cfg, state: GnssTrackerState::FetchTime, last_sync: None, next_scan: Instant::now(), last_accuracy: None, last_timing: None, cadence_period: None }
+    }
+
+    /// Current state of the tracker
+    pub fn state(&self) -> GnssTrackerState {
+        self.state
+    }
+
+    /// Timing of the last scan (None before the first scan)
+    /// Allows to tune the scan timeout and estimate the energy spent (see [`GnssScanTiming::energy_uj`])
+    pub fn last_timing(&self) -> Option<GnssScanTiming> {
+        self.last_timing
+    }
+
+    /// Last GPS time read from the chip with the corresponding local instant
+    /// Can be used as GPS time reference of other scans (e.g. [`WifiSniffer::set_gps_ref`](crate::wifi_sniffer::WifiSniffer::set_gps_ref))
+    pub fn gps_ref(&self) -> Option<(GpsTime, Instant)> {
+        self.last_sync.map(|(gps_time, at)| (GpsTime(gps_time), at))
+    }
+
+    /// Run the tracker forever: errors are published as event
+    pub async fn run<O, SPI, M, RM, const N: usize>(&mut self, lr: &mut Lr1120<O,SPI,M>, events: Sender<'_, RM, GnssEvent, N>) -> !
+        where O: OutputPin, SPI: SpiBus<u8>, M: BusyPin, RM: RawMutex
+    {
+        self.cadence_period = None;
+        loop {
+            let delay = match self.step(lr, &events).await {
+                Ok(delay) => delay,
+                Err(e) => {
+                    events.send(GnssEvent::Error(e)).await;
+                    self.cfg.assisted_period
+                }
+            };
+            Timer::after(delay).await;
+        }
+    }
+
+    /// Run the tracker forever with a scan period driven by motion events
+    /// The cadence period replaces the KeepSync period (the configuration is left unchanged),
+    /// and a scan is started immediately when motion is detected
+    pub async fn run_with_motion<O, SPI, M, RM, MM, const N: usize>(&mut self, lr: &mut Lr1120<O,SPI,M>, events: Sender<'_, RM, GnssEvent, N>, motion: &MotionHook<MM>, mut cadence: ScanCadence) -> !
+        where O: OutputPin, SPI: SpiBus<u8>, M: BusyPin, RM: RawMutex, MM: RawMutex
+    {
+        loop {
+            self.cadence_period = Some(cadence.period());
+            let delay = match self.step(lr, &events).await {
+                Ok(delay) => delay,
+                Err(e) => {
+                    events.send(GnssEvent::Error(e)).await;
+                    self.cfg.assisted_period
+                }
+            };
+            cadence.wait(delay, motion).await;
+        }
+    }
+
+    /// Run one step of the state machine and return the delay before the next one
+    pub async fn step<O, SPI, M, RM, const N: usize>(&mut self, lr: &mut Lr1120<O,SPI,M>, events: &Sender<'_, RM, GnssEvent, N>) -> Result<Duration, Lr1120Error>
+        where O: OutputPin, SPI: SpiBus<u8>, M: BusyPin, RM: RawMutex
+    {
+        if let Some(vbat_mv) = lr.check_low_bat().await? {
+            events.send(GnssEvent::LowBattery { vbat_mv }).await;
+        }
+        match self.state {
+            GnssTrackerState::FetchTime => {
+                lr.gnss_fetch_time(self.cfg.scan.best_effort, FetchTimeMode::TowWn).await?;
+                lr.wait_ready(self.cfg.scan_timeout).await?;
+                if self.sync_time(lr, events).await? {
+                    self.state = GnssTrackerState::Scan;
+                    Ok(Duration::from_ticks(0))
+                } else {
+                    Ok(self.cfg.assisted_period)
+                }
+            }
+            GnssTrackerState::Scan => {
+                let period = self.scan(lr, events).await?;
+                self.next_scan = Instant::now() + period;
+                // Almanac update only when time is still known: a pending time fetch must not be overwritten
+                if self.cfg.almanac_update
+                    && self.state == GnssTrackerState::Scan
+                    && let Some((gps, delay)) = self.almanac_pending(lr).await?
+                    && delay < period {
+                    self.state = GnssTrackerState::AlmanacUpdate { gps };
+                    return Ok(delay);
+                }
+                Ok(period)
+            }
+            GnssTrackerState::AlmanacUpdate { gps } => {
+                self.state = GnssTrackerState::Scan;
+                lr.gnss_updt_almanac_from_sat(self.cfg.scan.best_effort, gps).await?;
+                lr.wait_ready(self.cfg.scan_timeout).await?;
+                events.send(GnssEvent::AlmanacUpdate { gps }).await;
+                Ok(self.next_scan.saturating_duration_since(Instant::now()))
+            }
+        }
+    }
+
+    /// Run a scan, publish results and return the period until next scan
+    async fn scan<O, SPI, M, RM, const N: usize>(&mut self, lr: &mut Lr1120<O,SPI,M>, events: &Sender<'_, RM, GnssEvent, N>) -> Result<Duration, Lr1120Error>
+        where O: OutputPin, SPI: SpiBus<u8>, M: BusyPin, RM: RawMutex
+    {
+        if let Some(advice) = &self.cfg.mode_advice {
+            lr.gnss_apply_mode_advice(advice, self.last_accuracy).await?;
+        }
+        let timestamp = ScanTimestamp::new(Instant::now(), self.gps_ref());
+        lr.gnss_scan(self.cfg.scan.clone()).await?;
+        lr.wait_ready(self.cfg.scan_timeout).await?;
+        self.last_timing = Some(lr.gnss_get_scan_timing(Some(timestamp.age(Instant::now()))).await?);
+        let scan_type = lr.gnss_get_scan_type().await?;
+        let nb_sv = lr.gnss_get_nb_sv().await?;
+        if nb_sv == 0 {
+            events.send(GnssEvent::NoFix { scan_type }).await;
+        } else {
+            let nav = GnssNav::try_from_slice(lr.gnss_read_results().await?)?;
+            events.send(GnssEvent::Nav { scan_type, nb_sv, nav, timestamp }).await;
+            let doppler = lr.gnss_get_doppler().await?;
+            if doppler.solver_error() == SolverError::None {
+                self.last_accuracy = Some(doppler.accuracy());
+                events.send(GnssEvent::Position {
+                    latitude: doppler.latitude(),
+                    longitude: doppler.longitude(),
+                    accuracy: doppler.accuracy(),
+                    nb_sv: doppler.nb_sv_used(),
+                    timestamp,
+                }).await;
+            }
+        }
+        // Time lost (e.g. after a long time without fix): fetch it again before next scan
+        if !self.sync_time(lr, events).await? {
+            self.state = GnssTrackerState::FetchTime;
+        }
+        let gps = lr.gnss_get_warm_start_status(true, false).await?.nb_sv();
+        let beidou = lr.gnss_get_warm_start_status(false, true).await?.nb_sv();
+        let keep_sync_period = self.cadence_period.unwrap_or(self.cfg.keep_sync_period);
+        Ok(if gps + beidou > 0 { keep_sync_period } else { self.cfg.assisted_period })
+    }
+
+    /// Read GPS time and publish it with the drift since last synchronisation
+    /// Return false if time is unknown
+    async fn sync_time<O, SPI, M, RM, const N: usize>(&mut self, lr: &mut Lr1120<O,SPI,M>, events: &Sender<'_, RM, GnssEvent, N>) -> Result<bool, Lr1120Error>
+        where O: OutputPin, SPI: SpiBus<u8>, M: BusyPin, RM: RawMutex
+    {
+        let time = lr.gnss_get_time().await?;
+        let gps_time = time.gps_time();
+        if gps_time == 0 {
+            self.last_sync = None;
+            return Ok(false);
+        }
+        let now = Instant::now();
+        let drift_ms = match self.last_sync {
+            Some((prev_time, prev_instant)) => {
+                let gps_ms = (gps_time as i64 - prev_time as i64) * 1000;
+                let local_ms = (now - prev_instant).as_millis() as i64;
+                (gps_ms - local_ms).clamp(i32::MIN as i64, i32::MAX as i64) as i32
+            }
+            None => 0,
+        };
+        self.last_sync = Some((gps_time, now));
+        events.send(GnssEvent::TimeSync { gps_time, accuracy: time.accuracy(), drift_ms }).await;
+        Ok(true)
+    }
+
+    /// Check if almanac data for a constellation will be broadcast within the configured window
+    /// Return the constellation (true for GPS) and the delay before sending the update command
+    async fn almanac_pending<O, SPI, M>(&mut self, lr: &mut Lr1120<O,SPI,M>) -> Result<Option<(bool, Duration)>, Lr1120Error>
+        where O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+    {
+        let status = lr.gnss_get_almanac_status().await?;
+        let candidates = [
+            (true , status.gps_status()   , status.gps_total_sv_to_update()   , status.gps_time_to_next_subframe()),
+            (false, status.beidou_status(), status.beidou_total_sv_to_update(), status.beidou_time_to_next_subframe()),
+        ];
+        let pending = candidates.into_iter()
+            .filter(|&(_, st, nb, _)| st == AlmanacStatus::Success && nb > 0)
+            .map(|(gps, _, _, ms)| (gps, Duration::from_millis(ms as u64)))
+            .filter(|&(_, delay)| delay <= self.cfg.almanac_window)
+            .min_by_key(|&(_, delay)| delay)
+            .map(|(gps, delay)| (gps, delay.checked_sub(ALMANAC_LEAD).unwrap_or_default()));
+        Ok(pending)
+    }
+}
@@ -0,0 +1,96 @@
+//! # GPS time
+//!
+//! GPS time and calendar conversions, shared by the GNSS scans, the Class B beacons and the scan timestamps
+//! (available without the `gnss` feature).
+//!
+//! ## Available Types
+//! - [`GpsTime`] - GPS time with conversion from/to week number, time of week and calendar date
+
+/// Number of seconds in a day
+pub const SECS_PER_DAY: u32 = 86400;
+/// Number of seconds in a GPS week
+pub const SECS_PER_WEEK: u32 = 7 * SECS_PER_DAY;
+/// Number of days between the Unix epoch (1970/01/01) and the GPS epoch (1980/01/06)
+pub(crate) const GPS_EPOCH_UNIX_DAYS: i64 = 3657;
+
+/// Convert a calendar date into a number of days since 1970/01/01
+fn days_from_civil(year: u16, month: u8, day: u8) -> i64 {
+    let y = year as i64 - if month <= 2 {1} else {0};
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Convert a number of days since 1970/01/01 into a calendar date (year, month, day)
+pub(crate) fn civil_from_days(days: i64) -> (u16, u8, u8) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 {mp + 3} else {mp - 9} as u8;
+    let year = (yoe + era * 400 + if month <= 2 {1} else {0}) as u16;
+    (year, month, day)
+}
+
+/// Convert a calendar date into a number of days since the GPS epoch
+/// Return None for an invalid month or day, or a date before the GPS epoch
+pub(crate) fn gps_days_from_calendar(year: u16, month: u8, day: u8) -> Option<i64> {
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let days = days_from_civil(year, month, day) - GPS_EPOCH_UNIX_DAYS;
+    (days >= 0).then_some(days)
+}
+
+/// GPS time: seconds elapsed since the GPS epoch (1980/01/06 00:00:00)
+/// GPS time does not include leap seconds: it is ahead of UTC by 18s since 2017,
+/// which is ignored by the calendar conversions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GpsTime(pub u32);
+
+impl GpsTime {
+    /// Create GPS time from a week number (without rollover) and a time of week in seconds
+    /// Return None if the time of week is not below [`SECS_PER_WEEK`] or the time does not fit on 32 bits (from the middle of week 7101)
+    pub fn from_week_tow(week: u16, tow: u32) -> Option<Self> {
+        if tow >= SECS_PER_WEEK {
+            return None;
+        }
+        (week as u32).checked_mul(SECS_PER_WEEK)?
+            .checked_add(tow)
+            .map(GpsTime)
+    }
+
+    /// Create GPS time from a calendar date at midnight
+    /// Return None if the date is before the GPS epoch or after 2116/02/07 (32 bits overflow)
+    pub fn from_calendar(year: u16, month: u8, day: u8) -> Option<Self> {
+        let secs = gps_days_from_calendar(year, month, day)? * SECS_PER_DAY as i64;
+        u32::try_from(secs).ok().map(GpsTime)
+    }
+
+    /// Week number since the GPS epoch (without rollover)
+    pub fn week(&self) -> u16 {
+        (self.0 / SECS_PER_WEEK) as u16
+    }
+
+    /// Time of week in seconds
+    pub fn tow(&self) -> u32 {
+        self.0 % SECS_PER_WEEK
+    }
+
+    /// Number of whole days since the GPS epoch
+    pub fn days(&self) -> u32 {
+        self.0 / SECS_PER_DAY
+    }
+
+    /// Calendar date (year, month, day)
+    pub fn to_calendar(&self) -> (u16, u8, u8) {
+        civil_from_days(self.days() as i64 + GPS_EPOCH_UNIX_DAYS)
+    }
+}
@@ -0,0 +1,182 @@
+//! # embedded-io adapters for the radio buffers
+//!
+//! With the `io` feature, the TX and RX buffers of the LR1120 can be accessed through the `embedded-io-async` traits,
+//! allowing protocol code written against `Read`/`Write` to stream directly to/from the radio without intermediate copies.
+//!
+//! - [`TxBufferWriter`] streams data into the TX buffer inside a single `WriteBuffer8` command:
+//!   the SPI transaction is kept open between writes and closed on `flush` (or drop).
+//!   The TX buffer is written from its start: use one writer per packet.
+//! - [`RxBufferReader`] reads the last packet received, each `read` being a `ReadBuffer8` command.
+//!
+//! ## Example
+//! ```rust,no_run
+//! let mut tx = lr1120.tx_writer().await?;
+//! frame.write_to(&mut tx).await?;
+//! tx.flush().await?;
+//! lr1120.set_tx(0).await?;
+//!
+//! let mut rx = lr1120.rx_reader().await?;
+//! let frame = Frame::read_from(&mut rx).await?;
+//! ```
+//!
+//! ## Available Methods
+//! - [`tx_writer`](Lr1120::tx_writer) - Start writing the TX buffer
+//! - [`rx_reader`](Lr1120::rx_reader) - Read the last packet received
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiBus;
+use embedded_io_async::{ErrorKind, ErrorType, Read, Write};
+
+use super::{BusyPin, Lr1120, Lr1120Error};
+use super::cmd::cmd_system::{read_buffer8_cmd, write_buffer8_cmd};
+
+pub use super::radio::RADIO_BUFFER_LEN;
+
+impl embedded_io_async::Error for Lr1120Error {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Lr1120Error::InvalidSize => ErrorKind::OutOfMemory,
+            Lr1120Error::InvalidParam => ErrorKind::InvalidInput,
+            Lr1120Error::BusyTimeout => ErrorKind::TimedOut,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+/// Writer streaming data into the TX buffer
+/// Created by [`Lr1120::tx_writer`]: the write command is ended by `flush` or when the writer is dropped
+pub struct TxBufferWriter<'a, O: OutputPin, SPI, M: BusyPin> {
+    lr: &'a mut Lr1120<O,SPI, M>,
+    len: usize,
+    open: bool,
+}
+
+impl<O,SPI, M> TxBufferWriter<'_, O,SPI, M> where
+    O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+{
+    /// Number of bytes written
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// True if nothing was written
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<O,SPI, M> ErrorType for TxBufferWriter<'_, O,SPI, M> where
+    O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+{
+    type Error = Lr1120Error;
+}
+
+impl<O,SPI, M> Write for TxBufferWriter<'_, O,SPI, M> where
+    O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+{
+    /// Write data in the TX buffer
+    /// Return InvalidConfig after a flush and InvalidSize once the buffer is full
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Lr1120Error> {
+        if !self.open {
+            return Err(Lr1120Error::InvalidConfig);
+        }
+        let n = buf.len().min(RADIO_BUFFER_LEN - self.len);
+        if n == 0 && !buf.is_empty() {
+            return Err(Lr1120Error::InvalidSize);
+        }
+        let data = buf.get(..n).ok_or(Lr1120Error::InvalidSize)?;
+        #[cfg(feature = "metrics")] {self.lr.metrics.spi_bytes(n);}
+        self.lr.spi.write(data).await.map_err(|_| Lr1120Error::Spi)?;
+        self.len += n;
+        Ok(n)
+    }
+
+    /// End the write command: the TX buffer is ready for transmission
+    async fn flush(&mut self) -> Result<(), Lr1120Error> {
+        if self.open {
+            self.open = false;
+            self.lr.spi.flush().await.map_err(|_| Lr1120Error::Spi)?;
+            self.lr.nss.set_high().map_err(|_| Lr1120Error::Pin)?;
+        }
+        Ok(())
+    }
+}
+
+impl<O: OutputPin, SPI, M: BusyPin> Drop for TxBufferWriter<'_, O,SPI, M> {
+    fn drop(&mut self) {
+        // Keep the SPI framing consistent if the writer was not flushed
+        if self.open {
+            self.lr.nss.set_high().ok();
+        }
+    }
+}
+
+/// Reader on the last packet received in the RX buffer
+/// Created by [`Lr1120::rx_reader`]
+pub struct RxBufferReader<'a, O: OutputPin, SPI, M: BusyPin> {
+    lr: &'a mut Lr1120<O,SPI, M>,
+    offset: u8,
+    remaining: u8,
+}
+
+impl<O,SPI, M> RxBufferReader<'_, O,SPI, M> where
+    O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+{
+    /// Number of bytes not read yet
+    pub fn remaining(&self) -> usize {
+        self.remaining as usize
+    }
+}
+
+impl<O,SPI, M> ErrorType for RxBufferReader<'_, O,SPI, M> where
+    O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+{
+    type Error = Lr1120Error;
+}
+
+impl<O,SPI, M> Read for RxBufferReader<'_, O,SPI, M> where
+    O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+{
+    /// Read data from the RX buffer directly into `buf`
+    /// Return 0 once the whole packet was read
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Lr1120Error> {
+        let n = buf.len().min(self.remaining as usize);
+        if n == 0 {
+            return Ok(0);
+        }
+        let data = buf.get_mut(..n).ok_or(Lr1120Error::InvalidSize)?;
+        self.lr.cmd_wr(&read_buffer8_cmd(self.offset, n as u8)).await?;
+        self.lr.wait_ready(self.lr.timeouts.rsp).await?;
+        // Response: status byte followed by the data
+        let mut stat = [0u8];
+        data.fill(0);
+        self.lr.nss.set_low().map_err(|_| Lr1120Error::Pin)?;
+        #[cfg(feature = "metrics")] {self.lr.metrics.spi_transaction(1 + n);}
+        self.lr.spi.transfer_in_place(&mut stat).await.map_err(|_| Lr1120Error::Spi)?;
+        self.lr.spi.transfer_in_place(data).await.map_err(|_| Lr1120Error::Spi)?;
+        self.lr.nss.set_high().map_err(|_| Lr1120Error::Pin)?;
+        self.lr.updt_rsp_status(stat[0]);
+        self.lr.status.check()?;
+        self.offset = self.offset.wrapping_add(n as u8);
+        self.remaining -= n as u8;
+        Ok(n)
+    }
+}
+
+impl<O,SPI, M> Lr1120<O,SPI, M> where
+    O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+{
+
+    /// Start a write command on the TX buffer and return a writer streaming the data
+    pub async fn tx_writer(&mut self) -> Result<TxBufferWriter<'_, O,SPI, M>, Lr1120Error> {
+        self.cmd_wr_begin(&write_buffer8_cmd()).await?;
+        Ok(TxBufferWriter { lr: self, len: 0, open: true })
+    }
+
+    /// Return a reader on the last packet received
+    pub async fn rx_reader(&mut self) -> Result<RxBufferReader<'_, O,SPI, M>, Lr1120Error> {
+        let status = self.get_rx_buffer_status().await?;
+        Ok(RxBufferReader { offset: status.offset(), remaining: status.pld_len(), lr: self })
+    }
+
+}
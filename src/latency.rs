@@ -0,0 +1,110 @@
+//! # Command latency budget
+//!
+//! The chip keeps BUSY high while processing a command: the driver waits for it with a timeout
+//! before sending the next command or reading a response. The constants below give the processing
+//! time budget of each command family and are used as defaults:
+//! - the driver timeouts are grouped in [`Timeouts`] and can be overridden per deployment with [`set_timeouts`](Lr1120::set_timeouts),
+//! - the scan timeouts of [`GnssTracker`](crate::gnss_tracker::GnssTracker) and [`WifiSniffer`](crate::wifi_sniffer::WifiSniffer)
+//!   default to [`GNSS_SCAN`] and [`WIFI_SCAN`] and are set in their configuration.
+//!
+//! | Command family | Typical | Budget |
+//! |----------------|---------|--------|
+//! | Short command (configuration, status) | few µs | [`CMD_READY`] |
+//! | Response processing (e.g. 13-bit temperature measurement) | up to 270µs | [`RSP_READY`] |
+//! | Calibration (all blocks or image) | tens of ms | [`CALIBRATE`] |
+//! | WiFi scan | few hundred ms | [`WIFI_SCAN`], or [`wifi_time_limit_max`] with a time limit |
+//! | GNSS scan (dual constellation adds a delay between GPS and BeiDou) | seconds | [`GNSS_SCAN`] |
+//! | Abort of a long operation | up to 2.9s | [`ABORT`] |
+//! | Flash erase (bootloader) | seconds | [`FLASH_ERASE`] |
+//!
+//! ## Available Methods
+//! - [`set_timeouts`](Lr1120::set_timeouts) - Override the timeouts used by the driver
+//! - [`timeouts`](Lr1120::timeouts) - Return the timeouts used by the driver
+//! - [`wifi_time_limit_max`] - Maximum duration of a WiFi scan with a time limit
+
+use embassy_time::Duration;
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiBus;
+
+use super::{BusyPin, Lr1120};
+
+/// Budget for the chip to be ready for a new command after a short command
+pub const CMD_READY: Duration = Duration::from_millis(100);
+
+/// Budget for the processing of a command before reading its response
+pub const RSP_READY: Duration = Duration::from_millis(1);
+
+/// Budget for a calibration
+pub const CALIBRATE: Duration = Duration::from_millis(100);
+
+/// Budget for a WiFi scan with the default scan parameters
+pub const WIFI_SCAN: Duration = Duration::from_secs(5);
+
+/// Fixed offset per channel of a WiFi scan with a time limit
+pub const WIFI_SCAN_OFFSET: Duration = Duration::from_micros(9590);
+
+/// Budget for a GNSS scan or time fetch
+pub const GNSS_SCAN: Duration = Duration::from_secs(60);
+
+/// Budget for the chip to abort a long operation (GNSS scan): specified at 2.9s maximum
+pub const ABORT: Duration = Duration::from_millis(3000);
+
+/// Budget for the flash erase in bootloader mode: the duration is not specified, this is a generous margin
+pub const FLASH_ERASE: Duration = Duration::from_secs(10);
+
+/// Maximum duration of a WiFi scan with a time limit (in ms) on the channels of `chan_mask`:
+/// N_channel x ((1 + Xtal_precision) x Timeout + T_offset), with the crystal precision in ppm
+pub fn wifi_time_limit_max(chan_mask: u16, time_limit: u16, xtal_ppm: u32) -> Duration {
+    let nb_chan = (chan_mask & 0x3FFF).count_ones() as u64;
+    let timeout_us = time_limit as u64 * 1000;
+    let drift_us = (timeout_us * xtal_ppm as u64).div_ceil(1_000_000);
+    Duration::from_micros(nb_chan * (timeout_us + drift_us + WIFI_SCAN_OFFSET.as_micros()))
+}
+
+/// Timeouts used by the driver when waiting for the BUSY pin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Timeouts {
+    /// Wait before sending a command (end of the previous one) and after results or crypto commands
+    pub cmd: Duration,
+    /// Wait before reading a response
+    pub rsp: Duration,
+    /// Wait for the end of a calibration
+    pub calibrate: Duration,
+    /// Wait for the end of an abort
+    pub abort: Duration,
+}
+
+impl Timeouts {
+    /// Default timeouts from the latency budget
+    pub const fn new() -> Self {
+        Self {
+            cmd: CMD_READY,
+            rsp: RSP_READY,
+            calibrate: CALIBRATE,
+            abort: ABORT,
+        }
+    }
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<O,SPI, M> Lr1120<O,SPI, M> where
+    O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+{
+
+    /// Override the timeouts used by the driver (e.g. longer ones with a slow host or shorter ones to detect failures earlier)
+    pub fn set_timeouts(&mut self, timeouts: Timeouts) {
+        self.timeouts = timeouts;
+    }
+
+    /// Return the timeouts used by the driver
+    pub fn timeouts(&self) -> Timeouts {
+        self.timeouts
+    }
+
+}
@@ -36,6 +36,12 @@
 //! let radio = Lr1120::new_blocking(reset_pin, busy_pin, spi_device, nss_pin);
 //! ```
 //!
+//! ### Releasing the peripherals
+//! The SPI bus and pins can be taken back, e.g. to share the bus with another device while the radio sleeps:
+//! ```rust,no_run
+//! let (reset_pin, busy_pin, spi_device, nss_pin) = radio.sleep_and_release().await.map_err(|(_radio, e)| e)?;
+//! ```
+//!
 //! ## Architecture
 //!
 //! The driver is organized into several modules:
@@ -43,8 +49,23 @@
 //! - [`cmd`] - Low-level command interface and protocol-specific commands
 //! - [`status`] - Status and interrupt handling
 //! - [`system`] - System-level operations (reset, sleep, etc.)
+//! - [`recal`] - Temperature-triggered recalibration task
+//! - [`errata`] - Workarounds for known chip behaviours, individually controllable
+//! - [`calib_store`] - Per-board calibration storage (RSSI, ranging delay, crystal trim, image bands) applied at init
+//! - [`latency`] - Processing time budget per command family and timeouts used by the driver
+//! - [`retry`] - Retry of idempotent read commands on SPI errors
+//! - [`coex`] - Pause of the RX duty cycle around GNSS/WiFi scans
 //! - [`radio`] - Common radio operations
 //! - Protocol modules: [`lora`], [`fsk`], [`lrfhss`].
+//! - [`link`] - Link quality estimation and data rate suggestion for point-to-point links
+//! - [`sensitivity`] - Sensitivity and link budget of the LoRa and FSK modulations
+//! - [`ranging`] - Ranging exchange and plausibility check of its results against the RSSI
+//! - Geolocation modules: [`wifi_scan`], [`wifi_sniffer`], [`gnss`], [`gnss_tracker`], with scan cadence driven by [`motion`] events.
+//! - [`almanac_writer`] - Almanac update from downlinks, resumable after a reboot
+//! - [`payload`] - Geolocation uplink payload builders
+//! - [`gps_time`] - GPS time and calendar conversions
+//! - [`fw_image`] - Parser of the firmware update files (version, target chip and image words)
+//! - [`bootloader`] - Firmware update and device identifiers in bootloader mode
 //!
 //! ## Error Handling
 //!
@@ -56,30 +77,102 @@
 //! - `CmdErr` - Invalid command sent to LR1120
 //! - `BusyTimeout` - Timeout waiting for busy pin
 //! - `InvalidSize` - Command size exceeds buffer limits
+//! - `InvalidParam` - Command parameter out of range
+//! - `InvalidConfig` - Configuration conflicting with the current driver state
+//! - `CrcError` - Packet received with a CRC error
+//! - `Unsupported` - Command not supported by the chip firmware
+//! - `PayloadLength` - Packet received in implicit header mode with a length different from the one configured
+//! - `Aborted` - Long operation aborted by the host
+//! - `Io` - Error from an external I/O source (e.g. firmware image reader)
+//! - `LowBattery` - Flash write refused with a battery voltage below the low battery threshold
+//! - `WrongHwType` - Firmware image built for another chip variant
+//! - `AlmanacCrc` - Almanac stored in the chip not matching the CRC expected after an update
+//! - `BatchFailed` - Command of a batch reporting a failure
+//! - `IrqTimeout` - Timeout waiting for an interrupt
 //!
 //! ## Cargo Features
 //!
 //! - `defmt` - Enable defmt logging support for debugging
-
-#![no_std]
+//! - `tick-hz-32_768` (default) - Configure `embassy-time` with a 32.768kHz tick
+//! - `std` - Run on Linux using spidev and the GPIO character device (see the `linux` module), requires `default-features = false`
+//!   (the 32.768kHz tick conflicts with the `std` time driver): re-enable the subsystems used,
+//!   e.g. `features = ["std", "fsk", "lrfhss", "ranging", "wifi", "gnss", "crypto"]`
+//! - `sim` - Behavioral simulator of the chip to test higher-level code without hardware (see the `sim` module)
+//! - `shadow` - Record the last radio configuration set, to read it back or restore it after reset (see the `shadow` module)
+//! - `io` - `embedded-io-async` Read/Write adapters on the radio TX/RX buffers (see the `io` module)
+//! - `metrics` - Telemetry counters of SPI traffic, command failures, interrupts and busy time (see the `metrics` module)
+//! - `rand` - `rand_core` adapter on the chip random number generator (see `system::RandomPool`)
+//!
+//! The subsystems are enabled by default and can be disabled individually to reduce code size
+//! (e.g. `default-features = false, features = ["tick-hz-32_768"]` for a LoRa-only firmware):
+//! - `fsk` - (G)FSK modem (see [`fsk`])
+//! - `lrfhss` - LR-FHSS modem (see [`lrfhss`])
+//! - `ranging` - LoRa ranging (see [`ranging`])
+//! - `wifi` - WiFi scan (see [`wifi_scan`], [`wifi_sniffer`])
+//! - `gnss` - GNSS scan and almanac (see [`gnss`], [`gnss_tracker`], [`almanac_writer`])
+//! - `crypto` - Crypto engine (see [`crypto`])
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// Runtime paths must report malformed or truncated data as an error instead of panicking
+#![deny(clippy::indexing_slicing, clippy::unwrap_used, clippy::expect_used, clippy::panic)]
 pub mod cmd;
 pub mod system;
 pub mod status;
 pub mod radio;
 pub mod lora;
+#[cfg(feature = "fsk")]
 pub mod fsk;
+#[cfg(feature = "lrfhss")]
 pub mod lrfhss;
+#[cfg(feature = "wifi")]
 pub mod wifi_scan;
+#[cfg(feature = "wifi")]
+pub mod wifi_sniffer;
+#[cfg(feature = "crypto")]
 pub mod crypto;
+#[cfg(feature = "gnss")]
 pub mod gnss;
+pub mod gps_time;
+pub mod motion;
+pub mod beacon;
+pub mod link;
+pub mod sensitivity;
+#[cfg(feature = "ranging")]
+pub mod ranging;
+pub mod recal;
+pub mod errata;
+pub mod calib_store;
+pub mod latency;
+pub mod retry;
+pub mod coex;
+pub mod payload;
+pub mod fw_image;
+pub mod bootloader;
+#[cfg(feature = "std")]
+pub mod linux;
+// The simulator is a test double: its buffers are sized by construction
+#[cfg(feature = "sim")]
+#[allow(clippy::indexing_slicing)]
+pub mod sim;
+#[cfg(feature = "shadow")]
+pub mod shadow;
+#[cfg(feature = "io")]
+pub mod io;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(all(feature = "gnss", not(feature = "gnss_v1")))]
+pub mod gnss_tracker;
+#[cfg(all(feature = "gnss", not(feature = "gnss_v1")))]
+pub mod almanac_writer;
 
 use core::marker::PhantomData;
 
 use embassy_time::{with_timeout, Duration, Instant, Timer};
 use embedded_hal::digital::{OutputPin, InputPin};
 use embedded_hal_async::{digital::Wait, spi::SpiBus};
+use embedded_io_async::Write;
 
-use status::{CmdStatus, Status};
+use status::{CmdStatus, Intr, Status, IRQ_MASK_GNSS_ABORT, IRQ_MASK_GNSS_DONE, IRQ_MASK_WIFI_DONE};
 // pub use cmd::{RxBw, PulseShape}; // Re-export Bandwidth enum as it is used for all packet types
 
 trait Sealed{}
@@ -138,6 +231,10 @@ impl<I: InputPin + Wait> BusyPin for BusyAsync<I> {
 
 /// Size of an the internal buffer set to the largest command
 const BUFFER_SIZE: usize = 1023;
+/// Size of the chunks of a response streamed with `rsp_rd_into`
+const RSP_CHUNK_LEN: usize = 64;
+/// Largest response which can be read into the internal buffer with `rsp_rd`
+pub(crate) const RSP_MAX_LEN: usize = BUFFER_SIZE - 2;
 /// Command Buffer:
 pub struct CmdBuffer ([u8;BUFFER_SIZE+1]);
 
@@ -221,6 +318,81 @@ pub struct Lr1120<O,SPI, M: BusyPin> {
     nss: O,
     /// Buffer to store SPI commands/response
     buffer: CmdBuffer,
+    /// Last status: stat2 comes from the last command phase
+    status: Status,
+    /// Function currently assigned to each configurable DIO
+    dio_usage: system::DioUsage,
+    /// Mask of DIO driven during sleep
+    dio_sleep_drive: u8,
+    /// RF switch configuration (when set)
+    rf_switch: Option<system::DioRfSwitchCfg>,
+    /// Check the RF switch configuration before GNSS/WiFi scans
+    rf_switch_check: bool,
+    /// GNSS capabilities (when read from the chip)
+    #[cfg(feature = "gnss")]
+    gnss_caps: Option<gnss::GnssCapabilities>,
+    /// GNSS scan mode last configured (None if unknown)
+    #[cfg(feature = "gnss")]
+    gnss_mode: Option<gnss::GnssScanMode>,
+    /// Last FSK packet parameters configured
+    #[cfg(feature = "fsk")]
+    fsk_packet: Option<fsk::FskPacketParams>,
+    /// Last LoRa packet parameters configured
+    lora_packet: Option<lora::LoraPacketParams>,
+    /// LoRa coding rate last configured (None if unknown)
+    lora_cr: Option<lora::LoraCr>,
+    /// RF frequency currently configured (None if unknown)
+    rf_freq: Option<u32>,
+    /// Ranging delay calibration for the 2.4GHz band (none built-in)
+    #[cfg(feature = "ranging")]
+    ranging_delay_hf: Option<ranging::RangingDelayTable>,
+    /// CAD exit mode configured
+    cad_exit: lora::ExitMode,
+    /// Progress of the last CAD started
+    cad_state: lora::CadState,
+    /// CAD outcome counters
+    cad_stats: lora::CadStats,
+    /// Packet type currently configured (None if unknown)
+    packet_type: Option<radio::PacketType>,
+    /// Interrupt masks configured on IRQ1 (DIO9) and IRQ2 (DIO11)
+    irq_masks: [status::Intr; 2],
+    /// Fallback mode after TX/RX
+    fallback: radio::FallbackMode,
+    /// Last radio configuration set
+    #[cfg(feature = "shadow")]
+    shadow: shadow::RadioConfig,
+    /// Low battery threshold in mV, compared to the VBAT measurement (None when disabled)
+    low_bat_mv: Option<u16>,
+    /// Errata workarounds applied
+    errata: errata::Errata,
+    /// TCXO configuration set (voltage, start time in LF clock steps), restored after a cold wake-up
+    tcxo: Option<(system::TcxoVoltage, u32)>,
+    /// Chip sent to sleep without retention: the firmware restarts on wake-up
+    cold_start: bool,
+    /// LF clock frequency (Hz) used to convert durations into LF clock steps
+    lf_clk_hz: u32,
+    /// LF clock source (None until set or reset)
+    lf_clk: Option<system::LfClock>,
+    /// Behavior on dual constellation request with the RC LF clock
+    #[cfg(feature = "gnss")]
+    gnss_dual_policy: gnss::DualConstellationPolicy,
+    /// Last constellation configuration fell back to GPS only
+    #[cfg(feature = "gnss")]
+    gnss_fallback: bool,
+    /// WiFi timestamp threshold in seconds (None until set or reset)
+    #[cfg(feature = "wifi")]
+    wifi_timestamp_thr: Option<u32>,
+    /// Timeouts waiting for the BUSY pin
+    timeouts: latency::Timeouts,
+    /// Check the battery before operations writing the chip flash
+    flash_guard: bool,
+    /// RX duty cycle active (None when stopped or unknown)
+    rx_duty_cycle: Option<coex::RxDutyCycle>,
+    /// Retry policy of idempotent read commands on SPI errors
+    spi_retry: retry::SpiRetry,
+    /// Telemetry counters
+    #[cfg(feature = "metrics")]
+    metrics: metrics::Metrics,
 }
 
 /// Error using the LR1120
@@ -241,17 +413,121 @@ pub enum Lr1120Error {
     InvalidSize,
     /// Command with invalid parameter
     InvalidParam,
+    /// Configuration conflicting with the current driver state (e.g. DIO already in use)
+    InvalidConfig,
+    /// Packet received with a CRC error
+    CrcError,
+    /// Command not supported by the chip firmware
+    Unsupported,
+    /// Long operation aborted by the host (see [`Lr1120::wait_ready_progress`])
+    Aborted,
+    /// Error from an external I/O source (e.g. firmware image reader)
+    Io,
+    /// Command not applicable to the packet type currently configured
+    WrongPacketType { expected: radio::PacketType, actual: radio::PacketType },
+    /// Length of the packet received not matching the length configured (implicit header)
+    PayloadLength { expected: u8, actual: u8 },
+    /// Operation writing the chip flash refused: battery voltage (mV) below the low battery threshold
+    LowBattery { vbat_mv: u16 },
+    /// Firmware image built for another chip variant (or a modem firmware)
+    WrongHwType { image: system::HwType, chip: system::HwType },
+    /// Almanac CRC computed by the chip after an update not matching the CRC expected from the almanac header
+    AlmanacCrc { expected: u32, actual: u32 },
+    /// Command of a batch reporting a failure (index in the batch, see [`Lr1120::cmd_wr_batch`])
+    BatchFailed { index: usize },
+    /// Timeout while waiting for an interrupt (e.g. TxDone in [`Lr1120::transmit_timed`])
+    IrqTimeout,
     /// Unknown error
     Unknown,
 }
 
+/// Action requested by the progress callback of [`Lr1120::wait_ready_progress`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BusyProgress {
+    /// Keep waiting
+    Continue,
+    /// Abort the operation in progress
+    Abort,
+}
+
+/// Operation stopped by [`Lr1120::abort_current_operation`], identified from the interrupts raised by the abort
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AbortedOp {
+    /// The chip was not busy: nothing to abort
+    None,
+    /// GNSS scan (GnssAbort or GnssDone interrupt)
+    Gnss,
+    /// WiFi scan (WifiDone interrupt)
+    Wifi,
+    /// Other long operation without a dedicated interrupt (e.g. calibration, flash erase)
+    Other,
+}
+
+/// Status check policy used when sending a batch of write commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BatchCheck {
+    /// Check status after each command and stop on the first failure
+    EachCmd,
+    /// Send the whole batch without stopping, then report the first command with a failure status.
+    /// Commands following the failing one have already been sent
+    EndOnly,
+}
+
+impl core::fmt::Display for Lr1120Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let msg = match self {
+            Lr1120Error::Pin => "pin access failed",
+            Lr1120Error::Spi => "SPI transfer failed",
+            Lr1120Error::CmdFail => "command failed",
+            Lr1120Error::CmdErr => "invalid command or parameters",
+            Lr1120Error::BusyTimeout => "timeout waiting for busy",
+            Lr1120Error::InvalidSize => "invalid size",
+            Lr1120Error::InvalidParam => "invalid parameter",
+            Lr1120Error::InvalidConfig => "configuration conflict",
+            Lr1120Error::CrcError => "CRC error",
+            Lr1120Error::Unsupported => "not supported by firmware",
+            Lr1120Error::Aborted => "aborted",
+            Lr1120Error::Io => "I/O source error",
+            Lr1120Error::WrongPacketType { expected, actual } =>
+                return write!(f, "wrong packet type (expected {expected:?}, configured {actual:?})"),
+            Lr1120Error::PayloadLength { expected, actual } =>
+                return write!(f, "payload length mismatch (expected {expected}, received {actual})"),
+            Lr1120Error::LowBattery { vbat_mv } =>
+                return write!(f, "battery too low for a flash write ({vbat_mv}mV)"),
+            Lr1120Error::WrongHwType { image, chip } =>
+                return write!(f, "firmware image for {image:?}, chip is {chip:?}"),
+            Lr1120Error::AlmanacCrc { expected, actual } =>
+                return write!(f, "almanac CRC mismatch (expected {expected:08x}, chip {actual:08x})"),
+            Lr1120Error::BatchFailed { index } =>
+                return write!(f, "command {index} of the batch failed"),
+            Lr1120Error::IrqTimeout => "timeout waiting for interrupt",
+            Lr1120Error::Unknown => "unknown error",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl core::error::Error for Lr1120Error {}
+
 // Create driver with busy pin not implementing wait
 impl<I,O,SPI> Lr1120<O,SPI, BusyBlocking<I>> where
     I: InputPin, O: OutputPin, SPI: SpiBus<u8>
 {
     /// Create a LR1120 Device with blocking access on the busy pin
     pub fn new_blocking(nreset: O, busy: I, spi: SPI, nss: O) -> Self {
-        Self { nreset, busy, spi, nss, buffer: CmdBuffer::new()}
+        Self { nreset, busy, spi, nss, buffer: CmdBuffer::new(), status: Status::default(), dio_usage: system::DioUsage::new(), dio_sleep_drive: 0, rf_switch: None, rf_switch_check: true, lora_packet: None, lora_cr: None, rf_freq: None, cad_exit: lora::ExitMode::CadOnly, cad_state: lora::CadState::Idle, cad_stats: lora::CadStats::new(), packet_type: None, irq_masks: [status::Intr::default(); 2], fallback: radio::FallbackMode::StdbyRc, low_bat_mv: None, errata: errata::Errata::all(), tcxo: None, cold_start: false, lf_clk_hz: system::LF_CLK_HZ, lf_clk: None, timeouts: latency::Timeouts::new(), flash_guard: true, rx_duty_cycle: None, spi_retry: retry::SpiRetry::none(),
+            #[cfg(feature = "gnss")] gnss_caps: None,
+            #[cfg(feature = "gnss")] gnss_mode: None,
+            #[cfg(feature = "gnss")] gnss_dual_policy: gnss::DualConstellationPolicy::Reject,
+            #[cfg(feature = "gnss")] gnss_fallback: false,
+            #[cfg(feature = "wifi")] wifi_timestamp_thr: None,
+            #[cfg(feature = "fsk")] fsk_packet: None,
+            #[cfg(feature = "ranging")] ranging_delay_hf: None,
+            #[cfg(feature = "shadow")] shadow: shadow::RadioConfig::default(),
+            #[cfg(feature = "metrics")] metrics: metrics::Metrics::new()}
     }
 
 }
@@ -262,7 +538,16 @@ impl<I,O,SPI> Lr1120<O,SPI, BusyAsync<I>> where
 {
     /// Create a LR1120 Device with async busy pin
     pub fn new(nreset: O, busy: I, spi: SPI, nss: O) -> Self {
-        Self { nreset, busy, spi, nss, buffer: CmdBuffer::new()}
+        Self { nreset, busy, spi, nss, buffer: CmdBuffer::new(), status: Status::default(), dio_usage: system::DioUsage::new(), dio_sleep_drive: 0, rf_switch: None, rf_switch_check: true, lora_packet: None, lora_cr: None, rf_freq: None, cad_exit: lora::ExitMode::CadOnly, cad_state: lora::CadState::Idle, cad_stats: lora::CadStats::new(), packet_type: None, irq_masks: [status::Intr::default(); 2], fallback: radio::FallbackMode::StdbyRc, low_bat_mv: None, errata: errata::Errata::all(), tcxo: None, cold_start: false, lf_clk_hz: system::LF_CLK_HZ, lf_clk: None, timeouts: latency::Timeouts::new(), flash_guard: true, rx_duty_cycle: None, spi_retry: retry::SpiRetry::none(),
+            #[cfg(feature = "gnss")] gnss_caps: None,
+            #[cfg(feature = "gnss")] gnss_mode: None,
+            #[cfg(feature = "gnss")] gnss_dual_policy: gnss::DualConstellationPolicy::Reject,
+            #[cfg(feature = "gnss")] gnss_fallback: false,
+            #[cfg(feature = "wifi")] wifi_timestamp_thr: None,
+            #[cfg(feature = "fsk")] fsk_packet: None,
+            #[cfg(feature = "ranging")] ranging_delay_hf: None,
+            #[cfg(feature = "shadow")] shadow: shadow::RadioConfig::default(),
+            #[cfg(feature = "metrics")] metrics: metrics::Metrics::new()}
     }
 }
 
@@ -276,17 +561,62 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         Timer::after_millis(10).await;
         self.nreset.set_high().map_err(|_| Lr1120Error::Pin)?;
         Timer::after_millis(10).await;
+        self.clear_chip_state();
+        // The TCXO configuration is expected from the application after a reset (see init_tcxo)
+        self.cold_start = false;
         Ok(())
     }
 
+    /// Forget the configuration tracked by the driver after the chip restarted
+    pub(crate) fn clear_chip_state(&mut self) {
+        self.lora_packet = None;
+        self.lora_cr = None;
+        self.rf_freq = None;
+        self.lf_clk = Some(system::LfClock::Rc);
+        #[cfg(feature = "fsk")] {self.fsk_packet = None;}
+        #[cfg(feature = "gnss")] {
+            self.gnss_mode = None;
+            self.gnss_fallback = false;
+        }
+        #[cfg(feature = "wifi")] {self.wifi_timestamp_thr = Some(wifi_scan::WIFI_TIMESTAMP_THR_DEFAULT_S);}
+        self.cad_exit = lora::ExitMode::CadOnly;
+        self.cad_state = lora::CadState::Idle;
+        self.packet_type = None;
+        self.irq_masks = [status::Intr::default(); 2];
+        self.fallback = radio::FallbackMode::StdbyRc;
+        self.rx_duty_cycle = None;
+    }
+
+    /// Release the peripherals owned by the driver: (nreset, busy, spi, nss), in the order of the constructor
+    /// The chip is left in its current state (see [`sleep_and_release`](Lr1120::sleep_and_release))
+    pub fn release(self) -> (O, M::Pin, SPI, O) {
+        (self.nreset, self.busy, self.spi, self.nss)
+    }
+
+    /// Put the chip in deep sleep (no retention) then release the peripherals owned by the driver
+    /// On error the driver is returned with the error to keep the peripherals
+    pub async fn sleep_and_release(mut self) -> Result<(O, M::Pin, SPI, O), (Self, Lr1120Error)> {
+        match self.set_chip_mode(system::ChipMode::DeepSleep).await {
+            Ok(()) => Ok(self.release()),
+            Err(e) => Err((self, e)),
+        }
+    }
+
     /// Check if the busy pin is high (debug)
     pub fn is_busy(&mut self) -> bool {
         self.busy.is_high().unwrap_or(false)
     }
 
     /// Last status (command status, chip mode, interrupt, ...)
+    /// The first byte is updated by each transaction (command or response), the second one by each command
     pub fn status(&self) -> Status {
-        self.buffer.status()
+        self.status
+    }
+
+    /// Update the last status with the first byte of a response
+    fn updt_rsp_status(&mut self, stat1: u8) {
+        self.status = self.status.with_stat1(stat1);
+        #[cfg(feature = "metrics")] {self.metrics.cmd_status(self.status.cmd());}
     }
 
     /// Read access to internal buffer
@@ -299,23 +629,101 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         self.buffer.data_mut()
     }
 
+    /// First `len` bytes of the response read into the internal buffer by `rsp_rd`
+    /// Return InvalidSize if `len` exceeds the internal buffer
+    pub(crate) fn rsp_data(&self, len: usize) -> Result<&[u8], Lr1120Error> {
+        self.buffer().get(..len).ok_or(Lr1120Error::InvalidSize)
+    }
+
     /// Wait for LR1120 to be ready for a command, i.e. busy pin low
     pub async fn wait_ready(&mut self, timeout: Duration) -> Result<(), Lr1120Error> {
-        M::wait_ready(&mut self.busy, timeout).await
+        #[cfg(feature = "metrics")] let start = Instant::now();
+        let res = M::wait_ready(&mut self.busy, timeout).await;
+        #[cfg(feature = "metrics")] {self.metrics.busy(start.elapsed());}
+        res
+    }
+
+    /// Wait for LR1120 to be ready after a long operation (e.g. GNSS scan), calling `on_progress` with the elapsed time every `period`
+    /// When the callback returns [`BusyProgress::Abort`] the operation is aborted (a 0x00 byte is sent while busy)
+    /// and the function returns `Aborted` once the chip is ready again (up to 3s).
+    /// With the blocking busy pin each period is spent polling: use a short period to keep the callback responsive.
+    pub async fn wait_ready_progress<F>(&mut self, timeout: Duration, period: Duration, mut on_progress: F) -> Result<(), Lr1120Error>
+        where F: FnMut(Duration) -> BusyProgress
+    {
+        let start = Instant::now();
+        loop {
+            let remaining = timeout.checked_sub(start.elapsed()).unwrap_or(Duration::from_ticks(0));
+            match self.wait_ready(period.min(remaining)).await {
+                Err(Lr1120Error::BusyTimeout) if remaining > period => {}
+                res => return res,
+            }
+            if on_progress(start.elapsed()) == BusyProgress::Abort {
+                self.abort_busy().await?;
+                return Err(Lr1120Error::Aborted);
+            }
+        }
+    }
+
+    /// Abort the long operation in progress by sending a 0x00 byte while busy and wait for the chip to be ready
+    pub async fn abort_busy(&mut self) -> Result<(), Lr1120Error> {
+        self.nss.set_low().map_err(|_| Lr1120Error::Pin)?;
+        #[cfg(feature = "metrics")] {self.metrics.spi_transaction(1);}
+        self.spi.write(&[0]).await.map_err(|_| Lr1120Error::Spi)?;
+        self.nss.set_high().map_err(|_| Lr1120Error::Pin)?;
+        self.wait_ready(self.timeouts.abort).await
+    }
+
+    /// Abort whatever long operation keeps the chip busy, e.g. from watchdog or recovery code:
+    /// a 0x00 byte is sent while busy and the chip is waited for (see [`Timeouts::abort`](crate::latency::Timeouts::abort)).
+    /// The interrupts are raised before BUSY goes low: they are read once to identify the aborted operation,
+    /// and the done/abort interrupts of the GNSS and WiFi scans are cleared.
+    /// Return [`AbortedOp::None`] without sending anything if the chip was not busy
+    pub async fn abort_current_operation(&mut self) -> Result<AbortedOp, Lr1120Error> {
+        if !self.is_busy() {
+            return Ok(AbortedOp::None);
+        }
+        self.abort_busy().await?;
+        let (_, intr) = self.get_status().await?;
+        let op = if intr.gnss_abort() || intr.gnss_done() {
+            AbortedOp::Gnss
+        } else if intr.wifi_done() {
+            AbortedOp::Wifi
+        } else {
+            AbortedOp::Other
+        };
+        if op != AbortedOp::Other {
+            self.clear_irqs(Some(Intr::new(IRQ_MASK_GNSS_ABORT | IRQ_MASK_GNSS_DONE | IRQ_MASK_WIFI_DONE))).await?;
+        }
+        Ok(op)
     }
 
     /// Write the beginning of a command, allowing to fill with variable length fields
     pub async fn cmd_wr_begin(&mut self, req: &[u8]) -> Result<(), Lr1120Error> {
+        self.cmd_wr_begin_unchecked(req).await?;
+        let status = self.buffer.cmd_status().check();
+        // Terminate the command on error to keep the SPI framing consistent:
+        // callers return early on error and would otherwise leave NSS low for the next transaction
+        if status.is_err() {
+            self.nss.set_high().map_err(|_| Lr1120Error::Pin)?;
+        }
+        status
+    }
+
+    /// Write the beginning of a command without checking the command status
+    async fn cmd_wr_begin_unchecked(&mut self, req: &[u8]) -> Result<(), Lr1120Error> {
         if req.len() > BUFFER_SIZE {
             return Err(Lr1120Error::InvalidSize);
         }
-        self.wait_ready(Duration::from_millis(100)).await?;
+        self.wait_ready(self.timeouts.cmd).await?;
+        let rsp_buf = self.buffer.0.get_mut(..req.len()).ok_or(Lr1120Error::InvalidSize)?;
         self.nss.set_low().map_err(|_| Lr1120Error::Pin)?;
-        let rsp_buf = &mut self.buffer.0[..req.len()];
+        #[cfg(feature = "metrics")] {self.metrics.spi_transaction(req.len());}
         self.spi
             .transfer(rsp_buf, req).await
             .map_err(|_| Lr1120Error::Spi)?;
-        self.buffer.cmd_status().check()
+        self.status = Status::from_slice(rsp_buf);
+        #[cfg(feature = "metrics")] {self.metrics.cmd_status(self.status.cmd());}
+        Ok(())
     }
 
     /// Write a command
@@ -325,30 +733,86 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         self.nss.set_high().map_err(|_| Lr1120Error::Pin)
     }
 
+    /// Write a batch of write-only commands (e.g. configuration at init)
+    /// Each command is still sent in its own transaction once the chip is ready (BUSY low).
+    /// With `BatchCheck::EndOnly` the status returned by each transaction (status of the previous command)
+    /// is recorded without stopping the batch, and a final GetStatus provides the status of the last command:
+    /// the first failing command is reported with `BatchFailed`. No command is ever sent twice.
+    ///
+    /// The commands bypass the state tracked by the driver: before sending them, the radio configuration is forgotten
+    /// (packet type, LoRa/FSK packet parameters, coding rate, RF frequency, RX duty cycle and the `shadow` record)
+    /// and is read back from the chip or must be set again. DIO interrupts (SetDioIrqParams) and RF switches
+    /// (SetDioAsRfSwitch) found in the batch update the interrupt masks, the DIO usage and the RF switch configuration.
+    pub async fn cmd_wr_batch(&mut self, cmds: &[&[u8]], check: BatchCheck) -> Result<(), Lr1120Error> {
+        self.batch_sync_state(cmds);
+        if check == BatchCheck::EachCmd {
+            for req in cmds {
+                self.cmd_wr(req).await?;
+            }
+            return Ok(());
+        }
+        let mut failed = None;
+        for (index, req) in cmds.iter().enumerate() {
+            self.cmd_wr_begin_unchecked(req).await?;
+            self.nss.set_high().map_err(|_| Lr1120Error::Pin)?;
+            if failed.is_none() && index > 0 && !self.buffer.status().is_ok() {
+                failed = Some(index - 1);
+            }
+        }
+        // Status of the last command is only available on the next SPI transaction
+        self.cmd_wr_begin_unchecked(&cmd::cmd_system::get_status_req()).await?;
+        self.nss.set_high().map_err(|_| Lr1120Error::Pin)?;
+        if failed.is_none() && !cmds.is_empty() && !self.buffer.status().is_ok() {
+            failed = Some(cmds.len() - 1);
+        }
+        self.wait_ready(self.timeouts.rsp).await?;
+        let mut rsp = cmd::cmd_system::StatusRsp::new();
+        self.rsp_rd_to(rsp.as_mut()).await?;
+        match failed {
+            Some(index) => Err(Lr1120Error::BatchFailed { index }),
+            None => Ok(()),
+        }
+    }
+
     /// Write a command and read response
     /// Rsp must be n bytes where n is the number of expected byte
     pub async fn cmd_rd(&mut self, req: &[u8], rsp: &mut [u8]) -> Result<(), Lr1120Error> {
-        self.cmd_wr(req).await?;
+        // A failure status refers to the previous command: the response must still be read,
+        // otherwise the next command would be clocked out while the chip sends the response.
+        // Only a bus error (pin or SPI) aborts the read
+        let status = self.cmd_wr(req).await;
+        if matches!(status, Err(Lr1120Error::Pin | Lr1120Error::Spi)) {
+            return status;
+        }
         // Wait for busy to go down before reading the response
         // Some command can have large delay: temperature measurement with highest resolution (13b) takes more than 270us
-        self.wait_ready(Duration::from_millis(1)).await?;
+        self.wait_ready(self.timeouts.rsp).await?;
         // Read response by transfering a buffer starting with two 0 and replacing it by the read bytes
         self.nss.set_low().map_err(|_| Lr1120Error::Pin)?;
+        #[cfg(feature = "metrics")] {self.metrics.spi_transaction(rsp.len());}
         self.spi
             .transfer_in_place(rsp).await
             .map_err(|_| Lr1120Error::Spi)?;
         self.nss.set_high().map_err(|_| Lr1120Error::Pin)?;
         // #[cfg(feature = "defmt")]{defmt::info!("[CMD RD] {:02x} => {:02x}", req, rsp);}
-        // Save the first two bytes from the response to keep the command status
+        // Save the first byte from the response to keep the command status
         self.buffer.updt_status(rsp);
-        self.buffer.cmd_status().check()
+        self.updt_rsp_status(self.buffer.0[0]);
+        status.and(self.buffer.cmd_status().check())
     }
 
     /// Write a command with variable length payload
     /// Any feedback data will be available in side the local buffer
     pub async fn cmd_data_wr(&mut self, opcode: &[u8], data: &[u8]) -> Result<(), Lr1120Error> {
+        if data.len() > BUFFER_SIZE {
+            return Err(Lr1120Error::InvalidSize);
+        }
         self.cmd_wr_begin(opcode).await?;
-        let rsp = &mut self.buffer.data_mut()[..data.len()];
+        let Some(rsp) = self.buffer.data_mut().get_mut(..data.len()) else {
+            self.nss.set_high().map_err(|_| Lr1120Error::Pin)?;
+            return Err(Lr1120Error::InvalidSize);
+        };
+        #[cfg(feature = "metrics")] {self.metrics.spi_bytes(data.len());}
         self.spi
             .transfer(rsp, data).await
             .map_err(|_| Lr1120Error::Spi)?;
@@ -358,6 +822,7 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// Write a command with variable length payload, and save result provided buffer
     pub async fn cmd_data_rw(&mut self, opcode: &[u8], data: &mut [u8]) -> Result<(), Lr1120Error> {
         self.cmd_wr_begin(opcode).await?;
+        #[cfg(feature = "metrics")] {self.metrics.spi_bytes(data.len());}
         self.spi
             .transfer_in_place(data).await
             .map_err(|_| Lr1120Error::Spi)?;
@@ -366,13 +831,22 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
 
     /// Read response from SPI into local buffer
     pub async fn rsp_rd(&mut self, rsp_len: usize) -> Result<(), Lr1120Error> {
+        if rsp_len > RSP_MAX_LEN {
+            return Err(Lr1120Error::InvalidSize);
+        }
+        // The bytes sent while reading a response must be NOP (0x00)
+        self.buffer.nop();
         self.buffer.clear(rsp_len);
+        // Response starts with the status byte (first byte of the buffer, used by `cmd_status`),
+        // followed by the data available with `buffer()`
+        let rsp_buf = self.buffer.0.get_mut(..=rsp_len).ok_or(Lr1120Error::InvalidSize)?;
         self.nss.set_low().map_err(|_| Lr1120Error::Pin)?;
-        // Add extra byte on the respnse length to take into acocunt the first status byte ?
+        #[cfg(feature = "metrics")] {self.metrics.spi_transaction(rsp_len + 1);}
         self.spi
-            .transfer_in_place(&mut self.buffer.as_mut()[..rsp_len+1]).await
+            .transfer_in_place(rsp_buf).await
             .map_err(|_| Lr1120Error::Spi)?;
         self.nss.set_high().map_err(|_| Lr1120Error::Pin)?;
+        self.updt_rsp_status(self.buffer.0[0]);
         self.buffer.cmd_status().check()
     }
 
@@ -380,24 +854,65 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// First two bytes must be initialized to 0
     pub async fn rsp_rd_to(&mut self, rsp:  &mut [u8]) -> Result<(), Lr1120Error> {
         self.nss.set_low().map_err(|_| Lr1120Error::Pin)?;
+        #[cfg(feature = "metrics")] {self.metrics.spi_transaction(rsp.len());}
         self.spi
             .transfer_in_place(rsp).await
             .map_err(|_| Lr1120Error::Spi)?;
         self.nss.set_high().map_err(|_| Lr1120Error::Pin)?;
         // Save the first byte from the response to keep the command status
         self.buffer.updt_status(rsp);
+        self.updt_rsp_status(self.buffer.0[0]);
         self.buffer.cmd_status().check()
     }
 
+    /// Read a response from SPI and stream its data into a writer by chunks, without length limit
+    /// Nothing is written if the response status reports a failure. Return Io on writer error
+    pub async fn rsp_rd_into<W: Write>(&mut self, rsp_len: usize, writer: &mut W) -> Result<(), Lr1120Error> {
+        self.nss.set_low().map_err(|_| Lr1120Error::Pin)?;
+        #[cfg(feature = "metrics")] {self.metrics.spi_transaction(rsp_len + 1);}
+        let res = self.rsp_stream(rsp_len, writer).await;
+        // Always terminate the transaction, even when the writer failed
+        self.nss.set_high().map_err(|_| Lr1120Error::Pin)?;
+        res
+    }
+
+    /// Transfer the status byte then the response data by chunks (NSS already low)
+    async fn rsp_stream<W: Write>(&mut self, rsp_len: usize, writer: &mut W) -> Result<(), Lr1120Error> {
+        let mut stat1 = [0u8];
+        self.spi
+            .transfer_in_place(&mut stat1).await
+            .map_err(|_| Lr1120Error::Spi)?;
+        self.buffer.updt_status(&stat1);
+        self.updt_rsp_status(stat1[0]);
+        self.buffer.cmd_status().check()?;
+        let mut chunk = [0u8; RSP_CHUNK_LEN];
+        let mut remaining = rsp_len;
+        while remaining > 0 {
+            let data = chunk.get_mut(..remaining.min(RSP_CHUNK_LEN)).unwrap_or_default();
+            data.fill(0);
+            self.spi
+                .transfer_in_place(data).await
+                .map_err(|_| Lr1120Error::Spi)?;
+            writer.write_all(data).await.map_err(|_| Lr1120Error::Io)?;
+            remaining -= data.len();
+        }
+        Ok(())
+    }
+
     /// Send content of the local buffer as a command
+    /// Return InvalidSize if `len` exceeds the internal buffer
     pub async fn cmd_buf_wr(&mut self, len: usize) -> Result<(), Lr1120Error> {
         // #[cfg(feature = "defmt")]{defmt::info!("[CMD BUF WR] {:02x}", self.buffer.data_mut()[..len]);}
-        self.wait_ready(Duration::from_millis(100)).await?;
+        self.wait_ready(self.timeouts.cmd).await?;
+        let cmd = self.buffer.as_mut().get_mut(..len).ok_or(Lr1120Error::InvalidSize)?;
         self.nss.set_low().map_err(|_| Lr1120Error::Pin)?;
+        #[cfg(feature = "metrics")] {self.metrics.spi_transaction(len);}
         self.spi
-            .transfer_in_place(&mut self.buffer.as_mut()[..len]).await
+            .transfer_in_place(cmd).await
             .map_err(|_| Lr1120Error::Spi)?;
         self.nss.set_high().map_err(|_| Lr1120Error::Pin)?;
+        self.status = self.buffer.status();
+        #[cfg(feature = "metrics")] {self.metrics.cmd_status(self.status.cmd());}
         self.buffer.cmd_status().check()
     }
 
@@ -406,23 +921,27 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         self.cmd_buf_wr(len).await?;
         // Wait for busy to go down before reading the response
         // Some command can have large delay: temperature measurement with highest resolution (13b) takes more than 270us
-        self.wait_ready(Duration::from_millis(1)).await?;
+        self.wait_ready(self.timeouts.rsp).await?;
         // Read response by transfering a buffer full of 0 and replacing it by the read bytes
         self.nss.set_low().map_err(|_| Lr1120Error::Pin)?;
+        #[cfg(feature = "metrics")] {self.metrics.spi_transaction(rsp.len());}
         self.spi
             .transfer_in_place(rsp).await
             .map_err(|_| Lr1120Error::Spi)?;
         self.nss.set_high().map_err(|_| Lr1120Error::Pin)?;
         // Save the first byte from the response to keep the command status
         self.buffer.updt_status(rsp);
+        self.updt_rsp_status(self.buffer.0[0]);
         self.buffer.cmd_status().check()
     }
 
     /// Wake-up the chip from a sleep mode (Set NSS low until busy goes low)
+    /// Also to be called after a wake-up on timeout, to apply the workarounds of a cold start (see [`errata`])
     pub async fn wake_up(&mut self) -> Result<(), Lr1120Error> {
         self.nss.set_low().map_err(|_| Lr1120Error::Pin)?;
-        self.wait_ready(Duration::from_millis(100)).await?;
-        self.nss.set_high().map_err(|_| Lr1120Error::Pin)
+        self.wait_ready(self.timeouts.cmd).await?;
+        self.nss.set_high().map_err(|_| Lr1120Error::Pin)?;
+        self.errata_after_wakeup().await
     }
 
 }
@@ -0,0 +1,271 @@
+//! # Link quality estimation
+//!
+//! [`LinkQuality`] combines the SNR/RSSI of the packets received with the error counters of `GetStats`
+//! (CRC errors, header errors and false synchronisations) into a smoothed score,
+//! and suggests a spreading factor adjustment for point-to-point LoRa links (pseudo-ADR).
+//!
+//! The suggestion is never applied automatically: on a point-to-point link both ends must switch together,
+//! so the application decides when to apply it (e.g. after signaling it to the peer) using [`LinkQuality::apply`].
+//!
+//! ## Example
+//! ```rust,no_run
+//! let mut lq = LinkQuality::new(LinkQualityCfg::default(), modulation.sf);
+//! // After each reception
+//! match lr1120.update_link_quality(&mut lq, intr).await? {
+//!     DrAdjust::Keep => {}
+//!     adj => {
+//!         notify_peer(adj);
+//!         lq.apply(adj, &mut modulation);
+//!         lr1120.set_lora_modulation(&modulation).await?;
+//!     }
+//! }
+//! ```
+//!
+//! ## Available Methods
+//! - [`update_link_quality`](Lr1120::update_link_quality) - Update a [`LinkQuality`] with the last packet status and the RX statistics
+//! - [`LinkQuality::rssi_margin_db`] - Smoothed RSSI margin above the sensitivity (see [`sensitivity`](crate::sensitivity))
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiBus;
+
+use super::{BusyPin, Lr1120, Lr1120Error};
+use super::lora::{LoraBw, LoraModulationParams, LoraPacketStatusRsp, Sf};
+use super::radio::LoraStats;
+use super::sensitivity::{lora_snr_floor_cdb, SensitivityModel};
+use super::status::Intr;
+
+/// Fractional bits of the smoothed values
+const EMA_FRAC: u32 = 4;
+
+/// Link quality estimator settings
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LinkQualityCfg {
+    /// Smoothing factor: each new sample has a weight of 1/2^n (n clamped to 15)
+    pub smoothing: u8,
+    /// SNR margin (dB) above the demodulation floor to suggest a faster spreading factor
+    pub margin_up_db: u8,
+    /// SNR margin (dB) above the demodulation floor below which a slower spreading factor is suggested
+    pub margin_down_db: u8,
+    /// Packet error ratio (%) above which a slower spreading factor is suggested
+    pub max_per: u8,
+    /// Number of packets received before suggesting a change
+    pub min_packets: u16,
+    /// Fastest spreading factor allowed
+    pub sf_min: Sf,
+    /// Slowest spreading factor allowed
+    pub sf_max: Sf,
+}
+
+impl Default for LinkQualityCfg {
+    fn default() -> Self {
+        Self {
+            smoothing: 3,
+            margin_up_db: 10,
+            margin_down_db: 3,
+            max_per: 10,
+            min_packets: 8,
+            sf_min: Sf::Sf7,
+            sf_max: Sf::Sf12,
+        }
+    }
+}
+
+/// Data rate adjustment suggested by the link quality estimator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DrAdjust {
+    /// Keep the current spreading factor
+    Keep,
+    /// Link margin is large: switch to a lower spreading factor
+    Faster(Sf),
+    /// Link margin is too small or too many errors: switch to a higher spreading factor
+    Slower(Sf),
+}
+
+/// Spreading factor one step faster
+fn sf_faster(sf: Sf) -> Option<Sf> {
+    match sf {
+        Sf::Sf5  => None,
+        Sf::Sf6  => Some(Sf::Sf5),
+        Sf::Sf7  => Some(Sf::Sf6),
+        Sf::Sf8  => Some(Sf::Sf7),
+        Sf::Sf9  => Some(Sf::Sf8),
+        Sf::Sf10 => Some(Sf::Sf9),
+        Sf::Sf11 => Some(Sf::Sf10),
+        Sf::Sf12 => Some(Sf::Sf11),
+    }
+}
+
+/// Spreading factor one step slower
+fn sf_slower(sf: Sf) -> Option<Sf> {
+    match sf {
+        Sf::Sf5  => Some(Sf::Sf6),
+        Sf::Sf6  => Some(Sf::Sf7),
+        Sf::Sf7  => Some(Sf::Sf8),
+        Sf::Sf8  => Some(Sf::Sf9),
+        Sf::Sf9  => Some(Sf::Sf10),
+        Sf::Sf10 => Some(Sf::Sf11),
+        Sf::Sf11 => Some(Sf::Sf12),
+        Sf::Sf12 => None,
+    }
+}
+
+/// Minimum SNR (in 0.25dB) to demodulate a spreading factor (see [`LORA_SNR_FLOOR_CDB`](crate::sensitivity::LORA_SNR_FLOOR_CDB))
+pub fn snr_floor_qdb(sf: Sf) -> i16 {
+    lora_snr_floor_cdb(sf) / 25
+}
+
+/// Smoothed link quality of a LoRa point-to-point link
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LinkQuality {
+    cfg: LinkQualityCfg,
+    sf: Sf,
+    /// Smoothed SNR in 0.25dB with EMA_FRAC fractional bits
+    snr: Option<i32>,
+    /// Smoothed RSSI in dBm with EMA_FRAC fractional bits
+    rssi: Option<i32>,
+    /// Smoothed packet error ratio in % with EMA_FRAC fractional bits
+    per: Option<i32>,
+    /// Number of packets received since the last adjustment
+    nb_pkt: u16,
+    /// Last RX statistics: received, CRC errors, header errors, false sync
+    last_stats: Option<[u16; 4]>,
+}
+
+/// Exponential moving average with EMA_FRAC fractional bits
+fn ema(avg: &mut Option<i32>, sample: i32, shift: u8) {
+    let sample = sample << EMA_FRAC;
+    let shift = shift.min(15);
+    *avg = Some(match *avg {
+        Some(v) => v + ((sample - v) >> shift),
+        None => sample,
+    });
+}
+
+impl LinkQuality {
+    /// Create an estimator for a link currently using spreading factor `sf`
+    pub fn new(cfg: LinkQualityCfg, sf: Sf) -> Self {
+        Self { cfg, sf, snr: None, rssi: None, per: None, nb_pkt: 0, last_stats: None }
+    }
+
+    /// Spreading factor currently used
+    pub fn sf(&self) -> Sf {
+        self.sf
+    }
+
+    /// Add the status of a packet received without error
+    pub fn add_packet(&mut self, status: &LoraPacketStatusRsp) {
+        let shift = self.cfg.smoothing;
+        ema(&mut self.snr, status.snr_pkt() as i8 as i32, shift);
+        ema(&mut self.rssi, status.rssi_pkt_dbm() as i32, shift);
+        self.nb_pkt = self.nb_pkt.saturating_add(1);
+    }
+
+    /// Add RX statistics: the error ratio is computed on the counters increment since the previous call
+    /// False synchronisations and header errors are counted as lost packets
+    pub fn add_stats(&mut self, stats: &LoraStats) {
+        let cnt = [stats.pkt_rx, stats.crc_error, stats.header_error, stats.false_sync];
+        if let Some(last) = self.last_stats {
+            let mut delta = cnt;
+            delta.iter_mut().zip(last).for_each(|(d, l)| *d = d.wrapping_sub(l));
+            let [rx, crc, hdr, sync] = delta;
+            let total = rx as i32 + hdr as i32 + sync as i32;
+            if total > 0 {
+                let errors = crc as i32 + hdr as i32 + sync as i32;
+                ema(&mut self.per, (errors * 100 / total).min(100), self.cfg.smoothing);
+            }
+        }
+        self.last_stats = Some(cnt);
+    }
+
+    /// Smoothed SNR in dB
+    pub fn snr_db(&self) -> Option<i16> {
+        self.snr.map(|v| (v >> (EMA_FRAC + 2)) as i16)
+    }
+
+    /// Smoothed RSSI in dBm
+    pub fn rssi_dbm(&self) -> Option<i16> {
+        self.rssi.map(|v| (v >> EMA_FRAC) as i16)
+    }
+
+    /// Smoothed packet error ratio in %
+    pub fn per(&self) -> Option<u8> {
+        self.per.map(|v| (v >> EMA_FRAC) as u8)
+    }
+
+    /// SNR margin above the demodulation floor of the current spreading factor (in dB)
+    pub fn margin_db(&self) -> Option<i16> {
+        self.snr.map(|v| ((v >> EMA_FRAC) as i16 - snr_floor_qdb(self.sf)) / 4)
+    }
+
+    /// Smoothed RSSI margin above the sensitivity of the current spreading factor with bandwidth `bw` (in dB)
+    pub fn rssi_margin_db(&self, model: &SensitivityModel, bw: LoraBw) -> Option<i16> {
+        self.rssi_dbm().map(|rssi| model.lora_margin_cdb(self.sf, bw, rssi) / 100)
+    }
+
+    /// Link score from 0 (unusable) to 100: SNR margin (20dB or more gives 100) weighted by the packet success ratio
+    pub fn score(&self) -> Option<u8> {
+        let margin = self.margin_db()?.clamp(0, 20) as u16 * 5;
+        let success = 100 - self.per().unwrap_or(0).min(100) as u16;
+        Some((margin * success / 100) as u8)
+    }
+
+    /// Suggested data rate adjustment
+    /// No change is suggested until `min_packets` packets were received since the last adjustment
+    pub fn suggestion(&self) -> DrAdjust {
+        let Some(margin) = self.margin_db() else {
+            return DrAdjust::Keep;
+        };
+        if self.nb_pkt < self.cfg.min_packets {
+            return DrAdjust::Keep;
+        }
+        let per = self.per().unwrap_or(0);
+        if per > self.cfg.max_per || margin < self.cfg.margin_down_db as i16 {
+            match sf_slower(self.sf) {
+                Some(sf) if sf <= self.cfg.sf_max => DrAdjust::Slower(sf),
+                _ => DrAdjust::Keep,
+            }
+        } else if margin > self.cfg.margin_up_db as i16 && per <= self.cfg.max_per / 2 {
+            match sf_faster(self.sf) {
+                Some(sf) if sf >= self.cfg.sf_min => DrAdjust::Faster(sf),
+                _ => DrAdjust::Keep,
+            }
+        } else {
+            DrAdjust::Keep
+        }
+    }
+
+    /// Apply an adjustment to modulation parameters (spreading factor and LDRO)
+    /// and restart the packet count used to validate the next suggestion
+    /// Return false if there is nothing to change
+    pub fn apply(&mut self, adj: DrAdjust, params: &mut LoraModulationParams) -> bool {
+        let sf = match adj {
+            DrAdjust::Keep => return false,
+            DrAdjust::Faster(sf) | DrAdjust::Slower(sf) => sf,
+        };
+        *params = LoraModulationParams { sf, ..*params }.with_auto_ldro();
+        self.sf = sf;
+        self.nb_pkt = 0;
+        true
+    }
+}
+
+impl<O,SPI, M> Lr1120<O,SPI, M> where
+    O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+{
+
+    /// Update a link quality estimator with the last packet status (if received without error) and the RX statistics
+    /// `intr` is the interrupt status read at the end of the reception. Return the suggested data rate adjustment
+    pub async fn update_link_quality(&mut self, lq: &mut LinkQuality, intr: Intr) -> Result<DrAdjust, Lr1120Error> {
+        if intr.rx_done() && !intr.crc_error() && !intr.header_err() {
+            let status = self.get_lora_packet_status().await?;
+            lq.add_packet(&status);
+        }
+        let stats = self.get_lora_stats().await?;
+        lq.add_stats(&stats);
+        Ok(lq.suggestion())
+    }
+
+}
@@ -0,0 +1,176 @@
+//! # Linux support (feature `std`)
+//!
+//! Adapters to drive an LR1120 from a Linux host (gateway, PC with an evaluation board, HIL test rig)
+//! using spidev for the SPI bus and the GPIO character device for reset, busy and NSS pins.
+//!
+//! The adapters are built directly on `spidev` and `gpio-cdev` rather than on `linux-embedded-hal`, which wraps the same crates:
+//! its 0.3 release implements the embedded-hal 0.2 traits, while the driver needs embedded-hal 1.0 pins
+//! and an `embedded_hal_async::spi::SpiBus`. Only the few calls used by the driver are wrapped here.
+//!
+//! The `std` feature enables the `embassy-time` std driver, so the driver runs with any async runtime (tokio, async-std, ...).
+//! Since `embassy-time` accepts only one tick rate, the default features must be disabled:
+//! ```toml
+//! lr1120 = { version = "0.5", default-features = false, features = ["std"] }
+//! ```
+//!
+//! ## Example
+//! ```rust,no_run
+//! let mut lr1120 = Lr1120::open_linux(&LinuxCfg::new("/dev/spidev0.0", "/dev/gpiochip0", 17, 27, 8))?;
+//! lr1120.reset().await?;
+//! ```
+
+use std::path::PathBuf;
+
+use embedded_hal::digital::{self, InputPin, OutputPin};
+use embedded_hal::spi;
+use embedded_hal_async::spi::SpiBus;
+use gpio_cdev::{Chip, LineHandle, LineRequestFlags};
+use spidev::{SpiModeFlags, Spidev, SpidevOptions, SpidevTransfer};
+
+use super::{BusyBlocking, Lr1120, Lr1120Error};
+
+/// Consumer name used when requesting GPIO lines
+const GPIO_CONSUMER: &str = "lr1120";
+
+/// Linux device configuration
+#[derive(Debug, Clone)]
+pub struct LinuxCfg {
+    /// SPI device (e.g. /dev/spidev0.0)
+    pub spi_dev: PathBuf,
+    /// SPI clock frequency in Hz
+    pub spi_speed_hz: u32,
+    /// GPIO chip (e.g. /dev/gpiochip0)
+    pub gpio_chip: PathBuf,
+    /// GPIO line offset of the reset pin
+    pub nreset: u32,
+    /// GPIO line offset of the busy pin
+    pub busy: u32,
+    /// GPIO line offset of the NSS pin
+    pub nss: u32,
+}
+
+impl LinuxCfg {
+    /// Create a Linux configuration with a 4MHz SPI clock
+    pub fn new(spi_dev: &str, gpio_chip: &str, nreset: u32, busy: u32, nss: u32) -> Self {
+        Self {
+            spi_dev: spi_dev.into(),
+            spi_speed_hz: 4_000_000,
+            gpio_chip: gpio_chip.into(),
+            nreset, busy, nss,
+        }
+    }
+}
+
+/// SPI bus over spidev
+/// Chip select is disabled on the spidev side since NSS is driven as a GPIO by the driver
+pub struct LinuxSpi(Spidev);
+
+impl LinuxSpi {
+    /// Open and configure a spidev device (mode 0, 8 bits, no chip select)
+    pub fn open(cfg: &LinuxCfg) -> Result<Self, Lr1120Error> {
+        let mut spi = Spidev::open(&cfg.spi_dev).map_err(|_| Lr1120Error::Spi)?;
+        let options = SpidevOptions::new()
+            .bits_per_word(8)
+            .max_speed_hz(cfg.spi_speed_hz)
+            .mode(SpiModeFlags::SPI_MODE_0 | SpiModeFlags::SPI_NO_CS)
+            .build();
+        spi.configure(&options).map_err(|_| Lr1120Error::Spi)?;
+        Ok(Self(spi))
+    }
+
+    fn xfer(&mut self, mut transfer: SpidevTransfer) -> Result<(), spi::ErrorKind> {
+        self.0.transfer(&mut transfer).map_err(|_| spi::ErrorKind::Other)
+    }
+}
+
+impl spi::ErrorType for LinuxSpi {
+    type Error = spi::ErrorKind;
+}
+
+impl SpiBus<u8> for LinuxSpi {
+    async fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        self.xfer(SpidevTransfer::read(words))
+    }
+
+    async fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.xfer(SpidevTransfer::write(words))
+    }
+
+    async fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        // Spidev requires buffers of the same size
+        let mut tx = write.to_vec();
+        tx.resize(read.len().max(write.len()), 0);
+        let mut rx = vec![0; tx.len()];
+        self.xfer(SpidevTransfer::read_write(&tx, &mut rx))?;
+        rx.truncate(read.len());
+        read.copy_from_slice(&rx);
+        Ok(())
+    }
+
+    async fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        let tx = words.to_vec();
+        self.xfer(SpidevTransfer::read_write(&tx, words))
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// GPIO line from the Linux GPIO character device
+pub struct LinuxPin(LineHandle);
+
+impl LinuxPin {
+    /// Request a line as output with an initial level
+    pub fn output(chip: &mut Chip, offset: u32, high: bool) -> Result<Self, Lr1120Error> {
+        let line = chip.get_line(offset).map_err(|_| Lr1120Error::Pin)?;
+        let handle = line.request(LineRequestFlags::OUTPUT, high as u8, GPIO_CONSUMER).map_err(|_| Lr1120Error::Pin)?;
+        Ok(Self(handle))
+    }
+
+    /// Request a line as input
+    pub fn input(chip: &mut Chip, offset: u32) -> Result<Self, Lr1120Error> {
+        let line = chip.get_line(offset).map_err(|_| Lr1120Error::Pin)?;
+        let handle = line.request(LineRequestFlags::INPUT, 0, GPIO_CONSUMER).map_err(|_| Lr1120Error::Pin)?;
+        Ok(Self(handle))
+    }
+}
+
+impl digital::ErrorType for LinuxPin {
+    type Error = digital::ErrorKind;
+}
+
+impl OutputPin for LinuxPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.set_value(0).map_err(|_| digital::ErrorKind::Other)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.set_value(1).map_err(|_| digital::ErrorKind::Other)
+    }
+}
+
+impl InputPin for LinuxPin {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.0.get_value().map(|v| v != 0).map_err(|_| digital::ErrorKind::Other)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.is_high().map(|v| !v)
+    }
+}
+
+/// LR1120 driver running on Linux
+pub type Lr1120Linux = Lr1120<LinuxPin, LinuxSpi, BusyBlocking<LinuxPin>>;
+
+impl Lr1120Linux {
+    /// Open SPI and GPIO devices and create a driver (busy pin is polled)
+    pub fn open_linux(cfg: &LinuxCfg) -> Result<Self, Lr1120Error> {
+        let spi = LinuxSpi::open(cfg)?;
+        let mut chip = Chip::new(&cfg.gpio_chip).map_err(|_| Lr1120Error::Pin)?;
+        let nreset = LinuxPin::output(&mut chip, cfg.nreset, true)?;
+        let busy = LinuxPin::input(&mut chip, cfg.busy)?;
+        let nss = LinuxPin::output(&mut chip, cfg.nss, true)?;
+        Ok(Self::new_blocking(nreset, busy, spi, nss))
+    }
+}
@@ -27,7 +27,8 @@
 //!
 //! ### Core LoRa Methods
 //! - [`set_lora_modulation`](Lr1120::set_lora_modulation) - Configure spreading factor, bandwidth, coding rate, and LDRO
-//! - [`set_lora_packet`](Lr1120::set_lora_packet) - Set packet parameters (preamble, payload length, header type, CRC)
+//! - [`set_lora_packet`](Lr1120::set_lora_packet) - Set packet parameters (preamble, payload length, header type, CRC),
+//!   checking the payload length against long interleaving coding rates (8 to 253 bytes with CRC, 255 without)
 //! - [`set_lora_syncword`](Lr1120::set_lora_syncword) - Set syncword using legacy 1-byte format
 //! - [`set_lora_syncword_ext`](Lr1120::set_lora_syncword_ext) - Set syncword using extended 2-byte format
 //! - [`set_lora_synch_timeout`](Lr1120::set_lora_synch_timeout) - Configure synchronization timeout
@@ -35,10 +36,13 @@
 //! ### Status and Statistics
 //! - [`get_lora_rx_header_info`](Lr1120::get_lora_rx_header_info) - Get RX header information (CRC and coding rate)
 //! - [`get_lora_packet_status`](Lr1120::get_lora_packet_status) - Get RSSI/SNR on the last received packet
+//! - [`get_lora_payload`](Lr1120::get_lora_payload) - Read last payload received, optionally even with a CRC error
 //!
 //! ### Channel Activity Detection (CAD)
 //! - [`set_lora_cad_params`](Lr1120::set_lora_cad_params) - Configure CAD parameters for listen-before-talk
 //! - [`set_lora_cad`](Lr1120::set_lora_cad) - Start channel activity detection
+//! - [`cad_stats`](Lr1120::cad_stats) - CAD outcome counters (detected, clear, timeout) updated from [`get_status`](Lr1120::get_status)
+//! - [`reset_cad_stats`](Lr1120::reset_cad_stats) - Reset the CAD outcome counters
 //!
 //! ### Misc Features
 //! - [`comp_sx127x_sf6`](Lr1120::comp_sx127x_sf6) - Enable SX127x compatibility for SF6
@@ -48,20 +52,30 @@
 //! - [`set_lora_sidedet_syncword`](Lr1120::set_lora_sidedet_syncword) - Configure side-detector syncwords
 //!
 //! ### Ranging Operations
-//! - [`set_ranging_dev_addr`](Lr1120::set_ranging_dev_addr) - Set device address for ranging
-//! - [`set_ranging_req_addr`](Lr1120::set_ranging_req_addr) - Set request address for ranging
-//! - [`set_ranging_txrx_delay`](Lr1120::set_ranging_txrx_delay) - Set ranging calibration delay
-//! - [`get_ranging_base_delay`](Lr1120::get_ranging_base_delay) - Get base delay for ranging based on bandwidth and SF
-//! - [`set_ranging_params`](Lr1120::set_ranging_params) - Configure ranging parameters
-//! - [`get_ranging_result`](Lr1120::get_ranging_result) - Get basic ranging results (distance)
-//! - [`get_ranging_rssi`](Lr1120::get_ranging_rssi) - Get RSSI measured during ranging
+//! See the [`ranging`](crate::ranging) module (`ranging` feature).
 
 use embedded_hal::digital::OutputPin;
 use embedded_hal_async::spi::SpiBus;
 
 pub use super::cmd::cmd_lora::*;
 pub use super::cmd::cmd_regmem::*;
+#[cfg(feature = "ranging")]
+pub use crate::ranging::{RangingDelayTable, RangingFei, RANGING_DELAY_SUB_GHZ, RANGING_HF_MIN_FREQ, ranging_addr_cover, ranging_base_delay};
 use super::{BusyPin, Lr1120, Lr1120Error};
+use super::status::Intr;
+use super::radio::{rssi_to_dbm, PacketType};
+
+impl LoraPacketStatusRsp {
+    /// RSSI averaged on the whole packet in dBm
+    pub fn rssi_pkt_dbm(&self) -> i16 {
+        rssi_to_dbm(self.rssi_pkt())
+    }
+
+    /// Estimation of the LoRa signal RSSI in dBm
+    pub fn signal_rssi_dbm(&self) -> i16 {
+        rssi_to_dbm(self.signal_rssi())
+    }
+}
 
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -80,21 +94,50 @@ pub struct LoraModulationParams {
 impl LoraModulationParams {
     /// Modulation with default coderate (4/5) and LDRO based on SF/BW
     pub fn basic(sf: Sf, bw: LoraBw) -> Self {
-        let ldro_en = (sf==Sf::Sf12 && !matches!(bw,LoraBw::Bw1000|LoraBw::Bw500))
-                    || (sf==Sf::Sf11 && !matches!(bw,LoraBw::Bw1000|LoraBw::Bw500|LoraBw::Bw250) );
         Self {
             sf, bw,
             cr: LoraCr::Cr1Ham45Si,
-            ldro: if ldro_en {Ldro::On} else {Ldro::Off},
+            ldro: Ldro::required_for(sf, bw),
         }
     }
 
-    /// Modulation with default coderate (4/5) and LDRO based on SF/BW
+    /// Modulation with all parameters set manually
+    /// Use `with_auto_ldro` to set LDRO based on SF/BW
     pub fn new(sf: Sf, bw: LoraBw, cr: LoraCr, ldro: Ldro) -> Self {
         Self {sf, bw, cr, ldro}
     }
+
+    /// Update LDRO based on SF/BW
+    pub fn with_auto_ldro(self) -> Self {
+        Self {ldro: Ldro::required_for(self.sf, self.bw), ..self}
+    }
 }
 
+impl Ldro {
+    /// Return LDRO setting required for a given SF/BW:
+    /// enabled for SF12 below 500kHz and SF11 below 250kHz
+    pub fn required_for(sf: Sf, bw: LoraBw) -> Self {
+        let ldro_en = (sf==Sf::Sf12 && !matches!(bw,LoraBw::Bw1000|LoraBw::Bw500))
+                    || (sf==Sf::Sf11 && !matches!(bw,LoraBw::Bw1000|LoraBw::Bw500|LoraBw::Bw250) );
+        if ldro_en {Ldro::On} else {Ldro::Off}
+    }
+}
+
+/// Payload of the last packet received
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LoraRxPayload<'a> {
+    /// Payload content
+    pub data: &'a [u8],
+    /// False when the payload was received with a CRC error
+    pub crc_ok: bool,
+}
+
+/// Minimum payload length (in byte) with a long interleaving coding rate
+pub const LORA_LI_PLD_LEN_MIN: u8 = 8;
+/// Maximum payload length (in byte) with a long interleaving coding rate and the CRC enabled
+pub const LORA_LI_PLD_LEN_MAX_CRC: u8 = 253;
+
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 /// LoRa Modulation parameters: SF, Bandwidth, Code-rate, LDRO
@@ -127,6 +170,22 @@ impl LoraPacketParams {
     pub fn new(pbl_len: u16, payload_len: u8, header_type: HeaderType, crc_en: bool, invert_iq: bool) -> Self {
         Self {pbl_len, payload_len, header_type, crc_en, invert_iq}
     }
+
+    /// Check the payload length against the coding rate:
+    /// with long interleaving (`Cr*Li`) the payload must be between 8 and 253 bytes with CRC, 255 without.
+    /// The payload length is the length sent in TX and, in implicit header, the exact length expected in RX:
+    /// both sides of an implicit header link must use the same (valid) length and coding rate.
+    /// Return InvalidSize when the length is out of range
+    pub fn validate(&self, cr: LoraCr) -> Result<(), Lr1120Error> {
+        if !cr.is_li() {
+            return Ok(());
+        }
+        let max_len = if self.crc_en {LORA_LI_PLD_LEN_MAX_CRC} else {u8::MAX};
+        if !(LORA_LI_PLD_LEN_MIN..=max_len).contains(&self.payload_len) {
+            return Err(Lr1120Error::InvalidSize);
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -164,6 +223,41 @@ pub fn lora_cad_thr(sf: Sf, nb_symbols: u8) -> u8 {
     base_symb + offset_sf
 }
 
+/// Counters of CAD outcomes, e.g. for listen-before-talk compliance logging
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CadStats {
+    /// CAD done with activity detected
+    pub detected: u32,
+    /// CAD done without activity detected (channel clear)
+    pub clear: u32,
+    /// CAD ended by a timeout, or reception after a detection (exit mode CadRx) ended by a timeout
+    pub timeout: u32,
+}
+
+impl CadStats {
+    /// Counters set to 0
+    pub const fn new() -> Self {
+        Self { detected: 0, clear: 0, timeout: 0 }
+    }
+
+    /// Number of CAD completed (detected or clear)
+    pub fn total(&self) -> u32 {
+        self.detected.saturating_add(self.clear)
+    }
+}
+
+/// Progress of the last CAD started
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CadState {
+    /// No CAD in progress
+    Idle,
+    /// CAD started
+    Running,
+    /// Reception started after a detection (exit mode CadRx)
+    Rx,
+}
+
 impl LoraCadParams {
 
     /// Create CAD parameter for a CAD only operation
@@ -192,14 +286,6 @@ impl LoraCadParams {
     }
 }
 
-// Recommneded delay for ranging
-// One line per bandwidth: 500, 250, 125
-const RANGING_DELAY : [u32; 24] = [
-    19115, 19113, 19121, 19127, 19141, 19178, 19242, 19370,
-    20265, 20266, 20279, 20292, 20236, 20305, 20433, 20689,
-    20154, 20268, 20298, 20319, 20323, 20314, 20570, 21082,
-];
-
 #[derive(Debug, Clone, Copy)]
 pub struct SidedetCfg(u8);
 impl SidedetCfg {
@@ -278,17 +364,6 @@ impl BlankingCfg {
     }
 }
 
-#[derive(Debug, Clone)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
-/// Frequency estimation during ranging exchange (valid only on responder side)
-pub struct RangingFei {
-    /// Frequency estimation on first exchange
-    pub fei1: i32,
-    /// Frequency estimation on second exchange
-    pub fei2: i32,
-}
-
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 /// Define duration of the TimingSync pulse of the responder
@@ -303,14 +378,27 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
 
     /// Set LoRa Modulation parameters
     pub async fn set_lora_modulation(&mut self, params: &LoraModulationParams) -> Result<(), Lr1120Error> {
+        self.check_packet_type(PacketType::Lora).await?;
         let req = set_lora_modulation_params_cmd(params.sf, params.bw, params.cr, params.ldro);
-        self.cmd_wr(&req).await
+        self.cmd_wr(&req).await?;
+        self.lora_cr = Some(params.cr);
+        #[cfg(feature = "shadow")] {self.shadow.lora_modulation = Some(*params);}
+        Ok(())
     }
 
     /// Set LoRa Packet parameters
+    /// The payload length is checked against the coding rate of the last [`set_lora_modulation`](Lr1120::set_lora_modulation)
+    /// (see [`LoraPacketParams::validate`]): set the modulation first
     pub async fn set_lora_packet(&mut self, params: &LoraPacketParams) -> Result<(), Lr1120Error> {
+        if let Some(cr) = self.lora_cr {
+            params.validate(cr)?;
+        }
+        self.check_packet_type(PacketType::Lora).await?;
         let req = set_lora_packet_params_cmd(params.pbl_len, params.header_type, params.payload_len,  params.crc_en, params.invert_iq);
-        self.cmd_wr(&req).await
+        self.cmd_wr(&req).await?;
+        self.lora_packet = Some(*params);
+        #[cfg(feature = "shadow")] {self.shadow.lora_packet = Some(*params);}
+        Ok(())
     }
 
     /// Set LoRa Syncword using legacy (SX127x) 1B notation: 0x34 for public network, 0x12 for private
@@ -342,6 +430,28 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         Ok(rsp)
     }
 
+    /// Read the payload of the last packet received into the local buffer
+    /// `intr` is the interrupt status read at the end of the reception.
+    /// On CRC error the payload is still available in the RX buffer: it is returned (with `crc_ok` false)
+    /// only when `salvage_crc_err` is set, otherwise the error CrcError is returned.
+    /// In implicit header mode the length received is checked against the payload length configured:
+    /// a mismatch (e.g. chip configuration lost or changed behind the driver) returns PayloadLength.
+    pub async fn get_lora_payload(&mut self, intr: Intr, salvage_crc_err: bool) -> Result<LoraRxPayload<'_>, Lr1120Error> {
+        let crc_ok = !intr.crc_error();
+        if !crc_ok && !salvage_crc_err {
+            return Err(Lr1120Error::CrcError);
+        }
+        let status = self.get_rx_buffer_status().await?;
+        let len = status.pld_len();
+        if let Some(params) = self.lora_packet
+            && params.header_type == HeaderType::Implicit
+            && params.payload_len != len {
+            return Err(Lr1120Error::PayloadLength { expected: params.payload_len, actual: len });
+        }
+        self.rd_rx_buffer(status.offset(), len).await?;
+        Ok(LoraRxPayload{data: self.rsp_data(len as usize)?, crc_ok})
+    }
+
     /// Read LoRa RX stats (RSSI/SNR)
     pub async fn get_lora_packet_status(&mut self) -> Result<LoraPacketStatusRsp, Lr1120Error> {
         let req = get_lora_packet_status_req();
@@ -353,13 +463,49 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// Set LoRa Channel Activity Detection parameters
     pub async fn set_lora_cad_params(&mut self, params: LoraCadParams) -> Result<(), Lr1120Error> {
         let req = set_lora_cad_params_cmd(params.nb_symbols, params.thr, 10, params.exit_mode, params.timeout);
-        self.cmd_wr(&req).await
+        self.cmd_wr(&req).await?;
+        self.cad_exit = params.exit_mode;
+        Ok(())
     }
 
     /// Start a LoRa Channel Activity Detection (CAD)
     pub async fn set_lora_cad(&mut self) -> Result<(), Lr1120Error> {
         let req = set_lora_cad_cmd();
-        self.cmd_wr(&req).await
+        self.cmd_wr(&req).await?;
+        self.cad_state = CadState::Running;
+        Ok(())
+    }
+
+    /// CAD outcome counters since the last reset of the statistics
+    /// Outcomes are counted from the interrupts read by [`get_status`](Lr1120::get_status)
+    pub fn cad_stats(&self) -> CadStats {
+        self.cad_stats
+    }
+
+    /// Reset the CAD outcome counters
+    pub fn reset_cad_stats(&mut self) {
+        self.cad_stats = CadStats::new();
+    }
+
+    /// Update the CAD outcome counters from the interrupts read
+    pub(crate) fn record_cad(&mut self, intr: Intr) {
+        self.cad_state = match self.cad_state {
+            CadState::Idle => CadState::Idle,
+            CadState::Running if intr.cad_done() && intr.cad_detected() => {
+                self.cad_stats.detected = self.cad_stats.detected.saturating_add(1);
+                if self.cad_exit == ExitMode::CadRx {CadState::Rx} else {CadState::Idle}
+            }
+            CadState::Running if intr.cad_done() => {
+                self.cad_stats.clear = self.cad_stats.clear.saturating_add(1);
+                CadState::Idle
+            }
+            CadState::Running | CadState::Rx if intr.timeout() => {
+                self.cad_stats.timeout = self.cad_stats.timeout.saturating_add(1);
+                CadState::Idle
+            }
+            CadState::Rx if intr.rx_done() || intr.header_err() || intr.crc_error() => CadState::Idle,
+            state => state,
+        };
     }
 
     /// Enable compatibility with SX127x for SF6 communication
@@ -373,6 +519,7 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// Configure Side-Detector allowing multiple SF to be detected
     /// Must be called after set_lora_modulation
     /// If cfg is an empty slice, this disabled all side-detector
+    /// Return InvalidParam if more than 3 side-detectors are provided
     pub async fn set_lora_sidedet_cfg(&mut self, cfg: &[SidedetCfg]) -> Result<(), Lr1120Error> {
         let req = [
             0x02, 0x24,
@@ -380,12 +527,13 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
             cfg.get(1).map(|c| c.to_byte()).unwrap_or(0),
             cfg.get(2).map(|c| c.to_byte()).unwrap_or(0),
         ];
-        let len = cfg.len() + 2;
-        self.cmd_wr(&req[..len]).await
+        let req = req.get(..cfg.len() + 2).ok_or(Lr1120Error::InvalidParam)?;
+        self.cmd_wr(req).await
     }
 
     #[allow(clippy::get_first)]
     /// Configure Side-Detector Syncword using basic syncword format
+    /// Return InvalidParam if more than 3 syncwords are provided
     pub async fn set_lora_sidedet_syncword(&mut self, sw: &[u8]) -> Result<(), Lr1120Error> {
         let req = [
             0x02, 0x25,
@@ -393,65 +541,8 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
             sw.get(1).copied().unwrap_or(0x24),
             sw.get(2).copied().unwrap_or(0x24),
         ];
-        let len = sw.len() + 2;
-        self.cmd_wr(&req[..len]).await
-    }
-
-    /// Set the device address for ranging operation
-    /// The device will answer to ranging request only if the request address matches the device address
-    /// The length allows to define how many bytes from the address are checked (starting from LSB)
-    pub async fn set_ranging_dev_addr(&mut self, addr: u32, length: Option<CheckLength>) -> Result<(), Lr1120Error> {
-         let req = set_ranging_addr_cmd(addr, length.unwrap_or(CheckLength::Addr32b));
-        self.cmd_wr(&req).await
-   }
-
-    /// Set the request address for ranging operation
-    pub async fn set_ranging_req_addr(&mut self, addr: u32) -> Result<(), Lr1120Error> {
-         let req = set_ranging_req_addr_cmd(addr);
-        self.cmd_wr(&req).await
-    }
-
-    /// Set the ranging calibration value
-    pub async fn set_ranging_txrx_delay(&mut self, delay: u32) -> Result<(), Lr1120Error> {
-         let req = set_ranging_tx_rx_delay_cmd(delay);
-        self.cmd_wr(&req).await
-   }
-
-    /// Get the base delay for ranging depdending on bandwidth and SF
-    /// Delay was calibrated only for bandwidth 125kHz, 250kHz and 500kHz.
-    pub fn get_ranging_base_delay(&self, modulation: &LoraModulationParams) -> u32 {
-        let offset = match modulation.bw {
-            LoraBw::Bw500 =>  0,
-            LoraBw::Bw250 =>  8,
-            LoraBw::Bw125 =>  16,
-            _ => 32,
-        };
-        let idx = offset + (modulation.sf as usize - 5);
-        RANGING_DELAY.get(idx).copied().unwrap_or(18000 - (5600 >> (12 - modulation.sf as u32)))
-    }
-
-    /// Set the ranging parameters: number of symbols
-    /// Number of symbols should typically be between 8 to 16 symbols, with 12 being close to optimal performances
-    pub async fn set_ranging_params(&mut self, nb_symbols: u8) -> Result<(), Lr1120Error> {
-         let req = set_ranging_parameter_cmd(0, nb_symbols);
-        self.cmd_wr(&req).await
-   }
-
-    /// Return the result of last ranging exchange (round-trip time of flight and RSSI)
-    /// The distance is provided
-    pub async fn get_ranging_result(&mut self) -> Result<RangingResultRsp, Lr1120Error> {
-        let req = get_ranging_result_req(RangingResKind::Distance);
-        let mut rsp = RangingResultRsp::new();
-        self.cmd_rd(&req, rsp.as_mut()).await?;
-        Ok(rsp)
-    }
-
-    /// Return the RSSI measured during ranging
-    pub async fn get_ranging_rssi(&mut self) -> Result<RangingRssiRsp, Lr1120Error> {
-        let req = get_ranging_result_req(RangingResKind::Rssi);
-        let mut rsp = RangingRssiRsp::new();
-        self.cmd_rd(&req, rsp.as_mut()).await?;
-        Ok(rsp)
+        let req = req.get(..sw.len() + 2).ok_or(Lr1120Error::InvalidParam)?;
+        self.cmd_wr(req).await
     }
 
 }
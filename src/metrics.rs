@@ -0,0 +1,142 @@
+//! # Telemetry counters
+//!
+//! With the `metrics` feature the driver counts its activity on the SPI bus and the events reported by the chip:
+//! - SPI transactions (one per NSS assertion) and bytes transferred, read commands retried after an SPI error,
+//! - commands reported as failed in the status (`CmdFail` or `CmdErr`),
+//! - interrupts raised, by type (counted once when first seen set in [`get_status`](Lr1120::get_status), until cleared),
+//! - cumulative time spent waiting for the BUSY pin.
+//!
+//! This helps optimizing the power consumption (SPI traffic, long busy periods)
+//! and spotting misbehaving subsystems in the field (command failures, unexpected interrupts).
+//! Counters are not cleared by a chip reset and wrap on overflow.
+//! The feature is opt-in to keep RAM usage and overhead small when not needed.
+//!
+//! ## Example
+//! ```rust,no_run
+//! let m = lr1120.metrics();
+//! info!("SPI: {} transactions, {} bytes, busy {} ms", m.spi_transactions, m.spi_bytes, m.busy_wait.as_millis());
+//! info!("RX done: {}, CRC errors: {}", m.irq_count(IRQ_MASK_RX_DONE), m.irq_count(IRQ_MASK_CRC_ERROR));
+//! lr1120.reset_metrics();
+//! ```
+//!
+//! ## Available Methods
+//! - [`metrics`](Lr1120::metrics) - Return the telemetry counters
+//! - [`reset_metrics`](Lr1120::reset_metrics) - Clear the telemetry counters
+
+use embassy_time::Duration;
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiBus;
+
+use super::{BusyPin, Lr1120};
+use super::status::{CmdStatus, Intr};
+
+/// Telemetry counters of the driver
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Metrics {
+    /// Number of SPI transactions
+    pub spi_transactions: u32,
+    /// Number of bytes transferred on the SPI bus (both directions counted once)
+    pub spi_bytes: u64,
+    /// Number of read commands retried after an SPI error (see [`retry`](crate::retry))
+    pub spi_retries: u32,
+    /// Number of commands reported as failed in the status
+    pub cmd_failures: u32,
+    /// Cumulative time spent waiting for the BUSY pin
+    pub busy_wait: Duration,
+    /// Number of times each interrupt was raised, indexed by bit position
+    irqs: [u32; 32],
+    /// Interrupts set in the last status read
+    irq_pending: u32,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    /// Create cleared counters
+    pub const fn new() -> Self {
+        Self {
+            spi_transactions: 0,
+            spi_bytes: 0,
+            spi_retries: 0,
+            cmd_failures: 0,
+            busy_wait: Duration::from_ticks(0),
+            irqs: [0; 32],
+            irq_pending: 0,
+        }
+    }
+
+    /// Number of times the interrupts of a mask were raised (e.g. `IRQ_MASK_RX_DONE`)
+    /// With several interrupts in the mask, the counts are summed
+    pub fn irq_count(&self, mask: u32) -> u32 {
+        self.irqs.iter()
+            .enumerate()
+            .filter(|(bit, _)| mask & (1 << bit) != 0)
+            .fold(0, |sum, (_, n)| sum.wrapping_add(*n))
+    }
+
+    /// Record the start of an SPI transaction of `len` bytes
+    pub(crate) fn spi_transaction(&mut self, len: usize) {
+        self.spi_transactions = self.spi_transactions.wrapping_add(1);
+        self.spi_bytes(len);
+    }
+
+    /// Record `len` bytes transferred in the current SPI transaction
+    pub(crate) fn spi_bytes(&mut self, len: usize) {
+        self.spi_bytes = self.spi_bytes.wrapping_add(len as u64);
+    }
+
+    /// Record a read command retried after an SPI error
+    pub(crate) fn spi_retry(&mut self) {
+        self.spi_retries = self.spi_retries.wrapping_add(1);
+    }
+
+    /// Record the command status of a transaction
+    pub(crate) fn cmd_status(&mut self, status: CmdStatus) {
+        if matches!(status, CmdStatus::Fail | CmdStatus::PErr) {
+            self.cmd_failures = self.cmd_failures.wrapping_add(1);
+        }
+    }
+
+    /// Record the interrupts read from the chip: only the ones not already pending are counted
+    pub(crate) fn irq_status(&mut self, intr: Intr) {
+        let raised = intr.value() & !self.irq_pending;
+        for (bit, n) in self.irqs.iter_mut().enumerate() {
+            if raised & (1 << bit) != 0 {
+                *n = n.wrapping_add(1);
+            }
+        }
+        self.irq_pending = intr.value();
+    }
+
+    /// Record interrupts cleared by the host
+    pub(crate) fn irq_clear(&mut self, mask: u32) {
+        self.irq_pending &= !mask;
+    }
+
+    /// Record time spent waiting for the BUSY pin
+    pub(crate) fn busy(&mut self, wait: Duration) {
+        self.busy_wait += wait;
+    }
+}
+
+impl<O,SPI, M> Lr1120<O,SPI, M> where
+    O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+{
+
+    /// Return the telemetry counters
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Clear the telemetry counters
+    /// Interrupts still pending are not counted again
+    pub fn reset_metrics(&mut self) {
+        self.metrics = Metrics { irq_pending: self.metrics.irq_pending, ..Metrics::new() };
+    }
+
+}
@@ -0,0 +1,155 @@
+//! # Motion-driven scan cadence
+//!
+//! Geolocation scans are mostly useful when the device moves. This module allows an external motion source
+//! (e.g. accelerometer interrupt) to drive the scan period of [`GnssTracker`](crate::gnss_tracker::GnssTracker)
+//! and [`WifiSniffer`](crate::wifi_sniffer::WifiSniffer):
+//!  - [`MotionHook`] is shared between the motion source and the scan task: `on_motion()` and `on_still()` can be called from any context
+//!  - [`ScanCadence`] is the policy: motion switches immediately to the fast period and triggers a scan,
+//!    while still events decay the period towards the slow one (doubling on each event) after a configurable hysteresis.
+//!
+//! ## Example
+//! ```rust,no_run
+//! static MOTION: MotionHook<CriticalSectionRawMutex> = MotionHook::new();
+//!
+//! // In the accelerometer task
+//! MOTION.on_motion();
+//!
+//! // In the WiFi task
+//! let cadence = ScanCadence::new(ScanCadenceCfg::default());
+//! sniffer.run_with_motion(&mut lr1120, WIFI_EVENTS.sender(), &MOTION, cadence).await
+//! ```
+
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embassy_sync::channel::Channel;
+use embassy_time::{with_deadline, Duration, Instant};
+
+/// Number of motion events which can be queued before being processed by the scan task
+const MOTION_QUEUE_SIZE: usize = 4;
+
+/// Motion event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Motion {
+    /// Device started moving
+    Moving,
+    /// Device is still
+    Still,
+}
+
+/// Input hook for an external motion event source
+pub struct MotionHook<RM: RawMutex> {
+    queue: Channel<RM, Motion, MOTION_QUEUE_SIZE>,
+}
+
+impl<RM: RawMutex> Default for MotionHook<RM> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<RM: RawMutex> MotionHook<RM> {
+    /// Create a motion hook (can be used in a static)
+    pub const fn new() -> Self {
+        Self { queue: Channel::new() }
+    }
+
+    /// Report that the device is moving
+    /// Event is dropped if the queue is full
+    pub fn on_motion(&self) {
+        self.queue.try_send(Motion::Moving).ok();
+    }
+
+    /// Report that the device is still
+    /// Event is dropped if the queue is full
+    pub fn on_still(&self) {
+        self.queue.try_send(Motion::Still).ok();
+    }
+
+    /// Wait for the next motion event
+    pub async fn wait(&self) -> Motion {
+        self.queue.receive().await
+    }
+}
+
+/// Scan cadence configuration
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ScanCadenceCfg {
+    /// Scan period while moving
+    pub moving_period: Duration,
+    /// Longest scan period when still
+    pub still_period: Duration,
+    /// Number of consecutive still events before the period starts to decay
+    pub still_hysteresis: u8,
+}
+
+impl Default for ScanCadenceCfg {
+    /// Scan every 30s while moving, up to every hour when still, after 2 still events
+    fn default() -> Self {
+        Self {
+            moving_period: Duration::from_secs(30),
+            still_period: Duration::from_secs(3600),
+            still_hysteresis: 2,
+        }
+    }
+}
+
+/// Scan period policy driven by motion events
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ScanCadence {
+    cfg: ScanCadenceCfg,
+    period: Duration,
+    still_cnt: u8,
+}
+
+impl ScanCadence {
+    /// Create a scan cadence, starting as still (slowest period)
+    pub fn new(cfg: ScanCadenceCfg) -> Self {
+        Self { cfg, period: cfg.still_period, still_cnt: cfg.still_hysteresis }
+    }
+
+    /// Current scan period
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Escalate to the moving period
+    /// Return true if a scan should be started immediately (period was slower)
+    pub fn on_motion(&mut self) -> bool {
+        let escalate = self.period > self.cfg.moving_period;
+        self.still_cnt = 0;
+        self.period = self.cfg.moving_period;
+        escalate
+    }
+
+    /// Decay the period towards the still period once the hysteresis is reached
+    pub fn on_still(&mut self) {
+        self.still_cnt = self.still_cnt.saturating_add(1);
+        if self.still_cnt > self.cfg.still_hysteresis {
+            self.period = self.period.checked_mul(2)
+                .unwrap_or(self.cfg.still_period)
+                .min(self.cfg.still_period);
+        }
+    }
+
+    /// Update cadence with a motion event
+    /// Return true if a scan should be started immediately
+    pub fn update(&mut self, motion: Motion) -> bool {
+        match motion {
+            Motion::Moving => self.on_motion(),
+            Motion::Still => {self.on_still(); false}
+        }
+    }
+
+    /// Wait for `delay` while processing motion events
+    /// Return early if a motion event requires an immediate scan
+    pub async fn wait<RM: RawMutex>(&mut self, delay: Duration, hook: &MotionHook<RM>) {
+        let deadline = Instant::now() + delay;
+        while let Ok(motion) = with_deadline(deadline, hook.wait()).await {
+            if self.update(motion) {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,353 @@
+//! # Payload builders
+//!
+//! Builders for geolocation uplink payloads (LoRa Cloud), writing either into a `heapless::Vec`
+//! or into a caller-provided `&mut [u8]` through the [`PayloadBuffer`] trait.
+//! No allocation and no implicit truncation: a payload not fitting in the buffer returns `InvalidSize`,
+//! and `max_encoded_len` const functions allow to size buffers at compile time.
+//!
+//! ## Example
+//! ```rust,no_run
+//! let mut payload : heapless::Vec<u8, {WifiUplink::max_encoded_len(8)}> = heapless::Vec::new();
+//! WifiUplink::encode(sniffer.cache().map(|ap| (ap.mac, ap.rssi)).take(8), &mut payload)?;
+//! ```
+//!
+//! ## Available Builders
+//! - [`WifiUplink`] - Access points list (RSSI and MAC), with the `wifi` feature
+//! - [`GnssUplink`] - NAV message from a GNSS scan
+//!
+//! ## Measurement age
+//! The scan results published by [`GnssTracker`](crate::gnss_tracker::GnssTracker) and [`WifiSniffer`](crate::wifi_sniffer::WifiSniffer)
+//! carry a [`ScanTimestamp`]: the capture instant, and its GPS time when known.
+//! The age of the measurement when the uplink is sent (or its GPS time) can be sent along the payload
+//! so that the solver takes into account the time elapsed since the capture.
+//! ```rust,no_run
+//! let age_s = timestamp.age_s(Instant::now());
+//! ```
+//!
+//! ## Fragmentation
+//! A payload larger than the maximum LoRaWAN payload of the data rate (see [`LorawanRegion::max_payload`])
+//! can be split with [`Fragmenter`]: each fragment starts with a 2-byte header (message identifier, then last flag and fragment index)
+//! allowing the application server to reassemble the payload.
+//! ```rust,no_run
+//! let frags = Fragmenter::for_region(payload.as_slice(), msg_id, LorawanRegion::Eu868, 0)?;
+//! for frag in frags {
+//!     let mut uplink : heapless::Vec<u8, 256> = heapless::Vec::new();
+//!     frag.encode(&mut uplink)?;
+//!     lorawan.send(uplink.as_slice()).await;
+//! }
+//! ```
+
+use embassy_time::{Duration, Instant};
+
+use super::Lr1120Error;
+use super::gps_time::GpsTime;
+#[cfg(feature = "wifi")]
+use super::wifi_scan::MacAddress;
+
+/// Capture time of a scan result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ScanTimestamp {
+    /// Local instant of the scan start
+    pub at: Instant,
+    /// GPS time (s) of the scan start, when a GPS time reference is known
+    pub gps_time: Option<GpsTime>,
+}
+
+impl ScanTimestamp {
+    /// Timestamp of a scan started at `at`, with the GPS time derived from a reference (GPS time, local instant) if any
+    pub fn new(at: Instant, gps_ref: Option<(GpsTime, Instant)>) -> Self {
+        let gps_time = gps_ref.map(|(gps, ref_at)| {
+            let elapsed = at.as_secs() as i64 - ref_at.as_secs() as i64;
+            GpsTime((gps.0 as i64 + elapsed).max(0) as u32)
+        });
+        Self { at, gps_time }
+    }
+
+    /// Age of the measurement at instant `now`
+    pub fn age(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.at)
+    }
+
+    /// Age of the measurement in seconds at instant `now` (saturating)
+    pub fn age_s(&self, now: Instant) -> u32 {
+        self.age(now).as_secs().min(u32::MAX as u64) as u32
+    }
+}
+
+/// Buffer receiving an encoded payload
+pub trait PayloadBuffer {
+    /// Maximum number of bytes the buffer can hold
+    fn capacity(&self) -> usize;
+    /// Number of bytes already written
+    fn len(&self) -> usize;
+    /// True if no byte was written
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Append bytes, returning InvalidSize if the buffer is too small
+    fn extend(&mut self, bytes: &[u8]) -> Result<(), Lr1120Error>;
+    /// Remove all bytes
+    fn clear(&mut self);
+    /// Content written so far
+    fn as_slice(&self) -> &[u8];
+}
+
+impl<const N: usize> PayloadBuffer for heapless::Vec<u8, N> {
+    fn capacity(&self) -> usize {
+        N
+    }
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+    fn extend(&mut self, bytes: &[u8]) -> Result<(), Lr1120Error> {
+        self.extend_from_slice(bytes).map_err(|_| Lr1120Error::InvalidSize)
+    }
+    fn clear(&mut self) {
+        heapless::Vec::clear(self)
+    }
+    fn as_slice(&self) -> &[u8] {
+        heapless::Vec::as_slice(self)
+    }
+}
+
+/// Payload buffer over a caller-provided slice
+pub struct SliceBuffer<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> SliceBuffer<'a> {
+    /// Create an empty payload buffer using the whole slice as capacity
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+}
+
+impl PayloadBuffer for SliceBuffer<'_> {
+    fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+    fn len(&self) -> usize {
+        self.len
+    }
+    fn extend(&mut self, bytes: &[u8]) -> Result<(), Lr1120Error> {
+        let end = self.len.checked_add(bytes.len()).ok_or(Lr1120Error::InvalidSize)?;
+        let dst = self.buf.get_mut(self.len..end).ok_or(Lr1120Error::InvalidSize)?;
+        dst.copy_from_slice(bytes);
+        self.len = end;
+        Ok(())
+    }
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+    fn as_slice(&self) -> &[u8] {
+        self.buf.get(..self.len).unwrap_or_default()
+    }
+}
+
+/// WiFi geolocation uplink: format byte followed by RSSI (dBm) and MAC address for each access point
+#[cfg(feature = "wifi")]
+pub struct WifiUplink;
+
+#[cfg(feature = "wifi")]
+impl WifiUplink {
+    /// Format byte for results including RSSI
+    pub const FORMAT_RSSI_MAC: u8 = 0x01;
+    /// Size of one access point entry
+    pub const AP_LEN: usize = 7;
+
+    /// Maximum encoded length for `nb_ap` access points
+    pub const fn max_encoded_len(nb_ap: usize) -> usize {
+        1 + nb_ap * Self::AP_LEN
+    }
+
+    /// Encode access points (MAC, RSSI) in the buffer (cleared first)
+    /// Return the payload length or InvalidSize if the buffer is too small
+    pub fn encode<B: PayloadBuffer, I: IntoIterator<Item=(MacAddress, i8)>>(aps: I, buf: &mut B) -> Result<usize, Lr1120Error> {
+        buf.clear();
+        buf.extend(&[Self::FORMAT_RSSI_MAC])?;
+        for (mac, rssi) in aps {
+            buf.extend(&[rssi as u8])?;
+            buf.extend(&mac.bytes())?;
+        }
+        Ok(buf.len())
+    }
+}
+
+/// GNSS geolocation uplink: NAV message without the destination byte
+pub struct GnssUplink;
+
+impl GnssUplink {
+    /// Destination byte of a scan result targeting the solver (i.e. a NAV message)
+    pub const DEST_SOLVER: u8 = 0x01;
+    /// Maximum size of a NAV message
+    pub const NAV_MAX_LEN: usize = 255;
+
+    /// Maximum encoded length
+    pub const fn max_encoded_len() -> usize {
+        Self::NAV_MAX_LEN
+    }
+
+    /// Encode the result of a GNSS scan (see [`gnss_read_results`](crate::Lr1120::gnss_read_results)) in the buffer (cleared first)
+    /// Return InvalidParam if the result is not a NAV message or InvalidSize if the buffer is too small
+    pub fn encode<B: PayloadBuffer>(result: &[u8], buf: &mut B) -> Result<usize, Lr1120Error> {
+        match result.split_first() {
+            Some((&Self::DEST_SOLVER, nav)) => {
+                buf.clear();
+                buf.extend(nav)?;
+                Ok(buf.len())
+            }
+            _ => Err(Lr1120Error::InvalidParam),
+        }
+    }
+}
+
+/// LoRaWAN regions (regional parameters RP002-1.0.3)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LorawanRegion {
+    Eu868,
+    Us915,
+    Au915,
+    /// AS923 without uplink dwell time limitation
+    As923,
+    In865,
+    Kr920,
+}
+
+impl LorawanRegion {
+    /// Maximum application payload (FRMPayload without FOpts) for an uplink data rate
+    /// Return None if the data rate is not defined for the region
+    pub const fn max_payload(&self, dr: u8) -> Option<usize> {
+        let max = match self {
+            LorawanRegion::Eu868 |
+            LorawanRegion::In865 => match dr {
+                0..=2 => 51,
+                3 => 115,
+                4..=7 => 222,
+                _ => return None,
+            },
+            LorawanRegion::Kr920 => match dr {
+                0..=2 => 51,
+                3 => 115,
+                4..=5 => 222,
+                _ => return None,
+            },
+            LorawanRegion::Us915 => match dr {
+                0 => 11,
+                1 => 53,
+                2 => 125,
+                3..=4 => 242,
+                _ => return None,
+            },
+            LorawanRegion::Au915 => match dr {
+                0..=2 => 51,
+                3 => 115,
+                4..=6 => 242,
+                _ => return None,
+            },
+            LorawanRegion::As923 => match dr {
+                0..=2 => 51,
+                3 => 115,
+                4..=7 => 242,
+                _ => return None,
+            },
+        };
+        Some(max)
+    }
+}
+
+/// Size of the fragment header
+pub const FRAGMENT_HEADER_LEN: usize = 2;
+/// Maximum number of fragments per message
+pub const FRAGMENT_MAX_NB: usize = 128;
+/// Flag set in the second header byte of the last fragment
+const FRAGMENT_LAST: u8 = 0x80;
+
+/// One fragment of a payload
+#[derive(Debug, Clone, Copy)]
+pub struct Fragment<'a> {
+    /// Header: message identifier, then last flag (bit 7) and fragment index (bits 6:0)
+    pub header: [u8; FRAGMENT_HEADER_LEN],
+    /// Part of the payload carried by this fragment
+    pub data: &'a [u8],
+}
+
+impl Fragment<'_> {
+    /// Fragment index
+    pub fn index(&self) -> u8 {
+        self.header[1] & !FRAGMENT_LAST
+    }
+
+    /// True for the last fragment of the message
+    pub fn is_last(&self) -> bool {
+        self.header[1] & FRAGMENT_LAST != 0
+    }
+
+    /// Length of the encoded fragment
+    pub fn len(&self) -> usize {
+        FRAGMENT_HEADER_LEN + self.data.len()
+    }
+
+    /// Always false: a fragment contains at least its header
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Encode header and data in the buffer (cleared first)
+    pub fn encode<B: PayloadBuffer>(&self, buf: &mut B) -> Result<usize, Lr1120Error> {
+        buf.clear();
+        buf.extend(&self.header)?;
+        buf.extend(self.data)?;
+        Ok(buf.len())
+    }
+}
+
+/// Split a payload (e.g. a NAV message or WiFi uplink) into fragments fitting in an uplink
+#[derive(Debug, Clone)]
+pub struct Fragmenter<'a> {
+    chunks: core::slice::Chunks<'a, u8>,
+    msg_id: u8,
+    index: u8,
+}
+
+impl<'a> Fragmenter<'a> {
+    /// Create a fragmenter for a maximum uplink size `max_len` (including the fragment header)
+    /// Return InvalidParam if no data fits after the header, and InvalidSize if more than 128 fragments are needed
+    pub fn new(payload: &'a [u8], msg_id: u8, max_len: usize) -> Result<Self, Lr1120Error> {
+        let chunk_len = max_len.checked_sub(FRAGMENT_HEADER_LEN).filter(|l| *l > 0).ok_or(Lr1120Error::InvalidParam)?;
+        if payload.len().div_ceil(chunk_len) > FRAGMENT_MAX_NB {
+            return Err(Lr1120Error::InvalidSize);
+        }
+        Ok(Self { chunks: payload.chunks(chunk_len), msg_id, index: 0 })
+    }
+
+    /// Create a fragmenter for the maximum payload of a data rate
+    /// Return InvalidParam if the data rate is not defined for the region
+    pub fn for_region(payload: &'a [u8], msg_id: u8, region: LorawanRegion, dr: u8) -> Result<Self, Lr1120Error> {
+        let max_len = region.max_payload(dr).ok_or(Lr1120Error::InvalidParam)?;
+        Self::new(payload, msg_id, max_len)
+    }
+
+    /// Number of fragments remaining
+    pub fn nb_fragments(&self) -> usize {
+        self.chunks.len()
+    }
+}
+
+impl<'a> Iterator for Fragmenter<'a> {
+    type Item = Fragment<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let data = self.chunks.next()?;
+        let last = if self.chunks.len() == 0 {FRAGMENT_LAST} else {0};
+        let header = [self.msg_id, self.index | last];
+        self.index += 1;
+        Some(Fragment { header, data })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
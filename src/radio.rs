@@ -10,38 +10,270 @@
 //! ### RF Configuration
 //! - [`set_rf`](Lr1120::set_rf) - Set RF frequency channel in Hz
 //! - [`set_packet_type`](Lr1120::set_packet_type) - Set packet type (LoRa, FSK)
+//! - [`get_packet_type`](Lr1120::get_packet_type) - Read packet type currently configured in the chip
+//! - [`check_packet_type`](Lr1120::check_packet_type) - Check the configured packet type before sending modem specific parameters
 //!
 //! ### Power Amplifier Configuration
 //! - [`set_tx_params`](Lr1120::set_tx_params) - Set TX power level and ramp time
 //! - [`set_pa`](Lr1120::set_pa) - Configure Power Amplifier (LF/HF) with duty cycle
+//! - [`check_pa_config`] - Validate the duty cycle and HP PA size against the PA selected
 //!
 //! ### Operation Mode Control
 //! - [`set_fallback`](Lr1120::set_fallback) - Set fallback mode after TX/RX completion
+//! - [`fallback`](Lr1120::fallback) - Return the fallback mode currently configured
+//! - [`set_fast_tx`](Lr1120::set_fast_tx) - Keep the chip in FS between transmissions for minimum turn-around time
+//! - [`transmit`](Lr1120::transmit) - Write the payload and start a transmission, skipping standby in fast TX mode
+//! - [`transmit_timed`](Lr1120::transmit_timed) - Transmit and measure the latency between the TX command and TxDone
+//! - [`transmit_staged`](Lr1120::transmit_staged) - Transmit the payload staged in a [`TxDoubleBuffer`] during the previous transmission
 //! - [`set_tx`](Lr1120::set_tx) - Enter transmission mode with timeout
 //! - [`set_tx_cw`](Lr1120::set_tx_cw) - Start TX in continuous wave test mode
 //! - [`set_rx`](Lr1120::set_rx) - Enter reception mode with timeout and ready wait option
+//! - [`set_tx_for`](Lr1120::set_tx_for) / [`set_rx_for`](Lr1120::set_rx_for) - Enter transmission/reception mode with a timeout given as a duration
 //! - [`set_rx_continous`](Lr1120::set_rx_continous) - Start RX in continuous mode
 //! - [`set_rx_duty_cycle`](Lr1120::set_rx_duty_cycle) - Start periodic RX
 //!
 //! ### Gain and Signal Control
 //! - [`get_rssi_inst`](Lr1120::get_rssi_inst) - Get instantaneous RSSI measurement
 //! - [`get_rssi_avg`](Lr1120::get_rssi_avg) - Get average RSSI measurement over specified duration
+//! - [`get_rssi_inst_dbm`](Lr1120::get_rssi_inst_dbm) / [`get_rssi_avg_dbm`](Lr1120::get_rssi_avg_dbm) - Same measurements in dBm
+//! - [`set_rssi_calibration`](Lr1120::set_rssi_calibration) - Set the gain offsets of the RSSI estimation ([`RssiCalibration`])
+//! - [`rssi_to_dbm`] - Convert a raw RSSI (-0.5dBm unit) to dBm, used by all `*_dbm` accessors of LoRa/FSK/ranging responses
 //!
 //! ### Reception Management
+//! - [`get_rx_stats`](Lr1120::get_rx_stats) - Read reception statistics, interpreted for the packet type configured ([`RxStats`])
+//! - [`get_lora_stats`](Lr1120::get_lora_stats) / [`get_fsk_stats`](Lr1120::get_fsk_stats) - Read reception statistics of a LoRa/FSK reception
+//! - [`get_rx_stats_raw`](Lr1120::get_rx_stats_raw) - Read raw reception statistics
 //! - [`clear_rx_stats`](Lr1120::clear_rx_stats) - Clear reception statistics
 //! - [`get_rx_buffer_status`](Lr1120::get_rx_buffer_status) - Get RX buffer status (packet length and pointer)
 //!
 //! ### Timing
 //! - [`set_stop_timeout`](Lr1120::set_stop_timeout) - Set whether the RX timeout stops when preamble is detected or when the synchronization is confirmed
 //!
+//! ## TX/RX buffers
+//! Unlike other Semtech transceivers, the chip has no command to set the TX/RX buffer base addresses:
+//! the buffers are managed by the chip and cannot be partitioned by the host.
+//! The RX buffer is a ring buffer: the offset of the last packet received is given by
+//! [`get_rx_buffer_status`](Lr1120::get_rx_buffer_status).
+//! `WriteBuffer8` has no offset parameter: a payload is always written from the start of the TX buffer,
+//! which is read by the ongoing transmission.
+//! To prepare the next packet during a transmission, stage it on the host in a [`TxDoubleBuffer`]
+//! and send it with [`transmit_staged`](Lr1120::transmit_staged) on TxDone:
+//! ```rust,no_run
+//! let mut next = TxDoubleBuffer::<64>::new();
+//! lr1120.transmit(&first, 0).await?;
+//! next.stage(&second)?;
+//! // ... on TxDone
+//! lr1120.transmit_staged(&mut next, 0).await?;
+//! ```
+//!
 
 
-use embassy_time::Duration;
+use embassy_time::{Duration, Instant, Timer};
 use embedded_hal::digital::OutputPin;
 use embedded_hal_async::spi::SpiBus;
 
 pub use super::cmd::cmd_radio::*;
 use super::{BusyPin, Lr1120, Lr1120Error};
+use super::coex::RxDutyCycle;
+use super::status::{Intr, IRQ_MASK_TX_DONE};
+use super::system::{duration_to_lf_steps_at, ChipMode, LF_STEP_MAX};
+
+/// Longest RX timeout in LF clock step: the next value (0xFFFFFF) selects the continuous reception
+pub const RX_TIMEOUT_MAX : u32 = LF_STEP_MAX - 1;
+
+/// Convert a raw RSSI in -0.5dBm unit to dBm (rounded to nearest, half towards the lower power)
+pub const fn rssi_to_dbm(raw: u8) -> i16 {
+    -((raw as i16 + 1) / 2)
+}
+
+/// Maximum size of the TX/RX buffer accessible with WriteBuffer8/ReadBuffer8
+pub const RADIO_BUFFER_LEN: usize = 255;
+
+/// Period of the status polling while waiting for TxDone in [`Lr1120::transmit_timed`]
+pub const TX_DONE_POLL_PERIOD: Duration = Duration::from_micros(100);
+
+/// Host-side buffer staging the next payload while a transmission is in progress
+/// The chip TX buffer holds the packet being sent, the staged payload is written to it by
+/// [`transmit_staged`](Lr1120::transmit_staged) once the transmission is done
+#[derive(Debug, Clone, Default)]
+pub struct TxDoubleBuffer<const N: usize> {
+    next: Option<heapless::Vec<u8, N>>,
+}
+
+impl<const N: usize> TxDoubleBuffer<N> {
+    /// Create an empty staging buffer
+    pub const fn new() -> Self {
+        Self { next: None }
+    }
+
+    /// Stage the next payload, replacing any payload already staged
+    /// Return InvalidSize if the payload is longer than N or than the radio buffer
+    pub fn stage(&mut self, data: &[u8]) -> Result<(), Lr1120Error> {
+        if data.len() > RADIO_BUFFER_LEN {
+            return Err(Lr1120Error::InvalidSize);
+        }
+        let next = heapless::Vec::from_slice(data).map_err(|_| Lr1120Error::InvalidSize)?;
+        self.next = Some(next);
+        Ok(())
+    }
+
+    /// Payload staged
+    pub fn staged(&self) -> Option<&[u8]> {
+        self.next.as_deref()
+    }
+
+    /// Drop the payload staged
+    pub fn clear(&mut self) {
+        self.next = None;
+    }
+}
+
+/// Reception statistics in LoRa (or ranging)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LoraStats {
+    /// Number of packets received
+    pub pkt_rx: u16,
+    /// Number of packets received with a CRC error
+    pub crc_error: u16,
+    /// Number of headers with a checksum error
+    pub header_error: u16,
+    /// Number of false synchronisations
+    pub false_sync: u16,
+}
+
+impl From<&StatsRsp> for LoraStats {
+    fn from(rsp: &StatsRsp) -> Self {
+        Self {
+            pkt_rx: rsp.pkt_rx(),
+            crc_error: rsp.crc_error(),
+            header_error: rsp.header_error(),
+            false_sync: rsp.false_sync(),
+        }
+    }
+}
+
+/// Reception statistics in FSK
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FskStats {
+    /// Number of packets received
+    pub pkt_rx: u16,
+    /// Number of packets received with a CRC error
+    pub crc_error: u16,
+    /// Number of packets with a length above the length configured
+    pub length_error: u16,
+}
+
+impl From<&StatsRsp> for FskStats {
+    fn from(rsp: &StatsRsp) -> Self {
+        Self {
+            pkt_rx: rsp.pkt_rx(),
+            crc_error: rsp.crc_error(),
+            length_error: rsp.header_error(),
+        }
+    }
+}
+
+/// Reception statistics interpreted for the packet type
+/// The header error counter of the chip counts checksum errors in LoRa and length errors in FSK,
+/// and the false synchronisation counter is only meaningful in LoRa
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RxStats {
+    /// Statistics of a LoRa or ranging reception
+    Lora(LoraStats),
+    /// Statistics of an FSK reception
+    Fsk(FskStats),
+}
+
+impl RxStats {
+    /// Number of packets received
+    pub fn pkt_rx(&self) -> u16 {
+        match self {
+            RxStats::Lora(s) => s.pkt_rx,
+            RxStats::Fsk(s) => s.pkt_rx,
+        }
+    }
+
+    /// Number of packets received with a CRC error
+    pub fn crc_error(&self) -> u16 {
+        match self {
+            RxStats::Lora(s) => s.crc_error,
+            RxStats::Fsk(s) => s.crc_error,
+        }
+    }
+}
+
+impl RssiInstRsp {
+    /// Instantaneous RSSI in dBm
+    pub fn rssi_dbm(&self) -> i16 {
+        rssi_to_dbm(self.rssi())
+    }
+}
+
+/// Maximum PA duty cycle of the sub-GHz PAs (LP and HP)
+pub const PA_DUTY_CYCLE_MAX: u8 = 7;
+
+/// Maximum size of the HP PA (PaHpSel)
+pub const PA_HP_SEL_MAX: u8 = 7;
+
+/// Validate a PA configuration: the sub-GHz PAs accept a duty cycle up to [`PA_DUTY_CYCLE_MAX`],
+/// the HP PA size (up to [`PA_HP_SEL_MAX`]) only applies to the HP PA and the HF PA is used with both set to 0.
+/// Values outside these ranges can damage the matching network or violate the emission limits: return InvalidParam
+pub fn check_pa_config(pa_sel: PaSel, duty_cycle: u8, hp_sel: u8) -> Result<(), Lr1120Error> {
+    let valid = match pa_sel {
+        PaSel::LpPa => duty_cycle <= PA_DUTY_CYCLE_MAX && hp_sel == 0,
+        PaSel::HpPa => duty_cycle <= PA_DUTY_CYCLE_MAX && hp_sel <= PA_HP_SEL_MAX,
+        PaSel::HfPa => duty_cycle == 0 && hp_sel == 0,
+    };
+    if valid {Ok(())} else {Err(Lr1120Error::InvalidParam)}
+}
+
+/// Size of a serialized RSSI calibration
+pub const RSSI_CALIBRATION_SIZE: usize = 10;
+
+/// RSSI calibration of a board: gain tuning (4 bits) for G4 to G13 and G13 HP1 to HP7, and a 12-bit gain offset
+/// The calibration depends on the hardware design, not on the device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RssiCalibration {
+    /// Gain tuning, from G4 to G13 then G13 HP1 to HP7
+    pub tune: [u8; 17],
+    /// Gain offset
+    pub gain_offset: u16,
+}
+
+impl RssiCalibration {
+    /// Serialize the calibration with the layout of the SetRssiCalibration parameters
+    pub fn to_bytes(&self) -> [u8; RSSI_CALIBRATION_SIZE] {
+        let mut bytes = [0; RSSI_CALIBRATION_SIZE];
+        let nibbles = self.tune.iter().map(|t| t & 0xF).chain([(self.gain_offset >> 8) as u8 & 0xF]);
+        for (i, n) in nibbles.enumerate() {
+            if let Some(b) = bytes.get_mut(i / 2) {
+                *b |= if i.is_multiple_of(2) {n << 4} else {n};
+            }
+        }
+        if let Some(b) = bytes.last_mut() {
+            *b = self.gain_offset as u8;
+        }
+        bytes
+    }
+
+    /// Deserialize a calibration saved with [`to_bytes`](RssiCalibration::to_bytes)
+    pub fn from_bytes(bytes: &[u8; RSSI_CALIBRATION_SIZE]) -> Self {
+        let [pairs @ .., offset_hi, offset_lo] = *bytes;
+        let mut tune = [0; 17];
+        let nibbles = pairs.iter().chain([&offset_hi]).flat_map(|b| [b >> 4, b & 0xF]);
+        for (t, n) in tune.iter_mut().zip(nibbles) {
+            *t = n;
+        }
+        Self {
+            tune,
+            gain_offset: ((offset_hi as u16 & 0xF) << 8) | offset_lo as u16,
+        }
+    }
+}
 
 impl<O,SPI, M> Lr1120<O,SPI, M> where
     O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
@@ -50,13 +282,45 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// Set the RF channel (in Hz)
     pub async fn set_rf(&mut self, freq: u32) -> Result<(), Lr1120Error> {
         let req = set_rf_frequency_cmd(freq);
-        self.cmd_wr(&req).await
+        self.rf_freq = None;
+        self.cmd_wr(&req).await?;
+        self.rf_freq = Some(freq);
+        #[cfg(feature = "shadow")] {self.shadow.rf_freq = Some(freq);}
+        Ok(())
     }
 
     /// Set the packet type
     pub async fn set_packet_type(&mut self, packet_type: PacketType) -> Result<(), Lr1120Error> {
         let req = set_packet_type_cmd(packet_type);
-        self.cmd_wr(&req).await
+        self.cmd_wr(&req).await?;
+        self.packet_type = Some(packet_type);
+        #[cfg(feature = "shadow")] {self.shadow.packet_type = Some(packet_type);}
+        Ok(())
+    }
+
+    /// Check the configured packet type is compatible with `expected` (LoRa parameters are also used by Ranging)
+    /// The packet type is read from the chip when not known by the driver
+    pub async fn check_packet_type(&mut self, expected: PacketType) -> Result<(), Lr1120Error> {
+        let actual = match self.packet_type {
+            Some(t) => t,
+            None => self.get_packet_type().await?,
+        };
+        let compatible = actual == expected || (expected == PacketType::Lora && actual == PacketType::Ranging);
+        if compatible {
+            Ok(())
+        } else {
+            Err(Lr1120Error::WrongPacketType { expected, actual })
+        }
+    }
+
+    /// Read the packet type currently configured in the chip
+    /// Note: RF frequency and modulation parameters cannot be read back (no documented command or register)
+    pub async fn get_packet_type(&mut self) -> Result<PacketType, Lr1120Error> {
+        let req = get_packet_type_req();
+        let mut rsp = PacketTypeRsp::new();
+        self.cmd_rd(&req, rsp.as_mut()).await?;
+        self.packet_type = Some(rsp.packet_type());
+        Ok(rsp.packet_type())
     }
 
     /// Set Tx power and ramp time
@@ -64,31 +328,116 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// Ramp-time is important to reduce Out-of-band emission. A safe rule of thumb is to set it to around 4/Bandwidth.
     pub async fn set_tx_params(&mut self, tx_power: i8, ramp_time: RampTime) -> Result<(), Lr1120Error> {
         let req = set_tx_params_cmd(tx_power, ramp_time);
-        self.cmd_wr(&req).await
+        self.cmd_wr(&req).await?;
+        #[cfg(feature = "shadow")] {self.shadow.tx_params = Some((tx_power, ramp_time));}
+        Ok(())
     }
 
     /// Configure Power PA
+    /// The HP PA is used at its maximum size. Return InvalidParam if the duty cycle is out of range for the PA
+    /// (up to [`PA_DUTY_CYCLE_MAX`] for the LP/HP PA, 0 for the HF PA): see [`check_pa_config`]
     pub async fn set_pa(&mut self, pa_sel: PaSel, duty_cycle: u8) -> Result<(), Lr1120Error> {
-        let pa_supply = if pa_sel==PaSel::HpPa {RegPaSupply::Vbat} else {RegPaSupply::Vreg};
-        let req = set_pa_config_cmd(pa_sel, pa_supply, duty_cycle, 7);
-        self.cmd_wr(&req).await
+        let (pa_supply, hp_sel) = if pa_sel==PaSel::HpPa {(RegPaSupply::Vbat, PA_HP_SEL_MAX)} else {(RegPaSupply::Vreg, 0)};
+        check_pa_config(pa_sel, duty_cycle, hp_sel)?;
+        let req = set_pa_config_cmd(pa_sel, pa_supply, duty_cycle, hp_sel);
+        self.cmd_wr(&req).await?;
+        #[cfg(feature = "shadow")] {self.shadow.pa = Some((pa_sel, duty_cycle));}
+        Ok(())
     }
 
     /// Set the Fallback mode after TX/RX
     pub async fn set_fallback(&mut self, fallback_mode: FallbackMode) -> Result<(), Lr1120Error> {
         let req = set_rx_tx_fallback_mode_cmd(fallback_mode);
-        self.cmd_wr(&req).await
+        self.cmd_wr(&req).await?;
+        self.fallback = fallback_mode;
+        Ok(())
+    }
+
+    /// Fallback mode currently configured
+    pub fn fallback(&self) -> FallbackMode {
+        self.fallback
+    }
+
+    /// Enable/Disable the fast TX mode
+    /// When enabled, the fallback mode is set to FS and the chip is put in FS immediately:
+    /// the synthesizer stays locked between transmissions, giving the minimum TX turn-around time
+    /// at the expense of a higher idle consumption. When disabled, the fallback mode is restored to Standby RC.
+    pub async fn set_fast_tx(&mut self, en: bool) -> Result<(), Lr1120Error> {
+        if en {
+            self.set_fallback(FallbackMode::Fs).await?;
+            self.set_chip_mode(ChipMode::Fs).await
+        } else {
+            self.set_fallback(FallbackMode::StdbyRc).await
+        }
+    }
+
+    /// Return true when fast TX mode is enabled (i.e. fallback mode is FS)
+    pub fn is_fast_tx(&self) -> bool {
+        self.fallback == FallbackMode::Fs
+    }
+
+    /// Write the payload in the TX buffer and start a transmission
+    /// Without fast TX the chip is first set in Standby RC to stop any ongoing operation.
+    /// In fast TX mode the chip is expected to be idle in FS (fallback after previous TX/RX) and no standby transition is done:
+    /// the application must not call this while a reception is still running.
+    /// Timeout is given in LF clock step (1/32.768kHz ~ 30.5us)
+    pub async fn transmit(&mut self, data: &[u8], tx_timeout: u32) -> Result<(), Lr1120Error> {
+        if !self.is_fast_tx() {
+            self.set_chip_mode(ChipMode::StandbyRc).await?;
+        }
+        self.wr_tx_buffer_from(data).await?;
+        self.set_tx(tx_timeout).await
+    }
+
+    /// Transmit the payload staged in a double buffer (see [`transmit`](Lr1120::transmit)), typically on TxDone of the previous packet
+    /// The staged payload is consumed once the transmission is started (kept on error).
+    /// Return false when nothing was staged (no transmission started)
+    pub async fn transmit_staged<const N: usize>(&mut self, buffer: &mut TxDoubleBuffer<N>, tx_timeout: u32) -> Result<bool, Lr1120Error> {
+        let Some(data) = buffer.staged() else {
+            return Ok(false);
+        };
+        self.transmit(data, tx_timeout).await?;
+        buffer.clear();
+        Ok(true)
+    }
+
+    /// Transmit a payload and measure the latency between the TX command and the TxDone interrupt
+    /// The interrupt is detected by polling the status every [`TX_DONE_POLL_PERIOD`], which bounds the measurement uncertainty.
+    /// The TxDone interrupt is cleared before returning. An IrqTimeout error is returned if TxDone is not raised before `max_wait`.
+    pub async fn transmit_timed(&mut self, data: &[u8], tx_timeout: u32, max_wait: Duration) -> Result<Duration, Lr1120Error> {
+        let tx_done = Intr::new(IRQ_MASK_TX_DONE);
+        self.clear_irqs(Some(tx_done)).await?;
+        if !self.is_fast_tx() {
+            self.set_chip_mode(ChipMode::StandbyRc).await?;
+        }
+        self.wr_tx_buffer_from(data).await?;
+        let start = Instant::now();
+        self.set_tx(tx_timeout).await?;
+        loop {
+            let (_, intr) = self.get_status().await?;
+            let elapsed = start.elapsed();
+            if intr.tx_done() {
+                self.clear_irqs(Some(tx_done)).await?;
+                return Ok(elapsed);
+            }
+            if elapsed > max_wait {
+                return Err(Lr1120Error::IrqTimeout);
+            }
+            Timer::after(TX_DONE_POLL_PERIOD).await;
+        }
     }
 
     /// Set chip in TX mode. Set timeout to 0 or to a value longer than the packet duration.
     /// Timeout is given in LF clock step (1/32.768kHz ~ 30.5us)
     pub async fn set_tx(&mut self, tx_timeout: u32) -> Result<(), Lr1120Error> {
+        self.rx_duty_cycle = None;
         let req = set_tx_cmd(tx_timeout);
         self.cmd_wr(&req).await
     }
 
     /// Start TX in test continuous wave
     pub async fn set_tx_cw(&mut self) -> Result<(), Lr1120Error> {
+        self.rx_duty_cycle = None;
         let req = set_tx_cw_cmd();
         self.cmd_wr(&req).await
     }
@@ -97,14 +446,43 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// and any other value, the chip will go back to its fallback mode if a reception does not occur before the timeout is elapsed
     /// Timeout is given in LF clock step (1/32.768kHz ~ 30.5us)
     pub async fn set_rx(&mut self, rx_timeout: u32, wait_ready: bool) -> Result<(), Lr1120Error> {
+        self.rx_duty_cycle = None;
         let req = set_rx_cmd(rx_timeout);
         self.cmd_wr(&req).await?;
         if wait_ready {
-            self.wait_ready(Duration::from_millis(100)).await?;
+            self.wait_ready(self.timeouts.cmd).await?;
         }
         Ok(())
     }
 
+    /// Number of LF clock steps for a timeout, saturated at `max`
+    fn timeout_steps(&self, timeout: Duration, max: u32) -> u32 {
+        let steps = duration_to_lf_steps_at(timeout, self.lf_clk_hz);
+        if steps > max as u64 {
+            #[cfg(feature = "defmt")]
+            defmt::warn!("Timeout of {}ms saturated to {} LF clock steps", timeout.as_millis(), max);
+            return max;
+        }
+        steps as u32
+    }
+
+    /// Set chip in TX mode with a timeout given as a duration (zero to disable the timeout)
+    /// The duration is converted with the LF clock rate (see [`set_lf_clk_rate`](Lr1120::set_lf_clk_rate)), rounded up,
+    /// and saturated at the maximum timeout (~512s at 32.768kHz, a warning is logged with `defmt`)
+    pub async fn set_tx_for(&mut self, timeout: Duration) -> Result<(), Lr1120Error> {
+        let steps = self.timeout_steps(timeout, LF_STEP_MAX);
+        self.set_tx(steps).await
+    }
+
+    /// Set chip in RX mode with a timeout given as a duration (zero for a single reception without timeout)
+    /// The duration is converted with the LF clock rate (see [`set_lf_clk_rate`](Lr1120::set_lf_clk_rate)), rounded up,
+    /// and saturated at the maximum timeout (~512s at 32.768kHz, a warning is logged with `defmt`):
+    /// use [`set_rx_continous`](Lr1120::set_rx_continous) for a continuous reception
+    pub async fn set_rx_for(&mut self, timeout: Duration, wait_ready: bool) -> Result<(), Lr1120Error> {
+        let steps = self.timeout_steps(timeout, RX_TIMEOUT_MAX);
+        self.set_rx(steps, wait_ready).await
+    }
+
     /// Set RX in continuous mode
     pub async fn set_rx_continous(&mut self) -> Result<(), Lr1120Error> {
         self.set_rx(0xFFFFFF,true).await
@@ -115,19 +493,51 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// Repeat operation every `cycle_time` (which must be bigger than rx_max_time)
     /// The `use_lora_cad` is only valid if packet type was set to LoRa and performs a CAD instead of a standard reception.
     /// In this case the exit mode of the CAD is performed, i.e. it can start a TX if configured as Listen-Before-Talk
+    /// The duty cycle is recorded until another mode is set: GNSS/WiFi scans are refused meanwhile (see [`coex`](crate::coex))
     pub async fn set_rx_duty_cycle(&mut self, listen_time: u32, cycle_time: u32, use_lora_cad: bool) -> Result<(), Lr1120Error> {
         let req = set_rx_duty_cycle_cmd(listen_time, cycle_time, use_lora_cad);
-        self.cmd_wr(&req).await
+        self.cmd_wr(&req).await?;
+        self.rx_duty_cycle = Some(RxDutyCycle { listen_time, cycle_time, use_lora_cad });
+        Ok(())
     }
 
-    /// Read RX stats
-    pub async fn get_rx_stats(&mut self) -> Result<StatsRsp, Lr1120Error> {
+    /// Read RX stats without interpretation: the meaning of the counters depends on the packet type (see [`RxStats`])
+    pub async fn get_rx_stats_raw(&mut self) -> Result<StatsRsp, Lr1120Error> {
         let req = get_stats_req();
         let mut rsp = StatsRsp::new();
         self.cmd_rd(&req, rsp.as_mut()).await?;
         Ok(rsp)
     }
 
+    /// Read RX stats interpreted for the packet type configured (read from the chip when not known by the driver)
+    /// Return WrongPacketType for packet types other than LoRa, ranging and FSK
+    pub async fn get_rx_stats(&mut self) -> Result<RxStats, Lr1120Error> {
+        let packet_type = match self.packet_type {
+            Some(t) => t,
+            None => self.get_packet_type().await?,
+        };
+        let rsp = self.get_rx_stats_raw().await?;
+        match packet_type {
+            PacketType::Lora | PacketType::Ranging => Ok(RxStats::Lora((&rsp).into())),
+            PacketType::Gfsk => Ok(RxStats::Fsk((&rsp).into())),
+            actual => Err(Lr1120Error::WrongPacketType { expected: PacketType::Lora, actual }),
+        }
+    }
+
+    /// Read RX stats of a LoRa reception
+    /// Return WrongPacketType if the packet type configured is not LoRa (or ranging)
+    pub async fn get_lora_stats(&mut self) -> Result<LoraStats, Lr1120Error> {
+        self.check_packet_type(PacketType::Lora).await?;
+        self.get_rx_stats_raw().await.map(|rsp| (&rsp).into())
+    }
+
+    /// Read RX stats of an FSK reception
+    /// Return WrongPacketType if the packet type configured is not FSK
+    pub async fn get_fsk_stats(&mut self) -> Result<FskStats, Lr1120Error> {
+        self.check_packet_type(PacketType::Gfsk).await?;
+        self.get_rx_stats_raw().await.map(|rsp| (&rsp).into())
+    }
+
     /// Clear RX stats
     pub async fn clear_rx_stats(&mut self) -> Result<(), Lr1120Error> {
         self.cmd_wr(&reset_stats_cmd()).await
@@ -141,7 +551,7 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         Ok(rsp)
     }
 
-    /// Measure RSSI instantaneous
+    /// Measure RSSI instantaneous (in -0.5dBm)
     pub async fn get_rssi_inst(&mut self) -> Result<u8, Lr1120Error> {
         let req = get_rssi_inst_req();
         let mut rsp = RssiInstRsp::new();
@@ -160,6 +570,23 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         Ok(avg as u8)
     }
 
+    /// Measure RSSI instantaneous in dBm
+    pub async fn get_rssi_inst_dbm(&mut self) -> Result<i16, Lr1120Error> {
+        self.get_rssi_inst().await.map(rssi_to_dbm)
+    }
+
+    /// Measure an average RSSI in dBm (see [`get_rssi_avg`](Self::get_rssi_avg))
+    pub async fn get_rssi_avg_dbm(&mut self, nb_meas: u16) -> Result<i16, Lr1120Error> {
+        self.get_rssi_avg(nb_meas).await.map(rssi_to_dbm)
+    }
+
+    /// Set the gain offsets of the RSSI estimation
+    pub async fn set_rssi_calibration(&mut self, cal: &RssiCalibration) -> Result<(), Lr1120Error> {
+        let [g4, g5, g6, g7, g8, g9, g10, g11, g12, g13, hp1, hp2, hp3, hp4, hp5, hp6, hp7] = cal.tune;
+        let req = set_rssi_calibration_cmd(g4, g5, g6, g7, g8, g9, g10, g11, g12, g13, hp1, hp2, hp3, hp4, hp5, hp6, hp7, cal.gain_offset);
+        self.cmd_wr(&req).await
+    }
+
     /// Set whether the RX timeout stops when preamble is detected or when the synchronization is confirmed (Default)
     pub async fn set_stop_timeout(&mut self, on_preamble: bool) -> Result<(), Lr1120Error> {
         let req = stop_timeout_on_preamble_cmd(on_preamble);
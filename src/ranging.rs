@@ -0,0 +1,384 @@
+//! # Ranging
+//!
+//! API of the LoRa ranging exchange (available with the `ranging` feature) and post-processing of its results.
+//!
+//! The LoRa ranging exchange reports a round-trip time of flight, converted to a distance.
+//! Multipath or an obstructed line of sight (NLOS) bias this distance without any indication from the chip.
+//! [`RangingMeasurement`] compares the RSSI measured during the exchange with the path loss expected
+//! for the distance reported, and annotates the result with a [`RangingQuality`]:
+//! - an RSSI much higher than the free-space model means the distance is overestimated (reflected path, excess delay),
+//! - an RSSI much lower than a lossy model (path loss exponent `exp_max`) indicates a heavily obstructed link.
+//!
+//! ## Example
+//! ```rust,no_run
+//! let cfg = RangingPlausibilityCfg::default();
+//! let meas = lr1120.get_ranging_measurement(&modulation, &cfg).await?;
+//! if meas.is_plausible() {
+//!     info!("Distance = {} cm", meas.distance_cm);
+//! }
+//! ```
+//!
+//! ## Calibration campaigns
+//! The TxRx delay (see [`set_ranging_txrx_delay`](Lr1120::set_ranging_txrx_delay)) depends on the board and should be calibrated
+//! by running exchanges at known distances. [`log_ranging_exchange`](Lr1120::log_ranging_exchange) records the raw result,
+//! RSSI, modulation, temperature and timestamp of each exchange into a [`RangingLogSink`] (e.g. a `heapless::Vec`)
+//! for offline processing. The frequency estimation of the exchange is not recorded: it cannot be read with the commands of this driver.
+//!
+//! ## Available Methods
+//! - [`set_ranging_dev_addr`](Lr1120::set_ranging_dev_addr) - Set device address for ranging
+//! - [`set_ranging_addr_set`](Lr1120::set_ranging_addr_set) - Set device address to answer a set of request addresses
+//! - [`set_ranging_req_addr`](Lr1120::set_ranging_req_addr) - Set request address for ranging
+//! - [`set_ranging_txrx_delay`](Lr1120::set_ranging_txrx_delay) - Set ranging calibration delay
+//! - [`get_ranging_base_delay`](Lr1120::get_ranging_base_delay) - Get base delay for ranging based on RF band, bandwidth and SF
+//! - [`set_ranging_delay_table_hf`](Lr1120::set_ranging_delay_table_hf) - Set the ranging delay calibration of the 2.4GHz band
+//! - [`set_ranging_params`](Lr1120::set_ranging_params) - Configure ranging parameters
+//! - [`get_ranging_result`](Lr1120::get_ranging_result) - Get basic ranging results (distance)
+//! - [`get_ranging_rssi`](Lr1120::get_ranging_rssi) - Get RSSI measured during ranging
+//! - [`get_ranging_measurement`](Lr1120::get_ranging_measurement) - Read the last ranging result and RSSI and check their plausibility
+//! - [`log_ranging_exchange`](Lr1120::log_ranging_exchange) - Record the last ranging exchange into a sink for calibration campaigns
+//! - [`RangingResultRsp::distance_cm`] - Distance of a ranging result
+//! - [`path_loss_cdb`] - Path loss for a distance and frequency
+
+use embassy_time::Instant;
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiBus;
+
+use super::{BusyPin, Lr1120, Lr1120Error};
+use super::cmd::cmd_lora::*;
+use super::lora::LoraModulationParams;
+use super::radio::rssi_to_dbm;
+
+/// Ranging delay calibration: one line per bandwidth (500, 250, 125kHz), one column per SF (SF5 to SF12)
+pub type RangingDelayTable = [[u32; 8]; 3];
+
+/// Recommended delay for ranging in the sub-GHz bands
+pub const RANGING_DELAY_SUB_GHZ: RangingDelayTable = [
+    [19115, 19113, 19121, 19127, 19141, 19178, 19242, 19370],
+    [20265, 20266, 20279, 20292, 20236, 20305, 20433, 20689],
+    [20154, 20268, 20298, 20319, 20323, 20314, 20570, 21082],
+];
+
+/// RF frequency from which the 2.4GHz ranging calibration is used
+pub const RANGING_HF_MIN_FREQ: u32 = 2_000_000_000;
+
+/// Base delay for ranging from a calibration table depending on bandwidth and SF
+/// Bandwidths without calibration use an approximation
+pub fn ranging_base_delay(table: &RangingDelayTable, modulation: &LoraModulationParams) -> u32 {
+    let line = match modulation.bw {
+        LoraBw::Bw500 => 0,
+        LoraBw::Bw250 => 1,
+        LoraBw::Bw125 => 2,
+        _ => 3,
+    };
+    table.get(line)
+        .and_then(|l| l.get((modulation.sf as usize).wrapping_sub(5)))
+        .copied()
+        .unwrap_or(18000 - (5600 >> (12 - modulation.sf as u32)))
+}
+
+/// Compute the device address and check length covering a set of ranging request addresses
+/// The chip only checks the LSB bytes of the address, so the result is the longest common suffix (in bytes) of all addresses.
+/// Note that any other request address sharing the same suffix will also be answered.
+/// Return an error if the set is empty or if addresses do not share at least their LSB.
+pub fn ranging_addr_cover(addrs: &[u32]) -> Result<(u32, CheckLength), Lr1120Error> {
+    let (first, others) = addrs.split_first().ok_or(Lr1120Error::InvalidParam)?;
+    let nb_bytes = others.iter()
+        .map(|a| ((a ^ first).trailing_zeros() / 8) as u8)
+        .min()
+        .unwrap_or(4);
+    let length = match nb_bytes {
+        0 => return Err(Lr1120Error::InvalidParam),
+        1 => CheckLength::Addr8b,
+        2 => CheckLength::Addr16b,
+        3 => CheckLength::Addr24b,
+        _ => CheckLength::Addr32b,
+    };
+    Ok((*first, length))
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+/// Frequency estimation during ranging exchange (valid only on responder side)
+pub struct RangingFei {
+    /// Frequency estimation on first exchange
+    pub fei1: i32,
+    /// Frequency estimation on second exchange
+    pub fei2: i32,
+}
+
+/// Free-space path loss constant: 20*log10(4*pi/c) in 0.01dB
+const FSPL_CONST_CDB: i32 = -14755;
+
+/// 1000*log10(x) for x >= 1 (0 for x = 0), with an accuracy better than 0.001
+fn log10_x1000(x: u64) -> i32 {
+    if x == 0 {
+        return 0;
+    }
+    let int = 63 - x.leading_zeros();
+    // Mantissa in [1,2) with 30 fractional bits
+    let mut m = if int >= 30 {x >> (int - 30)} else {x << (30 - int)};
+    let mut frac = 0u64;
+    for i in (0..16).rev() {
+        m = (m * m) >> 30;
+        if m >= 2 << 30 {
+            m >>= 1;
+            frac |= 1 << i;
+        }
+    }
+    let log2_q16 = ((int as u64) << 16) | frac;
+    // log10(2) = 0.30103
+    ((log2_q16 * 30103 / 100) >> 16) as i32
+}
+
+/// Path loss in 0.01dB for a distance (cm) and RF frequency (Hz)
+/// `exp` is the path loss exponent in tenths (20 for free space). Distances below 1m are counted as 1m.
+pub fn path_loss_cdb(distance_cm: u32, freq: u32, exp: u8) -> i32 {
+    let d = log10_x1000(distance_cm.max(100) as u64) - 2000;
+    (exp as i32 * d + 20 * log10_x1000(freq as u64)) / 10 + FSPL_CONST_CDB
+}
+
+impl RangingRssiRsp {
+    /// RSSI measured during ranging in dBm
+    pub fn rssi_dbm(&self) -> i16 {
+        rssi_to_dbm(self.rssi())
+    }
+}
+
+impl RangingResultRsp {
+    /// Distance in cm for a ranging exchange done with bandwidth `bw`
+    /// The raw result is a signed 24-bit value: the distance can be negative for very short range
+    pub fn distance_cm(&self, bw: LoraBw) -> i32 {
+        let rng = ((self.rng() << 8) as i32) >> 8;
+        // Distance in meter is given by rng*150/(2^12*Bandwidth in MHz)
+        (rng as i64 * 15_000_000_000 / (4096 * bw.to_hz() as i64)) as i32
+    }
+}
+
+/// Settings of the ranging plausibility check
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RangingPlausibilityCfg {
+    /// TX power of the peer in dBm
+    pub tx_power_dbm: i8,
+    /// Sum of the antenna gains of both ends minus cable losses (dB)
+    pub gain_db: i8,
+    /// Path loss exponent (in tenths) of the most lossy environment expected
+    pub exp_max: u8,
+    /// Tolerance (dB) on both sides of the expected RSSI range
+    pub margin_db: u8,
+}
+
+impl Default for RangingPlausibilityCfg {
+    fn default() -> Self {
+        Self {
+            tx_power_dbm: 14,
+            gain_db: 0,
+            exp_max: 40,
+            margin_db: 6,
+        }
+    }
+}
+
+impl RangingPlausibilityCfg {
+    /// Expected RSSI (dBm) for a distance and frequency with a path loss exponent (in tenths)
+    pub fn expected_rssi_dbm(&self, distance_cm: u32, freq: u32, exp: u8) -> i16 {
+        let eirp = (self.tx_power_dbm as i32 + self.gain_db as i32) * 100;
+        ((eirp - path_loss_cdb(distance_cm, freq, exp)) / 100) as i16
+    }
+}
+
+/// Quality of a ranging result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RangingQuality {
+    /// RSSI consistent with the distance
+    Plausible,
+    /// RSSI above the free-space model: distance likely overestimated (multipath)
+    TooStrong,
+    /// RSSI below the lossy model: link heavily obstructed (NLOS)
+    TooWeak,
+    /// Negative distance
+    Invalid,
+}
+
+/// Ranging result annotated with its plausibility
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RangingMeasurement {
+    /// Distance in cm
+    pub distance_cm: i32,
+    /// RSSI measured during the exchange in dBm
+    pub rssi_dbm: i16,
+    /// RSSI expected in free space for this distance in dBm
+    pub expected_rssi_dbm: i16,
+    /// Plausibility of the distance compared with the RSSI
+    pub quality: RangingQuality,
+}
+
+impl RangingMeasurement {
+    /// Check a distance (cm) against the RSSI (dBm) measured at RF frequency `freq` (Hz)
+    pub fn new(distance_cm: i32, rssi_dbm: i16, freq: u32, cfg: &RangingPlausibilityCfg) -> Self {
+        let d = distance_cm.max(0) as u32;
+        let expected_rssi_dbm = cfg.expected_rssi_dbm(d, freq, 20);
+        let min_rssi_dbm = cfg.expected_rssi_dbm(d, freq, cfg.exp_max);
+        let margin = cfg.margin_db as i16;
+        let quality = if distance_cm < 0 {
+            RangingQuality::Invalid
+        } else if rssi_dbm > expected_rssi_dbm + margin {
+            RangingQuality::TooStrong
+        } else if rssi_dbm < min_rssi_dbm - margin {
+            RangingQuality::TooWeak
+        } else {
+            RangingQuality::Plausible
+        };
+        Self { distance_cm, rssi_dbm, expected_rssi_dbm, quality }
+    }
+
+    /// True if the RSSI is consistent with the distance
+    pub fn is_plausible(&self) -> bool {
+        self.quality == RangingQuality::Plausible
+    }
+}
+
+/// Record of one ranging exchange for delay calibration campaigns
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RangingLogEntry {
+    /// Local instant when the result was read
+    pub timestamp: Instant,
+    /// Raw ranging result (signed 24-bit value)
+    pub rng: u32,
+    /// Distance in cm computed from the raw result
+    pub distance_cm: i32,
+    /// RSSI measured during the exchange in dBm
+    pub rssi_dbm: i16,
+    /// Spreading factor of the exchange
+    pub sf: Sf,
+    /// Bandwidth of the exchange
+    pub bw: LoraBw,
+    /// RF frequency in Hz (None if unknown)
+    pub freq: Option<u32>,
+    /// Chip temperature in °C
+    pub temperature: i16,
+}
+
+/// Destination of the ranging exchange records
+pub trait RangingLogSink {
+    /// Store a record, returning an error when it cannot be stored (e.g. InvalidSize when full)
+    fn record(&mut self, entry: &RangingLogEntry) -> Result<(), Lr1120Error>;
+}
+
+impl<const N: usize> RangingLogSink for heapless::Vec<RangingLogEntry, N> {
+    fn record(&mut self, entry: &RangingLogEntry) -> Result<(), Lr1120Error> {
+        self.push(*entry).map_err(|_| Lr1120Error::InvalidSize)
+    }
+}
+
+impl<O,SPI, M> Lr1120<O,SPI, M> where
+    O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+{
+
+    /// Set the device address for ranging operation
+    /// The device will answer to ranging request only if the request address matches the device address
+    /// The length allows to define how many bytes from the address are checked (starting from LSB)
+    pub async fn set_ranging_dev_addr(&mut self, addr: u32, length: Option<CheckLength>) -> Result<(), Lr1120Error> {
+         let req = set_ranging_addr_cmd(addr, length.unwrap_or(CheckLength::Addr32b));
+        self.cmd_wr(&req).await
+   }
+
+    /// Set the device address for ranging operation such that the device answers to all request addresses from the set
+    /// Fails if the addresses do not share a common LSB (see [`ranging_addr_cover`])
+    pub async fn set_ranging_addr_set(&mut self, addrs: &[u32]) -> Result<(), Lr1120Error> {
+        let (addr, length) = ranging_addr_cover(addrs)?;
+        self.set_ranging_dev_addr(addr, Some(length)).await
+    }
+
+    /// Set the request address for ranging operation
+    pub async fn set_ranging_req_addr(&mut self, addr: u32) -> Result<(), Lr1120Error> {
+         let req = set_ranging_req_addr_cmd(addr);
+        self.cmd_wr(&req).await
+    }
+
+    /// Set the ranging calibration value
+    pub async fn set_ranging_txrx_delay(&mut self, delay: u32) -> Result<(), Lr1120Error> {
+         let req = set_ranging_tx_rx_delay_cmd(delay);
+        self.cmd_wr(&req).await
+   }
+
+    /// Set the ranging delay calibration used in the 2.4GHz band (None to remove it)
+    /// The driver has no built-in 2.4GHz calibration: it must come from the chip vendor or a calibration of the board.
+    pub fn set_ranging_delay_table_hf(&mut self, table: Option<RangingDelayTable>) {
+        self.ranging_delay_hf = table;
+    }
+
+    /// Get the base delay for ranging depdending on the RF frequency configured, bandwidth and SF
+    /// Delay was calibrated only for bandwidth 125kHz, 250kHz and 500kHz.
+    /// The sub-GHz calibration is used when the frequency is unknown (`set_rf` not called since reset).
+    /// Return Unsupported in the 2.4GHz band when no calibration was set with [`set_ranging_delay_table_hf`](Lr1120::set_ranging_delay_table_hf),
+    /// since the sub-GHz values would bias the distance by several meters.
+    pub fn get_ranging_base_delay(&self, modulation: &LoraModulationParams) -> Result<u32, Lr1120Error> {
+        match self.rf_freq {
+            Some(freq) if freq >= RANGING_HF_MIN_FREQ => {
+                let table = self.ranging_delay_hf.as_ref().ok_or(Lr1120Error::Unsupported)?;
+                Ok(ranging_base_delay(table, modulation))
+            }
+            _ => Ok(ranging_base_delay(&RANGING_DELAY_SUB_GHZ, modulation)),
+        }
+    }
+
+    /// Set the ranging parameters: number of symbols
+    /// Number of symbols should typically be between 8 to 16 symbols, with 12 being close to optimal performances
+    pub async fn set_ranging_params(&mut self, nb_symbols: u8) -> Result<(), Lr1120Error> {
+         let req = set_ranging_parameter_cmd(0, nb_symbols);
+        self.cmd_wr(&req).await
+   }
+
+    /// Return the result of last ranging exchange (round-trip time of flight and RSSI)
+    /// The distance is provided
+    pub async fn get_ranging_result(&mut self) -> Result<RangingResultRsp, Lr1120Error> {
+        let req = get_ranging_result_req(RangingResKind::Distance);
+        let mut rsp = RangingResultRsp::new();
+        self.cmd_rd(&req, rsp.as_mut()).await?;
+        Ok(rsp)
+    }
+
+    /// Return the RSSI measured during ranging
+    pub async fn get_ranging_rssi(&mut self) -> Result<RangingRssiRsp, Lr1120Error> {
+        let req = get_ranging_result_req(RangingResKind::Rssi);
+        let mut rsp = RangingRssiRsp::new();
+        self.cmd_rd(&req, rsp.as_mut()).await?;
+        Ok(rsp)
+    }
+
+    /// Read the result and RSSI of the last ranging exchange and check their plausibility
+    /// `modulation` is the modulation used for the exchange.
+    /// Return InvalidConfig if the RF frequency is unknown (`set_rf` not called since reset)
+    pub async fn get_ranging_measurement(&mut self, modulation: &LoraModulationParams, cfg: &RangingPlausibilityCfg) -> Result<RangingMeasurement, Lr1120Error> {
+        let freq = self.rf_freq.ok_or(Lr1120Error::InvalidConfig)?;
+        let distance_cm = self.get_ranging_result().await?.distance_cm(modulation.bw);
+        let rssi_dbm = self.get_ranging_rssi().await?.rssi_dbm();
+        Ok(RangingMeasurement::new(distance_cm, rssi_dbm, freq, cfg))
+    }
+
+    /// Read the result, RSSI and temperature of the last ranging exchange and store them into a sink
+    /// `modulation` is the modulation used for the exchange. Return the record stored
+    pub async fn log_ranging_exchange<S: RangingLogSink>(&mut self, modulation: &LoraModulationParams, sink: &mut S) -> Result<RangingLogEntry, Lr1120Error> {
+        let result = self.get_ranging_result().await?;
+        let timestamp = Instant::now();
+        let rssi_dbm = self.get_ranging_rssi().await?.rssi_dbm();
+        let temperature = self.get_temperature_celsius().await?;
+        let entry = RangingLogEntry {
+            timestamp,
+            rng: result.rng(),
+            distance_cm: result.distance_cm(modulation.bw),
+            rssi_dbm,
+            sf: modulation.sf,
+            bw: modulation.bw,
+            freq: self.rf_freq,
+            temperature,
+        };
+        sink.record(&entry)?;
+        Ok(entry)
+    }
+
+}
@@ -0,0 +1,173 @@
+//! # Temperature-triggered recalibration
+//!
+//! The PLL and image calibrations depend on temperature: the chip calibrates at startup,
+//! but a large temperature drift since then degrades the RF performances.
+//! [`TempRecal`] samples the temperature periodically, compares it with the temperature of the last calibration
+//! and publishes an event on an `embassy-sync` channel when the drift exceeds a threshold (10°C by default).
+//!
+//! By default the recalibration is left to the application (event [`RecalEvent::Needed`]),
+//! which can delay it until no TX/RX is active and then call [`TempRecal::recalibrate`].
+//! With `auto` enabled the recalibration is run immediately by the task.
+//! [`run`](TempRecal::run) owns the driver and is meant for the `auto` mode: otherwise call [`step`](TempRecal::step)
+//! periodically from the task owning the radio.
+//! Calibration ends in standby RC: any TX/RX in progress is stopped.
+//!
+//! The temperature of the last calibration can be saved and restored (e.g. in non-volatile memory)
+//! with [`last_cal_temp`](TempRecal::last_cal_temp) and [`set_last_cal_temp`](TempRecal::set_last_cal_temp).
+//!
+//! ## Example
+//! ```rust,no_run
+//! static RECAL_EVENTS: Channel<CriticalSectionRawMutex, RecalEvent, 2> = Channel::new();
+//!
+//! #[embassy_executor::task]
+//! async fn recal_task(mut lr1120: MyLr1120) {
+//!     let mut recal = TempRecal::new(RecalCfg::default().with_band(FreqBand::ism_868()).with_auto(true));
+//!     recal.run(&mut lr1120, RECAL_EVENTS.sender()).await
+//! }
+//! ```
+//!
+//! ## Available Methods
+//! - [`new`](TempRecal::new) - Create the recalibration task
+//! - [`check`](TempRecal::check) - Measure the temperature and return the drift since the last calibration
+//! - [`recalibrate`](TempRecal::recalibrate) - Run the calibration and record the temperature
+//! - [`step`](TempRecal::step) - Check the temperature and publish an event (or recalibrate) on drift
+//! - [`run`](TempRecal::run) - Run the task forever
+
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embassy_sync::channel::Sender;
+use embassy_time::{Duration, Timer};
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiBus;
+
+use super::{BusyPin, Lr1120, Lr1120Error};
+use super::system::{CalibrateBlocks, FreqBand};
+
+/// Recalibration task configuration
+#[derive(Debug, Clone, Copy)]
+pub struct RecalCfg {
+    /// Temperature sampling period
+    pub period: Duration,
+    /// Temperature drift (°C) since the last calibration triggering a recalibration
+    pub threshold: u8,
+    /// Blocks to recalibrate
+    pub blocks: CalibrateBlocks,
+    /// Band for the image calibration (None to use the IMG block default band, if selected)
+    pub band: Option<FreqBand>,
+    /// Recalibrate immediately instead of publishing [`RecalEvent::Needed`]
+    pub auto: bool,
+}
+
+impl Default for RecalCfg {
+    fn default() -> Self {
+        Self {
+            period: Duration::from_secs(60),
+            threshold: 10,
+            blocks: CalibrateBlocks::PLL,
+            band: None,
+            auto: false,
+        }
+    }
+}
+
+impl RecalCfg {
+    /// Add an image calibration on a frequency band
+    pub fn with_band(self, band: FreqBand) -> Self {
+        Self { band: Some(band), ..self }
+    }
+
+    /// Recalibrate immediately on drift
+    pub fn with_auto(self, auto: bool) -> Self {
+        Self { auto, ..self }
+    }
+}
+
+/// Event published by the recalibration task
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RecalEvent {
+    /// Temperature drift above the threshold: the application should call [`TempRecal::recalibrate`] when the radio is idle
+    Needed { temp: i16, drift: i16 },
+    /// Recalibration done at temperature `temp`
+    Done { temp: i16 },
+    /// An operation failed
+    Error(Lr1120Error),
+}
+
+/// Temperature-triggered recalibration
+pub struct TempRecal {
+    cfg: RecalCfg,
+    /// Temperature of the last calibration (None until the first measurement)
+    last_cal_temp: Option<i16>,
+}
+
+impl TempRecal {
+    /// Create the recalibration task
+    /// The first temperature measured is used as reference, assuming the chip was calibrated at startup
+    pub fn new(cfg: RecalCfg) -> Self {
+        Self { cfg, last_cal_temp: None }
+    }
+
+    /// Temperature of the last calibration
+    pub fn last_cal_temp(&self) -> Option<i16> {
+        self.last_cal_temp
+    }
+
+    /// Set the temperature of the last calibration (e.g. restored from non-volatile memory)
+    pub fn set_last_cal_temp(&mut self, temp: Option<i16>) {
+        self.last_cal_temp = temp;
+    }
+
+    /// Measure the temperature and return it with the drift since the last calibration
+    pub async fn check<O, SPI, M>(&mut self, lr: &mut Lr1120<O,SPI,M>) -> Result<(i16, i16), Lr1120Error>
+        where O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+    {
+        let temp = lr.get_temperature_celsius().await?;
+        let last = *self.last_cal_temp.get_or_insert(temp);
+        Ok((temp, temp - last))
+    }
+
+    /// Run the calibration of the configured blocks (and image calibration on the configured band)
+    /// and record the temperature. The chip ends in standby RC.
+    pub async fn recalibrate<O, SPI, M>(&mut self, lr: &mut Lr1120<O,SPI,M>) -> Result<i16, Lr1120Error>
+        where O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+    {
+        let temp = lr.get_temperature_celsius().await?;
+        if self.cfg.blocks != CalibrateBlocks::empty() {
+            lr.calibrate_blocks(self.cfg.blocks).await?;
+        }
+        if let Some(band) = self.cfg.band {
+            lr.calib_image(band).await?;
+        }
+        self.last_cal_temp = Some(temp);
+        Ok(temp)
+    }
+
+    /// Check the temperature and, on drift above the threshold, publish an event or recalibrate
+    pub async fn step<O, SPI, M, RM, const N: usize>(&mut self, lr: &mut Lr1120<O,SPI,M>, events: &Sender<'_, RM, RecalEvent, N>) -> Result<(), Lr1120Error>
+        where O: OutputPin, SPI: SpiBus<u8>, M: BusyPin, RM: RawMutex
+    {
+        let (temp, drift) = self.check(lr).await?;
+        if drift.unsigned_abs() < self.cfg.threshold as u16 {
+            return Ok(());
+        }
+        if self.cfg.auto {
+            let temp = self.recalibrate(lr).await?;
+            events.send(RecalEvent::Done { temp }).await;
+        } else {
+            events.send(RecalEvent::Needed { temp, drift }).await;
+        }
+        Ok(())
+    }
+
+    /// Run the task forever: errors are published as event
+    pub async fn run<O, SPI, M, RM, const N: usize>(&mut self, lr: &mut Lr1120<O,SPI,M>, events: Sender<'_, RM, RecalEvent, N>) -> !
+        where O: OutputPin, SPI: SpiBus<u8>, M: BusyPin, RM: RawMutex
+    {
+        loop {
+            if let Err(e) = self.step(lr, &events).await {
+                events.send(RecalEvent::Error(e)).await;
+            }
+            Timer::after(self.cfg.period).await;
+        }
+    }
+}
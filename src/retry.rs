@@ -0,0 +1,92 @@
+//! # Retry of read commands on SPI errors
+//!
+//! A transient SPI error (e.g. on a noisy bus or a bus shared with other devices) is reported as `Spi`.
+//! With a [`SpiRetry`] policy set by [`set_spi_retry`](Lr1120::set_spi_retry), the idempotent read commands
+//! are sent again after a short delay, up to a number of retries:
+//! - [`get_status`](Lr1120::get_status), [`get_version`](Lr1120::get_version),
+//!   [`get_chip_eui`](Lr1120::get_chip_eui) and [`get_join_eui`](Lr1120::get_join_eui),
+//! - GNSS results: [`gnss_get_result_size`](Lr1120::gnss_get_result_size), [`gnss_read_results`](Lr1120::gnss_read_results)
+//!   and [`gnss_get_nb_sv`](Lr1120::gnss_get_nb_sv),
+//! - WiFi results: [`wifi_get_nb_res`](Lr1120::wifi_get_nb_res), [`wifi_get_nb_country_code`](Lr1120::wifi_get_nb_country_code)
+//!   and the `wifi_get_result_*` methods.
+//!
+//! Other commands (configuration, mode changes, buffer or flash writes, crypto, reads clearing a state)
+//! are never retried: the error is returned immediately. No retry is done by default.
+//!
+//! ## Example
+//! ```rust,no_run
+//! lr1120.set_spi_retry(SpiRetry::new(3, Duration::from_micros(500)));
+//! ```
+//!
+//! ## Available Methods
+//! - [`set_spi_retry`](Lr1120::set_spi_retry) - Set the retry policy of read commands
+//! - [`spi_retry`](Lr1120::spi_retry) - Return the retry policy of read commands
+
+use embassy_time::{Duration, Timer};
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiBus;
+
+use super::{BusyPin, Lr1120, Lr1120Error};
+
+/// Retry policy of idempotent read commands on SPI errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SpiRetry {
+    /// Number of retries after the first attempt
+    pub retries: u8,
+    /// Delay before each retry
+    pub delay: Duration,
+}
+
+impl SpiRetry {
+    /// Retry up to `retries` times, waiting `delay` before each retry
+    pub const fn new(retries: u8, delay: Duration) -> Self {
+        Self { retries, delay }
+    }
+
+    /// No retry: SPI errors are returned immediately
+    pub const fn none() -> Self {
+        Self::new(0, Duration::from_ticks(0))
+    }
+}
+
+impl Default for SpiRetry {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl<O,SPI, M> Lr1120<O,SPI, M> where
+    O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+{
+
+    /// Set the retry policy of idempotent read commands on SPI errors
+    pub fn set_spi_retry(&mut self, retry: SpiRetry) {
+        self.spi_retry = retry;
+    }
+
+    /// Return the retry policy of idempotent read commands
+    pub fn spi_retry(&self) -> SpiRetry {
+        self.spi_retry
+    }
+
+    /// Run an idempotent operation, retrying it on SPI errors according to the policy
+    pub(crate) async fn with_spi_retry<F, T>(&mut self, mut op: F) -> Result<T, Lr1120Error>
+        where F: AsyncFnMut(&mut Self) -> Result<T, Lr1120Error>
+    {
+        let mut retries = self.spi_retry.retries;
+        loop {
+            match op(self).await {
+                Err(Lr1120Error::Spi) if retries > 0 => {
+                    retries -= 1;
+                    #[cfg(feature = "metrics")] {self.metrics.spi_retry();}
+                    // The failed transfer may have left the transaction open
+                    self.nss.set_high().map_err(|_| Lr1120Error::Pin)?;
+                    Timer::after(self.spi_retry.delay).await;
+                }
+                res => return res,
+            }
+        }
+    }
+
+}
@@ -0,0 +1,141 @@
+//! # Sensitivity and link budget
+//!
+//! The sensitivity of a receiver is the thermal noise in the receiver bandwidth, plus the noise figure of the receiver,
+//! plus the SNR required by the demodulator:
+//! - the noise floors ([`lora_noise_floor_cdbm`], [`fsk_noise_floor_cdbm`]) are the thermal noise (-174dBm/Hz) integrated over each bandwidth,
+//! - the LoRa demodulation floors ([`LORA_SNR_FLOOR_CDB`]) are the minimum SNR per spreading factor (-2.5dB per SF above SF4),
+//!   also used by the [`link`](crate::link) quality estimator.
+//!
+//! The noise figure depends on the board (matching network, RF switch, boosted RX) and the FSK required SNR on the modulation index
+//! and the target error rate: both are provided by the application in a [`SensitivityModel`],
+//! e.g. from the datasheet or from a measurement of the sensitivity on the board.
+//! All values are in 0.01dB (cdB) or 0.01dBm (cdBm).
+//!
+//! ## Example
+//! ```rust,no_run
+//! const MODEL: SensitivityModel = SensitivityModel::new(600, 1000);
+//! let status = lr1120.get_lora_packet_status().await?;
+//! let margin_cdb = MODEL.lora_margin_cdb(modulation.sf, modulation.bw, status.rssi_pkt_dbm());
+//! ```
+//!
+//! ## Available Functions
+//! - [`lora_noise_floor_cdbm`] / [`fsk_noise_floor_cdbm`] - Thermal noise in a LoRa or FSK RX bandwidth
+//! - [`lora_snr_floor_cdb`] - Minimum SNR to demodulate a spreading factor
+//! - [`lora_snr_margin_cdb`] - Margin of a measured SNR above the demodulation floor
+//! - [`SensitivityModel::lora_cdbm`] / [`SensitivityModel::fsk_cdbm`] - Sensitivity for a modulation
+//! - [`SensitivityModel::lora_margin_cdb`] / [`SensitivityModel::fsk_margin_cdb`] - Margin of a measured RSSI above the sensitivity
+
+#[cfg(feature = "fsk")]
+use super::fsk::RxBw;
+use super::lora::{LoraBw, Sf};
+
+/// Minimum SNR (in 0.01dB) to demodulate each spreading factor, from SF5 to SF12
+pub const LORA_SNR_FLOOR_CDB: [i16; 8] = [-250, -500, -750, -1000, -1250, -1500, -1750, -2000];
+
+/// Minimum SNR (in 0.01dB) to demodulate a spreading factor
+pub const fn lora_snr_floor_cdb(sf: Sf) -> i16 {
+    match sf {
+        Sf::Sf5  => LORA_SNR_FLOOR_CDB[0],
+        Sf::Sf6  => LORA_SNR_FLOOR_CDB[1],
+        Sf::Sf7  => LORA_SNR_FLOOR_CDB[2],
+        Sf::Sf8  => LORA_SNR_FLOOR_CDB[3],
+        Sf::Sf9  => LORA_SNR_FLOOR_CDB[4],
+        Sf::Sf10 => LORA_SNR_FLOOR_CDB[5],
+        Sf::Sf11 => LORA_SNR_FLOOR_CDB[6],
+        Sf::Sf12 => LORA_SNR_FLOOR_CDB[7],
+    }
+}
+
+/// Margin (in 0.01dB) of an SNR measured in 0.25dB (e.g. [`snr_pkt`](crate::lora::LoraPacketStatusRsp::snr_pkt)) above the demodulation floor
+pub const fn lora_snr_margin_cdb(sf: Sf, snr_qdb: i8) -> i16 {
+    snr_qdb as i16 * 25 - lora_snr_floor_cdb(sf)
+}
+
+/// Thermal noise (in 0.01dBm) in a LoRa bandwidth
+pub const fn lora_noise_floor_cdbm(bw: LoraBw) -> i16 {
+    match bw {
+        LoraBw::Bw1000 => -11400,
+        LoraBw::Bw812  => -11490,
+        LoraBw::Bw500  => -11701,
+        LoraBw::Bw406  => -11791,
+        LoraBw::Bw250  => -12002,
+        LoraBw::Bw203  => -12092,
+        LoraBw::Bw125  => -12303,
+        LoraBw::Bw101  => -12393,
+        LoraBw::Bw83   => -12479,
+        LoraBw::Bw62   => -12604,
+        LoraBw::Bw41   => -12780,
+        LoraBw::Bw31   => -12905,
+        LoraBw::Bw20   => -13081,
+        LoraBw::Bw15   => -13206,
+        LoraBw::Bw10   => -13382,
+        LoraBw::Bw7    => -13507,
+    }
+}
+
+/// Thermal noise (in 0.01dBm) in an FSK RX bandwidth
+#[cfg(feature = "fsk")]
+pub const fn fsk_noise_floor_cdbm(bw: RxBw) -> i16 {
+    match bw {
+        RxBw::Bw4800   => -13719,
+        RxBw::Bw5800   => -13637,
+        RxBw::Bw7300   => -13537,
+        RxBw::Bw9700   => -13413,
+        RxBw::Bw11700  => -13332,
+        RxBw::Bw14600  => -13236,
+        RxBw::Bw19500  => -13110,
+        RxBw::Bw23400  => -13031,
+        RxBw::Bw29300  => -12933,
+        RxBw::Bw39000  => -12809,
+        RxBw::Bw46900  => -12729,
+        RxBw::Bw58600  => -12632,
+        RxBw::Bw78200  => -12507,
+        RxBw::Bw93800  => -12428,
+        RxBw::Bw117300 => -12331,
+        RxBw::Bw156200 => -12206,
+        RxBw::Bw187200 => -12128,
+        RxBw::Bw234300 => -12030,
+        RxBw::Bw312000 => -11906,
+        RxBw::Bw373600 => -11828,
+        RxBw::Bw467000 => -11731,
+    }
+}
+
+/// Receiver parameters of the board used to compute the sensitivity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SensitivityModel {
+    /// Noise figure of the receiver (in 0.01dB)
+    pub nf_cdb: i16,
+    /// SNR required by the FSK demodulator in the RX bandwidth (in 0.01dB)
+    pub fsk_snr_cdb: i16,
+}
+
+impl SensitivityModel {
+    /// Create a model from the noise figure and the FSK required SNR (in 0.01dB)
+    pub const fn new(nf_cdb: i16, fsk_snr_cdb: i16) -> Self {
+        Self { nf_cdb, fsk_snr_cdb }
+    }
+
+    /// LoRa sensitivity (in 0.01dBm) for a spreading factor and bandwidth
+    pub const fn lora_cdbm(&self, sf: Sf, bw: LoraBw) -> i16 {
+        lora_noise_floor_cdbm(bw) + self.nf_cdb + lora_snr_floor_cdb(sf)
+    }
+
+    /// Margin (in 0.01dB) of an RSSI measured in dBm above the LoRa sensitivity
+    pub const fn lora_margin_cdb(&self, sf: Sf, bw: LoraBw, rssi_dbm: i16) -> i16 {
+        rssi_dbm.saturating_mul(100).saturating_sub(self.lora_cdbm(sf, bw))
+    }
+
+    /// FSK sensitivity (in 0.01dBm) for an RX bandwidth
+    #[cfg(feature = "fsk")]
+    pub const fn fsk_cdbm(&self, bw: RxBw) -> i16 {
+        fsk_noise_floor_cdbm(bw) + self.nf_cdb + self.fsk_snr_cdb
+    }
+
+    /// Margin (in 0.01dB) of an RSSI measured in dBm above the FSK sensitivity
+    #[cfg(feature = "fsk")]
+    pub const fn fsk_margin_cdb(&self, bw: RxBw, rssi_dbm: i16) -> i16 {
+        rssi_dbm.saturating_mul(100).saturating_sub(self.fsk_cdbm(bw))
+    }
+}
@@ -0,0 +1,110 @@
+//! # Shadow of the radio configuration
+//!
+//! With the `shadow` feature the driver records the last radio configuration sent to the chip
+//! (packet type, RF frequency, PA and TX power, modulation and packet parameters).
+//! This is useful for debugging, to check the configuration actually applied,
+//! and to restore the configuration after a reset or a sleep without retention.
+//!
+//! The shadow is not cleared on reset or sleep: it always reflects the last configuration requested by the host.
+//! The feature is opt-in to keep RAM usage small when not needed.
+//!
+//! ## Example
+//! ```rust,no_run
+//! lr1120.reset().await?;
+//! // ... chip level initialisation (TCXO, calibration, DIO)
+//! lr1120.restore_config().await?;
+//! ```
+//!
+//! ## Available Methods
+//! - [`current_config`](Lr1120::current_config) - Return the last radio configuration set
+//! - [`clear_config`](Lr1120::clear_config) - Forget the recorded configuration
+//! - [`restore_config`](Lr1120::restore_config) - Send again the recorded configuration to the chip
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiBus;
+
+use super::{BusyPin, Lr1120, Lr1120Error};
+#[cfg(feature = "fsk")]
+use super::fsk::{FskModulationParams, FskPacketParams};
+use super::lora::{LoraModulationParams, LoraPacketParams};
+use super::radio::{PacketType, PaSel, RampTime};
+
+/// Last radio configuration set by the host
+/// Each field is None until the corresponding setter is called
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RadioConfig {
+    /// Packet type
+    pub packet_type: Option<PacketType>,
+    /// RF frequency in Hz
+    pub rf_freq: Option<u32>,
+    /// PA selection and duty cycle
+    pub pa: Option<(PaSel, u8)>,
+    /// TX power (half-dB unit) and ramp time
+    pub tx_params: Option<(i8, RampTime)>,
+    /// LoRa modulation parameters
+    pub lora_modulation: Option<LoraModulationParams>,
+    /// LoRa packet parameters
+    pub lora_packet: Option<LoraPacketParams>,
+    /// FSK modulation parameters
+    #[cfg(feature = "fsk")]
+    pub fsk_modulation: Option<FskModulationParams>,
+    /// FSK packet parameters
+    #[cfg(feature = "fsk")]
+    pub fsk_packet: Option<FskPacketParams>,
+}
+
+impl<O,SPI, M> Lr1120<O,SPI, M> where
+    O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+{
+
+    /// Return the last radio configuration set
+    pub fn current_config(&self) -> &RadioConfig {
+        &self.shadow
+    }
+
+    /// Forget the recorded configuration
+    pub fn clear_config(&mut self) {
+        self.shadow = RadioConfig::default();
+    }
+
+    /// Send again the recorded configuration to the chip (e.g. after a reset or a sleep without retention)
+    /// Only the modulation and packet parameters of the recorded packet type are sent.
+    pub async fn restore_config(&mut self) -> Result<(), Lr1120Error> {
+        let cfg = self.shadow;
+        if let Some(packet_type) = cfg.packet_type {
+            self.set_packet_type(packet_type).await?;
+        }
+        if let Some(freq) = cfg.rf_freq {
+            self.set_rf(freq).await?;
+        }
+        if let Some((pa_sel, duty_cycle)) = cfg.pa {
+            self.set_pa(pa_sel, duty_cycle).await?;
+        }
+        if let Some((tx_power, ramp_time)) = cfg.tx_params {
+            self.set_tx_params(tx_power, ramp_time).await?;
+        }
+        match cfg.packet_type {
+            Some(PacketType::Lora) | Some(PacketType::Ranging) => {
+                if let Some(params) = cfg.lora_modulation {
+                    self.set_lora_modulation(&params).await?;
+                }
+                if let Some(params) = cfg.lora_packet {
+                    self.set_lora_packet(&params).await?;
+                }
+            }
+            #[cfg(feature = "fsk")]
+            Some(PacketType::Gfsk) => {
+                if let Some(params) = cfg.fsk_modulation {
+                    self.set_fsk_modulation_params(&params).await?;
+                }
+                if let Some(params) = cfg.fsk_packet {
+                    self.set_fsk_packet_params(&params).await?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+}
@@ -0,0 +1,630 @@
+//! # Behavioral simulator (feature `sim`)
+//!
+//! [`SimChip`] models the LR1120 behind the SPI bus so that higher-level code (trackers, schedulers, protocol stacks)
+//! can be tested in CI without hardware:
+//!  - SPI framing: status bytes on each transfer, command decoding on NSS rising edge, response on next transfer
+//!  - BUSY pin: high for a configurable number of polls after each command (longer for GNSS/WiFi scans)
+//!  - Chip mode transitions (sleep, standby, TX, RX) and interrupt flags (TX/RX done, timeout, GNSS/WiFi done)
+//!  - Canned results for RX payload, GNSS scan and WiFi scan, capture of the TX payload (see [`SimChip::transmit_to`] to link two chips)
+//!  - Error injection: command failure, SPI transfer failure, stuck BUSY, chip errors (GetErrors)
+//!  - Bootloader context: reboot in bootloader mode, flash erase and write of a firmware image
+//!
+//! Commands not modeled are accepted, and their response reads as zeros.
+//! The driver is created with [`SimChip::driver`] and uses the busy pin in polling mode.
+//! Note that `embassy-time` still requires a time driver: the integration tests (`tests/`) register one based on the host clock
+//! (`tests/common`), working with any tick rate.
+//!
+//! ## Example
+//! ```rust,no_run
+//! let chip = SimChip::new();
+//! chip.set_rx_payload(&[0xCA, 0xFE]);
+//! let mut lr1120 = chip.driver();
+//! lr1120.set_rx(0, true).await?;
+//! let (_status, intr) = lr1120.get_status().await?;
+//! assert!(intr.rx_done());
+//! ```
+
+use core::cell::RefCell;
+
+use embedded_hal::digital::{self, InputPin, OutputPin};
+use embedded_hal::spi;
+use embedded_hal_async::spi::SpiBus;
+
+use super::status::{CmdStatus, IRQ_MASK_ERROR, IRQ_MASK_GNSS_DONE, IRQ_MASK_RX_DONE, IRQ_MASK_TIMEOUT, IRQ_MASK_TX_DONE, IRQ_MASK_WIFI_DONE};
+use super::{BusyBlocking, Lr1120};
+
+/// Maximum size of a SPI frame or response handled by the simulator
+const SIM_FRAME_LEN: usize = 1024;
+/// Maximum size of canned payload and results
+const SIM_DATA_LEN: usize = 256;
+
+/// Opcodes of all commands followed by a response read
+const READ_OPCODES: &[u16] = &[
+    0x0100, 0x0101, 0x0106, 0x010A, 0x010D, 0x0119, 0x011A, 0x0120, 0x0125, 0x0126,
+    0x0201, 0x0202, 0x0203, 0x0204, 0x0205, 0x021E, 0x0230,
+    0x0305, 0x0306, 0x0308, 0x0309, 0x030A, 0x0320,
+    0x0401, 0x0403, 0x0406, 0x0407, 0x040C, 0x040D, 0x0411, 0x0416, 0x0417, 0x0418, 0x0419,
+    0x041F, 0x0426, 0x0434, 0x044F, 0x0453, 0x0457, 0x0464, 0x0466, 0x0467, 0x0469,
+    0x0502, 0x0503, 0x0504, 0x0505, 0x0506, 0x0507, 0x0508, 0x0509, 0x050A, 0x050B,
+    0x050D, 0x050E, 0x0510,
+    0x8004, 0x800B, 0x800C, 0x800D,
+];
+
+/// Chip mode, encoded as in the status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SimMode {
+    Sleep = 0,
+    StandbyRc = 1,
+    StandbyXosc = 2,
+    Fs = 3,
+    Rx = 4,
+    Tx = 5,
+    Loc = 6,
+}
+
+/// Simulator configuration
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SimCfg {
+    /// Hardware type reported by GetVersion
+    pub hw_type: u8,
+    /// Firmware version reported by GetVersion (major, minor)
+    pub fw_version: (u8, u8),
+    /// Number of BUSY polls reading high after a command
+    pub busy_polls: u16,
+    /// Number of BUSY polls reading high after a GNSS/WiFi scan
+    pub scan_busy_polls: u16,
+}
+
+impl Default for SimCfg {
+    /// LR1120 with firmware 02.01
+    fn default() -> Self {
+        Self { hw_type: 2, fw_version: (2, 1), busy_polls: 1, scan_busy_polls: 100 }
+    }
+}
+
+struct SimState {
+    cfg: SimCfg,
+    mode: SimMode,
+    packet_type: u8,
+    irq: u32,
+    irq_mask: u32,
+    reset_src: u8,
+    /// Status of the last command, reported on the next transfer
+    cmd_status: CmdStatus,
+    busy: u16,
+    busy_stuck: bool,
+    /// Number of next commands to fail and the status reported
+    fail_nb: u8,
+    fail_status: CmdStatus,
+    /// Number of next SPI transfers to fail
+    spi_fail_nb: u8,
+    nss_low: bool,
+    /// Bytes received during current frame
+    frame: [u8; SIM_FRAME_LEN],
+    frame_len: usize,
+    /// Current frame is a response read
+    frame_rd: bool,
+    rsp_pending: bool,
+    rsp: [u8; SIM_FRAME_LEN],
+    rsp_len: usize,
+    rx_payload: [u8; SIM_DATA_LEN],
+    rx_len: usize,
+    /// Content of the TX buffer (WriteBuffer8)
+    tx_payload: [u8; SIM_DATA_LEN],
+    tx_len: usize,
+    gnss_result: [u8; SIM_DATA_LEN],
+    gnss_len: usize,
+    gnss_nb_sv: u8,
+    wifi_results: [u8; SIM_FRAME_LEN],
+    wifi_nb: u8,
+    /// Pending errors reported by GetErrors
+    errors: u16,
+    /// Running the bootloader
+    bootloader: bool,
+    /// Number of image words written in flash since the last erase
+    flash_words: u32,
+    nb_cmd: u32,
+}
+
+impl SimState {
+    fn stat1(&self) -> u8 {
+        let irq_pending = (self.irq & self.irq_mask) != 0;
+        ((self.cmd_status as u8) << 1) | irq_pending as u8
+    }
+
+    fn stat2(&self) -> u8 {
+        (self.reset_src << 4) | ((self.mode as u8) << 1) | !self.bootloader as u8
+    }
+
+    /// Byte sent on MISO at a given position of the frame
+    fn miso(&self, idx: usize) -> u8 {
+        if idx == 0 {
+            return self.stat1();
+        }
+        if self.frame_rd {
+            return self.rsp.get(idx - 1).filter(|_| idx <= self.rsp_len).copied().unwrap_or(0);
+        }
+        match idx {
+            1 => self.stat2(),
+            2..=5 => self.irq.to_be_bytes()[idx - 2],
+            _ => 0,
+        }
+    }
+
+    fn shift(&mut self, mosi: u8) -> u8 {
+        let miso = self.miso(self.frame_len);
+        if let Some(b) = self.frame.get_mut(self.frame_len) {
+            *b = mosi;
+        }
+        self.frame_len += 1;
+        miso
+    }
+
+    /// Consume one injected SPI failure, return true if the transfer must fail
+    fn spi_fault(&mut self) -> bool {
+        let fault = self.spi_fail_nb > 0;
+        self.spi_fail_nb = self.spi_fail_nb.saturating_sub(1);
+        fault
+    }
+
+    fn set_rsp(&mut self, data: &[u8]) {
+        let len = data.len().min(SIM_FRAME_LEN);
+        self.rsp[..len].copy_from_slice(&data[..len]);
+        self.rsp_len = len;
+    }
+
+    fn frame_start(&mut self) {
+        self.frame_len = 0;
+        self.frame_rd = self.rsp_pending;
+        self.rsp_pending = false;
+    }
+
+    fn frame_end(&mut self) {
+        if self.frame_rd {
+            self.rsp_len = 0;
+            return;
+        }
+        if self.frame_len < 2 {
+            return;
+        }
+        self.nb_cmd += 1;
+        let opcode = u16::from_be_bytes([self.frame[0], self.frame[1]]);
+        self.busy = if self.busy_stuck {u16::MAX} else {self.cfg.busy_polls};
+        self.rsp_len = 0;
+        self.rsp_pending = READ_OPCODES.contains(&opcode);
+        if self.fail_nb > 0 {
+            self.fail_nb -= 1;
+            self.cmd_status = self.fail_status;
+            return;
+        }
+        self.cmd_status = CmdStatus::Ok;
+        let len = self.frame_len.min(SIM_FRAME_LEN);
+        let mut params = [0u8; SIM_FRAME_LEN];
+        params[..len-2].copy_from_slice(&self.frame[2..len]);
+        self.execute(opcode, &params[..len-2]);
+    }
+
+    fn execute(&mut self, opcode: u16, params: &[u8]) {
+        let param = |i: usize| params.get(i).copied().unwrap_or(0);
+        let param32 = |i: usize| u32::from_be_bytes([param(i), param(i+1), param(i+2), param(i+3)]);
+        match opcode {
+            // GetStatus
+            0x0100 => {
+                let irq = self.irq.to_be_bytes();
+                let stat2 = self.stat2();
+                self.set_rsp(&[stat2, irq[0], irq[1], irq[2], irq[3]]);
+                self.reset_src = 0;
+            }
+            // GetErrors
+            0x010D => self.set_rsp(&self.errors.to_be_bytes()),
+            // ClearErrors
+            0x010E => self.errors = 0,
+            // GetVersion
+            0x0101 => {
+                let (major, minor) = self.cfg.fw_version;
+                let hw_type = if self.bootloader {0xDF} else {self.cfg.hw_type};
+                self.set_rsp(&[0x22, hw_type, major, minor]);
+            }
+            // WriteBuffer8
+            0x0109 => {
+                let len = params.len().min(SIM_DATA_LEN);
+                self.tx_payload[..len].copy_from_slice(&params[..len]);
+                self.tx_len = len;
+            }
+            // ReadBuffer8
+            0x010A => {
+                let offset = param(0) as usize;
+                let len = param(1) as usize;
+                let mut data = [0u8; SIM_DATA_LEN];
+                for (i, b) in data.iter_mut().take(len).enumerate() {
+                    *b = self.rx_payload.get(offset + i).copied().unwrap_or(0);
+                }
+                self.set_rsp(&data[..len]);
+            }
+            // SetDioIrqParams
+            0x0113 => self.irq_mask = param32(0) | param32(4),
+            // ClearIrq
+            0x0114 => self.irq &= !param32(0),
+            // Reboot
+            0x0118 => {
+                self.bootloader = param(0) == 3;
+                self.mode = SimMode::StandbyRc;
+            }
+            // SetSleep
+            0x011B => self.mode = SimMode::Sleep,
+            // SetStandby
+            0x011C => self.mode = if param(0) == 1 {SimMode::StandbyXosc} else {SimMode::StandbyRc},
+            // GetPacketType
+            0x0202 => self.set_rsp(&[self.packet_type]),
+            // GetRxBufferStatus
+            0x0203 => self.set_rsp(&[self.rx_len as u8, 0]),
+            // SetRx: complete immediately with the canned payload or a timeout
+            0x0209 => {
+                let timeout = u32::from_be_bytes([0, param(0), param(1), param(2)]);
+                if self.rx_len > 0 {
+                    self.irq |= IRQ_MASK_RX_DONE;
+                    self.mode = SimMode::StandbyRc;
+                } else if timeout != 0 && timeout != 0xFFFFFF {
+                    self.irq |= IRQ_MASK_TIMEOUT;
+                    self.mode = SimMode::StandbyRc;
+                } else {
+                    self.mode = SimMode::Rx;
+                }
+            }
+            // SetTx: complete immediately
+            0x020A => {
+                self.irq |= IRQ_MASK_TX_DONE;
+                self.mode = SimMode::StandbyRc;
+            }
+            // SetPacketType
+            0x020E => self.packet_type = param(0),
+            // WifiScan, WifiScanTimeLimit, WifiCountryCode, WifiCountryCodeTimeLimit
+            0x0300..=0x0303 => {
+                self.busy = self.busy.max(self.cfg.scan_busy_polls);
+                self.irq |= IRQ_MASK_WIFI_DONE;
+            }
+            // WifiGetNbResults
+            0x0305 => self.set_rsp(&[self.wifi_nb]),
+            // WifiReadResults (short format only)
+            0x0306 => {
+                let start = (param(0) as usize * SIM_WIFI_RES_LEN).min(SIM_FRAME_LEN);
+                let end = (start + param(1) as usize * SIM_WIFI_RES_LEN).min(SIM_FRAME_LEN);
+                let mut data = [0u8; SIM_FRAME_LEN];
+                data[..end-start].copy_from_slice(&self.wifi_results[start..end]);
+                self.set_rsp(&data[..end-start]);
+            }
+            // WifiReadVersion
+            0x0320 => self.set_rsp(&[1, 3]),
+            // GnssReadVersion
+            0x0406 => self.set_rsp(&[1, 1]),
+            // GnssScan
+            0x040B => {
+                self.busy = self.busy.max(self.cfg.scan_busy_polls);
+                self.irq |= IRQ_MASK_GNSS_DONE;
+            }
+            // GnssGetResultSize
+            0x040C => self.set_rsp(&(self.gnss_len as u16).to_be_bytes()),
+            // GnssReadResults
+            0x040D => {
+                let (data, len) = (self.gnss_result, self.gnss_len);
+                self.set_rsp(&data[..len]);
+            }
+            // GnssGetNbSvDetected
+            0x0417 => self.set_rsp(&[self.gnss_nb_sv]),
+            // EraseFlash
+            0x8000 if self.bootloader => self.flash_words = 0,
+            // WriteFlashEncrypted: track the end of the image written
+            0x8003 if self.bootloader => {
+                let end = param32(0) / 4 + (params.len().saturating_sub(4) / 4) as u32;
+                self.flash_words = self.flash_words.max(end);
+            }
+            // RebootBootloader
+            0x8005 if self.bootloader => {
+                self.bootloader = param(0) == 3;
+                self.mode = SimMode::StandbyRc;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Size of a WiFi short result
+const SIM_WIFI_RES_LEN: usize = 9;
+
+/// Simulated LR1120
+pub struct SimChip {
+    state: RefCell<SimState>,
+}
+
+impl Default for SimChip {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimChip {
+    /// Create a simulated LR1120 with default configuration, in standby RC
+    pub fn new() -> Self {
+        Self::with_cfg(SimCfg::default())
+    }
+
+    /// Create a simulated chip with a specific configuration
+    pub fn with_cfg(cfg: SimCfg) -> Self {
+        let state = SimState {
+            cfg,
+            mode: SimMode::StandbyRc,
+            packet_type: 0,
+            irq: 0,
+            irq_mask: 0,
+            reset_src: 0,
+            cmd_status: CmdStatus::Ok,
+            busy: 0,
+            busy_stuck: false,
+            fail_nb: 0,
+            fail_status: CmdStatus::Fail,
+            spi_fail_nb: 0,
+            nss_low: false,
+            frame: [0; SIM_FRAME_LEN],
+            frame_len: 0,
+            frame_rd: false,
+            rsp_pending: false,
+            rsp: [0; SIM_FRAME_LEN],
+            rsp_len: 0,
+            rx_payload: [0; SIM_DATA_LEN],
+            rx_len: 0,
+            tx_payload: [0; SIM_DATA_LEN],
+            tx_len: 0,
+            gnss_result: [0; SIM_DATA_LEN],
+            gnss_len: 0,
+            gnss_nb_sv: 0,
+            wifi_results: [0; SIM_FRAME_LEN],
+            wifi_nb: 0,
+            errors: 0,
+            bootloader: false,
+            flash_words: 0,
+            nb_cmd: 0,
+        };
+        Self { state: RefCell::new(state) }
+    }
+
+    /// Create a driver connected to the simulated chip
+    pub fn driver(&self) -> Lr1120<SimPin<'_>, SimSpi<'_>, BusyBlocking<SimPin<'_>>> {
+        Lr1120::new_blocking(
+            SimPin { chip: self, kind: SimPinKind::Reset },
+            SimPin { chip: self, kind: SimPinKind::Busy },
+            SimSpi(self),
+            SimPin { chip: self, kind: SimPinKind::Nss },
+        )
+    }
+
+    /// Current chip mode
+    pub fn mode(&self) -> SimMode {
+        self.state.borrow().mode
+    }
+
+    /// Interrupts currently raised
+    pub fn irq(&self) -> u32 {
+        self.state.borrow().irq
+    }
+
+    /// Raise interrupts (e.g. to simulate an external event)
+    pub fn raise_irq(&self, mask: u32) {
+        self.state.borrow_mut().irq |= mask;
+    }
+
+    /// Number of commands received since creation
+    pub fn nb_cmd(&self) -> u32 {
+        self.state.borrow().nb_cmd
+    }
+
+    /// Chip running the bootloader
+    pub fn is_bootloader(&self) -> bool {
+        self.state.borrow().bootloader
+    }
+
+    /// Number of firmware image words written in flash since the last erase
+    pub fn flash_words(&self) -> u32 {
+        self.state.borrow().flash_words
+    }
+
+    /// Payload delivered on next reception (empty to simulate a timeout)
+    pub fn set_rx_payload(&self, payload: &[u8]) {
+        let mut s = self.state.borrow_mut();
+        let len = payload.len().min(SIM_DATA_LEN);
+        s.rx_payload[..len].copy_from_slice(&payload[..len]);
+        s.rx_len = len;
+    }
+
+    /// Last payload written in the TX buffer
+    pub fn tx_payload(&self) -> ([u8; SIM_DATA_LEN], usize) {
+        let s = self.state.borrow();
+        (s.tx_payload, s.tx_len)
+    }
+
+    /// Deliver the last payload written in the TX buffer to `other`, as its next RX payload
+    pub fn transmit_to(&self, other: &SimChip) {
+        let (data, len) = self.tx_payload();
+        other.set_rx_payload(&data[..len]);
+    }
+
+    /// Result of next GNSS scans: number of satellites detected and NAV message (including destination byte)
+    pub fn set_gnss_result(&self, nb_sv: u8, result: &[u8]) {
+        let mut s = self.state.borrow_mut();
+        let len = result.len().min(SIM_DATA_LEN);
+        s.gnss_result[..len].copy_from_slice(&result[..len]);
+        s.gnss_len = len;
+        s.gnss_nb_sv = nb_sv;
+    }
+
+    /// Access points found by next WiFi scans: (MAC, channel, RSSI) reported as short results
+    pub fn set_wifi_results(&self, aps: &[([u8;6], u8, i8)]) {
+        let mut s = self.state.borrow_mut();
+        let nb = aps.len().min(SIM_FRAME_LEN / SIM_WIFI_RES_LEN);
+        for (i, (mac, channel, rssi)) in aps.iter().take(nb).enumerate() {
+            let res = &mut s.wifi_results[i * SIM_WIFI_RES_LEN..(i + 1) * SIM_WIFI_RES_LEN];
+            res[0] = 1;
+            res[1] = channel & 0xF;
+            res[2] = *rssi as u8;
+            res[3..9].copy_from_slice(mac);
+        }
+        s.wifi_nb = nb as u8;
+    }
+
+    /// Report `status` (Fail or PErr) for the next `nb` commands, which are then not executed
+    pub fn fail_next(&self, nb: u8, status: CmdStatus) {
+        let mut s = self.state.borrow_mut();
+        s.fail_nb = nb;
+        s.fail_status = status;
+    }
+
+    /// Fail the next `nb` SPI transfers (nothing is shifted on the bus)
+    pub fn fail_spi(&self, nb: u8) {
+        self.state.borrow_mut().spi_fail_nb = nb;
+    }
+
+    /// Report chip errors (as read by GetErrors) and raise the ERROR IRQ
+    pub fn set_errors(&self, errors: u16) {
+        let mut s = self.state.borrow_mut();
+        s.errors |= errors;
+        s.irq |= IRQ_MASK_ERROR;
+    }
+
+    /// Keep BUSY high after next command (e.g. to test timeout handling)
+    pub fn set_busy_stuck(&self, stuck: bool) {
+        let mut s = self.state.borrow_mut();
+        s.busy_stuck = stuck;
+        if !stuck {
+            s.busy = 0;
+        }
+    }
+}
+
+/// SPI bus connected to a simulated chip
+pub struct SimSpi<'a>(&'a SimChip);
+
+impl spi::ErrorType for SimSpi<'_> {
+    type Error = spi::ErrorKind;
+}
+
+impl SpiBus<u8> for SimSpi<'_> {
+    async fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        let mut s = self.0.state.borrow_mut();
+        if s.spi_fault() {
+            return Err(spi::ErrorKind::Other);
+        }
+        for w in words.iter_mut() {
+            *w = s.shift(0);
+        }
+        Ok(())
+    }
+
+    async fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        let mut s = self.0.state.borrow_mut();
+        if s.spi_fault() {
+            return Err(spi::ErrorKind::Other);
+        }
+        for w in words {
+            s.shift(*w);
+        }
+        Ok(())
+    }
+
+    async fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        let mut s = self.0.state.borrow_mut();
+        if s.spi_fault() {
+            return Err(spi::ErrorKind::Other);
+        }
+        for i in 0..read.len().max(write.len()) {
+            let miso = s.shift(write.get(i).copied().unwrap_or(0));
+            if let Some(r) = read.get_mut(i) {
+                *r = miso;
+            }
+        }
+        Ok(())
+    }
+
+    async fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        let mut s = self.0.state.borrow_mut();
+        if s.spi_fault() {
+            return Err(spi::ErrorKind::Other);
+        }
+        for w in words.iter_mut() {
+            *w = s.shift(*w);
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SimPinKind {
+    Reset,
+    Busy,
+    Nss,
+}
+
+/// Pin connected to a simulated chip (reset, busy or NSS)
+pub struct SimPin<'a> {
+    chip: &'a SimChip,
+    kind: SimPinKind,
+}
+
+impl digital::ErrorType for SimPin<'_> {
+    type Error = digital::ErrorKind;
+}
+
+impl OutputPin for SimPin<'_> {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        let mut s = self.chip.state.borrow_mut();
+        match self.kind {
+            SimPinKind::Nss if !s.nss_low => {
+                s.nss_low = true;
+                s.frame_start();
+                // NSS wakes up the chip from sleep
+                if s.mode == SimMode::Sleep {
+                    s.mode = SimMode::StandbyRc;
+                }
+            }
+            SimPinKind::Reset => {
+                let cfg = s.cfg;
+                drop(s);
+                *self.chip.state.borrow_mut() = SimChip::with_cfg(cfg).state.into_inner();
+                self.chip.state.borrow_mut().reset_src = 2;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        let mut s = self.chip.state.borrow_mut();
+        if self.kind == SimPinKind::Nss && s.nss_low {
+            s.nss_low = false;
+            s.frame_end();
+        }
+        Ok(())
+    }
+}
+
+impl InputPin for SimPin<'_> {
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        let mut s = self.chip.state.borrow_mut();
+        if self.kind != SimPinKind::Busy {
+            return Ok(false);
+        }
+        let busy = s.busy > 0;
+        if !s.busy_stuck {
+            s.busy = s.busy.saturating_sub(1);
+        }
+        Ok(busy)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.is_high().map(|v| !v)
+    }
+}
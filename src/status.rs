@@ -9,6 +9,9 @@
 //!
 //! Note that when the command is only one byte, only the previous command status
 //!  and interrupt pending are updated.
+//! Response reads also only provide the first byte (stat1): the driver keeps the second byte (stat2)
+//! from the command phase of the same transaction, so that [`status`](crate::Lr1120::status) always reports
+//! the full status. A status created from a single byte reports the reset source, chip mode and context as unknown.
 //!
 //! The interrupt structure `Intr` allows to both configure which interrupt should be assigned to a pin
 //! with the command [`set_dio_irq`](crate::Lr1120::set_dio_irq) and easily get which interrupt is currently raised
@@ -20,9 +23,14 @@ use super::Lr1120Error;
 ///  - 11:9 = Command status
 ///  -    8 Interrupt pending
 ///  -  7:4 Reset source
-///  -  2:0 Chip Mode
-#[derive(Default, Clone, Copy)]
-pub struct Status(u16);
+///  -  3:1 Chip Mode
+///  -    0 Execution context (bootloader/flash)
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub struct Status {
+    value: u16,
+    /// True when the second byte (stat2) is available
+    full: bool,
+}
 
 /// Command status
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -101,20 +109,42 @@ impl Status {
 
     /// Create a status from an array of two elements
     pub fn from_array(bytes: [u8;2]) -> Status {
-        let v = u16::from_be_bytes(bytes);
-        Status(v)
+        Status { value: u16::from_be_bytes(bytes), full: true }
     }
 
-    /// Create a status from a slice of at least two elements
+    /// Create a status from a slice: the status is full only if at least two bytes are provided
     pub fn from_slice(bytes: &[u8]) -> Status {
-        let v = ((*bytes.first().unwrap_or(&0) as u16) << 8)
-            | (*bytes.get(1).unwrap_or(&0) as u16);
-        Status(v)
+        match bytes {
+            [stat1, stat2, ..] => Status::from_array([*stat1, *stat2]),
+            [stat1] => Status::from(*stat1),
+            [] => Status::default(),
+        }
+    }
+
+    /// Return a status with the first byte replaced, keeping the second byte
+    /// Used to merge the stat1 of a response with the stat2 of the command phase
+    pub fn with_stat1(self, stat1: u8) -> Status {
+        Status { value: (self.value & 0x00FF) | ((stat1 as u16) << 8), full: self.full }
+    }
+
+    /// First status byte: command status and interrupt pending
+    pub fn stat1(&self) -> u8 {
+        (self.value >> 8) as u8
+    }
+
+    /// Second status byte: reset source, chip mode and execution context (None if not available)
+    pub fn stat2(&self) -> Option<u8> {
+        self.full.then_some(self.value as u8)
+    }
+
+    /// Return true when both status bytes are available
+    pub fn is_full(&self) -> bool {
+        self.full
     }
 
     /// Return Command status
     pub fn cmd(&self) -> CmdStatus {
-        let bits_cmd = ((self.0 >> 9) & 7) as u8;
+        let bits_cmd = ((self.value >> 9) & 7) as u8;
         bits_cmd.into()
     }
 
@@ -124,13 +154,15 @@ impl Status {
 
     /// Return true if an Interrupt is pending
     pub fn irq(&self) -> bool {
-        (self.0 & 0x0100) != 0
+        (self.value & 0x0100) != 0
     }
 
-    /// Return source of last reset
+    /// Return source of last reset (Unknown if stat2 is not available)
     pub fn reset_src(&self) -> ResetSrc {
-        let bits_rst = (self.0 >> 4) & 15;
-        match bits_rst {
+        let Some(stat2) = self.stat2() else {
+            return ResetSrc::Unknown;
+        };
+        match stat2 >> 4 {
             0 => ResetSrc::Cleared,
             1 => ResetSrc::Analog,
             2 => ResetSrc::External,
@@ -142,10 +174,12 @@ impl Status {
         }
     }
 
-    /// Return source of last reset
+    /// Return current chip mode (Unknown if stat2 is not available)
     pub fn chip_mode(&self) -> ChipModeStatus {
-        let bits_mode = self.0 & 7;
-        match bits_mode {
+        let Some(stat2) = self.stat2() else {
+            return ChipModeStatus::Unknown;
+        };
+        match (stat2 >> 1) & 7 {
             0 => ChipModeStatus::Sleep,
             1 => ChipModeStatus::Rc,
             2 => ChipModeStatus::Xosc,
@@ -162,22 +196,22 @@ impl Status {
         self.cmd().check()
     }
 
-    /// Check command status and return Ok/Err
-    pub fn context(&self) -> ExecutionContext {
-        if (self.0 & 1) == 1 {
+    /// Return the execution context (None if stat2 is not available)
+    pub fn context(&self) -> Option<ExecutionContext> {
+        let stat2 = self.stat2()?;
+        Some(if (stat2 & 1) == 1 {
             ExecutionContext::Flash
         } else {
             ExecutionContext::Bootloader
-        }
+        })
     }
 
 }
 
-// Handle shorten status where only the 8 LSB are provided
-// Simply force LSB to 0
+// Handle shorten status where only the first byte is provided
 impl From<u8> for Status {
     fn from(value: u8) -> Self {
-        Status( (value as u16) << 8)
+        Status { value: (value as u16) << 8, full: false }
     }
 }
 
@@ -194,11 +228,13 @@ impl defmt::Format for Status {
                 if self.irq() {
                     defmt::write!(fmt, " | IRQ pending");
                 }
-                let rst = self.reset_src();
-                if rst!=ResetSrc::Cleared {
-                    defmt::write!(fmt, " | Reset from {}", rst);
+                if self.is_full() {
+                    let rst = self.reset_src();
+                    if rst!=ResetSrc::Cleared {
+                        defmt::write!(fmt, " | Reset from {}", rst);
+                    }
+                    defmt::write!(fmt, " | Chip in {}", self.chip_mode());
                 }
-                defmt::write!(fmt, " | Chip in {}", self.chip_mode());
             }
         }
     }
@@ -295,6 +331,11 @@ impl Intr {
         Intr(IRQ_MASK_RX_DONE|IRQ_MASK_TX_DONE|IRQ_MASK_TIMEOUT)
     }
 
+    /// Add the chip error interrupt (see [`handle_error_irq`](crate::Lr1120::handle_error_irq))
+    pub fn with_errors(self) -> Intr {
+        Intr(self.0 | IRQ_MASK_ERROR)
+    }
+
     /// Return the interrupt status as u32
     pub fn value(&self) -> u32 {
         self.0
@@ -338,6 +379,18 @@ impl Intr {
     pub fn lrfhss_hop(&self) -> bool {
         (self.0 & IRQ_MASK_LRFHSS_HOP) != 0
     }
+    /// Returns true if the GNSS scan done interrupt has been raised
+    pub fn gnss_done(&self) -> bool {
+        (self.0 & IRQ_MASK_GNSS_DONE) != 0
+    }
+    /// Returns true if the GNSS scan aborted interrupt has been raised
+    pub fn gnss_abort(&self) -> bool {
+        (self.0 & IRQ_MASK_GNSS_ABORT) != 0
+    }
+    /// Returns true if the WiFi scan done interrupt has been raised
+    pub fn wifi_done(&self) -> bool {
+        (self.0 & IRQ_MASK_WIFI_DONE) != 0
+    }
     /// Returns true if an error other than a command error occurred interrupt has been raised (See GetErrors)
     pub fn error(&self) -> bool {
         (self.0 & IRQ_MASK_ERROR) != 0
@@ -406,6 +459,9 @@ impl defmt::Format for Intr {
         if self.sw_header_valid()     {defmt::write!(f, "SwHdrValid ")};
         if self.header_err()          {defmt::write!(f, "HeaderError ")};
         if self.lrfhss_hop()          {defmt::write!(f, "LrfhssHop ")};
+        if self.gnss_done()           {defmt::write!(f, "GnssDone ")};
+        if self.gnss_abort()          {defmt::write!(f, "GnssAbort ")};
+        if self.wifi_done()           {defmt::write!(f, "WifiDone ")};
         if self.rx_done()             {defmt::write!(f, "RxDone ")};
         if self.tx_done()             {defmt::write!(f, "TxDone ")};
         if self.cad_done()            {defmt::write!(f, "CadDone ")};
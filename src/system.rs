@@ -8,23 +8,34 @@
 //!
 //! ### Status and Information
 //! - [`get_status`](Lr1120::get_status) - Read current chip status and interrupt flags
-//! - [`get_errors`](Lr1120::get_errors) - Get detailed error information from the chip
+//! - [`get_errors`](Lr1120::get_errors) - Get detailed error information from the chip, optionally clearing them
+//! - [`clear_errors`](Lr1120::clear_errors) - Clear pending errors
+//! - [`handle_error_irq`](Lr1120::handle_error_irq) - Read and clear errors when the ERROR IRQ is raised
+//! - [`auto_recover`](Lr1120::auto_recover) - Read errors and recalibrate blocks when it is the recommended action
 //! - [`get_version`](Lr1120::get_version) - Get chip firmware version information
 //! - [`get_chip_eui`](Lr1120::get_chip_eui) - Read Chip EUI
 //! - [`get_join_eui`](Lr1120::get_join_eui) - Read Semtech Join EUI
+//! - [`chip_eui_bytes`](Lr1120::chip_eui_bytes) / [`join_eui_bytes`](Lr1120::join_eui_bytes) - Read the EUIs as byte arrays in the selected byte order
+//! - [`get_dev_eui`](Lr1120::get_dev_eui) / [`get_semtech_join_eui`](Lr1120::get_semtech_join_eui) - Read the EUIs as [`DevEui`]/[`JoinEui`] (displayed as `00:16:C0:...`)
 //! - [`clear_irqs`](Lr1120::clear_irqs) - Clear irqs with an optional mask
 //!
 //! ### Chip Mode and Power Management
 //! - [`set_chip_mode`](Lr1120::set_chip_mode) - Set chip operational mode (sleep, standby, FS, TX, RX)
 //! - [`set_regulator_mode`](Lr1120::set_regulator_mode) - Choose regulator (LDO or DCDC)
+//! - [`prepare_for_sleep`](Lr1120::prepare_for_sleep) - Drive RF switch DIO and clear pending IRQs before going to sleep
 //!
 //! ### Calibration
 //! - [`calibrate`](Lr1120::calibrate) - Run calibration of different blocks
+//! - [`calibrate_blocks`](Lr1120::calibrate_blocks) - Run calibration on a set of blocks (see [`CalibrateBlocks`])
 //! - [`calib_image`](Lr1120::calib_image) - Run front-end image calibration on a frequency band
 //!
 //! ### Clock Management
 //! - [`set_lf_clk`](Lr1120::set_lf_clk) - Configure the LF clock
+//! - [`lf_clk`](Lr1120::lf_clk) - Return the LF clock source configured
+//! - [`set_lf_clk_rate`](Lr1120::set_lf_clk_rate) / [`lf_clk_rate`](Lr1120::lf_clk_rate) - Set/Return the LF clock frequency used to convert durations
 //! - [`set_tcxo`](Lr1120::set_tcxo) - Configure the chip to use a TCXO
+//! - [`set_tcxo_startup`](Lr1120::set_tcxo_startup) - Configure the chip to use a TCXO with startup time as a Duration
+//! - [`init_tcxo`](Lr1120::init_tcxo) - Enable TCXO mode and re-run calibration
 //!
 //! ### TX/RX Buffer
 //! - [`fn wr_tx_buffer_from`](Lr1120::fn wr_tx_buffer_from) - Write TX data
@@ -35,19 +46,32 @@
 //!
 //! ### I/O Management
 //! - [`set_dio_irq`](Lr1120::set_dio_irq) - Configure a DIO pin for interrupt generation
+//! - [`enable_irqs`](Lr1120::enable_irqs) / [`disable_irqs`](Lr1120::disable_irqs) - Update interrupts of one pin without clobbering the others
+//! - [`irq_masks`](Lr1120::irq_masks) - Return the interrupt masks currently configured
 //! - [`set_dio_rf_switch`](Lr1120::set_dio_rf_switch) - Configure the DIO to control RF switches
+//! - [`rf_switch`](Lr1120::rf_switch) - Return the RF switch configuration set (no read-back from the chip is available, see [`DioRfSwitchCfg::from_masks`])
+//! - [`set_rf_switch_check`](Lr1120::set_rf_switch_check) - Enable/disable the check of the RF switch before GNSS/WiFi scans
+//! - [`dio_usage`](Lr1120::dio_usage) - Report the function currently assigned to each DIO
 //!
 //! ### Register and Memory Access
 //! - [`rd_reg`](Lr1120::rd_reg) - Read a 32-bit register value
 //! - [`wr_reg`](Lr1120::wr_reg) - Write a 32-bit register value
 //! - [`wr_reg_mask`](Lr1120::wr_reg_mask) - Write a 32-bit register value with a mask
 //! - [`wr_field`](Lr1120::wr_field) - Write to specific bit field in a register
+//! - [`wr_field_rmw`](Lr1120::wr_field_rmw) - Write to specific bit field with a read-modify-write (registers without masked write)
 //! - [`rd_mem`](Lr1120::rd_mem) - Read multiple 32-bit words from memory to internal buffer
 //!
 //! ### Measurements
 //! - [`get_temperature`](Lr1120::get_temperature) - Return temperature as voltage measurement (11-bit precision)
+//! - [`get_temperature_celsius`](Lr1120::get_temperature_celsius) - Return temperature in degree Celsius (see [`temp_to_celsius`])
 //! - [`get_vbat`](Lr1120::get_vbat) - Return the battery voltage
+//! - [`get_vbat_mv`](Lr1120::get_vbat_mv) - Return the battery voltage in mV
+//! - [`set_low_bat_threshold_mv`](Lr1120::set_low_bat_threshold_mv) - Set the low battery threshold used for early brown-out warning
+//! - [`check_low_bat`](Lr1120::check_low_bat) - Measure the battery voltage and compare it to the low battery threshold
+//! - [`set_flash_guard`](Lr1120::set_flash_guard) - Enable/Disable the battery check before operations writing the chip flash
 //! - [`get_random_number`](Lr1120::get_random_number) - Return a random number using entropy from PLL and ADC
+//! - [`fill_random`](Lr1120::fill_random) - Fill a buffer with random bytes (e.g. nonces), refused while RX/TX is active
+//! - `RandomPool` (feature `rand`) - Random bytes fetched with `fill_random` and consumed through `rand_core::TryRngCore`
 
 use embassy_time::Duration;
 use embedded_hal::digital::OutputPin;
@@ -56,10 +80,10 @@ use embedded_hal_async::spi::SpiBus;
 use crate::cmd::cmd_regmem::{read_reg_mem32_req, write_reg_mem32_cmd, write_reg_mem_mask32_cmd, ReadRegMem32Rsp};
 
 use super::{BusyPin, Lr1120, Lr1120Error};
-use super::status::{Intr, Status};
+use super::status::{ChipModeStatus, Intr, Status, IRQ_MASK_ERROR};
 
 pub use super::cmd::cmd_system::*;
-use super::radio::{set_rx_cmd, set_tx_cmd};
+use super::radio::{set_rx_cmd, set_tx_cmd, FallbackMode};
 
 /// Chip Mode: Sleep/Standby/Fs/...
 #[derive(Clone, Debug, PartialEq)]
@@ -132,6 +156,24 @@ impl From<u8> for DioNum {
 
 
 
+/// RF path used by an operation, each one with its own RF switch configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RfPath {
+    /// Sub-GHz reception
+    LfRx,
+    /// Sub-GHz transmission (low power PA)
+    LfTx,
+    /// Sub-GHz transmission (high power PA)
+    LfTxHp,
+    /// 2.4GHz transmission
+    HfTx,
+    /// GNSS reception
+    Gnss,
+    /// WiFi scanning (2.4GHz reception)
+    Wifi,
+}
+
 /// Configuration of which RF switch is connected to which DIO
 #[derive(Clone, Debug)]
 pub struct DioRfSwitchCfg {
@@ -179,6 +221,144 @@ impl DioRfSwitchCfg {
             rx_lf: self.rx_lf, rx_mf, rx_hf: self.rx_hf,
         }
     }
+
+    /// Return the DIO masks used by SetDioAsRfSwitch: RX, TX, TX high power, TX HF, GNSS and WiFi
+    pub fn to_masks(&self) -> [u8; 6] {
+        [self.rx_lf, self.tx_lf, self.tx_hp, self.tx_hf, self.rx_mf, self.rx_hf].map(|d| d.as_mask())
+    }
+
+    /// Create a configuration from the DIO masks of SetDioAsRfSwitch (same order as [`to_masks`](Self::to_masks))
+    /// Return InvalidParam if a mask selects more than one DIO (not representable by this configuration)
+    ///
+    /// The chip provides no command to read back the RF switch configuration and the registers holding it are not documented:
+    /// the masks must come from a known source (e.g. a board description or a configuration dump).
+    pub fn from_masks(masks: [u8; 6]) -> Result<Self, Lr1120Error> {
+        let mut dio = [DioNum::None; 6];
+        for (d, m) in dio.iter_mut().zip(masks) {
+            *d = match m {
+                0  => DioNum::None,
+                1  => DioNum::Dio5,
+                2  => DioNum::Dio6,
+                4  => DioNum::Dio7,
+                8  => DioNum::Dio8,
+                16 => DioNum::Dio10,
+                _  => return Err(Lr1120Error::InvalidParam),
+            };
+        }
+        let [rx_lf, tx_lf, tx_hp, tx_hf, rx_mf, rx_hf] = dio;
+        Ok(Self { tx_lf, tx_hp, tx_hf, rx_lf, rx_mf, rx_hf })
+    }
+
+    /// Return the DIO controlling the switch of an RF path
+    pub fn dio(&self, path: RfPath) -> DioNum {
+        match path {
+            RfPath::LfRx   => self.rx_lf,
+            RfPath::LfTx   => self.tx_lf,
+            RfPath::LfTxHp => self.tx_hp,
+            RfPath::HfTx   => self.tx_hf,
+            RfPath::Gnss   => self.rx_mf,
+            RfPath::Wifi   => self.rx_hf,
+        }
+    }
+}
+
+/// Function assigned to a DIO
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DioFunction {
+    /// DIO not used by the driver
+    #[default]
+    Unused,
+    /// DIO driving an RF switch (DIO5-8, DIO10)
+    RfSwitch,
+    /// DIO used as interrupt output (DIO9, DIO11)
+    Irq,
+    /// DIO used as external 32kHz clock input (DIO11)
+    LfClock,
+}
+
+/// Map of the function assigned to each configurable DIO (DIO5 to DIO11)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DioUsage([DioFunction;7]);
+
+impl DioUsage {
+    /// Create a map with all DIO unused
+    pub fn new() -> Self {
+        Self([DioFunction::Unused;7])
+    }
+
+    /// Return function assigned to a DIO (5 to 11). Any other DIO is reported as unused
+    pub fn get(&self, dio: u8) -> DioFunction {
+        self.slot(dio).copied().unwrap_or(DioFunction::Unused)
+    }
+
+    /// Entry of a DIO (5 to 11)
+    fn slot(&self, dio: u8) -> Option<&DioFunction> {
+        self.0.get(dio.checked_sub(5)? as usize)
+    }
+
+    /// Mutable entry of a DIO (5 to 11)
+    fn slot_mut(&mut self, dio: u8) -> Option<&mut DioFunction> {
+        self.0.get_mut(dio.checked_sub(5)? as usize)
+    }
+
+    /// Assign a function to a DIO, failing if the DIO is already used by another function
+    fn assign(&mut self, dio: u8, func: DioFunction) -> Result<(), Lr1120Error> {
+        let Some(cur) = self.slot_mut(dio) else {
+            return Ok(());
+        };
+        if *cur != DioFunction::Unused && *cur != func {
+            return Err(Lr1120Error::InvalidConfig);
+        }
+        *cur = func;
+        Ok(())
+    }
+
+    /// Release all DIO assigned to a function
+    fn release(&mut self, func: DioFunction) {
+        self.0.iter_mut()
+            .filter(|f| **f == func)
+            .for_each(|f| *f = DioFunction::Unused);
+    }
+
+    /// Assign a function to a DIO, whatever its current function (configuration already applied by the chip)
+    fn force(&mut self, dio: u8, func: DioFunction) {
+        if let Some(cur) = self.slot_mut(dio) {
+            *cur = func;
+        }
+    }
+
+    /// Release a single DIO if it is assigned to the function
+    fn release_dio(&mut self, dio: u8, func: DioFunction) {
+        if let Some(cur) = self.slot_mut(dio) && *cur == func {
+            *cur = DioFunction::Unused;
+        }
+    }
+
+    /// Return mask of DIO used as RF switch (same mapping as SetDioAsRfSwitch)
+    pub fn rf_switch_mask(&self) -> u8 {
+        [DioNum::Dio5, DioNum::Dio6, DioNum::Dio7, DioNum::Dio8, DioNum::Dio10].iter()
+            .filter(|d| self.get(**d as u8) == DioFunction::RfSwitch)
+            .fold(0, |m, d| m | d.as_mask())
+    }
+}
+
+/// Report of the actions taken by `prepare_for_sleep`
+#[derive(Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SleepReport {
+    /// Mask of RF switch DIO which were not driven during sleep and are now (pull-down, i.e. switch parked off)
+    pub dio_drive_added: u8,
+    /// Interrupts which were pending and have been cleared
+    pub irq_cleared: Intr,
+}
+
+impl SleepReport {
+    /// True if nothing had to be changed
+    pub fn unchanged(&self) -> bool {
+        self.dio_drive_added == 0 && self.irq_cleared.none()
+    }
 }
 
 /// Define a frequency range [min..max] used for image calibration
@@ -196,6 +376,16 @@ impl FreqBand {
         Self{min, max}
     }
 
+    /// Lower bound of the range (unit of 4MHz)
+    pub fn min(&self) -> u8 {
+        self.min
+    }
+
+    /// Upper bound of the range (unit of 4MHz)
+    pub fn max(&self) -> u8 {
+        self.max
+    }
+
     /// Create frequency range for ISM band around 430MHz
     pub fn ism_430() -> Self {
         Self {min: 0x6B, max: 0x6E}
@@ -223,11 +413,329 @@ impl FreqBand {
 
 }
 
+/// Convert a temperature measurement (11 bits) to degree Celsius:
+/// T = 25 + (Temp/2047 * 1.35V - 0.7295V) / -1.7mV/°C (the sensor voltage falls as the temperature rises)
+pub fn temp_to_celsius(raw: u16) -> i16 {
+    let uv = (raw & 0x7FF) as i32 * 1_350_000 / 2047;
+    let dt = (uv - 729_500) * 10 / -1700;
+    // Round to nearest degree (dt in 0.1°C)
+    (25 + (dt + if dt < 0 {-5} else {5}) / 10) as i16
+}
+
+/// Set of blocks to calibrate (LF RC, HF RC, PLL, ADC, Image, PLL TX)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CalibrateBlocks(u8);
+
+impl CalibrateBlocks {
+    /// LF RC oscillator
+    pub const LF_RC  : CalibrateBlocks = CalibrateBlocks(0x01);
+    /// HF RC oscillator
+    pub const HF_RC  : CalibrateBlocks = CalibrateBlocks(0x02);
+    /// PLL
+    pub const PLL    : CalibrateBlocks = CalibrateBlocks(0x04);
+    /// ADC
+    pub const ADC    : CalibrateBlocks = CalibrateBlocks(0x08);
+    /// Image rejection (default band)
+    pub const IMG    : CalibrateBlocks = CalibrateBlocks(0x10);
+    /// PLL in TX mode
+    pub const PLL_TX : CalibrateBlocks = CalibrateBlocks(0x20);
+
+    /// Create a set of blocks from a raw mask (same bit mapping as the Calibrate command)
+    pub fn from_bits(bits: u8) -> Self {
+        Self(bits & 0x3F)
+    }
+
+    /// No block selected
+    pub fn empty() -> Self {
+        Self(0)
+    }
+
+    /// All blocks
+    pub fn all() -> Self {
+        Self(0x3F)
+    }
+
+    /// Blocks calibrated by the chip at startup (everything except PLL TX)
+    pub fn startup_default() -> Self {
+        Self(0x1F)
+    }
+
+    /// Blocks to calibrate before the first transmission using the PLL in TX mode
+    pub fn ble_tx() -> Self {
+        Self::PLL | Self::PLL_TX
+    }
+
+    /// Raw mask value
+    pub fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// True when no block is selected
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// True when all blocks from other are selected
+    pub fn contains(&self, other: CalibrateBlocks) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl core::ops::BitOr for CalibrateBlocks {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl core::ops::BitOrAssign for CalibrateBlocks {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// Interrupt output pin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum IrqPin {
+    /// IRQ1 on DIO9
+    Irq1 = 0,
+    /// IRQ2 on DIO11
+    Irq2 = 1,
+}
+
+/// Action recommended to recover from errors reported by GetErrors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RecoveryAction {
+    /// No error pending
+    None,
+    /// Re-run calibration on the faulty blocks
+    Recalibrate{blocks: CalibrateBlocks},
+    /// HF crystal/TCXO did not start: check TCXO configuration (voltage/startup delay) and supply
+    CheckTcxo,
+    /// LF crystal did not start: check the 32.768kHz crystal or fall back to the RC oscillator
+    CheckLfXosc,
+    /// Error cannot be recovered by software: reset the chip
+    ResetChip,
+}
+
+impl ErrorsRsp {
+    /// Return the action recommended to recover from the pending errors
+    /// A failure to start the oscillators takes precedence on calibration errors since calibration cannot succeed without a clock
+    pub fn recommended_action(&self) -> RecoveryAction {
+        if self.none() {
+            return RecoveryAction::None;
+        }
+        if self.hf_xosc_start() {
+            return RecoveryAction::CheckTcxo;
+        }
+        if self.lf_xosc_start() {
+            return RecoveryAction::CheckLfXosc;
+        }
+        let mut blocks = CalibrateBlocks::empty();
+        if self.lf_rc_calib() { blocks |= CalibrateBlocks::LF_RC; }
+        if self.hf_rc_calib() { blocks |= CalibrateBlocks::HF_RC; }
+        if self.pll_calib() || self.pll_lock() { blocks |= CalibrateBlocks::PLL; }
+        if self.adc_calib() || self.rx_adc_offset() { blocks |= CalibrateBlocks::ADC; }
+        if self.img_calib() { blocks |= CalibrateBlocks::IMG; }
+        if blocks.is_empty() {
+            RecoveryAction::ResetChip
+        } else {
+            RecoveryAction::Recalibrate{blocks}
+        }
+    }
+}
+
 pub fn pllstep_to_hz(val_step: u32) -> u32 {
     let val_scaled : u64 = (val_step as u64) * 15625;
     (val_scaled >> 14) as u32
 }
 
+/// Maximum value of a 24-bit delay/timeout in LF clock step
+pub const LF_STEP_MAX : u32 = 0xFFFFFF;
+
+/// Mask of a register field of `width` bits at position `pos`
+/// Return InvalidParam if the field is empty, does not fit in 32 bits or `value` does not fit in the field
+fn field_mask(pos: u8, width: u8, value: u32) -> Result<u32, Lr1120Error> {
+    if width == 0 || pos as u32 + width as u32 > 32 {
+        return Err(Lr1120Error::InvalidParam);
+    }
+    let mask = u32::MAX >> (32 - width);
+    if value & !mask != 0 {
+        return Err(Lr1120Error::InvalidParam);
+    }
+    Ok(mask << pos)
+}
+
+/// Byte order of an EUI byte array
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Endianness {
+    /// Most significant byte first (order of the display form)
+    Big,
+    /// Least significant byte first (order on the LoRaWAN air interface)
+    Little,
+}
+
+macro_rules! eui_type {
+    ($name:ident, $doc:literal) => {
+        #[doc = $doc]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+        pub struct $name(pub u64);
+
+        impl $name {
+            /// Create an EUI from a byte array in the given byte order
+            pub fn from_bytes(bytes: [u8; 8], order: Endianness) -> Self {
+                match order {
+                    Endianness::Big => Self(u64::from_be_bytes(bytes)),
+                    Endianness::Little => Self(u64::from_le_bytes(bytes)),
+                }
+            }
+
+            /// EUI as a byte array in the given byte order
+            pub fn to_bytes(&self, order: Endianness) -> [u8; 8] {
+                match order {
+                    Endianness::Big => self.0.to_be_bytes(),
+                    Endianness::Little => self.0.to_le_bytes(),
+                }
+            }
+
+            /// EUI as an integer
+            pub fn to_u64(&self) -> u64 {
+                self.0
+            }
+        }
+
+        impl From<u64> for $name {
+            fn from(value: u64) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for u64 {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl core::fmt::Display for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                let b = self.0.to_be_bytes();
+                write!(f, "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}", b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7])
+            }
+        }
+
+        #[cfg(feature = "defmt")]
+        impl defmt::Format for $name {
+            fn format(&self, f: defmt::Formatter) {
+                let b = self.0.to_be_bytes();
+                defmt::write!(f, "{=u8:02X}:{=u8:02X}:{=u8:02X}:{=u8:02X}:{=u8:02X}:{=u8:02X}:{=u8:02X}:{=u8:02X}", b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7])
+            }
+        }
+    };
+}
+
+eui_type!(DevEui, "LoRaWAN device EUI (e.g. the chip EUI)");
+eui_type!(JoinEui, "LoRaWAN join EUI");
+
+/// Nominal frequency of the LF clock (Hz)
+pub const LF_CLK_HZ : u32 = 32768;
+
+/// Convert a duration into a number of LF clock step (1/32.768kHz ~ 30.52us), rounded up
+pub fn duration_to_lf_steps(d: Duration) -> u64 {
+    duration_to_lf_steps_at(d, LF_CLK_HZ)
+}
+
+/// Convert a duration into a number of steps of an LF clock running at `lf_clk_hz`, rounded up
+pub fn duration_to_lf_steps_at(d: Duration, lf_clk_hz: u32) -> u64 {
+    d.as_micros().saturating_mul(lf_clk_hz as u64).div_ceil(1_000_000)
+}
+
+/// Minimum low battery threshold (mV): lowest supply voltage of the chip
+pub const LOW_BAT_MIN_MV : u16 = 1800;
+/// Maximum low battery threshold (mV): highest supply voltage of the chip
+pub const LOW_BAT_MAX_MV : u16 = 3700;
+
+/// Convert a VBAT measurement into mV: 1.35 * (5*v/256 - 1)
+pub fn vbat_to_mv(v: u8) -> u16 {
+    let mv = 1350 * (5 * v as i32 - 256) / 256;
+    mv.max(0) as u16
+}
+
+/// Pool of random bytes generated by the chip, consumed through `rand_core::TryRngCore`
+/// The random number command is async: the pool is refilled with [`refill`](RandomPool::refill)
+/// and returns `InvalidSize` when more bytes are requested than available.
+#[cfg(feature = "rand")]
+#[derive(Debug, Clone)]
+pub struct RandomPool<const N: usize> {
+    buf: [u8; N],
+    /// Index of the first byte not consumed
+    pos: usize,
+}
+
+#[cfg(feature = "rand")]
+impl<const N: usize> Default for RandomPool<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<const N: usize> RandomPool<N> {
+    /// Create an empty pool
+    pub const fn new() -> Self {
+        Self { buf: [0; N], pos: N }
+    }
+
+    /// Number of random bytes available
+    pub fn available(&self) -> usize {
+        N - self.pos
+    }
+
+    /// Fill the pool with random bytes from the chip (see [`fill_random`](Lr1120::fill_random))
+    pub async fn refill<O, SPI, M>(&mut self, lr: &mut Lr1120<O,SPI,M>) -> Result<(), Lr1120Error>
+        where O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
+    {
+        lr.fill_random(&mut self.buf).await?;
+        self.pos = 0;
+        Ok(())
+    }
+
+    /// Consume random bytes: return InvalidSize without consuming anything if not enough bytes are available
+    pub fn take(&mut self, dst: &mut [u8]) -> Result<(), Lr1120Error> {
+        let end = self.pos + dst.len();
+        let src = self.buf.get_mut(self.pos..end).ok_or(Lr1120Error::InvalidSize)?;
+        dst.copy_from_slice(src);
+        // Consumed bytes are not kept
+        src.fill(0);
+        self.pos = end;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<const N: usize> rand_core::TryRngCore for RandomPool<N> {
+    type Error = Lr1120Error;
+
+    fn try_next_u32(&mut self) -> Result<u32, Self::Error> {
+        let mut bytes = [0; 4];
+        self.take(&mut bytes)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn try_next_u64(&mut self) -> Result<u64, Self::Error> {
+        let mut bytes = [0; 8];
+        self.take(&mut bytes)?;
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn try_fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), Self::Error> {
+        self.take(dst)
+    }
+}
+
 impl<O,SPI, M> Lr1120<O,SPI, M> where
     O: OutputPin, SPI: SpiBus<u8>, M: BusyPin
 {
@@ -235,23 +743,59 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     pub async fn get_status(&mut self) -> Result<(Status,Intr), Lr1120Error> {
         let req = get_status_req();
         let mut rsp = StatusRsp::new();
-        self.cmd_rd(&req, rsp.as_mut()).await?;
+        self.with_spi_retry(async |lr| lr.cmd_rd(&req, rsp.as_mut()).await).await?;
+        self.record_cad(rsp.intr());
+        self.record_rx_duty_cycle(rsp.intr());
+        #[cfg(feature = "metrics")] {self.metrics.irq_status(rsp.intr());}
         Ok((rsp.status(), rsp.intr()))
     }
 
-    /// Read status and interrupt from the chip
-    pub async fn get_errors(&mut self) -> Result<ErrorsRsp, Lr1120Error> {
+    /// Read pending errors, and clear them after read if `clear` is set
+    pub async fn get_errors(&mut self, clear: bool) -> Result<ErrorsRsp, Lr1120Error> {
         let req = get_errors_req();
         let mut rsp = ErrorsRsp::new();
         self.cmd_rd(&req, rsp.as_mut()).await?;
+        if clear {
+            self.clear_errors().await?;
+        }
         Ok(rsp)
     }
 
+    /// Clear all pending errors
+    pub async fn clear_errors(&mut self) -> Result<(), Lr1120Error> {
+        self.cmd_wr(&clear_errors_cmd()).await
+    }
+
+    /// Read and clear pending errors when the ERROR IRQ is set in `intr`, as well as the IRQ itself
+    /// Allows an event loop to surface chip errors as they occur (see [`Intr::with_errors`](crate::status::Intr::with_errors))
+    /// Return None if the ERROR IRQ is not set
+    pub async fn handle_error_irq(&mut self, intr: Intr) -> Result<Option<ErrorsRsp>, Lr1120Error> {
+        if !intr.error() {
+            return Ok(None);
+        }
+        let errors = self.get_errors(true).await?;
+        self.clear_irqs(Some(Intr::new(IRQ_MASK_ERROR))).await?;
+        Ok(Some(errors))
+    }
+
+    /// Read pending errors and perform the safe part of the recommended recovery:
+    /// on calibration errors, the errors are cleared and the faulty blocks are recalibrated.
+    /// Other actions (TCXO/crystal check, reset) are only reported and left to the application.
+    /// Return the recommended action
+    pub async fn auto_recover(&mut self) -> Result<RecoveryAction, Lr1120Error> {
+        let action = self.get_errors(false).await?.recommended_action();
+        if let RecoveryAction::Recalibrate{blocks} = action {
+            self.clear_errors().await?;
+            self.calibrate_blocks(blocks).await?;
+        }
+        Ok(action)
+    }
+
     /// Read status and interrupt from the chip
     pub async fn get_version(&mut self) -> Result<VersionRsp, Lr1120Error> {
         let req = get_version_req();
         let mut rsp = VersionRsp::new();
-        self.cmd_rd(&req, rsp.as_mut()).await?;
+        self.with_spi_retry(async |lr| lr.cmd_rd(&req, rsp.as_mut()).await).await?;
         Ok(rsp)
     }
 
@@ -259,7 +803,7 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     pub async fn get_chip_eui(&mut self) -> Result<u64, Lr1120Error> {
         let req = get_chip_eui_req();
         let mut rsp = ChipEuiRsp::new();
-        self.cmd_rd(&req, rsp.as_mut()).await?;
+        self.with_spi_retry(async |lr| lr.cmd_rd(&req, rsp.as_mut()).await).await?;
         Ok(rsp.chip_eui())
     }
 
@@ -267,46 +811,102 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     pub async fn get_join_eui(&mut self) -> Result<u64, Lr1120Error> {
         let req = get_semtech_join_eui_req();
         let mut rsp = SemtechJoinEuiRsp::new();
-        self.cmd_rd(&req, rsp.as_mut()).await?;
+        self.with_spi_retry(async |lr| lr.cmd_rd(&req, rsp.as_mut()).await).await?;
         Ok(rsp.semtech_join_eui())
     }
 
+    /// Read Chip EUI, used as device EUI
+    pub async fn get_dev_eui(&mut self) -> Result<DevEui, Lr1120Error> {
+        self.get_chip_eui().await.map(DevEui)
+    }
+
+    /// Read Semtech Join EUI
+    pub async fn get_semtech_join_eui(&mut self) -> Result<JoinEui, Lr1120Error> {
+        self.get_join_eui().await.map(JoinEui)
+    }
+
+    /// Read Chip EUI as a byte array in the given byte order
+    /// (e.g. little-endian for LoRaWAN stacks, big-endian for display and provisioning tools)
+    pub async fn chip_eui_bytes(&mut self, order: Endianness) -> Result<[u8; 8], Lr1120Error> {
+        Ok(self.get_dev_eui().await?.to_bytes(order))
+    }
+
+    /// Read Semtech Join EUI as a byte array in the given byte order
+    pub async fn join_eui_bytes(&mut self, order: Endianness) -> Result<[u8; 8], Lr1120Error> {
+        Ok(self.get_semtech_join_eui().await?.to_bytes(order))
+    }
+
     /// Clear irqs with an optional mask.
     /// If no mask is provided, all IRQs are cleared
     pub async fn clear_irqs(&mut self, intr: Option<Intr>) -> Result<(), Lr1120Error> {
         let msk = intr.map(|i| i.value()).unwrap_or(0xFFFFFFFF);
         let req = clear_irq_cmd(msk);
-        self.cmd_wr(&req).await
+        self.cmd_wr(&req).await?;
+        #[cfg(feature = "metrics")] {self.metrics.irq_clear(msk);}
+        Ok(())
     }
 
     /// Run calibration of different blocks
     /// Work in any chip mode and on exit the chip goes into Standby RC
+    /// Wait for the end of the calibration (see [`Timeouts::calibrate`](crate::latency::Timeouts::calibrate))
     /// Eventual calibration error can be read with get_errors
     pub async fn calibrate(&mut self, lf_rc: bool, hf_rc: bool, pll: bool, adc: bool, img: bool, pll_tx: bool) -> Result<(), Lr1120Error> {
         let req = calibrate_cmd(lf_rc, hf_rc, pll, adc, img, pll_tx);
-        self.cmd_wr(&req).await
+        self.cmd_wr(&req).await?;
+        self.wait_ready(self.timeouts.calibrate).await
+    }
+
+    /// Run calibration on a set of blocks
+    /// Work in any chip mode and on exit the chip goes into Standby RC
+    /// Wait for the end of the calibration (see [`Timeouts::calibrate`](crate::latency::Timeouts::calibrate))
+    /// Eventual calibration error can be read with get_errors
+    pub async fn calibrate_blocks(&mut self, blocks: CalibrateBlocks) -> Result<(), Lr1120Error> {
+        let req = calibrate_cmd(
+            blocks.contains(CalibrateBlocks::LF_RC),
+            blocks.contains(CalibrateBlocks::HF_RC),
+            blocks.contains(CalibrateBlocks::PLL),
+            blocks.contains(CalibrateBlocks::ADC),
+            blocks.contains(CalibrateBlocks::IMG),
+            blocks.contains(CalibrateBlocks::PLL_TX),
+        );
+        self.cmd_wr(&req).await?;
+        self.wait_ready(self.timeouts.calibrate).await
     }
 
     /// Run image calibration on a frequency band
-    /// Frequency are given as multiple of 4 MHz
+    /// Frequency are given as multiple of 4 MHz. Wait for the end of the calibration
     pub async fn calib_image(&mut self, range: FreqBand) -> Result<(), Lr1120Error> {
         let req = calib_image_cmd(range.min, range.max);
-        self.cmd_wr(&req).await
+        self.cmd_wr(&req).await?;
+        self.wait_ready(self.timeouts.calibrate).await
     }
 
     /// Set Tx power and ramp time
     pub async fn set_chip_mode(&mut self, chip_mode: ChipMode) -> Result<(), Lr1120Error> {
-        match chip_mode {
-            ChipMode::DeepSleep      => self.cmd_wr(&set_sleep_cmd(false, false, 0)).await,
-            ChipMode::DeepRetention  => self.cmd_wr(&set_sleep_cmd(false, true, 0)).await,
-            ChipMode::Sleep(t)       => self.cmd_wr(&set_sleep_cmd(true, false, t)).await,
-            ChipMode::Retention(t)   => self.cmd_wr(&set_sleep_cmd(true, true, t)).await,
-            ChipMode::StandbyRc      => self.cmd_wr(&set_standby_cmd(StandbyMode::Rc)).await,
-            ChipMode::StandbyXosc    => self.cmd_wr(&set_standby_cmd(StandbyMode::Xosc)).await,
-            ChipMode::Fs => self.cmd_wr(&set_fs_cmd()).await,
-            ChipMode::Tx => self.cmd_wr(&set_tx_cmd(0)).await,
-            ChipMode::Rx => self.cmd_wr(&set_rx_cmd(0xFFFFFF)).await,
+        self.rx_duty_cycle = None;
+        let req = match chip_mode {
+            ChipMode::DeepSleep      => set_sleep_cmd(false, false, 0),
+            ChipMode::DeepRetention  => set_sleep_cmd(false, true, 0),
+            ChipMode::Sleep(t)       => set_sleep_cmd(true, false, t),
+            ChipMode::Retention(t)   => set_sleep_cmd(true, true, t),
+            ChipMode::StandbyRc      => return self.cmd_wr(&set_standby_cmd(StandbyMode::Rc)).await,
+            ChipMode::StandbyXosc    => return self.cmd_wr(&set_standby_cmd(StandbyMode::Xosc)).await,
+            ChipMode::Fs => return self.cmd_wr(&set_fs_cmd()).await,
+            ChipMode::Tx => return self.cmd_wr(&set_tx_cmd(0)).await,
+            ChipMode::Rx => return self.cmd_wr(&set_rx_cmd(0xFFFFFF)).await,
+        };
+        self.cmd_wr(&req).await?;
+        // Configuration is lost when sleeping without retention
+        if matches!(chip_mode, ChipMode::DeepSleep | ChipMode::Sleep(_)) {
+            self.packet_type = None;
+            self.lora_packet = None;
+            self.lora_cr = None;
+            #[cfg(feature = "fsk")] {self.fsk_packet = None;}
+            self.irq_masks = [Intr::default(); 2];
+            self.fallback = FallbackMode::StdbyRc;
+            self.cold_start = true;
         }
+        Ok(())
     }
 
     /// Configure regulator (LDO or DCDC)
@@ -318,44 +918,229 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     }
 
     /// Configure IRQ for DIO 9 and 11
+    /// IRQ1 is output on DIO9 and IRQ2 on DIO11: an error is returned if the DIO is already in use
     pub async fn set_dio_irq(&mut self, irq1: Intr, irq2: Intr) -> Result<(), Lr1120Error> {
+        let mut usage = self.dio_usage;
+        for (dio, irq) in [(9, irq1), (11, irq2)] {
+            if irq.none() {
+                usage.release_dio(dio, DioFunction::Irq);
+            } else {
+                usage.assign(dio, DioFunction::Irq)?;
+            }
+        }
         let req = set_dio_irq_params_cmd(irq1.value(), irq2.value());
-        self.cmd_wr(&req).await
+        self.cmd_wr(&req).await?;
+        self.dio_usage = usage;
+        self.irq_masks = [irq1, irq2];
+        Ok(())
+    }
+
+    /// Update the state tracked by the driver for a batch of raw commands (see [`cmd_wr_batch`](Lr1120::cmd_wr_batch))
+    /// The radio configuration is forgotten, while the DIO interrupts and RF switches configured by the batch are decoded
+    pub(crate) fn batch_sync_state(&mut self, cmds: &[&[u8]]) {
+        self.packet_type = None;
+        self.lora_packet = None;
+        self.lora_cr = None;
+        self.rf_freq = None;
+        self.rx_duty_cycle = None;
+        #[cfg(feature = "fsk")] {self.fsk_packet = None;}
+        #[cfg(feature = "shadow")] {self.clear_config();}
+        for req in cmds {
+            match *req {
+                // SetDioIrqParams
+                [0x01, 0x13, a0, a1, a2, a3, b0, b1, b2, b3, ..] => {
+                    let irqs = [
+                        Intr::new(u32::from_be_bytes([*a0, *a1, *a2, *a3])),
+                        Intr::new(u32::from_be_bytes([*b0, *b1, *b2, *b3])),
+                    ];
+                    for (dio, irq) in [(9, irqs[0]), (11, irqs[1])] {
+                        if irq.none() {
+                            self.dio_usage.release_dio(dio, DioFunction::Irq);
+                        } else {
+                            self.dio_usage.force(dio, DioFunction::Irq);
+                        }
+                    }
+                    self.irq_masks = irqs;
+                }
+                // SetDioAsRfSwitch: enable, standby, then RX, TX, TX HP, TX HF, GNSS and WiFi masks
+                [0x01, 0x12, _, _, rx, tx, tx_hp, tx_hf, gnss, wifi, ..] => {
+                    self.dio_usage.release(DioFunction::RfSwitch);
+                    let cfg = DioRfSwitchCfg::from_masks([*rx, *tx, *tx_hp, *tx_hf, *gnss, *wifi]).ok();
+                    if let Some(cfg) = &cfg {
+                        for dio in [cfg.tx_lf, cfg.tx_hp, cfg.tx_hf, cfg.rx_lf, cfg.rx_mf, cfg.rx_hf] {
+                            self.dio_usage.force(dio as u8, DioFunction::RfSwitch);
+                        }
+                    }
+                    self.rf_switch = cfg;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// IRQ masks currently configured on IRQ1 (DIO9) and IRQ2 (DIO11)
+    pub fn irq_masks(&self) -> (Intr, Intr) {
+        (self.irq_masks[0], self.irq_masks[1])
+    }
+
+    /// Enable interrupts on a pin, keeping the ones already enabled
+    /// Allows independent tasks to manage their own interrupt bits
+    pub async fn enable_irqs(&mut self, pin: IrqPin, mask: Intr) -> Result<(), Lr1120Error> {
+        let (mut irq1, mut irq2) = self.irq_masks();
+        let cur = match pin {IrqPin::Irq1 => &mut irq1, IrqPin::Irq2 => &mut irq2};
+        *cur = Intr::new(cur.value() | mask.value());
+        self.set_dio_irq(irq1, irq2).await
+    }
+
+    /// Disable interrupts on a pin, keeping the other ones enabled
+    pub async fn disable_irqs(&mut self, pin: IrqPin, mask: Intr) -> Result<(), Lr1120Error> {
+        let (mut irq1, mut irq2) = self.irq_masks();
+        let cur = match pin {IrqPin::Irq1 => &mut irq1, IrqPin::Irq2 => &mut irq2};
+        *cur = Intr::new(cur.value() & !mask.value());
+        self.set_dio_irq(irq1, irq2).await
     }
 
     /// Configure the DIO to control RF switches
     /// Drive_sleep allow to set up pull-up or pull-down on all enabled RF switches when chip goes into sleep
+    /// An error is returned if one of the DIO is already in use for another function
     pub async  fn set_dio_rf_switch(&mut self, cfg: DioRfSwitchCfg, drive_sleep: bool) -> Result<(), Lr1120Error> {
-        let rfsw_tx_cfg    = cfg.tx_lf.as_mask();
-        let rfsw_tx_hp_cfg = cfg.tx_hp.as_mask();
-        let rfsw_tx_hf_cfg = cfg.tx_hf.as_mask();
-        let rfsw_rx_cfg    = cfg.rx_lf.as_mask();
-        let rfsw_gnss_cfg  = cfg.rx_mf.as_mask();
-        let rfsw_wifi_cfg  = cfg.rx_hf.as_mask();
+        let mut usage = self.dio_usage;
+        usage.release(DioFunction::RfSwitch);
+        for dio in [cfg.tx_lf, cfg.tx_hp, cfg.tx_hf, cfg.rx_lf, cfg.rx_mf, cfg.rx_hf] {
+            usage.assign(dio as u8, DioFunction::RfSwitch)?;
+        }
+        let [rfsw_rx_cfg, rfsw_tx_cfg, rfsw_tx_hp_cfg, rfsw_tx_hf_cfg, rfsw_gnss_cfg, rfsw_wifi_cfg] = cfg.to_masks();
         let rfsw_enable = rfsw_tx_cfg | rfsw_tx_hp_cfg | rfsw_tx_hf_cfg | rfsw_rx_cfg | rfsw_gnss_cfg | rfsw_wifi_cfg;
         let req = set_dio_as_rf_switch_cmd(rfsw_enable, 0, rfsw_rx_cfg, rfsw_tx_cfg, rfsw_tx_hp_cfg, rfsw_tx_hf_cfg, rfsw_gnss_cfg, rfsw_wifi_cfg);
         self.cmd_wr(&req).await?;
         // Configure pull-up/down for all enabled switch
         let drive_en = if drive_sleep {rfsw_enable} else {0};
         let req = drive_dios_in_sleep_mode_cmd(drive_en);
-        self.cmd_wr(&req).await
+        self.cmd_wr(&req).await?;
+        self.dio_usage = usage;
+        self.dio_sleep_drive = drive_en;
+        self.rf_switch = Some(cfg);
+        Ok(())
+    }
+
+    /// Return the RF switch configuration set with `set_dio_rf_switch` (None if never configured)
+    /// This is the configuration sent by the driver: the chip has no command to read it back.
+    pub fn rf_switch(&self) -> Option<&DioRfSwitchCfg> {
+        self.rf_switch.as_ref()
+    }
+
+    /// Enable/disable the check of the RF switch configuration before GNSS and WiFi scans (enabled by default)
+    /// Disable it when the board has no switch on the GNSS or WiFi path while using DIO switches on other paths
+    pub fn set_rf_switch_check(&mut self, en: bool) {
+        self.rf_switch_check = en;
+    }
+
+    /// Check that an RF path has a switch configured, when an RF switch configuration was set
+    /// The RF switches are then driven by the chip itself based on the operation
+    #[cfg(any(feature = "gnss", feature = "wifi"))]
+    pub(crate) fn check_rf_switch(&self, path: RfPath) -> Result<(), Lr1120Error> {
+        match &self.rf_switch {
+            Some(cfg) if self.rf_switch_check && cfg.dio(path) == DioNum::None => Err(Lr1120Error::InvalidConfig),
+            _ => Ok(()),
+        }
+    }
+
+    /// Prepare the chip for a low-power sleep:
+    ///  - Ensure all DIO used as RF switch are driven during sleep (avoid floating switch control lines)
+    ///  - Clear all pending interrupts (a pending IRQ keeps the IRQ line high)
+    ///
+    /// Note: the TCXO supply is automatically switched off by the chip while in sleep.
+    /// Return a report of what was changed.
+    pub async fn prepare_for_sleep(&mut self) -> Result<SleepReport, Lr1120Error> {
+        let mut report = SleepReport::default();
+        let rfsw_mask = self.dio_usage.rf_switch_mask();
+        let missing = rfsw_mask & !self.dio_sleep_drive;
+        if missing != 0 {
+            let drive_en = self.dio_sleep_drive | missing;
+            self.cmd_wr(&drive_dios_in_sleep_mode_cmd(drive_en)).await?;
+            self.dio_sleep_drive = drive_en;
+            report.dio_drive_added = missing;
+        }
+        let (_, intr) = self.get_status().await?;
+        if !intr.none() {
+            self.clear_irqs(Some(intr)).await?;
+            report.irq_cleared = intr;
+        }
+        Ok(report)
+    }
+
+    /// Return the function currently assigned to each DIO
+    pub fn dio_usage(&self) -> DioUsage {
+        self.dio_usage
     }
 
 
     /// Configure the LF clock
+    /// Using an external clock on DIO11 fails if the DIO is already used as IRQ output
     pub async fn set_lf_clk(&mut self, sel: LfClock, busy_release: bool) -> Result<(), Lr1120Error> {
+        let mut usage = self.dio_usage;
+        if sel == LfClock::Dio11 {
+            usage.assign(11, DioFunction::LfClock)?;
+        } else {
+            usage.release_dio(11, DioFunction::LfClock);
+        }
         let req = config_lf_clock_cmd(sel, busy_release);
-        self.cmd_wr(&req).await
+        self.cmd_wr(&req).await?;
+        self.dio_usage = usage;
+        self.lf_clk = Some(sel);
+        Ok(())
+    }
+
+    /// LF clock source configured (None if unknown: not set since the driver was created)
+    pub fn lf_clk(&self) -> Option<LfClock> {
+        self.lf_clk
+    }
+
+    /// Set the frequency of the LF clock (Hz) used to convert durations into LF clock steps
+    /// The default is the nominal 32.768kHz: a frequency measured on the board (e.g. RC oscillator against a host timer)
+    /// gives more accurate timeouts with [`set_rx_for`](Lr1120::set_rx_for)/[`set_tx_for`](Lr1120::set_tx_for).
+    /// Return InvalidParam for a null frequency
+    pub fn set_lf_clk_rate(&mut self, lf_clk_hz: u32) -> Result<(), Lr1120Error> {
+        if lf_clk_hz == 0 {
+            return Err(Lr1120Error::InvalidParam);
+        }
+        self.lf_clk_hz = lf_clk_hz;
+        Ok(())
+    }
+
+    /// Frequency of the LF clock (Hz) used to convert durations into LF clock steps
+    pub fn lf_clk_rate(&self) -> u32 {
+        self.lf_clk_hz
     }
 
     /// Configure the chip to use a TCXO
+    /// Start time is given in LF clock step (1/32.768kHz ~ 30.5us)
     pub async fn set_tcxo(&mut self, volt: TcxoVoltage, start_time: u32) -> Result<(), Lr1120Error> {
         let req = set_tcxo_mode_cmd(volt, start_time);
-        self.cmd_wr(&req).await
+        self.cmd_wr(&req).await?;
+        self.tcxo = Some((volt, start_time));
+        Ok(())
+    }
+
+    /// Configure the chip to use a TCXO with a startup time given as a duration (up to ~512s)
+    pub async fn set_tcxo_startup(&mut self, volt: TcxoVoltage, startup: Duration) -> Result<(), Lr1120Error> {
+        let steps = duration_to_lf_steps(startup);
+        if steps > LF_STEP_MAX as u64 {
+            return Err(Lr1120Error::InvalidParam);
+        }
+        self.set_tcxo(volt, steps as u32).await
+    }
+
+    /// Enable TCXO mode and re-run a full calibration:
+    /// calibration done at startup fails without TCXO (HF_XOSC start error) and must be performed again
+    pub async fn init_tcxo(&mut self, volt: TcxoVoltage, startup: Duration) -> Result<(), Lr1120Error> {
+        self.set_tcxo_startup(volt, startup).await?;
+        self.errata_after_tcxo().await?;
+        self.calibrate_blocks(CalibrateBlocks::all()).await
     }
 
     /// Return temperature as a voltage measurement (11b precision)
-    /// Conversion in degree Celcius is given by 25+1000/1.7*(v/2048*1.35 - 0.7295)
+    /// Conversion in degree Celcius is given by 25-1000/1.7*(v/2048*1.35 - 0.7295)
     pub async fn get_temperature(&mut self) -> Result<u16, Lr1120Error> {
         let req = get_temp_req();
         let mut rsp = TempRsp::new();
@@ -363,6 +1148,11 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         Ok(rsp.temp())
     }
 
+    /// Return temperature in degree Celsius
+    pub async fn get_temperature_celsius(&mut self) -> Result<i16, Lr1120Error> {
+        self.get_temperature().await.map(temp_to_celsius)
+    }
+
     /// Return the battery voltage
     // Conversion in volt is given by 1.35 * (5*v/256 - 1)
     pub async fn get_vbat(&mut self) -> Result<u8, Lr1120Error> {
@@ -372,6 +1162,67 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         Ok(rsp.vbat())
     }
 
+    /// Return the battery voltage in mV
+    pub async fn get_vbat_mv(&mut self) -> Result<u16, Lr1120Error> {
+        self.get_vbat().await.map(vbat_to_mv)
+    }
+
+    /// Set the low battery threshold in mV (0 to disable)
+    /// The LOW_BAT interrupt of the chip uses a fixed end-of-life threshold which cannot be configured:
+    /// this threshold is handled by the driver and compared to the VBAT measurement in [`check_low_bat`](Lr1120::check_low_bat),
+    /// giving an early warning before brown-out. The threshold is kept across reset and sleep.
+    pub fn set_low_bat_threshold_mv(&mut self, mv: u16) -> Result<(), Lr1120Error> {
+        if mv == 0 {
+            self.low_bat_mv = None;
+            return Ok(());
+        }
+        if !(LOW_BAT_MIN_MV..=LOW_BAT_MAX_MV).contains(&mv) {
+            return Err(Lr1120Error::InvalidParam);
+        }
+        self.low_bat_mv = Some(mv);
+        Ok(())
+    }
+
+    /// Low battery threshold in mV (None when disabled)
+    pub fn low_bat_threshold_mv(&self) -> Option<u16> {
+        self.low_bat_mv
+    }
+
+    /// Measure the battery voltage and return it (in mV) when below the low battery threshold
+    /// No measurement is done when the threshold is disabled
+    pub async fn check_low_bat(&mut self) -> Result<Option<u16>, Lr1120Error> {
+        let Some(threshold) = self.low_bat_mv else {
+            return Ok(None);
+        };
+        let mv = self.get_vbat_mv().await?;
+        Ok((mv < threshold).then_some(mv))
+    }
+
+    /// Enable/Disable the battery check before operations writing the chip flash (enabled by default)
+    /// A flash write interrupted by a brown-out can corrupt the flash content: with the guard enabled and a low battery
+    /// threshold set (see [`set_low_bat_threshold_mv`](Lr1120::set_low_bat_threshold_mv)), the almanac updates
+    /// and the crypto engine store return `LowBattery` without sending the command when the battery is below the threshold
+    pub fn set_flash_guard(&mut self, en: bool) {
+        self.flash_guard = en;
+    }
+
+    /// Return true when the battery is checked before operations writing the chip flash
+    pub fn flash_guard(&self) -> bool {
+        self.flash_guard
+    }
+
+    /// Check the battery before an operation writing the chip flash
+    #[cfg(any(feature = "gnss", feature = "crypto"))]
+    pub(crate) async fn check_flash_write(&mut self) -> Result<(), Lr1120Error> {
+        if !self.flash_guard {
+            return Ok(());
+        }
+        match self.check_low_bat().await? {
+            Some(vbat_mv) => Err(Lr1120Error::LowBattery { vbat_mv }),
+            None => Ok(()),
+        }
+    }
+
     /// Return a random number using entropy from PLL and ADC
     pub async fn get_random_number(&mut self) -> Result<u32, Lr1120Error> {
         let req = get_random_number_req();
@@ -380,6 +1231,24 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         Ok(rsp.random_number())
     }
 
+    /// Fill a buffer with random bytes (e.g. nonces), using one random number command per 4 bytes
+    /// The entropy comes from the PLL and ADC, used by the radio: the chip mode is read first
+    /// and InvalidConfig is returned while a reception or transmission is active
+    pub async fn fill_random(&mut self, buf: &mut [u8]) -> Result<(), Lr1120Error> {
+        let (status, _) = self.get_status().await?;
+        if matches!(status.chip_mode(), ChipModeStatus::Rx | ChipModeStatus::Tx) {
+            return Err(Lr1120Error::InvalidConfig);
+        }
+        let req = get_random_number_req();
+        let mut rsp = RandomNumberRsp::new();
+        for chunk in buf.chunks_mut(4) {
+            self.cmd_rd(&req, rsp.as_mut()).await?;
+            let bytes = rsp.random_number().to_le_bytes();
+            chunk.copy_from_slice(bytes.get(..chunk.len()).unwrap_or_default());
+        }
+        Ok(())
+    }
+
     /// Write TX data
     pub async fn wr_tx_buffer_from(&mut self, buffer: &[u8]) -> Result<(), Lr1120Error> {
         let req = write_buffer8_cmd();
@@ -387,11 +1256,23 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     }
 
     /// Send TX data using internal buffer
+    /// Return InvalidSize if `len` exceeds the internal buffer
     pub async fn wr_tx_buffer(&mut self, len: usize) -> Result<(), Lr1120Error> {
+        if len > self.buffer.data().len() {
+            return Err(Lr1120Error::InvalidSize);
+        }
         let req = write_buffer8_cmd();
+        // The command status overwrites the first byte of the local buffer
+        let first = self.buffer.0[1];
         self.cmd_wr_begin(&req).await?;
+        self.buffer.0[1] = first;
+        let Some(data) = self.buffer.data_mut().get_mut(..len) else {
+            self.nss.set_high().map_err(|_| Lr1120Error::Pin)?;
+            return Err(Lr1120Error::InvalidSize);
+        };
+        #[cfg(feature = "metrics")] {self.metrics.spi_bytes(len);}
         self.spi
-            .transfer_in_place(&mut self.buffer.data_mut()[..len]).await
+            .transfer_in_place(data).await
             .map_err(|_| Lr1120Error::Spi)?;
         self.nss.set_high().map_err(|_| Lr1120Error::Pin)
     }
@@ -413,7 +1294,7 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     pub async fn rd_rx_buffer(&mut self, offset: u8, len: u8) -> Result<(), Lr1120Error> {
         let req = read_buffer8_cmd(offset, len);
         self.cmd_wr(&req).await?;
-        self.wait_ready(Duration::from_millis(1)).await?;
+        self.wait_ready(self.timeouts.rsp).await?;
         self.rsp_rd(len.into()).await
     }
 
@@ -432,14 +1313,16 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         }
         let req = read_reg_mem32_req(addr, nb32);
         self.cmd_wr(&req).await?;
-        self.wait_ready(Duration::from_millis(1)).await?;
-        self.nss.set_low().map_err(|_| Lr1120Error::Pin)?;
+        self.wait_ready(self.timeouts.rsp).await?;
         self.buffer.clear(4*nb32 as usize);
-        let rsp_buf = &mut self.buffer.0[..4*nb32 as usize];
+        let rsp_buf = self.buffer.0.get_mut(..4*nb32 as usize).ok_or(Lr1120Error::InvalidSize)?;
+        self.nss.set_low().map_err(|_| Lr1120Error::Pin)?;
+        #[cfg(feature = "metrics")] {self.metrics.spi_transaction(4*nb32 as usize);}
         self.spi
             .transfer_in_place(rsp_buf).await
             .map_err(|_| Lr1120Error::Spi)?;
         self.nss.set_high().map_err(|_| Lr1120Error::Pin)?;
+        self.updt_rsp_status(self.buffer.0[0]);
         self.buffer.cmd_status().check()
     }
 
@@ -456,12 +1339,19 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     }
 
     /// Write a field value
+    /// Return InvalidParam if the field does not fit in 32 bits or the value does not fit in the field
     pub async fn wr_field(&mut self, addr: u32, value: u32, pos: u8, width: u8) -> Result<(), Lr1120Error> {
-        let mask =
-            if width >= 32 {0xFFFFFFFF}
-            else { ((1 << width) - 1) << pos };
+        let mask = field_mask(pos, width, value)?;
         let req = write_reg_mem_mask32_cmd(addr, mask, value << pos);
         self.cmd_wr(&req).await
     }
 
+    /// Write a field value with a read-modify-write, for registers not supporting the masked write command
+    /// Return InvalidParam if the field does not fit in 32 bits or the value does not fit in the field
+    pub async fn wr_field_rmw(&mut self, addr: u32, value: u32, pos: u8, width: u8) -> Result<(), Lr1120Error> {
+        let mask = field_mask(pos, width, value)?;
+        let reg = self.rd_reg(addr).await?;
+        self.wr_reg(addr, (reg & !mask) | (value << pos)).await
+    }
+
 }
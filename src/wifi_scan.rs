@@ -10,19 +10,54 @@
 //! - [`wifi_scan_country_code`](Lr1120::wifi_scan_country_code) - Capture WiFi-B beacon and look for `max_res` country code
 //! - [`wifi_scan_country_code_time_limit`](Lr1120::wifi_scan_country_code_time_limit) - Capture WiFi-B beacon and look for `max_res` country code
 //!
+//! Scans fail with `InvalidConfig` when an RF switch configuration without WiFi (HF RX) switch was set (see [`set_rf_switch_check`](Lr1120::set_rf_switch_check)).
+//!
 //! ### Results
 //! - [`wifi_get_nb_res`](Lr1120::wifi_get_nb_res) - Return number of result capture by previous scanning. Must be called before `wifi_get_result_*` methods
 //! - [`wifi_get_nb_country_code`](Lr1120::wifi_get_nb_country_code) - Return number of result capture by previous country code scanning. Must be called before `wifi_get_result_country`
 //! - [`wifi_get_result_short`](Lr1120::wifi_get_result_short) - Return short result (9B) of previous Wifi Scanning
-//! - [`wifi_get_result_long`](Lr1120::wifi_get_result_long) - Return long result 229B) of previous Wifi Scanning
+//! - [`wifi_get_result_long`](Lr1120::wifi_get_result_long) - Return long result (22B) of previous Wifi Scanning
 //! - [`wifi_get_result_ext`](Lr1120::wifi_get_result_ext) - Return extended result (79B) of previous Wifi Scanning
 //! - [`wifi_get_result_country`](Lr1120::wifi_get_result_country) - Return country code result (10B) of previous Wifi Scanning Country Code
+//! - [`wifi_collect_country_code`](Lr1120::wifi_collect_country_code) - Add country code results of previous scan to a [`CountryCodeStats`]
+//!
+//! The RSSI of each result is available in dBm with `rssi_dbm()` (the raw `rssi()` is a signed byte).
+//!
+//! Results are returned as an iterator of `Result`: a malformed results buffer yields an `InvalidSize` error instead of panicking.
+//!
+//! Results can be filtered with [`WifiApFilter`] to remove randomized MAC and mobile access points before geolocation:
+//! ```rust,no_run
+//! let nb = lr1120.wifi_get_nb_res().await?;
+//! let aps = WifiApFilter::geoloc().apply(lr1120.wifi_get_result_short(0, nb).await?);
+//! ```
+//!
+//! Long results carry the AP uptime (beacon timestamp) and beacon period: [`ApStabilityCfg`] scores them to rank
+//! stable gateways before ephemeral hotspots, beyond the mobile-AP flag of the chip, and selects the best ones for a geolocation payload:
+//! ```rust,no_run
+//! let nb = lr1120.wifi_get_nb_res().await?;
+//! let aps : heapless::Vec<RankedAp, 8> = ApStabilityCfg::default().select(lr1120.wifi_get_result_long(0, nb).await?)?;
+//! WifiUplink::encode(aps.iter().map(|ap| (ap.mac, ap.rssi)), &mut payload)?;
+//! ```
+//!
+//! Country code results can be aggregated across scans with [`CountryCodeStats`] to get a coarse location:
+//! ```rust,no_run
+//! let mut stats : CountryCodeStats<8> = CountryCodeStats::default();
+//! lr1120.wifi_scan_country_code(&params).await?;
+//! lr1120.wait_ready(Duration::from_secs(2)).await?;
+//! lr1120.wifi_collect_country_code(&mut stats).await?;
+//! if let Some(guess) = stats.best() && guess.confidence > 75 {
+//!     info!("Country {}", guess.country.as_str());
+//! }
+//! ```
 //!
 //! ### Misc
 //! - [`wifi_reset_timings`](Lr1120::wifi_reset_timings) - Reset cumulative timings
 //! - [`wifi_get_timings`](Lr1120::wifi_get_timings) - Get scanning cumulative timings
 //! - [`wifi_set_timestamp_thr`](Lr1120::wifi_set_timestamp_thr) - Configure timestamp threshold (in seconds) to discrimante mobile access point from gateways
+//! - [`wifi_set_timestamp_thr_duration`](Lr1120::wifi_set_timestamp_thr_duration) - Configure timestamp threshold as a `Duration`
+//! - [`wifi_timestamp_thr`](Lr1120::wifi_timestamp_thr) - Return the timestamp threshold configured
 //! - [`wifi_get_fw_version`](Lr1120::wifi_get_fw_version) - Return firmware version of wifi-scanning
+//! - [`wifi_capabilities`](Lr1120::wifi_capabilities) - Return WiFi scanning capabilities (supported acquisition modes)
 //!
 
 use core::marker::PhantomData;
@@ -33,7 +68,8 @@ use embedded_hal_async::spi::SpiBus;
 
 pub use crate::cmd::cmd_wifi::*;
 
-use super::{BusyPin, Lr1120, Lr1120Error};
+use super::{BusyPin, Lr1120, Lr1120Error, RSP_MAX_LEN};
+use super::system::{HwType, RfPath};
 
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -71,48 +107,556 @@ impl WifiScanParams {
     }
 }
 
-trait ResultFromSlice<T> {
-    fn from_slice(buffer: &[u8]) -> T;
-    const SIZE : u8;
+/// MAC address of an access point
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub struct MacAddress([u8;6]);
+
+impl MacAddress {
+    /// Create a MAC address from its 6 bytes (first byte transmitted first)
+    pub fn new(bytes: [u8;6]) -> Self {
+        Self(bytes)
+    }
+
+    /// Return the 6 bytes of the address
+    pub fn bytes(&self) -> [u8;6] {
+        self.0
+    }
+
+    /// Return the address as an integer (first byte in MSB)
+    pub fn to_u64(&self) -> u64 {
+        self.0.iter().fold(0, |acc, &b| (acc << 8) | b as u64)
+    }
+
+    /// Organizationally Unique Identifier (first three bytes)
+    pub fn oui(&self) -> [u8;3] {
+        [self.0[0], self.0[1], self.0[2]]
+    }
+
+    /// True for a locally administered address (e.g. randomized MAC used by phones)
+    pub fn is_local(&self) -> bool {
+        self.0[0] & 0x02 != 0
+    }
+
+    /// True for a multicast address
+    pub fn is_multicast(&self) -> bool {
+        self.0[0] & 0x01 != 0
+    }
+
+    /// True for a globally unique unicast address, i.e. the only kind relevant for geolocation
+    pub fn is_universal_unicast(&self) -> bool {
+        !self.is_local() && !self.is_multicast()
+    }
+}
+
+impl From<[u8;6]> for MacAddress {
+    fn from(value: [u8;6]) -> Self {
+        Self(value)
+    }
+}
+
+impl From<MacAddress> for u64 {
+    fn from(value: MacAddress) -> Self {
+        value.to_u64()
+    }
+}
+
+impl core::fmt::Display for MacAddress {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let b = &self.0;
+        write!(f, "{:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}", b[0], b[1], b[2], b[3], b[4], b[5])
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for MacAddress {
+    fn format(&self, f: defmt::Formatter) {
+        let b = &self.0;
+        defmt::write!(f, "{=u8:02X}:{=u8:02X}:{=u8:02X}:{=u8:02X}:{=u8:02X}:{=u8:02X}", b[0], b[1], b[2], b[3], b[4], b[5])
+    }
+}
+
+/// WiFi scanning capabilities based on the chip type and firmware versions
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WifiCapabilities {
+    /// Chip type
+    pub hw_type: HwType,
+    /// Chip firmware version (major, minor)
+    pub fw_version: (u8,u8),
+    /// WiFi scanning firmware version (major, minor)
+    pub wifi_version: (u8,u8),
+    /// Timestamp threshold discriminating mobile access points (None when unknown, see [`wifi_timestamp_thr`](Lr1120::wifi_timestamp_thr))
+    pub timestamp_thr: Option<Duration>,
+}
+
+impl WifiCapabilities {
+    /// True if the chip supports WiFi scanning at all (not the case for LR1121 or when running the bootloader)
+    /// and reports a WiFi scanning firmware version
+    pub fn has_wifi(&self) -> bool {
+        matches!(self.hw_type, HwType::Lr1110 | HwType::Lr1120) && self.wifi_version != (0,0)
+    }
+
+    /// True if the acquisition mode is supported:
+    /// SsidBeacon requires firmware 3.6 on LR1110 and 1.2 on LR1120, other modes are always available
+    /// when a WiFi scanning firmware is reported.
+    /// The WiFi scanning firmware is bundled with the chip firmware: the minimum versions are given for the chip firmware,
+    /// as no requirement on the WiFi firmware version alone is documented.
+    pub fn supports(&self, mode: AcqMode) -> bool {
+        if !self.has_wifi() {
+            return false;
+        }
+        match mode {
+            AcqMode::SsidBeacon => match self.hw_type {
+                HwType::Lr1110 => self.fw_version >= (3,6),
+                _ => self.fw_version >= (1,2),
+            },
+            _ => true,
+        }
+    }
+}
+
+impl WifiReadResultsRsp {
+    /// RSSI of the captured signal in dBm
+    pub fn rssi_dbm(&self) -> i16 {
+        self.rssi() as i8 as i16
+    }
+}
+
+impl WifiReadLongResultsRsp {
+    /// RSSI of the captured signal in dBm
+    pub fn rssi_dbm(&self) -> i16 {
+        self.rssi() as i8 as i16
+    }
+}
+
+impl WifiReadExtendedResultsRsp {
+    /// RSSI of the captured signal in dBm
+    pub fn rssi_dbm(&self) -> i16 {
+        self.rssi() as i8 as i16
+    }
+}
+
+/// Common information on access points found in WiFi results, used for filtering
+pub trait WifiApResult {
+    /// MAC address of the access point
+    fn mac(&self) -> MacAddress;
+    /// True if the access point is likely a mobile one (e.g. a phone hotspot)
+    /// For results providing the AP uptime, anything below `min_uptime_us` is considered mobile
+    fn is_mobile(&self, min_uptime_us: u64) -> bool;
+}
+
+impl WifiApResult for WifiReadResultsRsp {
+    fn mac(&self) -> MacAddress {
+        WifiReadResultsRsp::mac(self)
+    }
+    fn is_mobile(&self, _min_uptime_us: u64) -> bool {
+        self.mac_origin() == MacOrigin::Phone
+    }
+}
+
+impl WifiApResult for WifiReadLongResultsRsp {
+    fn mac(&self) -> MacAddress {
+        WifiReadLongResultsRsp::mac(self)
+    }
+    fn is_mobile(&self, min_uptime_us: u64) -> bool {
+        self.timestamp() < min_uptime_us
+    }
+}
+
+impl WifiApResult for WifiReadCountryCodeResultsRsp {
+    fn mac(&self) -> MacAddress {
+        WifiReadCountryCodeResultsRsp::mac(self)
+    }
+    fn is_mobile(&self, _min_uptime_us: u64) -> bool {
+        self.mac_origin() == MacOrigin::Phone
+    }
+}
+
+/// Filter to remove access points not suitable for geolocation from scan results
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WifiApFilter {
+    /// Drop locally administered MAC (randomized)
+    pub drop_local: bool,
+    /// Drop multicast MAC
+    pub drop_multicast: bool,
+    /// Drop access points identified as mobile
+    pub drop_mobile: bool,
+    /// Minimum AP uptime in us for results providing a timestamp (long results)
+    pub min_uptime_us: u64,
+}
+
+impl Default for WifiApFilter {
+    fn default() -> Self {
+        Self::geoloc()
+    }
+}
+
+impl WifiApFilter {
+    /// Filter keeping only fixed access points with a universal MAC address
+    /// Minimum uptime is set to 1 day for results providing a timestamp
+    pub fn geoloc() -> Self {
+        Self {
+            drop_local: true,
+            drop_multicast: true,
+            drop_mobile: true,
+            min_uptime_us: 86_400_000_000,
+        }
+    }
+
+    /// Return true if the result should be kept
+    pub fn keep<T: WifiApResult>(&self, res: &T) -> bool {
+        let mac = res.mac();
+        let drop = (self.drop_local && mac.is_local())
+            || (self.drop_multicast && mac.is_multicast())
+            || (self.drop_mobile && res.is_mobile(self.min_uptime_us));
+        !drop
+    }
+
+    /// Filter an iterator of results (errors are kept)
+    pub fn apply<T: WifiApResult, I: Iterator<Item=Result<T, Lr1120Error>>>(self, iter: I) -> impl Iterator<Item=Result<T, Lr1120Error>> {
+        iter.filter(move |r| r.as_ref().map_or(true, |r| self.keep(r)))
+    }
+}
+
+/// Access point timing found in long and extended WiFi results (beacon or probe response)
+pub trait WifiApTiming {
+    /// AP uptime in us (timestamp field, 0 for frames without timestamp)
+    fn uptime_us(&self) -> u64;
+    /// Beacon period in Time Unit (1024us)
+    fn beacon_period(&self) -> u16;
+}
+
+impl WifiApTiming for WifiReadLongResultsRsp {
+    fn uptime_us(&self) -> u64 {
+        self.timestamp()
+    }
+    fn beacon_period(&self) -> u16 {
+        WifiReadLongResultsRsp::beacon_period(self)
+    }
+}
+
+impl WifiApTiming for WifiReadExtendedResultsRsp {
+    fn uptime_us(&self) -> u64 {
+        self.timestamp()
+    }
+    fn beacon_period(&self) -> u16 {
+        WifiReadExtendedResultsRsp::beacon_period(self)
+    }
+}
+
+/// Stability class of an access point
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ApStability {
+    /// Likely a hotspot (phone, car, ...): randomized MAC or short uptime
+    Ephemeral,
+    /// Neither clearly ephemeral nor stable
+    Uncertain,
+    /// Likely a fixed gateway: long uptime and usual beacon period
+    Stable,
+}
+
+/// Stability score of an access point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ApScore {
+    /// Stability class
+    pub class: ApStability,
+    /// Score from 0 (ephemeral) to 100
+    pub score: u8,
+}
+
+/// Access point selected for geolocation by [`ApStabilityCfg::select`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RankedAp {
+    /// MAC address
+    pub mac: MacAddress,
+    /// RSSI in dBm
+    pub rssi: i8,
+    /// Stability score
+    pub score: ApScore,
+}
+
+/// Settings of the access point stability scoring
+///
+/// The score is 0 for a locally administered or multicast MAC, or an uptime below `min_uptime_us`.
+/// Above it, the score grows linearly from 50 to 100 at `stable_uptime_us`,
+/// and is halved when the beacon period differs from `beacon_period`.
+/// An AP is stable when its score reaches 100 and uncertain otherwise.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ApStabilityCfg {
+    /// Uptime (us) below which an access point is ephemeral
+    pub min_uptime_us: u64,
+    /// Uptime (us) from which an access point is stable
+    pub stable_uptime_us: u64,
+    /// Usual beacon period of fixed gateways (in TU)
+    pub beacon_period: u16,
+}
+
+impl Default for ApStabilityCfg {
+    /// Ephemeral below 1 day, stable after 7 days, usual beacon period of 100 TU
+    fn default() -> Self {
+        Self {
+            min_uptime_us: 86_400_000_000,
+            stable_uptime_us: 7 * 86_400_000_000,
+            beacon_period: 100,
+        }
+    }
+}
+
+impl ApStabilityCfg {
+    /// Score an access point from its MAC address, uptime (us) and beacon period (TU)
+    pub fn score(&self, mac: MacAddress, uptime_us: u64, beacon_period: u16) -> ApScore {
+        if mac.is_local() || mac.is_multicast() || uptime_us < self.min_uptime_us {
+            return ApScore { class: ApStability::Ephemeral, score: 0 };
+        }
+        let range = self.stable_uptime_us.saturating_sub(self.min_uptime_us).max(1);
+        let age = (uptime_us - self.min_uptime_us).min(range);
+        // Computed on 128 bits: age * 50 overflows for uptime ranges above ~11700 years
+        let mut score = 50 + (age as u128 * 50 / range as u128) as u8;
+        if beacon_period != self.beacon_period {
+            score /= 2;
+        }
+        let class = if score >= 100 {ApStability::Stable} else {ApStability::Uncertain};
+        ApScore { class, score }
+    }
+
+    /// Score a WiFi result providing the AP uptime and beacon period
+    pub fn score_result<T: WifiApResult + WifiApTiming>(&self, res: &T) -> ApScore {
+        self.score(res.mac(), res.uptime_us(), res.beacon_period())
+    }
+
+    /// Select up to N non-ephemeral access points from long results, best score first then strongest RSSI
+    /// Access points seen several times are kept once. Return the first error of the results
+    pub fn select<const N: usize, I>(&self, results: I) -> Result<heapless::Vec<RankedAp, N>, Lr1120Error>
+        where I: Iterator<Item=Result<WifiReadLongResultsRsp, Lr1120Error>>
+    {
+        let mut aps : heapless::Vec<RankedAp, N> = heapless::Vec::new();
+        for res in results {
+            let res = res?;
+            let score = self.score_result(&res);
+            if score.class == ApStability::Ephemeral || aps.iter().any(|ap| ap.mac == res.mac()) {
+                continue;
+            }
+            let ap = RankedAp { mac: res.mac(), rssi: res.rssi_dbm() as i8, score };
+            let rank = |ap: &RankedAp| (core::cmp::Reverse(ap.score.score), core::cmp::Reverse(ap.rssi));
+            let pos = aps.iter().position(|a| rank(&ap) < rank(a)).unwrap_or(aps.len());
+            if pos >= N {
+                continue;
+            }
+            if aps.is_full() {
+                aps.pop();
+            }
+            aps.insert(pos, ap).map_err(|_| Lr1120Error::InvalidSize)?;
+        }
+        Ok(aps)
+    }
 }
 
-const WIFI_RES_SHORT_SIZE : u8 = 9;
-const WIFI_RES_LONG_SIZE : u8 = 22;
-const WIFI_RES_EXT_SIZE : u8 = 79;
-const WIFI_RES_COUNTRY_SIZE : u8 = 10;
+impl WifiReadCountryCodeResultsRsp {
+    /// Country code as two ASCII characters
+    pub fn country_code(&self) -> [u8; 2] {
+        self.country().to_be_bytes()
+    }
+}
+
+/// Country code with its number of occurrences
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CountryCount {
+    /// Country code (two ASCII uppercase letters)
+    pub country: [u8; 2],
+    /// Number of results reporting this country
+    pub count: u16,
+}
+
+impl CountryCount {
+    /// Country code as a string
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.country).unwrap_or("??")
+    }
+}
+
+/// Most probable country from aggregated country code results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CountryGuess {
+    /// Country code and number of results reporting it
+    pub country: CountryCount,
+    /// Percentage of valid results reporting this country
+    pub confidence: u8,
+}
+
+/// Aggregate country code results across scans to find the most probable country
+///
+/// Country code results do not report the FCS status of the beacon: results are validated
+/// on the country code itself (two ASCII uppercase letters) and counted as rejected otherwise.
+/// Up to `N` different countries are tracked: valid results for other countries only lower the confidence.
+#[derive(Debug, Clone)]
+pub struct CountryCodeStats<const N: usize> {
+    counts: heapless::Vec<CountryCount, N>,
+    nb_valid: u16,
+    nb_rejected: u16,
+    drop_mobile: bool,
+}
+
+impl<const N: usize> Default for CountryCodeStats<N> {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl<const N: usize> CountryCodeStats<N> {
+    /// Create empty statistics
+    /// When drop_mobile is set, results from phones are ignored (a hotspot may advertise a foreign country)
+    pub fn new(drop_mobile: bool) -> Self {
+        Self { counts: heapless::Vec::new(), nb_valid: 0, nb_rejected: 0, drop_mobile }
+    }
+
+    /// Clear all statistics
+    pub fn clear(&mut self) {
+        self.counts.clear();
+        self.nb_valid = 0;
+        self.nb_rejected = 0;
+    }
+
+    /// Add one result and return true if it was counted
+    pub fn add(&mut self, res: &WifiReadCountryCodeResultsRsp) -> bool {
+        let country = res.country_code();
+        if !country.iter().all(u8::is_ascii_uppercase) {
+            self.nb_rejected = self.nb_rejected.saturating_add(1);
+            return false;
+        }
+        if self.drop_mobile && res.mac_origin() == MacOrigin::Phone {
+            return false;
+        }
+        self.nb_valid = self.nb_valid.saturating_add(1);
+        if let Some(c) = self.counts.iter_mut().find(|c| c.country == country) {
+            c.count = c.count.saturating_add(1);
+        } else {
+            // Table full: the result only counts as valid
+            let _ = self.counts.push(CountryCount { country, count: 1 });
+        }
+        true
+    }
+
+    /// Add all results from an iterator, stopping at the first error
+    pub fn add_results<I: Iterator<Item=Result<WifiReadCountryCodeResultsRsp, Lr1120Error>>>(&mut self, iter: I) -> Result<(), Lr1120Error> {
+        for res in iter {
+            self.add(&res?);
+        }
+        Ok(())
+    }
+
+    /// Number of valid results counted
+    pub fn nb_valid(&self) -> u16 {
+        self.nb_valid
+    }
+
+    /// Number of results rejected because of an invalid country code
+    pub fn nb_rejected(&self) -> u16 {
+        self.nb_rejected
+    }
+
+    /// Iterate over countries seen with their number of occurrences
+    pub fn counts(&self) -> impl Iterator<Item=&CountryCount> {
+        self.counts.iter()
+    }
+
+    /// Return the most probable country with a confidence level
+    /// On a tie, the country seen first is returned
+    pub fn best(&self) -> Option<CountryGuess> {
+        let best = self.counts.iter()
+            .fold(None, |acc: Option<&CountryCount>, c| match acc {
+                Some(b) if b.count >= c.count => Some(b),
+                _ => Some(c),
+            })?;
+        let confidence = (best.count as u32 * 100 / self.nb_valid.max(1) as u32) as u8;
+        Some(CountryGuess { country: *best, confidence })
+    }
+}
+
+/// WiFi result entry which can be parsed from a slice of the results buffer
+pub trait ResultFromSlice<T> {
+    /// Parse one entry, returning InvalidSize if the slice length does not match the entry size
+    fn try_from_slice(buffer: &[u8]) -> Result<T, Lr1120Error>;
+    /// Size of one result entry in byte
+    const SIZE : usize;
+    /// Maximum number of results which can be read at once
+    const MAX_NB : u8;
+}
+
+/// Result format code to use with WifiReadResults for a given entry size
+pub trait ResultFormat {
+    /// Format code
+    const FORMAT : WifiResultFormat;
+}
+
+/// Default timestamp threshold (in seconds) discriminating mobile access points: 1 day
+pub const WIFI_TIMESTAMP_THR_DEFAULT_S : u32 = 86_400;
+
+/// Maximum number of results read at once (standard and extended)
+const WIFI_RES_MAX_NB : u8 = 32;
+const WIFI_RES_EXT_MAX_NB : u8 = 12;
+
+// Ensure a full read of results always fits in the internal buffer
+const _ : () = assert!(WIFI_RES_MAX_NB as usize * WifiReadResultsRsp::RSP_LEN <= RSP_MAX_LEN);
+const _ : () = assert!(WIFI_RES_MAX_NB as usize * WifiReadLongResultsRsp::RSP_LEN <= RSP_MAX_LEN);
+const _ : () = assert!(WIFI_RES_EXT_MAX_NB as usize * WifiReadExtendedResultsRsp::RSP_LEN <= RSP_MAX_LEN);
+const _ : () = assert!(WIFI_RES_MAX_NB as usize * WifiReadCountryCodeResultsRsp::RSP_LEN <= RSP_MAX_LEN);
 
 impl ResultFromSlice<WifiReadResultsRsp> for WifiReadResultsRsp {
-    const SIZE : u8 = WIFI_RES_SHORT_SIZE;
-    fn from_slice(buffer: &[u8]) -> WifiReadResultsRsp {
-        WifiReadResultsRsp::from_slice(buffer)
+    const SIZE : usize = WifiReadResultsRsp::RSP_LEN;
+    const MAX_NB : u8 = WIFI_RES_MAX_NB;
+    fn try_from_slice(buffer: &[u8]) -> Result<WifiReadResultsRsp, Lr1120Error> {
+        WifiReadResultsRsp::try_from_slice(buffer)
     }
+}
 
+impl ResultFormat for WifiReadResultsRsp {
+    const FORMAT : WifiResultFormat = WifiResultFormat::Short;
 }
 
 impl ResultFromSlice<WifiReadLongResultsRsp> for WifiReadLongResultsRsp {
-    const SIZE : u8 = WIFI_RES_LONG_SIZE;
-    fn from_slice(buffer: &[u8]) -> WifiReadLongResultsRsp {
-        WifiReadLongResultsRsp::from_slice(buffer)
+    const SIZE : usize = WifiReadLongResultsRsp::RSP_LEN;
+    const MAX_NB : u8 = WIFI_RES_MAX_NB;
+    fn try_from_slice(buffer: &[u8]) -> Result<WifiReadLongResultsRsp, Lr1120Error> {
+        WifiReadLongResultsRsp::try_from_slice(buffer)
     }
+}
 
+// Basic complete format: 22 bytes per entry for beacon/packet search
+impl ResultFormat for WifiReadLongResultsRsp {
+    const FORMAT : WifiResultFormat = WifiResultFormat::Long;
 }
 
 impl ResultFromSlice<WifiReadExtendedResultsRsp> for WifiReadExtendedResultsRsp {
-    const SIZE : u8 = WIFI_RES_EXT_SIZE;
-    fn from_slice(buffer: &[u8]) -> WifiReadExtendedResultsRsp {
-        WifiReadExtendedResultsRsp::from_slice(buffer)
+    const SIZE : usize = WifiReadExtendedResultsRsp::RSP_LEN;
+    const MAX_NB : u8 = WIFI_RES_EXT_MAX_NB;
+    fn try_from_slice(buffer: &[u8]) -> Result<WifiReadExtendedResultsRsp, Lr1120Error> {
+        WifiReadExtendedResultsRsp::try_from_slice(buffer)
     }
 }
 
+// Basic complete format: 79 bytes per entry for full beacon scan
+impl ResultFormat for WifiReadExtendedResultsRsp {
+    const FORMAT : WifiResultFormat = WifiResultFormat::Long;
+}
+
 impl ResultFromSlice<WifiReadCountryCodeResultsRsp> for WifiReadCountryCodeResultsRsp {
-    const SIZE : u8 = WIFI_RES_COUNTRY_SIZE;
-    fn from_slice(buffer: &[u8]) -> WifiReadCountryCodeResultsRsp {
-        WifiReadCountryCodeResultsRsp::from_slice(buffer)
+    const SIZE : usize = WifiReadCountryCodeResultsRsp::RSP_LEN;
+    const MAX_NB : u8 = WIFI_RES_MAX_NB;
+    fn try_from_slice(buffer: &[u8]) -> Result<WifiReadCountryCodeResultsRsp, Lr1120Error> {
+        WifiReadCountryCodeResultsRsp::try_from_slice(buffer)
     }
 }
 
-/// Struct to iter over Wifi results, yielding own copy
-struct WifiResultsIter<'a, T> {
+/// Iterator over Wifi results, yielding own copy
+/// A truncated results buffer yields a single `InvalidSize` error before ending the iteration
+pub struct WifiResultsIter<'a, T> {
     marker: PhantomData<T>,
     buffer: &'a[u8],
     index: usize,
@@ -120,27 +664,31 @@ struct WifiResultsIter<'a, T> {
 }
 
 impl<'a, T: ResultFromSlice<T> > WifiResultsIter<'a, T> {
-    fn new(buffer: &'a [u8], nb: u8) -> Self {
+    /// Iterate over `nb` entries of a results buffer
+    pub fn new(buffer: &'a [u8], nb: u8) -> Self {
         WifiResultsIter {
             marker: PhantomData,
             buffer,
             index: 0,
-            max: nb as usize * T::SIZE as usize
+            max: nb as usize * T::SIZE
         }
     }
 }
 
 impl<'a,T: ResultFromSlice<T>> Iterator for WifiResultsIter<'a,T> {
-    type Item = T;
+    type Item = Result<T, Lr1120Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.index>=self.max {
             return None;
         }
-        let next = self.index + T::SIZE as usize;
-        let v = T::from_slice(&self.buffer[self.index..next]);
+        let next = self.index + T::SIZE;
+        let Some(entry) = self.buffer.get(self.index..next) else {
+            self.index = self.max;
+            return Some(Err(Lr1120Error::InvalidSize));
+        };
         self.index = next;
-        Some(v)
+        Some(T::try_from_slice(entry))
     }
 }
 
@@ -152,24 +700,32 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     /// Capture WiFi packet
     /// Busy stays high during scan and interrupt WifiScanDone is raised when finished.
     pub async fn wifi_scan(&mut self, params: &WifiScanParams) -> Result<(), Lr1120Error> {
+        self.check_rf_switch(RfPath::Wifi)?;
+        self.check_no_rx_duty_cycle()?;
         let req = wifi_scan_cmd(params.standard, params.chan_mask, params.acq_mode, params.max_res, params.max_scan, params.timeout, params.abort_on_timeout);
         self.cmd_wr(&req).await
     }
 
     /// Capture WiFi packet with a time limit per channel in ms
     pub async fn wifi_scan_time_limit(&mut self, params: &WifiScanParams, time_limit: u16) -> Result<(), Lr1120Error> {
+        self.check_rf_switch(RfPath::Wifi)?;
+        self.check_no_rx_duty_cycle()?;
         let req = wifi_scan_time_limit_cmd(params.standard, params.chan_mask, params.acq_mode, params.max_res, time_limit, params.timeout);
         self.cmd_wr(&req).await
     }
 
     /// Capture WiFi-B beacon and look for `max_res` country code
     pub async fn wifi_scan_country_code(&mut self, params: &WifiScanParams) -> Result<(), Lr1120Error> {
+        self.check_rf_switch(RfPath::Wifi)?;
+        self.check_no_rx_duty_cycle()?;
         let req = wifi_country_code_cmd(params.chan_mask, params.max_res, params.max_scan, params.timeout, params.abort_on_timeout);
         self.cmd_wr(&req).await
     }
 
     /// Capture WiFi-B beacon and look for `max_res` country code
     pub async fn wifi_scan_country_code_time_limit(&mut self, params: &WifiScanParams, time_limit: u16) -> Result<(), Lr1120Error> {
+        self.check_rf_switch(RfPath::Wifi)?;
+        self.check_no_rx_duty_cycle()?;
         let req = wifi_country_code_time_limit_cmd(params.chan_mask, params.max_res, time_limit, params.timeout);
         self.cmd_wr(&req).await
     }
@@ -178,7 +734,7 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     pub async fn wifi_get_nb_res(&mut self) -> Result<u8, Lr1120Error> {
         let req = wifi_get_nb_results_req();
         let mut rsp = WifiGetNbResultsRsp::new();
-        self.cmd_rd(&req, rsp.as_mut()).await?;
+        self.with_spi_retry(async |lr| lr.cmd_rd(&req, rsp.as_mut()).await).await?;
         Ok(rsp.nb_results())
     }
 
@@ -186,7 +742,7 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     pub async fn wifi_get_nb_country_code(&mut self) -> Result<u8, Lr1120Error> {
         let req = wifi_get_nb_country_code_results_req();
         let mut rsp = WifiGetNbCountryCodeResultsRsp::new();
-        self.cmd_rd(&req, rsp.as_mut()).await?;
+        self.with_spi_retry(async |lr| lr.cmd_rd(&req, rsp.as_mut()).await).await?;
         Ok(rsp.nb_results())
     }
 
@@ -205,9 +761,27 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
     }
 
     /// Configure timestamp threshold (in seconds) to discrimante mobile access point from gateways
+    /// Access points with an uptime above the threshold are reported as probable gateways (default 1 day)
     pub async fn wifi_set_timestamp_thr(&mut self, threshold: u32) -> Result<(), Lr1120Error> {
         let req = wifi_cfg_timestamp_a_pphone_cmd(threshold);
-        self.cmd_wr(&req).await
+        self.cmd_wr(&req).await?;
+        self.wifi_timestamp_thr = Some(threshold);
+        Ok(())
+    }
+
+    /// Configure timestamp threshold to discrimante mobile access point from gateways
+    /// The threshold is rounded up to the second: return InvalidParam above `u32::MAX` seconds
+    pub async fn wifi_set_timestamp_thr_duration(&mut self, threshold: Duration) -> Result<(), Lr1120Error> {
+        let secs = threshold.as_micros().div_ceil(1_000_000);
+        let secs = u32::try_from(secs).map_err(|_| Lr1120Error::InvalidParam)?;
+        self.wifi_set_timestamp_thr(secs).await
+    }
+
+    /// Return the timestamp threshold configured
+    /// The chip has no command to read it back: this is the last value set by the driver,
+    /// or the chip default (1 day) after a reset. None when unknown (not set nor reset since the driver creation)
+    pub fn wifi_timestamp_thr(&self) -> Option<Duration> {
+        self.wifi_timestamp_thr.map(|s| Duration::from_secs(s as u64))
     }
 
     /// Return firmware version of wifi-scanning
@@ -218,51 +792,68 @@ impl<O,SPI, M> Lr1120<O,SPI, M> where
         Ok((rsp.version_major(), rsp.version_minor()))
     }
 
+    /// Return WiFi scanning capabilities of the chip (based on chip type and firmware versions)
+    pub async fn wifi_capabilities(&mut self) -> Result<WifiCapabilities, Lr1120Error> {
+        let version = self.get_version().await?;
+        let hw_type = version.hw_type();
+        let fw_version = (version.major(), version.minor());
+        let wifi_version = if matches!(hw_type, HwType::Lr1110 | HwType::Lr1120) {
+            self.wifi_get_fw_version().await?
+        } else {
+            (0,0)
+        };
+        Ok(WifiCapabilities{hw_type, fw_version, wifi_version, timestamp_thr: self.wifi_timestamp_thr()})
+    }
+
+    /// Read results of previous WiFi scanning and return an iterator over entries of type T
+    async fn wifi_read_results<T: ResultFromSlice<T>>(&mut self, req: &[u8], nb: u8) -> Result<WifiResultsIter<'_, T>, Lr1120Error> {
+        let nb_byte = nb as usize * T::SIZE;
+        self.with_spi_retry(async |lr| {
+            lr.cmd_wr(req).await?;
+            lr.wait_ready(lr.timeouts.cmd).await?;
+            lr.rsp_rd(nb_byte).await
+        }).await?;
+        Ok(WifiResultsIter::new(self.buffer().get(..nb_byte).unwrap_or_default(), nb))
+    }
+
+    /// Read results using the format code matching the entry type
+    async fn wifi_read_results_fmt<T: ResultFromSlice<T> + ResultFormat>(&mut self, index: u8, nb: u8) -> Result<WifiResultsIter<'_, T>, Lr1120Error> {
+        let nb = nb.min(T::MAX_NB);
+        let req = wifi_read_results_req(index, nb, T::FORMAT);
+        self.wifi_read_results(&req, nb).await
+    }
+
     /// Return short result (9B) of previous Wifi Scanning
-    /// with acquisition mode set to BeaconSearch or BeaconPacket
-    pub async fn wifi_get_result_short(&mut self, index: u8, nb: u8) -> Result<impl Iterator<Item=WifiReadResultsRsp>, Lr1120Error> {
-        let req = wifi_read_results_req(index, nb, WifiResultFormat::Short);
-        let nb_byte = nb.min(32) as usize * WIFI_RES_SHORT_SIZE as usize;
-        self.cmd_wr(&req).await?;
-        self.wait_ready(Duration::from_millis(100)).await?;
-        self.rsp_rd(nb_byte).await?;
-        let iter : WifiResultsIter<'_, WifiReadResultsRsp> = WifiResultsIter::new(&self.buffer()[..nb_byte],nb);
-        Ok(iter)
+    /// with acquisition mode set to BeaconSearch or BeaconAndPacket
+    pub async fn wifi_get_result_short(&mut self, index: u8, nb: u8) -> Result<impl Iterator<Item=Result<WifiReadResultsRsp, Lr1120Error>>, Lr1120Error> {
+        self.wifi_read_results_fmt(index, nb).await
     }
 
-    /// Return long result 229B) of previous Wifi Scanning
-    /// with acquisition mode set to BeaconSearch or BeaconPacket
-    pub async fn wifi_get_result_long(&mut self, index: u8, nb: u8) -> Result<impl Iterator<Item=WifiReadLongResultsRsp>, Lr1120Error> {
-        let req = wifi_read_results_req(index, nb, WifiResultFormat::Long);
-        let nb_byte = nb.min(32) as usize * WIFI_RES_LONG_SIZE as usize;
-        self.cmd_wr(&req).await?;
-        self.wait_ready(Duration::from_millis(100)).await?;
-        self.rsp_rd(nb_byte).await?;
-        let iter : WifiResultsIter<'_, WifiReadLongResultsRsp> = WifiResultsIter::new(&self.buffer()[..nb_byte],nb);
-        Ok(iter)
+    /// Return long result (22B) of previous Wifi Scanning
+    /// with acquisition mode set to BeaconSearch or BeaconAndPacket
+    pub async fn wifi_get_result_long(&mut self, index: u8, nb: u8) -> Result<impl Iterator<Item=Result<WifiReadLongResultsRsp, Lr1120Error>>, Lr1120Error> {
+        self.wifi_read_results_fmt(index, nb).await
     }
 
     /// Return extended result (79B) of previous Wifi Scanning
-    /// with acquisition mode set to BeaconSearch or BeaconPacket
-    pub async fn wifi_get_result_ext(&mut self, index: u8, nb: u8) -> Result<impl Iterator<Item=WifiReadExtendedResultsRsp>, Lr1120Error> {
-        let req = wifi_read_results_req(index, nb, WifiResultFormat::Long);
-        let nb_byte = nb.min(12) as usize * WIFI_RES_EXT_SIZE as usize;
-        self.cmd_wr(&req).await?;
-        self.wait_ready(Duration::from_millis(100)).await?;
-        self.rsp_rd(nb_byte).await?;
-        let iter : WifiResultsIter<'_, WifiReadExtendedResultsRsp> = WifiResultsIter::new(&self.buffer()[..nb_byte],nb);
-        Ok(iter)
+    /// with acquisition mode set to FullBeacon or SsidBeacon (up to 12 results)
+    pub async fn wifi_get_result_ext(&mut self, index: u8, nb: u8) -> Result<impl Iterator<Item=Result<WifiReadExtendedResultsRsp, Lr1120Error>>, Lr1120Error> {
+        self.wifi_read_results_fmt(index, nb).await
     }
 
     /// Return country code result (10B) of previous Wifi Scanning Country Code
-    pub async fn wifi_get_result_country(&mut self, index: u8, nb: u8) -> Result<impl Iterator<Item=WifiReadCountryCodeResultsRsp>, Lr1120Error> {
+    pub async fn wifi_get_result_country(&mut self, index: u8, nb: u8) -> Result<impl Iterator<Item=Result<WifiReadCountryCodeResultsRsp, Lr1120Error>>, Lr1120Error> {
+        let nb = nb.min(WifiReadCountryCodeResultsRsp::MAX_NB);
         let req = wifi_read_country_code_results_req(index, nb);
-        let nb_byte = nb.min(32) as usize * WIFI_RES_COUNTRY_SIZE as usize;
-        self.cmd_wr(&req).await?;
-        self.wait_ready(Duration::from_millis(100)).await?;
-        self.rsp_rd(nb_byte).await?;
-        let iter : WifiResultsIter<'_, WifiReadCountryCodeResultsRsp> = WifiResultsIter::new(&self.buffer()[..nb_byte],nb);
-        Ok(iter)
+        self.wifi_read_results(&req, nb).await
+    }
+
+    /// Add all results of the previous Wifi Scanning Country Code to the statistics
+    /// Return the number of results read
+    pub async fn wifi_collect_country_code<const N: usize>(&mut self, stats: &mut CountryCodeStats<N>) -> Result<u8, Lr1120Error> {
+        let nb = self.wifi_get_nb_country_code().await?;
+        stats.add_results(self.wifi_get_result_country(0, nb).await?)?;
+        Ok(nb)
     }
 
 }
\ No newline at end of file
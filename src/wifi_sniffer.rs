@@ -0,0 +1,244 @@
+//! # Periodic WiFi scanning
+//!
+//! [`WifiSniffer`] periodically scans a subset of WiFi channels and maintains a cache of the access points
+//! seen recently, with their age (number of scans since they were last seen).
+//! An event is published on an `embassy-sync` channel when the environment changed significantly,
+//! i.e. when the strongest access point changed: this is useful for motion or zone detection on trackers.
+//!
+//! The sniffer uses short results (MAC, channel, RSSI) and thus requires acquisition mode
+//! `BeaconSearch` or `BeaconAndPacket`.
+//!
+//! ## Example
+//! ```rust,no_run
+//! static WIFI_EVENTS: Channel<CriticalSectionRawMutex, WifiEvent, 4> = Channel::new();
+//!
+//! #[embassy_executor::task]
+//! async fn wifi_task(mut lr1120: MyLr1120) {
+//!     // Scan only channels 1, 6 and 11
+//!     let mut sniffer = WifiSniffer::new(WifiSnifferCfg::new(0x0421));
+//!     sniffer.run(&mut lr1120, WIFI_EVENTS.sender()).await
+//! }
+//! ```
+//!
+//! ## Available Methods
+//! - [`new`](WifiSniffer::new) - Create a sniffer with an empty cache
+//! - [`cache`](WifiSniffer::cache) - Iterate over access points in the cache
+//! - [`strongest`](WifiSniffer::strongest) - Strongest access point seen during last scan
+//! - [`clear`](WifiSniffer::clear) - Empty the cache
+//! - [`last_scan`](WifiSniffer::last_scan) - Capture time of the last scan
+//! - [`set_gps_ref`](WifiSniffer::set_gps_ref) - Set the GPS time reference used to timestamp the scans
+//! - [`step`](WifiSniffer::step) - Run one scan and update the cache
+//! - [`run`](WifiSniffer::run) - Scan periodically forever
+//! - [`run_with_motion`](WifiSniffer::run_with_motion) - Scan forever with a period driven by motion events (see [`motion`](crate::motion))
+
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embassy_sync::channel::Sender;
+use embassy_time::{Duration, Instant, Timer};
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::spi::SpiBus;
+
+use super::gps_time::GpsTime;
+use super::motion::{MotionHook, ScanCadence};
+use super::payload::ScanTimestamp;
+use super::wifi_scan::{AcqMode, MacAddress, WifiApFilter, WifiScanParams, WifiStandard};
+use super::{latency, BusyPin, Lr1120, Lr1120Error};
+
+/// Number of access points kept in the sniffer cache
+pub const WIFI_CACHE_SIZE: usize = 32;
+
+/// WiFi sniffer configuration
+#[derive(Debug, Clone)]
+pub struct WifiSnifferCfg {
+    /// Scan parameters, including the channel subset
+    pub scan: WifiScanParams,
+    /// Period between two scans
+    pub period: Duration,
+    /// Maximum duration of a scan
+    pub scan_timeout: Duration,
+    /// Number of scans without seeing an access point before removing it from the cache
+    pub max_age: u8,
+    /// Filter applied on results before updating the cache
+    pub filter: WifiApFilter,
+}
+
+impl WifiSnifferCfg {
+    /// Sniffer configuration looking for beacons on the channels enabled in `chan_mask` (bit 0 to 13) every minute
+    /// Access points are removed from the cache after 5 scans without being seen
+    pub fn new(chan_mask: u16) -> Self {
+        let mut scan = WifiScanParams::new(WifiStandard::B, AcqMode::BeaconSearch);
+        scan.chan_mask = chan_mask;
+        Self {
+            scan,
+            period: Duration::from_secs(60),
+            scan_timeout: latency::WIFI_SCAN,
+            max_age: 5,
+            filter: WifiApFilter::geoloc(),
+        }
+    }
+}
+
+/// Access point stored in the sniffer cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WifiCacheEntry {
+    /// MAC address of the access point
+    pub mac: MacAddress,
+    /// RSSI (dBm) of the last capture
+    pub rssi: i8,
+    /// Channel of the last capture
+    pub channel: u8,
+    /// Number of scans since the access point was last seen
+    pub age: u8,
+}
+
+/// Event published by the sniffer
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WifiEvent {
+    /// Strongest access point changed (None when no access point was seen)
+    StrongestChanged { previous: Option<MacAddress>, current: Option<WifiCacheEntry> },
+    /// Scan completed: number of access points seen, added to and removed from the cache, and capture time
+    Scanned { nb_seen: u8, nb_new: u8, nb_lost: u8, timestamp: ScanTimestamp },
+    /// Battery voltage (mV) below the low battery threshold (see [`set_low_bat_threshold_mv`](Lr1120::set_low_bat_threshold_mv))
+    LowBattery { vbat_mv: u16 },
+    /// An operation failed: the sniffer retries on next period
+    Error(Lr1120Error),
+}
+
+/// Periodic WiFi scanner with a cache of recently seen access points
+pub struct WifiSniffer {
+    cfg: WifiSnifferCfg,
+    cache: [Option<WifiCacheEntry>; WIFI_CACHE_SIZE],
+    strongest: Option<MacAddress>,
+    /// GPS time reference: GPS time and corresponding local instant
+    gps_ref: Option<(GpsTime, Instant)>,
+    /// Capture time of the last scan
+    last_scan: Option<ScanTimestamp>,
+}
+
+impl WifiSniffer {
+    /// Create a sniffer with an empty cache
+    pub fn new(cfg: WifiSnifferCfg) -> Self {
+        Self { cfg, cache: [None; WIFI_CACHE_SIZE], strongest: None, gps_ref: None, last_scan: None }
+    }
+
+    /// Iterate over access points in the cache
+    pub fn cache(&self) -> impl Iterator<Item=&WifiCacheEntry> {
+        self.cache.iter().flatten()
+    }
+
+    /// Strongest access point seen during last scan
+    pub fn strongest(&self) -> Option<&WifiCacheEntry> {
+        self.cache()
+            .filter(|e| e.age == 0)
+            .max_by_key(|e| e.rssi)
+    }
+
+    /// Capture time of the last scan
+    pub fn last_scan(&self) -> Option<ScanTimestamp> {
+        self.last_scan
+    }
+
+    /// Set the GPS time reference (GPS time and corresponding local instant) used to timestamp the scans
+    /// e.g. from [`GnssTracker::gps_ref`](crate::gnss_tracker::GnssTracker::gps_ref)
+    pub fn set_gps_ref(&mut self, gps_ref: Option<(GpsTime, Instant)>) {
+        self.gps_ref = gps_ref;
+    }
+
+    /// Empty the cache
+    pub fn clear(&mut self) {
+        self.cache = [None; WIFI_CACHE_SIZE];
+        self.strongest = None;
+    }
+
+    /// Scan periodically forever: errors are published as event
+    pub async fn run<O, SPI, M, RM, const N: usize>(&mut self, lr: &mut Lr1120<O,SPI,M>, events: Sender<'_, RM, WifiEvent, N>) -> !
+        where O: OutputPin, SPI: SpiBus<u8>, M: BusyPin, RM: RawMutex
+    {
+        loop {
+            if let Err(e) = self.step(lr, &events).await {
+                events.send(WifiEvent::Error(e)).await;
+            }
+            Timer::after(self.cfg.period).await;
+        }
+    }
+
+    /// Scan forever with a period driven by motion events
+    /// The cadence period replaces the configured period, and a scan is started immediately when motion is detected
+    pub async fn run_with_motion<O, SPI, M, RM, MM, const N: usize>(&mut self, lr: &mut Lr1120<O,SPI,M>, events: Sender<'_, RM, WifiEvent, N>, motion: &MotionHook<MM>, mut cadence: ScanCadence) -> !
+        where O: OutputPin, SPI: SpiBus<u8>, M: BusyPin, RM: RawMutex, MM: RawMutex
+    {
+        loop {
+            if let Err(e) = self.step(lr, &events).await {
+                events.send(WifiEvent::Error(e)).await;
+            }
+            cadence.wait(cadence.period(), motion).await;
+        }
+    }
+
+    /// Run one scan, update the cache and publish events
+    pub async fn step<O, SPI, M, RM, const N: usize>(&mut self, lr: &mut Lr1120<O,SPI,M>, events: &Sender<'_, RM, WifiEvent, N>) -> Result<(), Lr1120Error>
+        where O: OutputPin, SPI: SpiBus<u8>, M: BusyPin, RM: RawMutex
+    {
+        if let Some(vbat_mv) = lr.check_low_bat().await? {
+            events.send(WifiEvent::LowBattery { vbat_mv }).await;
+        }
+        let timestamp = ScanTimestamp::new(Instant::now(), self.gps_ref);
+        lr.wifi_scan(&self.cfg.scan).await?;
+        lr.wait_ready(self.cfg.scan_timeout).await?;
+        let nb = lr.wifi_get_nb_res().await?;
+        for entry in self.cache.iter_mut().flatten() {
+            entry.age = entry.age.saturating_add(1);
+        }
+        let mut nb_seen = 0;
+        let mut nb_new = 0;
+        let results = lr.wifi_get_result_short(0, nb).await?;
+        for res in self.cfg.filter.apply(results) {
+            let res = res?;
+            nb_seen += 1;
+            // RSSI is reported as a signed value in dBm
+            let seen = WifiCacheEntry { mac: res.mac(), rssi: res.rssi() as i8, channel: res.channel_id(), age: 0 };
+            if self.insert(seen) {
+                nb_new += 1;
+            }
+        }
+        let nb_lost = self.evict();
+        self.last_scan = Some(timestamp);
+        events.send(WifiEvent::Scanned { nb_seen, nb_new, nb_lost, timestamp }).await;
+        let current = self.strongest().copied();
+        let current_mac = current.map(|e| e.mac);
+        if current_mac != self.strongest {
+            events.send(WifiEvent::StrongestChanged { previous: self.strongest, current }).await;
+            self.strongest = current_mac;
+        }
+        Ok(())
+    }
+
+    /// Update or add an access point in the cache, replacing the oldest (then weakest) entry when full
+    /// Return true if the access point was not in the cache
+    fn insert(&mut self, seen: WifiCacheEntry) -> bool {
+        if let Some(entry) = self.cache.iter_mut().flatten().find(|e| e.mac == seen.mac) {
+            *entry = seen;
+            return false;
+        }
+        let slot = self.cache.iter_mut()
+            .max_by_key(|e| e.map(|e| (1, e.age, -(e.rssi as i16))).unwrap_or((2, 0, 0)));
+        if let Some(slot) = slot {
+            *slot = Some(seen);
+        }
+        true
+    }
+
+    /// Remove access points not seen for more than max_age scans
+    /// Return the number of access points removed
+    fn evict(&mut self) -> u8 {
+        let mut nb_lost = 0;
+        for slot in self.cache.iter_mut() {
+            if slot.is_some_and(|e| e.age > self.cfg.max_age) {
+                *slot = None;
+                nb_lost += 1;
+            }
+        }
+        nb_lost
+    }
+}
@@ -0,0 +1,26 @@
+//! Shared helpers of the integration tests running on the simulator
+
+use core::task::Waker;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use embassy_time_driver::{Driver, TICK_HZ};
+
+/// Time driver based on the host clock, at the tick rate selected by the features.
+/// Wakers are woken immediately: `block_on` polls again until the timer expires.
+struct HostDriver {
+    start: OnceLock<Instant>,
+}
+
+impl Driver for HostDriver {
+    fn now(&self) -> u64 {
+        let elapsed = self.start.get_or_init(Instant::now).elapsed();
+        (elapsed.as_micros() as u64).saturating_mul(TICK_HZ) / 1_000_000
+    }
+
+    fn schedule_wake(&self, _at: u64, waker: &Waker) {
+        waker.wake_by_ref();
+    }
+}
+
+embassy_time_driver::time_driver_impl!(static DRIVER: HostDriver = HostDriver { start: OnceLock::new() });
@@ -0,0 +1,16 @@
+//! GPS time conversions
+use lr1120::gps_time::{GpsTime, SECS_PER_WEEK};
+
+#[test]
+fn week_tow_round_trip() {
+    let time = GpsTime::from_week_tow(2300, 12345).unwrap();
+    assert_eq!((time.week(), time.tow()), (2300, 12345));
+}
+
+#[test]
+fn week_tow_invalid() {
+    assert_eq!(GpsTime::from_week_tow(0, SECS_PER_WEEK), None);
+    assert_eq!(GpsTime::from_week_tow(7101, 0), Some(GpsTime(7101 * SECS_PER_WEEK)));
+    assert_eq!(GpsTime::from_week_tow(7101, SECS_PER_WEEK - 1), None);
+    assert_eq!(GpsTime::from_week_tow(u16::MAX, 0), None);
+}
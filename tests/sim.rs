@@ -0,0 +1,155 @@
+//! Scenarios driven through the chip simulator (feature `sim`)
+//! Not built with the `std` feature, which brings the time driver of `embassy-time` (requiring a timer queue)
+#![cfg(not(feature = "std"))]
+
+mod common;
+
+use embassy_futures::block_on;
+use lr1120::lora::{LoraBw, LoraModulationParams, LoraPacketParams, Sf};
+use lr1120::radio::PacketType;
+use lr1120::sim::{SimChip, SimMode};
+use lr1120::status::{CmdStatus, Intr, IRQ_MASK_RX_DONE, IRQ_MASK_TX_DONE};
+use lr1120::Lr1120Error;
+
+#[test]
+fn lora_tx_rx() {
+    let chip = SimChip::new();
+    let mut lr = chip.driver();
+    block_on(async {
+        lr.get_status().await.unwrap();
+        lr.set_packet_type(PacketType::Lora).await.unwrap();
+        let modulation = LoraModulationParams::basic(Sf::Sf7, LoraBw::Bw125);
+        lr.set_lora_modulation(&modulation).await.unwrap();
+        lr.set_lora_packet(&LoraPacketParams::basic(4, &modulation)).await.unwrap();
+
+        // TX
+        lr.wr_tx_buffer_from(&[1, 2, 3, 4]).await.unwrap();
+        lr.set_tx(0).await.unwrap();
+        let (_, intr) = lr.get_status().await.unwrap();
+        assert!(intr.tx_done());
+        assert_eq!(chip.mode(), SimMode::StandbyRc);
+        lr.clear_irqs(Some(Intr::new(IRQ_MASK_TX_DONE))).await.unwrap();
+
+        // RX with a canned payload
+        chip.set_rx_payload(&[0xCA, 0xFE]);
+        lr.set_rx(0, true).await.unwrap();
+        let (_, intr) = lr.get_status().await.unwrap();
+        assert!(intr.rx_done());
+        let payload = lr.get_lora_payload(intr, false).await.unwrap();
+        assert_eq!(payload.data, &[0xCA, 0xFE]);
+        assert!(payload.crc_ok);
+        lr.clear_irqs(Some(Intr::new(IRQ_MASK_RX_DONE))).await.unwrap();
+    });
+}
+
+#[test]
+fn lora_rx_timeout() {
+    let chip = SimChip::new();
+    let mut lr = chip.driver();
+    block_on(async {
+        lr.get_status().await.unwrap();
+        lr.set_packet_type(PacketType::Lora).await.unwrap();
+        lr.set_rx(1000, true).await.unwrap();
+        let (_, intr) = lr.get_status().await.unwrap();
+        assert!(intr.timeout());
+        assert!(!intr.rx_done());
+    });
+}
+
+#[cfg(feature = "wifi")]
+#[test]
+fn wifi_scan() {
+    use lr1120::wifi_scan::{AcqMode, WifiScanParams, WifiStandard};
+    let chip = SimChip::new();
+    chip.set_wifi_results(&[([1, 2, 3, 4, 5, 6], 1, -60), ([6, 5, 4, 3, 2, 1], 11, -75)]);
+    let mut lr = chip.driver();
+    block_on(async {
+        lr.get_status().await.unwrap();
+        lr.wifi_scan(&WifiScanParams::new(WifiStandard::All, AcqMode::BeaconSearch)).await.unwrap();
+        let (_, intr) = lr.get_status().await.unwrap();
+        assert!(intr.wifi_done());
+        assert_eq!(lr.wifi_get_nb_res().await.unwrap(), 2);
+        let results: Vec<_> = lr.wifi_get_result_short(0, 2).await.unwrap().collect();
+        assert_eq!(results.len(), 2);
+        let first = results[0].as_ref().unwrap();
+        assert_eq!(first.mac().bytes(), [1, 2, 3, 4, 5, 6]);
+        assert_eq!(first.channel_id(), 1);
+        assert_eq!(first.rssi_dbm(), -60);
+        assert_eq!(results[1].as_ref().unwrap().channel_id(), 11);
+    });
+}
+
+#[cfg(all(feature = "gnss", not(feature = "gnss_v1")))]
+#[test]
+fn gnss_scan() {
+    use lr1120::gnss::GnssScanCfg;
+    let chip = SimChip::new();
+    chip.set_gnss_result(3, &[0x01, 0xAA, 0xBB, 0xCC]);
+    let mut lr = chip.driver();
+    block_on(async {
+        lr.get_status().await.unwrap();
+        lr.gnss_scan(GnssScanCfg::new(false)).await.unwrap();
+        let (_, intr) = lr.get_status().await.unwrap();
+        assert!(intr.gnss_done());
+        assert_eq!(lr.gnss_get_nb_sv().await.unwrap(), 3);
+        assert_eq!(lr.gnss_get_result_size().await.unwrap(), 4);
+        assert_eq!(lr.gnss_read_results().await.unwrap(), &[0x01, 0xAA, 0xBB, 0xCC]);
+    });
+}
+
+#[test]
+fn command_failure() {
+    let chip = SimChip::new();
+    let mut lr = chip.driver();
+    block_on(async {
+        lr.get_status().await.unwrap();
+        chip.fail_next(1, CmdStatus::Fail);
+        lr.set_packet_type(PacketType::Lora).await.unwrap();
+        // The failure is reported with the next transaction
+        assert!(matches!(lr.get_status().await, Err(Lr1120Error::CmdFail)));
+        lr.get_status().await.unwrap();
+    });
+}
+
+#[test]
+fn tcxo_restored_on_cold_wake_up() {
+    use lr1120::errata::Errata;
+    use lr1120::system::{ChipMode, TcxoVoltage};
+    let chip = SimChip::new();
+    let mut lr = chip.driver();
+    block_on(async {
+        lr.get_status().await.unwrap();
+        lr.set_tcxo(TcxoVoltage::Tcxo1v8, 164).await.unwrap();
+        // Sleep with retention: nothing to restore
+        lr.set_chip_mode(ChipMode::DeepRetention).await.unwrap();
+        let nb_cmd = chip.nb_cmd();
+        lr.wake_up().await.unwrap();
+        assert_eq!(chip.nb_cmd(), nb_cmd);
+        // Sleep without retention: TCXO configuration, clear errors and calibration
+        lr.set_chip_mode(ChipMode::DeepSleep).await.unwrap();
+        let nb_cmd = chip.nb_cmd();
+        lr.wake_up().await.unwrap();
+        assert_eq!(chip.nb_cmd(), nb_cmd + 3);
+        // Workaround disabled
+        lr.set_errata(Errata::all() - Errata::TCXO_COLD_WAKEUP);
+        lr.set_chip_mode(ChipMode::DeepSleep).await.unwrap();
+        let nb_cmd = chip.nb_cmd();
+        lr.wake_up().await.unwrap();
+        assert_eq!(chip.nb_cmd(), nb_cmd);
+    });
+}
+
+#[test]
+fn batch_updates_irq_masks() {
+    use lr1120::BatchCheck;
+    let chip = SimChip::new();
+    let mut lr = chip.driver();
+    block_on(async {
+        lr.get_status().await.unwrap();
+        let set_dio_irq : &[u8] = &[0x01, 0x13, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00];
+        lr.cmd_wr_batch(&[set_dio_irq], BatchCheck::EndOnly).await.unwrap();
+        let (irq1, irq2) = lr.irq_masks();
+        assert!(irq1.tx_done() && !irq1.rx_done());
+        assert!(irq2.none());
+    });
+}
@@ -0,0 +1,15 @@
+//! Conversion of the temperature measurement
+use lr1120::system::temp_to_celsius;
+
+#[test]
+fn temp_reference() {
+    assert_eq!(temp_to_celsius(1106), 25);
+}
+
+#[test]
+fn temp_slope_negative() {
+    // Sensor voltage falls as the temperature rises
+    assert!(temp_to_celsius(1016) > temp_to_celsius(1106));
+    assert_eq!(temp_to_celsius(1016), 60);
+    assert!(temp_to_celsius(1200) < 0);
+}
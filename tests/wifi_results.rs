@@ -0,0 +1,134 @@
+//! Parsing of WiFi scan results from synthetic buffers through `WifiResultsIter`, access point scoring and capabilities
+#![cfg(feature = "wifi")]
+
+use lr1120::Lr1120Error;
+use lr1120::system::HwType;
+use lr1120::wifi_scan::{AcqMode, ApStability, ApStabilityCfg, MacAddress, WifiCapabilities, WifiReadExtendedResultsRsp, WifiReadLongResultsRsp, WifiResultsIter};
+
+/// Long result (22 bytes): standard, channel, RSSI, frame control, MAC, phi offset, timestamp, beacon period
+fn long_entry(channel: u8, rssi: i8, mac: [u8; 6], timestamp: u64, beacon_period: u16) -> [u8; 22] {
+    let mut entry = [0u8; 22];
+    entry[0] = 2;
+    entry[1] = channel;
+    entry[2] = rssi as u8;
+    entry[4..10].copy_from_slice(&mac);
+    entry[12..20].copy_from_slice(&timestamp.to_be_bytes());
+    entry[20..22].copy_from_slice(&beacon_period.to_be_bytes());
+    entry
+}
+
+/// Extended result (79 bytes): MAC addresses, timestamp, SSID, channel, country and FCS status
+fn ext_entry(channel: u8, rssi: i8, mac0: [u8; 6], ssid: &[u8], country: &[u8; 2], fcs_ok: bool) -> [u8; 79] {
+    let mut entry = [0u8; 79];
+    entry[0] = 1;
+    entry[1] = channel;
+    entry[2] = rssi as u8;
+    entry[10..16].copy_from_slice(&mac0);
+    entry[28..36].copy_from_slice(&0x0102_0304_0506_0708u64.to_be_bytes());
+    entry[40..40 + ssid.len()].copy_from_slice(ssid);
+    entry[72] = channel;
+    entry[73..75].copy_from_slice(country);
+    entry[76] = fcs_ok as u8;
+    entry
+}
+
+#[test]
+fn long_results() {
+    let mut buffer = [0u8; 2 * 22];
+    buffer[..22].copy_from_slice(&long_entry(1, -50, [0xA0, 1, 2, 3, 4, 5], 1_000_000, 100));
+    buffer[22..].copy_from_slice(&long_entry(6, -82, [0xB0, 6, 7, 8, 9, 10], 42, 102));
+    let results: Vec<_> = WifiResultsIter::<WifiReadLongResultsRsp>::new(&buffer, 2)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].channel_id(), 1);
+    assert_eq!(results[0].rssi_dbm(), -50);
+    assert_eq!(results[0].mac().bytes(), [0xA0, 1, 2, 3, 4, 5]);
+    assert_eq!(results[0].timestamp(), 1_000_000);
+    assert_eq!(results[0].beacon_period(), 100);
+    assert_eq!(results[1].channel_id(), 6);
+    assert_eq!(results[1].rssi_dbm(), -82);
+    assert_eq!(results[1].mac().bytes(), [0xB0, 6, 7, 8, 9, 10]);
+    assert_eq!(results[1].timestamp(), 42);
+}
+
+#[test]
+fn long_results_count() {
+    let mut buffer = [0u8; 3 * 22];
+    for (i, chunk) in buffer.chunks_exact_mut(22).enumerate() {
+        chunk.copy_from_slice(&long_entry(i as u8 + 1, -60, [i as u8; 6], 0, 100));
+    }
+    // Only the number of entries requested is returned, even with more data in the buffer
+    let iter = WifiResultsIter::<WifiReadLongResultsRsp>::new(&buffer, 2);
+    assert_eq!(iter.count(), 2);
+    let iter = WifiResultsIter::<WifiReadLongResultsRsp>::new(&buffer, 0);
+    assert_eq!(iter.count(), 0);
+}
+
+#[test]
+fn ext_results() {
+    let mut buffer = [0u8; 2 * 79];
+    buffer[..79].copy_from_slice(&ext_entry(11, -70, [0xC0, 1, 1, 1, 1, 1], b"home", b"FR", true));
+    buffer[79..].copy_from_slice(&ext_entry(3, -90, [0xD0, 2, 2, 2, 2, 2], b"office", b"DE", false));
+    let results: Vec<_> = WifiResultsIter::<WifiReadExtendedResultsRsp>::new(&buffer, 2)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].channel_id(), 11);
+    assert_eq!(results[0].rssi_dbm(), -70);
+    assert_eq!(results[0].mac0().bytes(), [0xC0, 1, 1, 1, 1, 1]);
+    assert_eq!(results[0].timestamp(), 0x0102_0304_0506_0708);
+    assert!(results[0].ssid().starts_with(b"home\0"));
+    assert_eq!(results[0].channel_num(), 11);
+    assert_eq!(results[0].country(), u16::from_be_bytes(*b"FR"));
+    assert!(results[0].fcs_ok());
+    assert_eq!(results[1].mac0().bytes(), [0xD0, 2, 2, 2, 2, 2]);
+    assert!(results[1].ssid().starts_with(b"office\0"));
+    assert!(!results[1].fcs_ok());
+}
+
+#[test]
+fn truncated_results() {
+    let mut buffer = [0u8; 22 + 10];
+    buffer[..22].copy_from_slice(&long_entry(1, -50, [1; 6], 0, 100));
+    // Complete entries are returned, then a single InvalidSize ends the iteration
+    let mut iter = WifiResultsIter::<WifiReadLongResultsRsp>::new(&buffer, 2);
+    assert!(matches!(iter.next(), Some(Ok(_))));
+    assert!(matches!(iter.next(), Some(Err(Lr1120Error::InvalidSize))));
+    assert!(iter.next().is_none());
+
+    let buffer = [0u8; 78];
+    let mut iter = WifiResultsIter::<WifiReadExtendedResultsRsp>::new(&buffer, 1);
+    assert!(matches!(iter.next(), Some(Err(Lr1120Error::InvalidSize))));
+    assert!(iter.next().is_none());
+
+    let mut iter = WifiResultsIter::<WifiReadExtendedResultsRsp>::new(&[], 3);
+    assert!(matches!(iter.next(), Some(Err(Lr1120Error::InvalidSize))));
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn ap_score_large_uptime() {
+    let cfg = ApStabilityCfg { min_uptime_us: 0, stable_uptime_us: u64::MAX, beacon_period: 100 };
+    let mac = MacAddress::new([0x00, 0x11, 0x22, 0x33, 0x44, 0x55]);
+    let score = cfg.score(mac, u64::MAX, 100);
+    assert_eq!(score.score, 100);
+    assert_eq!(score.class, ApStability::Stable);
+    let score = cfg.score(mac, u64::MAX / 2, 100);
+    assert_eq!(score.score, 74);
+    assert_eq!(score.class, ApStability::Uncertain);
+}
+
+#[test]
+fn capabilities() {
+    let caps = WifiCapabilities { hw_type: HwType::Lr1120, fw_version: (1, 2), wifi_version: (1, 3), timestamp_thr: None };
+    assert!(caps.supports(AcqMode::BeaconSearch));
+    assert!(caps.supports(AcqMode::SsidBeacon));
+    let old_fw = WifiCapabilities { fw_version: (1, 1), ..caps };
+    assert!(old_fw.supports(AcqMode::BeaconSearch));
+    assert!(!old_fw.supports(AcqMode::SsidBeacon));
+    // No WiFi scanning firmware reported
+    let no_wifi = WifiCapabilities { wifi_version: (0, 0), ..caps };
+    assert!(!no_wifi.has_wifi());
+    assert!(!no_wifi.supports(AcqMode::BeaconSearch));
+}